@@ -0,0 +1,13 @@
+use std::path::Path;
+
+fn main() {
+    let dict = Path::new(env!("CARGO_MANIFEST_DIR")).join("../dict/3gpp-ro-rf.xml");
+    println!("cargo:rerun-if-changed={}", dict.display());
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    diameter_codegen::Generator::new()
+        .xml(&diameter::dictionary::DEFAULT_DICT_XML)
+        .file(&dict)
+        .write_to(format!("{}/gy.rs", out_dir))
+        .unwrap();
+}
@@ -0,0 +1,80 @@
+//! # Diameter Codegen Tests
+//!
+//! Builds the API generated by `diameter-codegen` from the base and Ro/Rf
+//! dictionaries, so that the generated code is compiled against the
+//! `diameter` crate.
+
+pub mod gy {
+    include!(concat!(env!("OUT_DIR"), "/gy.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gy;
+    use diameter::dictionary::{self, Dictionary};
+    use diameter::typed::{TypedGroup, TypedMessage};
+    use diameter::{CommandCode, DiameterMessage};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        let ro_rf = include_str!("../../dict/3gpp-ro-rf.xml");
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, ro_rf]))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dict = dict();
+        let subscription_id = gy::SubscriptionId {
+            subscription_id_type: gy::SubscriptionIdType::EndUserE164,
+            subscription_id_data: "6591234567".into(),
+        };
+        let ccr = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .proxyable()
+            .hop_by_hop_id(1)
+            .avp("Session-Id", "pcef.example.com;1;1")
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Destination-Realm", "ocs.example.com")
+            .avp("Auth-Application-Id", 4u32)
+            .avp("Service-Context-Id", "32251@3gpp.org")
+            .avp("CC-Request-Type", gy::CcRequestType::InitialRequest)
+            .avp("CC-Request-Number", 0u32)
+            .avp(
+                "Subscription-Id",
+                subscription_id.to_grouped(&dict).unwrap(),
+            )
+            .avp("Requested-Action", 9)
+            .build()
+            .unwrap();
+
+        let decoded = gy::CreditControlRequest::from_message(&ccr).unwrap();
+        assert_eq!(decoded.session_id, "pcef.example.com;1;1");
+        assert_eq!(decoded.cc_request_type, gy::CcRequestType::InitialRequest);
+        assert_eq!(
+            decoded.requested_action,
+            Some(gy::RequestedAction::Unknown(9))
+        );
+        assert_eq!(decoded.destination_host, None);
+        let subscription_id = decoded.subscription_id.as_ref().unwrap();
+        assert_eq!(subscription_id.subscription_id_data, "6591234567");
+
+        // Encoded, sent and decoded again
+        let msg = decoded.to_message(1, 1, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::CreditControl);
+        assert_eq!(msg.get_flags(), gy::CreditControlRequest::FLAGS);
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let msg = DiameterMessage::decode_from(&mut Cursor::new(&encoded), dict).unwrap();
+        let ccr = gy::CreditControlRequest::from_message(&msg).unwrap();
+        assert_eq!(ccr.origin_realm, "example.com");
+        assert_eq!(ccr.service_context_id, "32251@3gpp.org");
+        assert_eq!(ccr.requested_action, Some(gy::RequestedAction::Unknown(9)));
+        assert_eq!(
+            ccr.subscription_id.unwrap().subscription_id_type,
+            gy::SubscriptionIdType::EndUserE164
+        );
+
+        assert!(gy::CreditControlAnswer::from_message(&msg).is_err());
+    }
+}
@@ -0,0 +1,740 @@
+//! # Diameter Codegen
+//!
+//! Generates typed application APIs from dictionary XML, for use in build
+//! scripts. For every dictionary the generator emits:
+//!
+//! * an `avp` module with a `(code, vendor_id)` constant per AVP, usable
+//!   wherever an `AvpKey` is expected,
+//! * an enum per `Enumerated` AVP that lists its values, keeping the
+//!   values not in the dictionary as `Unknown`,
+//! * a `GroupedAvp` struct per `Grouped` AVP that has rules,
+//! * a `DiameterMessage` request and answer struct per command.
+//!
+//! ```no_run
+//! // build.rs
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! diameter_codegen::Generator::new()
+//!     .xml(&diameter::dictionary::DEFAULT_DICT_XML)
+//!     .file("dict/3gpp-ro-rf.xml")
+//!     .write_to(format!("{}/gy.rs", out_dir))
+//!     .unwrap();
+//! ```
+//!
+//! The generated file is then included in a module of the application:
+//!
+//! ```ignore
+//! mod gy {
+//!     include!(concat!(env!("OUT_DIR"), "/gy.rs"));
+//! }
+//!
+//! let ccr = gy::CreditControlRequest {
+//!     session_id: "ses;1".into(),
+//!     cc_request_type: gy::CcRequestType::InitialRequest,
+//!     // ...
+//! };
+//! let msg = ccr.to_message(hop_by_hop_id, end_to_end_id, &dict)?;
+//! ```
+//!
+//! Struct fields follow the rules of the dictionary: required single AVPs are
+//! plain fields, optional single AVPs are `Option`s and everything else is a
+//! `Vec`. Rules that name AVPs missing from the loaded dictionaries are
+//! skipped, as are commands and applications without a `CommandCode` or
+//! `ApplicationId` variant. At runtime the same dictionaries must be loaded
+//! into the `Dictionary` passed to the generated types.
+
+mod xml;
+
+use diameter::{ApplicationId, CommandCode, Error, Result};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_CRATE_PATH: &str = "::diameter";
+
+enum Source {
+    Xml(String),
+    File(PathBuf),
+}
+
+/// Generates Rust code from one or more dictionary XML sources.
+pub struct Generator {
+    sources: Vec<Source>,
+    crate_path: String,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Generator::new()
+    }
+}
+
+impl Generator {
+    pub fn new() -> Generator {
+        Generator {
+            sources: Vec::new(),
+            crate_path: DEFAULT_CRATE_PATH.into(),
+        }
+    }
+
+    /// Adds a dictionary XML document.
+    pub fn xml(mut self, xml: &str) -> Self {
+        self.sources.push(Source::Xml(xml.into()));
+        self
+    }
+
+    /// Adds a dictionary XML file, read when generating.
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.sources.push(Source::File(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Sets the path of the `diameter` crate in generated code, `::diameter`
+    /// by default.
+    pub fn crate_path(mut self, path: &str) -> Self {
+        self.crate_path = path.into();
+        self
+    }
+
+    /// Generates the Rust source for all added dictionaries.
+    pub fn generate(&self) -> Result<String> {
+        let mut model = Model::default();
+        for source in &self.sources {
+            let xml = match source {
+                Source::Xml(xml) => xml.clone(),
+                Source::File(path) => fs::read_to_string(path)?,
+            };
+            let dict: xml::Diameter = serde_xml_rs::from_str(&xml)
+                .map_err(|e| Error::DecodeError(format!("invalid dictionary XML: {}", e)))?;
+            model.load(dict)?;
+        }
+
+        let krate: syn::Path = syn::parse_str(&self.crate_path).map_err(|e| {
+            Error::EncodeError(format!("invalid crate path {}: {}", self.crate_path, e))
+        })?;
+        let crate_attr = if self.crate_path == DEFAULT_CRATE_PATH {
+            None
+        } else {
+            Some(self.crate_path.as_str())
+        };
+
+        let tokens = Renderer::new(&model, quote! { #krate }, crate_attr).render();
+        let file: syn::File = syn::parse2(tokens)
+            .map_err(|e| Error::EncodeError(format!("invalid generated code: {}", e)))?;
+        Ok(format!(
+            "// @generated by diameter-codegen, do not edit.\n\n{}",
+            prettyplease::unparse(&file)
+        ))
+    }
+
+    /// Generates the Rust source and writes it to `path`.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let code = self.generate()?;
+        fs::write(path, code)?;
+        Ok(())
+    }
+}
+
+struct RuleDef {
+    avp: String,
+    required: bool,
+    single: bool,
+}
+
+struct AvpDef {
+    name: String,
+    code: u32,
+    vendor_id: Option<u32>,
+    data_type: String,
+    items: Vec<(i32, String)>,
+    rules: Vec<RuleDef>,
+}
+
+struct CommandDef {
+    name: String,
+    code: u32,
+    application_id: u32,
+    request: Vec<RuleDef>,
+    answer: Vec<RuleDef>,
+}
+
+/// AVPs and commands of all loaded dictionaries. Later definitions replace
+/// earlier ones with the same name, as in `Dictionary`.
+#[derive(Default)]
+struct Model {
+    avps: Vec<AvpDef>,
+    avp_index: HashMap<String, usize>,
+    commands: Vec<CommandDef>,
+    command_index: HashMap<String, usize>,
+}
+
+impl Model {
+    fn load(&mut self, dict: xml::Diameter) -> Result<()> {
+        for app in dict.applications {
+            let application_id = parse_u32(&app.id, "application id")?;
+
+            for avp in app.avps {
+                let mut items = Vec::new();
+                for item in &avp.data.item {
+                    if let Ok(code) = item.code.trim().parse::<i32>() {
+                        items.push((code, item.name.clone()));
+                    }
+                }
+                let avp_def = AvpDef {
+                    code: parse_u32(&avp.code, "AVP code")?,
+                    vendor_id: match avp.vendor_id {
+                        Some(ref vendor_id) => Some(parse_u32(vendor_id, "vendor id")?),
+                        None => None,
+                    },
+                    data_type: avp.data.data_type.clone(),
+                    items,
+                    rules: rules(&avp.data.rules),
+                    name: avp.name,
+                };
+                match self.avp_index.get(&avp_def.name) {
+                    Some(&index) => self.avps[index] = avp_def,
+                    None => {
+                        self.avp_index.insert(avp_def.name.clone(), self.avps.len());
+                        self.avps.push(avp_def);
+                    }
+                }
+            }
+
+            for cmd in app.commands {
+                let command_def = CommandDef {
+                    code: parse_u32(&cmd.code, "command code")?,
+                    application_id,
+                    request: rules(&cmd.request.rules),
+                    answer: rules(&cmd.answer.rules),
+                    name: cmd.name,
+                };
+                match self.command_index.get(&command_def.name) {
+                    Some(&index) => self.commands[index] = command_def,
+                    None => {
+                        self.command_index
+                            .insert(command_def.name.clone(), self.commands.len());
+                        self.commands.push(command_def);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_avp(&self, name: &str) -> Option<&AvpDef> {
+        self.avp_index.get(name).map(|&index| &self.avps[index])
+    }
+}
+
+fn rules(rules: &[xml::Rule]) -> Vec<RuleDef> {
+    rules
+        .iter()
+        .map(|rule| RuleDef {
+            avp: rule.avp.clone(),
+            required: rule.is_required(),
+            single: rule.is_single(),
+        })
+        .collect()
+}
+
+fn parse_u32(value: &str, what: &str) -> Result<u32> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| Error::DecodeError(format!("invalid {}: {}", what, value)))
+}
+
+struct Renderer<'a> {
+    model: &'a Model,
+    krate: TokenStream,
+    crate_attr: Option<&'a str>,
+    /// Type names of enumerated and grouped AVPs, by AVP name.
+    type_names: HashMap<String, Ident>,
+    used_names: HashSet<String>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(model: &'a Model, krate: TokenStream, crate_attr: Option<&'a str>) -> Renderer<'a> {
+        let mut renderer = Renderer {
+            model,
+            krate,
+            crate_attr,
+            type_names: HashMap::new(),
+            used_names: HashSet::new(),
+        };
+        for avp in &model.avps {
+            if is_enum(avp) || is_struct(avp) {
+                let ident = renderer.unique_ident(camel_case(&avp.name), avp.code);
+                renderer.type_names.insert(avp.name.clone(), ident);
+            }
+        }
+        renderer
+    }
+
+    fn unique_ident(&mut self, name: String, code: u32) -> Ident {
+        let mut name = name;
+        if !self.used_names.insert(name.clone()) {
+            name = format!("{}{}", name, code);
+            self.used_names.insert(name.clone());
+        }
+        Ident::new(&name, Span::call_site())
+    }
+
+    fn render(mut self) -> TokenStream {
+        let constants = self.render_constants();
+        let enums: Vec<_> = self
+            .model
+            .avps
+            .iter()
+            .filter(|avp| is_enum(avp))
+            .map(|avp| self.render_enum(avp))
+            .collect();
+        let structs: Vec<_> = self
+            .model
+            .avps
+            .iter()
+            .filter(|avp| is_struct(avp))
+            .map(|avp| self.render_group(avp))
+            .collect();
+
+        let mut commands = Vec::new();
+        for cmd in &self.model.commands {
+            let command = CommandCode::from_u32(cmd.code);
+            let application = ApplicationId::from_u32(cmd.application_id);
+            if let (Some(command), Some(application)) = (command, application) {
+                commands.push(self.render_command(cmd, command, application, true));
+                commands.push(self.render_command(cmd, command, application, false));
+            }
+        }
+
+        quote! {
+            #constants
+            #(#enums)*
+            #(#structs)*
+            #(#commands)*
+        }
+    }
+
+    fn render_constants(&self) -> TokenStream {
+        let mut used = HashSet::new();
+        let constants = self.model.avps.iter().map(|avp| {
+            let mut name = screaming_snake_case(&avp.name);
+            if !used.insert(name.clone()) {
+                name = format!("{}_{}", name, avp.code);
+            }
+            let ident = Ident::new(&name, Span::call_site());
+            let doc = format!(" {} ({}).", avp.name, avp.data_type);
+            let code = Literal::u32_unsuffixed(avp.code);
+            let vendor_id = match avp.vendor_id {
+                Some(vendor_id) => {
+                    let vendor_id = Literal::u32_unsuffixed(vendor_id);
+                    quote! { Some(#vendor_id) }
+                }
+                None => quote! { None },
+            };
+            quote! {
+                #[doc = #doc]
+                pub const #ident: (u32, Option<u32>) = (#code, #vendor_id);
+            }
+        });
+
+        quote! {
+            /// AVP codes and vendor IDs.
+            pub mod avp {
+                #(#constants)*
+            }
+        }
+    }
+
+    /// Renders an `enumerated!` invocation, the macro the `diameter` crate
+    /// defines its own enums with.
+    fn render_enum(&self, avp: &AvpDef) -> TokenStream {
+        let krate = &self.krate;
+        let name = &self.type_names[&avp.name];
+        let doc = format!(" Values of the {} AVP ({}).", avp.name, avp.code);
+        let avp_name = &avp.name;
+
+        let mut used_codes = HashSet::new();
+        // Reserved for the values not in the dictionary
+        let mut used_names = HashSet::from(["Unknown".to_string()]);
+        let mut variants = Vec::new();
+        for (code, item_name) in &avp.items {
+            if !used_codes.insert(*code) {
+                continue;
+            }
+            let mut variant = camel_case(item_name);
+            if !used_names.insert(variant.clone()) {
+                variant = format!("{}{}", variant, code.unsigned_abs());
+                used_names.insert(variant.clone());
+            }
+            let variant = Ident::new(&variant, Span::call_site());
+            let value = Literal::i32_unsuffixed(*code);
+            let doc = format!(" {} ({})", item_name, code);
+            variants.push(quote! {
+                #[doc = #doc]
+                #variant = #value,
+            });
+        }
+
+        quote! {
+            #krate::enumerated! {
+                #[doc = #doc]
+                pub enum #name (#avp_name) {
+                    #(#variants)*
+                }
+            }
+        }
+    }
+
+    fn render_group(&self, avp: &AvpDef) -> TokenStream {
+        let krate = &self.krate;
+        let name = &self.type_names[&avp.name];
+        let doc = format!(" The {} AVP ({}).", avp.name, avp.code);
+        let fields = self.render_fields(Some(&avp.name), &avp.rules);
+        let crate_attr = self.crate_attr.map(|path| {
+            quote! { #[diameter(crate = #path)] }
+        });
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, #krate::GroupedAvp)]
+            #crate_attr
+            pub struct #name {
+                #(#fields)*
+            }
+        }
+    }
+
+    fn render_command(
+        &mut self,
+        cmd: &CommandDef,
+        command: CommandCode,
+        application: ApplicationId,
+        request: bool,
+    ) -> TokenStream {
+        let krate = self.krate.clone();
+        let suffix = if request { "Request" } else { "Answer" };
+        let name = self.unique_ident(format!("{}{}", camel_case(&cmd.name), suffix), cmd.code);
+        let doc = format!(" {}-{} ({}).", cmd.name, suffix, cmd.code);
+        let rules = if request { &cmd.request } else { &cmd.answer };
+        let fields = self.render_fields(None, rules);
+
+        let mut args = Vec::new();
+        if let Some(path) = self.crate_attr {
+            args.push(quote! { crate = #path });
+        }
+        let command = Ident::new(&format!("{:?}", command), Span::call_site());
+        let application = Ident::new(&format!("{:?}", application), Span::call_site());
+        args.push(quote! { command = #command });
+        args.push(quote! { application = #application });
+        if request {
+            args.push(quote! { request });
+        }
+        if is_proxyable(cmd.code) {
+            args.push(quote! { proxyable });
+        }
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, #krate::DiameterMessage)]
+            #[diameter(#(#args),*)]
+            pub struct #name {
+                #(#fields)*
+            }
+        }
+    }
+
+    fn render_fields(&self, owner: Option<&str>, rules: &[RuleDef]) -> Vec<TokenStream> {
+        let mut used = HashSet::new();
+        let mut fields = Vec::new();
+        for rule in rules {
+            let avp = match self.model.get_avp(&rule.avp) {
+                Some(avp) => avp,
+                None => continue,
+            };
+            let (mut ty, grouped) = match self.field_type(avp) {
+                Some(field_type) => field_type,
+                None => continue,
+            };
+            let name = snake_case(&avp.name);
+            if !used.insert(name.clone()) {
+                continue;
+            }
+
+            let single = rule.single;
+            if grouped && single {
+                if let Some(owner) = owner {
+                    if self.reaches(&avp.name, owner) {
+                        ty = quote! { Box<#ty> };
+                    }
+                }
+            }
+            let ty = match (single, rule.required) {
+                (true, true) => ty,
+                (true, false) => quote! { Option<#ty> },
+                (false, _) => quote! { Vec<#ty> },
+            };
+
+            let ident = field_ident(&name);
+            let code = Literal::u32_unsuffixed(avp.code);
+            let vendor = avp.vendor_id.map(|vendor_id| {
+                let vendor_id = Literal::u32_unsuffixed(vendor_id);
+                quote! { , vendor = #vendor_id }
+            });
+            let grouped = if grouped {
+                Some(quote! { , grouped })
+            } else {
+                None
+            };
+            let doc = format!(" {} ({})", avp.name, avp.code);
+            fields.push(quote! {
+                #[doc = #doc]
+                #[avp(code = #code #vendor #grouped)]
+                pub #ident: #ty,
+            });
+        }
+        fields
+    }
+
+    /// Returns the field type of an AVP and whether it is a typed group.
+    fn field_type(&self, avp: &AvpDef) -> Option<(TokenStream, bool)> {
+        let krate = &self.krate;
+        let ty = match avp.data_type.as_str() {
+            // Filter rules are ASCII strings, as in the dictionary
+            "UTF8String" | "DiameterIdentity" | "DiameterURI" | "IPFilterRule"
+            | "QoSFilterRule" => {
+                quote! { String }
+            }
+            "OctetString" => quote! { Vec<u8> },
+            "Integer32" if !is_enum(avp) => quote! { i32 },
+            "Enumerated" if !is_enum(avp) => quote! { i32 },
+            "Integer32" | "Enumerated" => {
+                let name = &self.type_names[&avp.name];
+                quote! { #name }
+            }
+            "Integer64" => quote! { i64 },
+            "Unsigned32" => quote! { u32 },
+            "Unsigned64" => quote! { u64 },
+            "Float32" => quote! { f32 },
+            "Float64" => quote! { f64 },
+            "Time" => quote! { #krate::typed::DateTime<#krate::typed::Utc> },
+            "Address" => quote! { ::std::net::IpAddr },
+            "IPv4" => quote! { ::std::net::Ipv4Addr },
+            "IPv6" => quote! { ::std::net::Ipv6Addr },
+            "Grouped" if is_struct(avp) => {
+                let name = &self.type_names[&avp.name];
+                return Some((quote! { #name }, true));
+            }
+            "Grouped" => quote! { #krate::avp::Grouped },
+            _ => return None,
+        };
+        Some((ty, false))
+    }
+
+    /// Whether the group `from` contains the group `to` through single
+    /// instance fields, which then need to be boxed.
+    fn reaches(&self, from: &str, to: &str) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if !visited.insert(name) {
+                continue;
+            }
+            if let Some(avp) = self.model.get_avp(name) {
+                for rule in avp.rules.iter().filter(|rule| rule.single) {
+                    if self.model.get_avp(&rule.avp).is_some_and(is_struct) {
+                        stack.push(&rule.avp);
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+fn is_enum(avp: &AvpDef) -> bool {
+    (avp.data_type == "Enumerated" || avp.data_type == "Integer32") && !avp.items.is_empty()
+}
+
+fn is_struct(avp: &AvpDef) -> bool {
+    avp.data_type == "Grouped" && !avp.rules.is_empty()
+}
+
+/// Capabilities-Exchange, Device-Watchdog and Disconnect-Peer are the only
+/// commands that must not be proxied.
+fn is_proxyable(code: u32) -> bool {
+    !matches!(code, 257 | 280 | 282)
+}
+
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// `CC-Request-Type` becomes `CcRequestType`, `INITIAL_REQUEST` becomes
+/// `InitialRequest`. Mixed case words such as `Ipv4` are kept.
+fn camel_case(name: &str) -> String {
+    let mut result = String::new();
+    for word in words(name) {
+        let mixed = word.chars().any(|c| c.is_ascii_lowercase());
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.push(first.to_ascii_uppercase());
+            if mixed {
+                result.extend(chars);
+            } else {
+                result.extend(chars.map(|c| c.to_ascii_lowercase()));
+            }
+        }
+    }
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, 'V');
+    }
+    result
+}
+
+fn snake_case(name: &str) -> String {
+    let mut result = words(name)
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+fn screaming_snake_case(name: &str) -> String {
+    snake_case(name).to_ascii_uppercase()
+}
+
+fn field_ident(name: &str) -> Ident {
+    match syn::parse_str::<Ident>(name) {
+        Ok(ident) => ident,
+        Err(_) => Ident::new_raw(name, Span::call_site()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"
+<diameter>
+    <application id="4" name="Charging Control">
+        <command code="272" short="CC" name="Credit-Control">
+            <request>
+                <rule avp="Session-Id" required="true" max="1"/>
+                <rule avp="CC-Request-Type" required="true" max="1"/>
+                <rule avp="Multiple-Services-Credit-Control" required="false"/>
+                <rule avp="Flow-Description" required="false"/>
+                <rule avp="Unknown-AVP" required="false" max="1"/>
+            </request>
+            <answer>
+                <rule avp="Session-Id" required="true" max="1"/>
+                <rule avp="Result-Code" required="true" max="1"/>
+            </answer>
+        </command>
+        <command code="999" short="XX" name="Unsupported">
+            <request/>
+            <answer/>
+        </command>
+
+        <avp name="Session-Id" code="263" must="M">
+            <data type="UTF8String"/>
+        </avp>
+        <avp name="Result-Code" code="268" must="M">
+            <data type="Unsigned32"/>
+        </avp>
+        <avp name="CC-Request-Type" code="416" must="M">
+            <data type="Enumerated">
+                <item code="1" name="INITIAL_REQUEST"/>
+                <item code="2" name="UPDATE_REQUEST"/>
+            </data>
+        </avp>
+        <avp name="Multiple-Services-Credit-Control" code="456" must="M">
+            <data type="Grouped">
+                <rule avp="Rating-Group" required="false" max="1"/>
+                <rule avp="Multiple-Services-Credit-Control" required="false" max="1"/>
+            </data>
+        </avp>
+        <avp name="Rating-Group" code="432" must="M">
+            <data type="Unsigned32"/>
+        </avp>
+        <avp name="Flow-Description" code="507" must="M,V" vendor-id="10415">
+            <data type="IPFilterRule"/>
+        </avp>
+        <avp name="Timezone-Offset" code="571" must="V" vendor-id="10415">
+            <data type="Integer32"/>
+        </avp>
+    </application>
+</diameter>
+"#;
+
+    #[test]
+    fn test_generate() {
+        let code = Generator::new().xml(XML).generate().unwrap();
+        syn::parse_file(&code).unwrap();
+
+        assert!(code.contains("pub const SESSION_ID: (u32, Option<u32>) = (263, None);"));
+        assert!(
+            code.contains("pub const TIMEZONE_OFFSET: (u32, Option<u32>) = (571, Some(10415));")
+        );
+        // Macro invocations are wrapped at any token
+        let tokens = code.split_whitespace().collect::<Vec<_>>().join(" ");
+        assert!(tokens.contains(
+            "::diameter::enumerated! { #[doc = \" Values of the CC-Request-Type AVP (416).\"] \
+             pub enum CcRequestType(\"CC-Request-Type\") {"
+        ));
+        assert!(tokens.contains("InitialRequest = 1,"));
+        assert!(code.contains("pub struct MultipleServicesCreditControl {"));
+        assert!(code.contains(
+            "pub multiple_services_credit_control: Option<Box<MultipleServicesCreditControl>>,"
+        ));
+        assert!(code.contains("pub struct CreditControlRequest {"));
+        assert!(code.contains("pub cc_request_type: CcRequestType,"));
+        assert!(code.contains("#[avp(code = 507, vendor = 10415)]"));
+        assert!(code.contains("pub flow_description: Vec<String>,"));
+        assert!(code
+            .contains("pub multiple_services_credit_control: Vec<MultipleServicesCreditControl>,"));
+        assert!(code.contains(
+            "#[diameter(command = CreditControl, application = CreditControl, request, proxyable)]"
+        ));
+        assert!(code.contains("pub struct CreditControlAnswer {"));
+        assert!(!code.contains("unknown_avp"));
+        assert!(!code.contains("Unsupported"));
+    }
+
+    #[test]
+    fn test_crate_path() {
+        let code = Generator::new()
+            .xml(XML)
+            .crate_path("crate")
+            .generate()
+            .unwrap();
+        assert!(code.contains("#[derive(Debug, Clone, crate::GroupedAvp)]"));
+        assert!(code.contains("#[diameter(crate = \"crate\")]"));
+        assert_eq!(code.matches("crate = \"crate\"").count(), 3);
+
+        assert!(Generator::new().xml("<diameter>").generate().is_err());
+        assert!(Generator::new()
+            .file("no/such/file.xml")
+            .generate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(camel_case("CC-Request-Type"), "CcRequestType");
+        assert_eq!(camel_case("INITIAL_REQUEST"), "InitialRequest");
+        assert_eq!(camel_case("Ipv4v6"), "Ipv4v6");
+        assert_eq!(camel_case("3GPP-IMSI"), "V3gppImsi");
+        assert_eq!(snake_case("TGPP-IMSI-MCC-MNC"), "tgpp_imsi_mcc_mnc");
+        assert_eq!(screaming_snake_case("CC-Request-Type"), "CC_REQUEST_TYPE");
+        assert_eq!(field_ident("type").to_string(), "r#type");
+    }
+}
@@ -0,0 +1,80 @@
+//! Dictionary XML model, including the command and grouped AVP rules that
+//! the runtime `Dictionary` does not keep.
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Diameter {
+    #[serde(rename = "application", default)]
+    pub applications: Vec<Application>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Application {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "command", default)]
+    pub commands: Vec<Command>,
+    #[serde(rename = "avp", default)]
+    pub avps: Vec<Avp>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Command {
+    pub code: String,
+    pub short: String,
+    pub name: String,
+    pub request: CommandDetail,
+    pub answer: CommandDetail,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CommandDetail {
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Rule {
+    pub avp: String,
+    pub required: String,
+    pub max: Option<String>,
+    pub min: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Avp {
+    pub name: String,
+    pub code: String,
+    pub must: Option<String>,
+    #[serde(rename = "vendor-id")]
+    pub vendor_id: Option<String>,
+    pub data: Data,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Data {
+    #[serde(rename = "type")]
+    pub data_type: String,
+    #[serde(default)]
+    pub item: Vec<Item>,
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Item {
+    pub code: String,
+    pub name: String,
+}
+
+impl Rule {
+    pub fn is_required(&self) -> bool {
+        self.required.eq_ignore_ascii_case("true")
+    }
+
+    /// Whether at most one instance of the AVP is allowed.
+    pub fn is_single(&self) -> bool {
+        self.max.as_deref().map(str::trim) == Some("1")
+    }
+}
@@ -0,0 +1,368 @@
+//! # Diameter Derive
+//!
+//! Derive macros for the `diameter` crate, mapping struct fields to AVPs.
+//!
+//! * `#[derive(GroupedAvp)]` implements `diameter::typed::TypedGroup`.
+//! * `#[derive(DiameterMessage)]` implements `diameter::typed::TypedGroup` and
+//!   `diameter::typed::TypedMessage`, and requires a struct level
+//!   `#[diameter(command = .., application = .., request, proxyable)]` attribute,
+//!   where `command` and `application` name `CommandCode` and `ApplicationId`
+//!   variants.
+//!
+//! Both derives accept `#[diameter(crate = "path")]` to refer to the
+//! `diameter` crate by another path.
+//!
+//! Every field is mapped with an `#[avp(..)]` attribute, see the
+//! `diameter::typed` module for the supported options.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Field, Fields, GenericArgument, Ident, LitInt, LitStr,
+    Path, PathArguments, Type,
+};
+
+#[proc_macro_derive(GroupedAvp, attributes(avp, diameter))]
+pub fn derive_grouped_avp(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let result = ContainerAttrs::parse(&input).and_then(|attrs| {
+        if let Some(span) = attrs.message_attr {
+            return Err(syn::Error::new(
+                span,
+                "message attributes are only supported by #[derive(DiameterMessage)]",
+            ));
+        }
+        expand_group(&input, &attrs)
+    });
+    result.unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+#[proc_macro_derive(DiameterMessage, attributes(avp, diameter))]
+pub fn derive_diameter_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let result = ContainerAttrs::parse(&input).and_then(|attrs| {
+        let group = expand_group(&input, &attrs)?;
+        let message = expand_message(&input, &attrs)?;
+        Ok(quote! {
+            #group
+            #message
+        })
+    });
+    result.unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+/// Struct level `#[diameter(..)]` attributes.
+struct ContainerAttrs {
+    krate: TokenStream2,
+    command: Option<Path>,
+    application: Option<Path>,
+    request: bool,
+    proxyable: bool,
+    message_attr: Option<Span>,
+}
+
+impl ContainerAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<ContainerAttrs> {
+        let mut attrs = ContainerAttrs {
+            krate: quote! { ::diameter },
+            command: None,
+            application: None,
+            request: false,
+            proxyable: false,
+            message_attr: None,
+        };
+
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("diameter")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    let path: Path = path.parse()?;
+                    attrs.krate = quote! { #path };
+                    return Ok(());
+                }
+
+                attrs.message_attr.get_or_insert(meta.path.span());
+                if meta.path.is_ident("command") {
+                    attrs.command = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("application") {
+                    attrs.application = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("request") {
+                    attrs.request = true;
+                } else if meta.path.is_ident("proxyable") {
+                    attrs.proxyable = true;
+                } else {
+                    return Err(meta.error("unsupported diameter attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// How a field maps to AVP instances.
+enum Kind {
+    Required,
+    Optional,
+    Multiple,
+}
+
+struct AvpField<'a> {
+    ident: &'a Ident,
+    kind: Kind,
+    key: TokenStream2,
+    mandatory: TokenStream2,
+    grouped: bool,
+}
+
+fn expand_group(input: &DeriveInput, attrs: &ContainerAttrs) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let krate = &attrs.krate;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "only structs with named fields are supported",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "only structs with named fields are supported",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(|field| parse_field(field, krate))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let encoders = fields.iter().map(|field| {
+        let ident = field.ident;
+        let key = &field.key;
+        let mandatory = &field.mandatory;
+        let encode = if field.grouped {
+            quote! { #krate::typed::encode_group(&key, #mandatory, value, dict)? }
+        } else {
+            quote! {
+                #krate::typed::encode_value(
+                    &key,
+                    #mandatory,
+                    ::std::clone::Clone::clone(value),
+                    dict,
+                )?
+            }
+        };
+        match field.kind {
+            Kind::Required => quote! {
+                {
+                    let key = #key;
+                    let value = &self.#ident;
+                    avps.push(#encode);
+                }
+            },
+            Kind::Optional => quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    let key = #key;
+                    avps.push(#encode);
+                }
+            },
+            Kind::Multiple => quote! {
+                {
+                    let key = #key;
+                    for value in &self.#ident {
+                        avps.push(#encode);
+                    }
+                }
+            },
+        }
+    });
+
+    let decoders = fields.iter().map(|field| {
+        let ident = field.ident;
+        let key = &field.key;
+        let decode = match (&field.kind, field.grouped) {
+            (Kind::Required, false) => quote! { decode_value },
+            (Kind::Optional, false) => quote! { decode_optional },
+            (Kind::Multiple, false) => quote! { decode_all },
+            (Kind::Required, true) => quote! { decode_group },
+            (Kind::Optional, true) => quote! { decode_optional_group },
+            (Kind::Multiple, true) => quote! { decode_all_groups },
+        };
+        quote! {
+            #ident: #krate::typed::#decode(avps, &#key)?
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::typed::TypedGroup for #name #ty_generics #where_clause {
+            fn to_avps(
+                &self,
+                dict: &::std::sync::Arc<#krate::dictionary::Dictionary>,
+            ) -> #krate::Result<::std::vec::Vec<#krate::avp::Avp>> {
+                let mut avps = ::std::vec::Vec::new();
+                #(#encoders)*
+                ::std::result::Result::Ok(avps)
+            }
+
+            fn from_avps(avps: &[#krate::avp::Avp]) -> #krate::Result<Self> {
+                ::std::result::Result::Ok(Self {
+                    #(#decoders,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_message(input: &DeriveInput, attrs: &ContainerAttrs) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let krate = &attrs.krate;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let command = attrs.command.as_ref().ok_or_else(|| {
+        syn::Error::new(input.span(), "missing #[diameter(command = ..)] attribute")
+    })?;
+    let application = attrs.application.as_ref().ok_or_else(|| {
+        syn::Error::new(
+            input.span(),
+            "missing #[diameter(application = ..)] attribute",
+        )
+    })?;
+
+    let request_flag = if attrs.request {
+        quote! { #krate::flags::REQUEST }
+    } else {
+        quote! { 0 }
+    };
+    let proxyable_flag = if attrs.proxyable {
+        quote! { #krate::flags::PROXYABLE }
+    } else {
+        quote! { 0 }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #krate::typed::TypedMessage for #name #ty_generics #where_clause {
+            const COMMAND_CODE: #krate::CommandCode = #krate::CommandCode::#command;
+            const APPLICATION_ID: #krate::ApplicationId = #krate::ApplicationId::#application;
+            const FLAGS: u8 = #request_flag | #proxyable_flag;
+        }
+    })
+}
+
+fn parse_field<'a>(field: &'a Field, krate: &TokenStream2) -> syn::Result<AvpField<'a>> {
+    let ident = field.ident.as_ref().expect("named field");
+
+    let mut name: Option<LitStr> = None;
+    let mut code: Option<LitInt> = None;
+    let mut vendor: Option<LitInt> = None;
+    let mut mandatory = false;
+    let mut grouped = false;
+
+    let attrs: Vec<_> = field
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("avp"))
+        .collect();
+    if attrs.is_empty() {
+        return Err(syn::Error::new(
+            field.span(),
+            "missing #[avp(..)] attribute on field",
+        ));
+    }
+
+    for attr in attrs {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("code") {
+                code = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("vendor") {
+                vendor = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("mandatory") {
+                mandatory = true;
+            } else if meta.path.is_ident("grouped") {
+                grouped = true;
+            } else {
+                return Err(meta.error("unsupported avp attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let key = match (name, code, vendor) {
+        (Some(name), None, None) => quote! {
+            #krate::dictionary::AvpKey::Name(::std::string::String::from(#name))
+        },
+        (None, Some(code), None) => quote! {
+            #krate::dictionary::AvpKey::Code(#code)
+        },
+        (None, Some(code), Some(vendor)) => quote! {
+            #krate::dictionary::AvpKey::CodeAndVendor(#code, #vendor)
+        },
+        _ => {
+            return Err(syn::Error::new(
+                field.span(),
+                "expected either #[avp(name = \"..\")] or #[avp(code = .., vendor = ..)]",
+            ))
+        }
+    };
+
+    let mandatory = if mandatory {
+        quote! { ::std::option::Option::Some(true) }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
+
+    let kind = match wrapper_type(&field.ty) {
+        Some(("Option", _)) => Kind::Optional,
+        Some(("Vec", inner)) if !is_u8(inner) => Kind::Multiple,
+        _ => Kind::Required,
+    };
+
+    Ok(AvpField {
+        ident,
+        kind,
+        key,
+        mandatory,
+        grouped,
+    })
+}
+
+/// Returns the name and argument of `Option<T>` or `Vec<T>`.
+fn wrapper_type(ty: &Type) -> Option<(&'static str, &Type)> {
+    let path = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    let wrapper = if segment.ident == "Option" {
+        "Option"
+    } else if segment.ident == "Vec" {
+        "Vec"
+    } else {
+        return None;
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some((wrapper, inner)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path.path.is_ident("u8"),
+        _ => false,
+    }
+}
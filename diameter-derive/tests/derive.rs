@@ -0,0 +1,99 @@
+use diameter::avp::Unsigned32;
+use diameter::dictionary::{self, Dictionary};
+use diameter::flags;
+use diameter::typed::TypedMessage;
+use diameter::{ApplicationId, CommandCode, DiameterMessage, Error, GroupedAvp};
+use std::io::Cursor;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+struct ServiceUnit {
+    #[avp(name = "CC-Total-Octets")]
+    total_octets: Option<u64>,
+    #[avp(code = 420)]
+    time: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, diameter::DiameterMessage)]
+#[diameter(command = CreditControl, application = CreditControl, request, proxyable)]
+struct Ccr {
+    #[avp(name = "Session-Id")]
+    session_id: String,
+    #[avp(name = "CC-Request-Number")]
+    request_number: u32,
+    #[avp(code = 1, mandatory)]
+    user_name: Option<String>,
+    #[avp(name = "CC-Correlation-Id")]
+    correlation_id: Option<Vec<u8>>,
+    #[avp(name = "Route-Record")]
+    route_records: Vec<String>,
+    #[avp(name = "Requested-Service-Unit", grouped)]
+    requested: Option<ServiceUnit>,
+    #[avp(code = 628, vendor = 10415)]
+    supported_features: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, diameter::DiameterMessage)]
+#[diameter(command = CreditControl, application = CreditControl)]
+struct Cca {
+    #[avp(name = "Session-Id")]
+    session_id: String,
+    #[avp(name = "Result-Code")]
+    result_code: u32,
+}
+
+#[test]
+fn test_derive() {
+    assert_eq!(Ccr::COMMAND_CODE, CommandCode::CreditControl);
+    assert_eq!(Ccr::APPLICATION_ID, ApplicationId::CreditControl);
+    assert_eq!(Ccr::FLAGS, flags::REQUEST | flags::PROXYABLE);
+    assert_eq!(Cca::FLAGS, 0);
+
+    let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+    let ccr = Ccr {
+        session_id: "ses;1".into(),
+        request_number: 1,
+        user_name: Some("alice".into()),
+        correlation_id: Some(vec![1, 2, 3]),
+        route_records: vec!["dra1.example.com".into(), "dra2.example.com".into()],
+        requested: Some(ServiceUnit {
+            total_octets: Some(1024),
+            time: None,
+        }),
+        supported_features: vec![],
+    };
+    let msg = ccr.to_message(7, 8, &dict).unwrap();
+    assert_eq!(msg.get_hop_by_hop_id(), 7);
+    assert_eq!(msg.get_avps().len(), 7);
+    let user_name = msg
+        .get_avps()
+        .iter()
+        .find(|avp| avp.get_code() == 1)
+        .unwrap();
+    assert!(user_name.get_flags().mandatory);
+
+    let mut encoded = Vec::new();
+    msg.encode_to(&mut encoded).unwrap();
+    let msg = DiameterMessage::decode_from(&mut Cursor::new(&encoded), Arc::clone(&dict)).unwrap();
+    assert_eq!(Ccr::from_message(&msg).unwrap(), ccr);
+
+    // The R bit and the required AVPs are checked
+    assert!(matches!(
+        Cca::from_message(&msg),
+        Err(Error::DecodeError(_))
+    ));
+    let mut cca = DiameterMessage::new(
+        CommandCode::CreditControl,
+        ApplicationId::CreditControl,
+        0,
+        7,
+        8,
+        dict,
+    );
+    cca.add_avp_by_name("Result-Code", Unsigned32::new(2001).into())
+        .unwrap();
+    match Cca::from_message(&cca) {
+        Err(Error::AvpNotFound(name)) => assert_eq!(name, "Session-Id"),
+        other => panic!("expected AvpNotFound, got {:?}", other),
+    }
+}
@@ -1,15 +1,19 @@
+use diameter::applications::credit_control::{
+    CcRequestType, CreditControlAnswer, CreditControlRequest, SubscriptionId, SubscriptionIdType,
+};
 use diameter::avp::address::Value::IPv4;
 use diameter::avp::flags::M;
 use diameter::avp::Address;
-use diameter::avp::Enumerated;
 use diameter::avp::Identity;
 use diameter::avp::UTF8String;
 use diameter::avp::Unsigned32;
 use diameter::dictionary::{self, Dictionary};
 use diameter::flags;
+use diameter::session::id::SessionIdGenerator;
 use diameter::transport::DiameterClient;
 use diameter::transport::DiameterClientConfig;
-use diameter::{ApplicationId, CommandCode, DiameterMessage};
+use diameter::typed::TypedMessage;
+use diameter::{ApplicationId, CommandCode, DiameterMessage, NodeConfig};
 use std::fs;
 use std::net::Ipv4Addr;
 use std::sync::Arc;
@@ -72,19 +76,21 @@ async fn send_cer(client: &mut DiameterClient, dict: Arc<Dictionary>) {
 
 async fn send_ccr(client: &mut DiameterClient, dict: Arc<Dictionary>) {
     let seq_num = client.get_next_seq_num();
-    let mut ccr = DiameterMessage::new(
-        CommandCode::CreditControl,
-        ApplicationId::CreditControl,
-        flags::REQUEST,
-        seq_num,
-        seq_num,
-        dict,
+    let node = NodeConfig::new("host.example.com", "realm.example.com");
+    let session_id = SessionIdGenerator::from_node(&node).generate();
+    let mut ccr = CreditControlRequest::new(
+        &session_id,
+        &node,
+        "realm.example.com",
+        "32251@3gpp.org",
+        CcRequestType::InitialRequest,
+        1000,
     );
-    ccr.add_avp(264, None, M, Identity::new("host.example.com").into());
-    ccr.add_avp(296, None, M, Identity::new("realm.example.com").into());
-    ccr.add_avp(263, None, M, UTF8String::new("ses;12345888").into());
-    ccr.add_avp(416, None, M, Enumerated::new(1).into());
-    ccr.add_avp(415, None, M, Unsigned32::new(1000).into());
+    ccr.subscription_id.push(SubscriptionId {
+        subscription_id_type: SubscriptionIdType::EndUserE164,
+        subscription_id_data: "6591234567".into(),
+    });
+    let mut ccr = ccr.to_message(seq_num, seq_num, &dict).unwrap();
     ccr.add_avp(
         1228,
         Some(10415),
@@ -95,4 +101,8 @@ async fn send_ccr(client: &mut DiameterClient, dict: Arc<Dictionary>) {
     let resp = client.send_message(ccr).await.unwrap();
     let cca = resp.await.unwrap();
     log::info!("Received rseponse: {}", cca);
+    match CreditControlAnswer::from_message(&cca) {
+        Ok(cca) => log::info!("Result-Code: {}", cca.result_code),
+        Err(e) => log::error!("Invalid CCA: {}", e),
+    }
 }
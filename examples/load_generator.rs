@@ -9,6 +9,7 @@ use diameter::avp::Unsigned32;
 use diameter::dictionary;
 use diameter::dictionary::Dictionary;
 use diameter::flags;
+use diameter::session::id::SessionIdGenerator;
 use diameter::transport::DiameterClient;
 use diameter::transport::DiameterClientConfig;
 use diameter::{ApplicationId, CommandCode, DiameterMessage};
@@ -69,12 +70,11 @@ async fn main() {
             send_cer(&mut client, Arc::clone(&dict)).await;
 
             // Send a batch of Credit-Control-Request Initial (CCR-I) Diameter message
-            let mut session_count = 0;
+            let session_ids = SessionIdGenerator::new("host.example.com");
             let mut ccri_futures = vec![];
             let batch_size = 10;
             for _ in 0..batch_size {
-                let session_id = format!("ses;{:09}", session_count);
-                session_count += 1;
+                let session_id = session_ids.generate();
                 let future = send_ccr_i(&mut client, &session_id, Arc::clone(&dict)).await;
                 ccri_futures.push(future);
             }
@@ -2,15 +2,15 @@ use chrono::Local;
 use diameter::avp::flags::M;
 use diameter::avp::Enumerated;
 use diameter::avp::Grouped;
-use diameter::avp::Identity;
 use diameter::avp::UTF8String;
 use diameter::avp::Unsigned32;
 use diameter::dictionary::{self, Dictionary};
-use diameter::flags;
+use diameter::result_code;
 use diameter::transport::DiameterServer;
 use diameter::transport::DiameterServerConfig;
 use diameter::CommandCode;
 use diameter::DiameterMessage;
+use diameter::NodeConfig;
 use std::fs;
 use std::io::Write;
 use std::sync::Arc;
@@ -58,35 +58,23 @@ async fn main() {
         .listen(
             move |req| {
                 let dict_ref2 = Arc::clone(&dict);
+                let node = NodeConfig::new("host.example.com", "realm.example.com");
                 async move {
                     log::info!("Received request: {}", req);
 
                     // Create a response message based on the received request
-                    let mut res = DiameterMessage::new(
-                        req.get_command_code(),
-                        req.get_application_id(),
-                        req.get_flags() ^ flags::REQUEST,
-                        req.get_hop_by_hop_id(),
-                        req.get_end_to_end_id(),
-                        Arc::clone(&dict_ref2),
-                    );
+                    let mut res =
+                        DiameterMessage::create_answer(&req, result_code::DIAMETER_SUCCESS, &node);
 
                     match req.get_command_code() {
                         CommandCode::CapabilitiesExchange => {
-                            res.add_avp(264, None, M, Identity::new("host.example.com").into());
-                            res.add_avp(296, None, M, Identity::new("realm.example.com").into());
                             res.add_avp(266, None, M, Unsigned32::new(35838).into());
                             res.add_avp(269, None, M, UTF8String::new("diameter-rs").into());
                             res.add_avp(258, None, M, Unsigned32::new(4).into());
-                            res.add_avp(268, None, M, Unsigned32::new(2001).into());
                         }
                         _ => {
-                            res.add_avp(264, None, M, Identity::new("host.example.com").into());
-                            res.add_avp(296, None, M, Identity::new("realm.example.com").into());
-                            res.add_avp(263, None, M, UTF8String::new("ses;123458890").into());
                             res.add_avp(416, None, M, Enumerated::new(1).into());
                             res.add_avp(415, None, M, Unsigned32::new(1000).into());
-                            res.add_avp(268, None, M, Unsigned32::new(2001).into());
 
                             let mut mscc = Grouped::new(vec![], Arc::clone(&dict_ref2));
                             mscc.add_avp(439, None, M, Unsigned32::new(7786).into());
@@ -0,0 +1,246 @@
+//! Accounting Record Buffer
+//!
+//! Accounting records that could not be delivered are kept in a
+//! [`RecordBuffer`] until they can be replayed, as described in
+//! [RFC 6733 section 9.4](https://tools.ietf.org/html/rfc6733#section-9.4).
+//!
+//! A buffer opened on a file keeps the encoded requests, one after the
+//! other, and syncs each record to disk before `push` returns, so records
+//! survive a restart of the process. A record left incomplete by a crash is
+//! discarded when the file is opened again.
+
+use crate::diameter::DiameterMessage;
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::session::store::file::sync_dir;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Length of the Diameter header, which carries the message length.
+const HEADER_LENGTH: usize = 20;
+
+/// Accounting requests waiting to be delivered, oldest first.
+#[derive(Debug, Default)]
+pub struct RecordBuffer {
+    path: Option<PathBuf>,
+    records: Vec<Vec<u8>>,
+    poisoned: bool,
+}
+
+impl RecordBuffer {
+    /// Creates a buffer that is lost when the process exits.
+    pub fn in_memory() -> RecordBuffer {
+        RecordBuffer::default()
+    }
+
+    /// Opens the buffer stored in the file at `path`, creating the file if
+    /// it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<RecordBuffer> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while data.len() - offset >= HEADER_LENGTH {
+            let length =
+                u32::from_be_bytes([0, data[offset + 1], data[offset + 2], data[offset + 3]])
+                    as usize;
+            if length < HEADER_LENGTH || offset + length > data.len() {
+                break;
+            }
+            records.push(data[offset..offset + length].to_vec());
+            offset += length;
+        }
+        if offset < data.len() {
+            log::warn!(
+                "Discarding {} bytes of incomplete accounting record in {}",
+                data.len() - offset,
+                path.display()
+            );
+            file.set_len(offset as u64)?;
+            file.sync_data()?;
+        }
+
+        Ok(RecordBuffer {
+            path: Some(path),
+            records,
+            poisoned: false,
+        })
+    }
+
+    /// Returns the number of buffered records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns true if no record is buffered.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Appends a record, writing it to the file before returning.
+    pub fn push(&mut self, record: &DiameterMessage) -> Result<()> {
+        if !record.is_request() {
+            return Err(Error::ClientError("Not a request".into()));
+        }
+        let mut encoded = Vec::new();
+        record.encode_to(&mut encoded)?;
+        if let Some(path) = &self.path {
+            if self.poisoned {
+                return Err(Error::IoError(std::io::Error::other(format!(
+                    "Accounting buffer {} holds an incomplete record",
+                    path.display()
+                ))));
+            }
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            let len = file.metadata()?.len();
+            let written = file.write_all(&encoded).and_then(|_| file.sync_data());
+            if let Err(e) = written {
+                // Cut the record off, lest it be read back when the file is opened
+                let truncated = file.set_len(len).and_then(|_| file.sync_data());
+                if let Err(e) = truncated {
+                    log::error!("Failed to truncate {}; error: {}", path.display(), e);
+                    self.poisoned = true;
+                }
+                return Err(e.into());
+            }
+        }
+        self.records.push(encoded);
+        Ok(())
+    }
+
+    /// Decodes the buffered records, oldest first.
+    ///
+    /// Each record is decoded on its own, so one that no longer decodes,
+    /// for instance with a different dictionary, yields its error in place.
+    pub fn records(&self, dict: &Arc<Dictionary>) -> Vec<Result<DiameterMessage>> {
+        self.records
+            .iter()
+            .map(|record| DiameterMessage::decode_from(&mut Cursor::new(record), Arc::clone(dict)))
+            .collect()
+    }
+
+    /// Removes the `count` oldest records, once they have been delivered.
+    pub fn remove_oldest(&mut self, count: usize) -> Result<()> {
+        let count = count.min(self.records.len());
+        if count == 0 {
+            return Ok(());
+        }
+        if let Some(path) = &self.path {
+            let mut tmp_path = path.clone().into_os_string();
+            tmp_path.push(".tmp");
+            let write = || -> Result<()> {
+                let mut file = File::create(&tmp_path)?;
+                for record in &self.records[count..] {
+                    file.write_all(record)?;
+                }
+                file.sync_data()?;
+                fs::rename(&tmp_path, path)?;
+                Ok(())
+            };
+            if let Err(e) = write() {
+                fs::remove_file(&tmp_path).ok();
+                return Err(e);
+            }
+            self.records.drain(..count);
+            self.poisoned = false;
+            // The rename itself is durable once the directory is synced
+            return sync_dir(path);
+        }
+        self.records.drain(..count);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::accounting::{AccountingRecordType, AccountingRequest};
+    use crate::dictionary;
+    use crate::node::NodeConfig;
+    use crate::typed::TypedMessage;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    fn record(dict: &Arc<Dictionary>, number: u32) -> DiameterMessage {
+        let node = NodeConfig::new("ctf.example.com", "example.com");
+        let acr = AccountingRequest::new(
+            "ctf.example.com;1;1",
+            &node,
+            "example.com",
+            AccountingRecordType::InterimRecord,
+            number,
+        );
+        acr.to_message(number, number, dict).unwrap()
+    }
+
+    fn numbers(buffer: &RecordBuffer, dict: &Arc<Dictionary>) -> Vec<u32> {
+        buffer
+            .records(dict)
+            .into_iter()
+            .map(|msg| {
+                msg.unwrap()
+                    .get::<u32, _>("Accounting-Record-Number")
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let dict = dict();
+        let mut buffer = RecordBuffer::in_memory();
+        assert!(buffer.is_empty());
+        buffer.push(&record(&dict, 1)).unwrap();
+        buffer.push(&record(&dict, 2)).unwrap();
+        assert_eq!(buffer.len(), 2);
+
+        buffer.remove_oldest(1).unwrap();
+        assert_eq!(numbers(&buffer, &dict), vec![2]);
+        assert_eq!(
+            buffer.records(&dict)[0]
+                .as_ref()
+                .unwrap()
+                .get_end_to_end_id(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_file_backed() {
+        let dict = dict();
+        let path =
+            std::env::temp_dir().join(format!("diameter-acct-buffer-{}.bin", std::process::id()));
+        fs::remove_file(&path).ok();
+
+        let mut buffer = RecordBuffer::open(&path).unwrap();
+        for number in 1..=3 {
+            buffer.push(&record(&dict, number)).unwrap();
+        }
+        buffer.remove_oldest(1).unwrap();
+
+        // Records survive reopening, an incomplete tail is discarded
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[1, 0, 0, 64, 0, 0]).unwrap();
+        drop(file);
+        let mut buffer = RecordBuffer::open(&path).unwrap();
+        assert_eq!(numbers(&buffer, &dict), vec![2, 3]);
+
+        buffer.push(&record(&dict, 4)).unwrap();
+        let buffer = RecordBuffer::open(&path).unwrap();
+        assert_eq!(numbers(&buffer, &dict), vec![2, 3, 4]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
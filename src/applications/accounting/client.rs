@@ -0,0 +1,756 @@
+//! Accounting Client Sessions
+//!
+//! The client side of the accounting session state machine of
+//! [RFC 6733 section 8.2](https://tools.ietf.org/html/rfc6733#section-8.2),
+//! running on top of a [`DiameterClient`] connection.
+//!
+//! Each [`AcctClientSession`] created from an [`AccountingClient`] numbers
+//! its records, supervises them with a Tx timer and applies
+//! Accounting-Realtime-Required when a record cannot be delivered: the
+//! service is terminated, the record is stored in the client's
+//! [`RecordBuffer`] for later replay, or the record is dropped. While the
+//! session is open, an [`InterimDue`](AcctSessionEvent::InterimDue) event is
+//! reported every Acct-Interim-Interval.
+//!
+//! ```no_run
+//! use diameter::applications::accounting::buffer::RecordBuffer;
+//! use diameter::applications::accounting::client::{AccountingClient, AcctClientConfig};
+//! use diameter::applications::accounting::AccountingRecordType;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("localhost:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle(&mut handler, dict_ref).await;
+//!     });
+//!
+//!     let node = NodeConfig::new("ctf.example.com", "example.com");
+//!     let config = AcctClientConfig::default();
+//!     let mut acct_client = AccountingClient::new(client, dict, node, config);
+//!     acct_client.set_buffer(RecordBuffer::open("/var/lib/ctf/acct.buf").unwrap());
+//!     let (mut session, _events) = acct_client.session("ctf.example.com;1;1", "example.com");
+//!
+//!     let acr = session.record(AccountingRecordType::StartRecord);
+//!     let outcome = session.send(acr).await.unwrap();
+//!     println!("{:?} -> {:?}", outcome, session.state());
+//! }
+//! ```
+
+use super::buffer::RecordBuffer;
+use super::{
+    AccountingAnswer, AccountingRealtimeRequired, AccountingRecordType, AccountingRequest,
+};
+use crate::diameter::{flags, result_code, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::DiameterClient;
+use crate::typed::TypedMessage;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// States of an accounting client session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcctSessionState {
+    /// No record is outstanding and no session is open.
+    Idle,
+    /// Waiting for the answer to the start record.
+    PendingS,
+    /// Waiting for the answer to an event record.
+    PendingE,
+    /// The session is open and interim records may be sent.
+    Open,
+    /// Waiting for the answer to an interim record.
+    PendingI,
+    /// Waiting for the answer to the stop record.
+    PendingL,
+}
+
+/// Events reported to the application while a session runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcctSessionEvent {
+    /// The session moved from one state to another.
+    StateChanged {
+        from: AcctSessionState,
+        to: AcctSessionState,
+    },
+    /// The Acct-Interim-Interval elapsed and an interim record is due.
+    InterimDue,
+    /// The Tx timer expired before the record was answered.
+    TxExpired(AccountingRecordType),
+    /// The end user's service must be terminated.
+    TerminateService,
+}
+
+/// Outcome of a record sent on an [`AcctClientSession`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AcctOutcome {
+    /// The server answered; the session state follows the Result-Code.
+    Answer(Box<AccountingAnswer>),
+    /// The record was not delivered and the end user's service must be
+    /// terminated.
+    Terminate,
+    /// The record was not delivered and was stored in the client's buffer.
+    Buffered,
+    /// The record was not delivered and was dropped.
+    Lost,
+}
+
+/// Configuration of an [`AccountingClient`].
+///
+/// The interim interval and the realtime requirement apply until the server
+/// overrides them in an Accounting-Answer.
+#[derive(Debug, Clone, Copy)]
+pub struct AcctClientConfig {
+    pub tx_timeout: Duration,
+    pub interim_interval: Option<Duration>,
+    pub realtime_required: AccountingRealtimeRequired,
+}
+
+impl Default for AcctClientConfig {
+    /// The RFC 6733 default of DELIVER_AND_GRANT, no interim records and a
+    /// Tx timer of 10 seconds.
+    fn default() -> AcctClientConfig {
+        AcctClientConfig {
+            tx_timeout: Duration::from_secs(10),
+            interim_interval: None,
+            realtime_required: AccountingRealtimeRequired::DeliverAndGrant,
+        }
+    }
+}
+
+/// An accounting client connected to a charging data function, with the
+/// buffer that undelivered records are stored in.
+///
+/// The connection and the buffer are shared by all sessions created from
+/// the client; the `DiameterClient` must be connected and have its handler
+/// running.
+#[derive(Clone)]
+pub struct AccountingClient {
+    client: Arc<Mutex<DiameterClient>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: AcctClientConfig,
+    buffer: Arc<Mutex<RecordBuffer>>,
+    replaying: Arc<Mutex<()>>,
+}
+
+impl AccountingClient {
+    /// Creates a client sending to `client`, buffering records in memory.
+    pub fn new(
+        client: DiameterClient,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: AcctClientConfig,
+    ) -> AccountingClient {
+        AccountingClient {
+            client: Arc::new(Mutex::new(client)),
+            dict,
+            node,
+            config,
+            buffer: Arc::new(Mutex::new(RecordBuffer::in_memory())),
+            replaying: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Replaces the buffer undelivered records are stored in, such as one
+    /// opened on a file with [`RecordBuffer::open`].
+    ///
+    /// Only sessions created afterwards use the new buffer.
+    pub fn set_buffer(&mut self, buffer: RecordBuffer) {
+        self.buffer = Arc::new(Mutex::new(buffer));
+    }
+
+    /// Returns the number of records waiting in the buffer.
+    pub async fn buffered(&self) -> usize {
+        self.buffer.lock().await.len()
+    }
+
+    /// Creates an idle session, returning it with the receiver of its events.
+    pub fn session(
+        &self,
+        session_id: &str,
+        destination_realm: &str,
+    ) -> (AcctClientSession, mpsc::UnboundedReceiver<AcctSessionEvent>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        let session = AcctClientSession {
+            client: self.clone(),
+            session_id: session_id.into(),
+            destination_realm: destination_realm.into(),
+            state: AcctSessionState::Idle,
+            record_number: 0,
+            interim_interval: self.config.interim_interval,
+            realtime_required: self.config.realtime_required,
+            interim_timer: None,
+            events,
+        };
+        (session, receiver)
+    }
+
+    /// Sends the buffered records, oldest first, with the T flag set,
+    /// returning how many were delivered.
+    ///
+    /// Replay stops at the first record that is not delivered; it and the
+    /// records after it stay in the buffer. A record that no longer decodes
+    /// is logged and dropped. Replay also runs after each record a session
+    /// delivers while the buffer is not empty.
+    pub async fn replay(&self) -> Result<usize> {
+        // Only one replay runs at a time, so the records sent are still the
+        // oldest ones when they are removed
+        let _replaying = self.replaying.lock().await;
+        let buffer = Arc::clone(&self.buffer);
+        let records = buffer.lock().await.records(&self.dict);
+        let mut delivered = 0;
+        let mut removed = 0;
+        for record in records {
+            let mut req = match record {
+                Ok(req) => req,
+                Err(e) => {
+                    log::error!("Dropping undecodable accounting record; error: {}", e);
+                    removed += 1;
+                    continue;
+                }
+            };
+            req.set_flags(req.get_flags() | flags::RETRANSMIT);
+            match self.transmit(req).await {
+                Ok(_) => {
+                    delivered += 1;
+                    removed += 1;
+                }
+                Err(failure) => {
+                    log::warn!("Accounting record replay failed; failure: {}", failure);
+                    break;
+                }
+            }
+        }
+        buffer.lock().await.remove_oldest(removed)?;
+        Ok(delivered)
+    }
+
+    async fn store(&self, req: DiameterMessage) -> Result<()> {
+        self.buffer.lock().await.push(&req)
+    }
+
+    /// Sends a request with a new Hop-by-Hop Identifier and waits for an
+    /// answer the server did not reject as undeliverable.
+    async fn transmit(
+        &self,
+        mut req: DiameterMessage,
+    ) -> std::result::Result<DiameterMessage, Failure> {
+        let response = {
+            let mut client = self.client.lock().await;
+            req.set_hop_by_hop_id(client.get_next_seq_num());
+            client
+                .send_message(req)
+                .await
+                .map_err(Failure::SendFailed)?
+        };
+        let answer = match tokio::time::timeout(self.config.tx_timeout, response).await {
+            Ok(Ok(answer)) => answer,
+            Ok(Err(e)) => return Err(Failure::SendFailed(e)),
+            Err(_) => return Err(Failure::TxExpired),
+        };
+        match answer.get::<u32, _>("Result-Code") {
+            Ok(
+                code @ (result_code::DIAMETER_UNABLE_TO_DELIVER | result_code::DIAMETER_TOO_BUSY),
+            ) => Err(Failure::ProtocolError(code)),
+            _ => Ok(answer),
+        }
+    }
+}
+
+/// Why a record was not delivered.
+#[derive(Debug)]
+enum Failure {
+    SendFailed(Error),
+    TxExpired,
+    ProtocolError(u32),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::SendFailed(e) => write!(f, "failed to send: {}", e),
+            Failure::TxExpired => write!(f, "Tx timer expired"),
+            Failure::ProtocolError(code) => write!(f, "Result-Code {}", code),
+        }
+    }
+}
+
+/// An accounting session on the client side.
+pub struct AcctClientSession {
+    client: AccountingClient,
+    session_id: String,
+    destination_realm: String,
+    state: AcctSessionState,
+    record_number: u32,
+    interim_interval: Option<Duration>,
+    realtime_required: AccountingRealtimeRequired,
+    interim_timer: Option<JoinHandle<()>>,
+    events: mpsc::UnboundedSender<AcctSessionEvent>,
+}
+
+impl AcctClientSession {
+    /// Returns the Session-Id of the session.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Returns the current state of the session.
+    pub fn state(&self) -> AcctSessionState {
+        self.state
+    }
+
+    /// Returns the interval between interim records, if any.
+    pub fn interim_interval(&self) -> Option<Duration> {
+        self.interim_interval
+    }
+
+    /// Returns the Accounting-Realtime-Required in effect.
+    pub fn realtime_required(&self) -> AccountingRealtimeRequired {
+        self.realtime_required
+    }
+
+    /// Creates the next record of the session, for the application to
+    /// complete before passing it to [`send`](AcctClientSession::send).
+    pub fn record(&self, accounting_record_type: AccountingRecordType) -> AccountingRequest {
+        AccountingRequest::new(
+            &self.session_id,
+            &self.client.node,
+            &self.destination_realm,
+            accounting_record_type,
+            self.record_number,
+        )
+    }
+
+    /// Sends a record and drives the session until it is answered or its
+    /// failure has been handled.
+    ///
+    /// Start and event records are sent from `Idle`, interim and stop
+    /// records from `Open`; Session-Id and Accounting-Record-Number are set
+    /// by the session. An error is returned for a record the state does not
+    /// allow, or for an answer that cannot be decoded.
+    pub async fn send(&mut self, mut acr: AccountingRequest) -> Result<AcctOutcome> {
+        let pending = match (self.state, acr.accounting_record_type) {
+            (AcctSessionState::Idle, AccountingRecordType::StartRecord) => {
+                AcctSessionState::PendingS
+            }
+            (AcctSessionState::Idle, AccountingRecordType::EventRecord) => {
+                AcctSessionState::PendingE
+            }
+            (AcctSessionState::Open, AccountingRecordType::InterimRecord) => {
+                AcctSessionState::PendingI
+            }
+            (AcctSessionState::Open, AccountingRecordType::StopRecord) => {
+                AcctSessionState::PendingL
+            }
+            (state, record_type) => {
+                return Err(Error::ClientError(format!(
+                    "Cannot send {:?} in state {:?}",
+                    record_type, state
+                )))
+            }
+        };
+        acr.session_id = self.session_id.clone();
+        acr.accounting_record_number = self.record_number;
+        let end_to_end_id = self.client.client.lock().await.get_next_seq_num();
+        let req = acr.to_message(0, end_to_end_id, &self.client.dict)?;
+        self.record_number = self.record_number.wrapping_add(1);
+        self.set_state(pending);
+
+        let failure = match self.client.transmit(req).await {
+            Ok(answer) => {
+                let aca = match AccountingAnswer::from_message(&answer) {
+                    Ok(aca) => aca,
+                    Err(e) => {
+                        self.set_state(AcctSessionState::Idle);
+                        return Err(e);
+                    }
+                };
+                let outcome = self.on_answer(aca);
+                if self.client.buffered().await > 0 {
+                    self.client.replay().await.ok();
+                }
+                return Ok(outcome);
+            }
+            Err(failure) => failure,
+        };
+        log::warn!(
+            "Accounting record failed; session: {}, failure: {}",
+            self.session_id,
+            failure
+        );
+        if let Failure::TxExpired = failure {
+            self.events
+                .send(AcctSessionEvent::TxExpired(acr.accounting_record_type))
+                .ok();
+        }
+
+        let outcome = match self.realtime_required {
+            AccountingRealtimeRequired::DeliverAndGrant => {
+                if pending == AcctSessionState::PendingL {
+                    AcctOutcome::Lost
+                } else {
+                    self.events.send(AcctSessionEvent::TerminateService).ok();
+                    AcctOutcome::Terminate
+                }
+            }
+            AccountingRealtimeRequired::GrantAndStore => match self
+                .client
+                .store(acr.to_message(0, end_to_end_id, &self.client.dict)?)
+                .await
+            {
+                Ok(()) => AcctOutcome::Buffered,
+                Err(e) => {
+                    log::error!("Failed to store accounting record; error: {}", e);
+                    AcctOutcome::Lost
+                }
+            },
+            AccountingRealtimeRequired::GrantAndLose | AccountingRealtimeRequired::Unknown(_) => {
+                AcctOutcome::Lost
+            }
+        };
+        let next = match (pending, &outcome) {
+            (AcctSessionState::PendingS | AcctSessionState::PendingI, AcctOutcome::Terminate) => {
+                AcctSessionState::Idle
+            }
+            (AcctSessionState::PendingS | AcctSessionState::PendingI, _) => AcctSessionState::Open,
+            _ => AcctSessionState::Idle,
+        };
+        self.set_state(next);
+        Ok(outcome)
+    }
+
+    fn on_answer(&mut self, aca: AccountingAnswer) -> AcctOutcome {
+        if let Some(interval) = aca.acct_interim_interval {
+            self.interim_interval = match interval {
+                0 => None,
+                seconds => Some(Duration::from_secs(seconds.into())),
+            };
+        }
+        match aca.accounting_realtime_required {
+            Some(AccountingRealtimeRequired::Unknown(value)) => {
+                log::warn!("Ignoring unknown Accounting-Realtime-Required {}", value);
+            }
+            Some(realtime_required) => self.realtime_required = realtime_required,
+            None => {}
+        }
+
+        let success = (2000..3000).contains(&aca.result_code);
+        let next = match self.state {
+            AcctSessionState::PendingS | AcctSessionState::PendingI
+                if success
+                    || self.realtime_required != AccountingRealtimeRequired::DeliverAndGrant =>
+            {
+                AcctSessionState::Open
+            }
+            _ => AcctSessionState::Idle,
+        };
+        if !success
+            && self.realtime_required == AccountingRealtimeRequired::DeliverAndGrant
+            && self.state != AcctSessionState::PendingL
+        {
+            self.events.send(AcctSessionEvent::TerminateService).ok();
+        }
+        self.set_state(next);
+        AcctOutcome::Answer(Box::new(aca))
+    }
+
+    fn set_state(&mut self, state: AcctSessionState) {
+        if state == AcctSessionState::Open {
+            self.start_interim_timer();
+        } else if let Some(timer) = self.interim_timer.take() {
+            timer.abort();
+        }
+        if self.state != state {
+            let from = std::mem::replace(&mut self.state, state);
+            self.events
+                .send(AcctSessionEvent::StateChanged { from, to: state })
+                .ok();
+        }
+    }
+
+    /// Restarts the interim timer, so the next interim record is due one
+    /// interval after the last record was handled.
+    fn start_interim_timer(&mut self) {
+        if let Some(timer) = self.interim_timer.take() {
+            timer.abort();
+        }
+        if let Some(interval) = self.interim_interval {
+            let events = self.events.clone();
+            self.interim_timer = Some(tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    if events.send(AcctSessionEvent::InterimDue).is_err() {
+                        return;
+                    }
+                }
+            }));
+        }
+    }
+}
+
+impl Drop for AcctClientSession {
+    fn drop(&mut self) {
+        if let Some(timer) = self.interim_timer.take() {
+            timer.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary;
+    use crate::transport::{DiameterClientConfig, DiameterServer, DiameterServerConfig};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    /// Starts a server answering every record with `result_code` after
+    /// `delay`, counting the retransmitted records it receives.
+    async fn start_server(
+        addr: &str,
+        result_code: u32,
+        delay: Duration,
+        interim_interval: Option<u32>,
+    ) -> Arc<AtomicU32> {
+        let dict = dict();
+        let retransmitted = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&retransmitted);
+        let mut server = DiameterServer::new(addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| {
+                        let dict = Arc::clone(&dict);
+                        let counter = Arc::clone(&counter);
+                        async move {
+                            tokio::time::sleep(delay).await;
+                            if req.get_flags() & flags::RETRANSMIT != 0 {
+                                counter.fetch_add(1, Ordering::SeqCst);
+                            }
+                            let acr = AccountingRequest::from_message(&req)?;
+                            let node = NodeConfig::new("cdf.example.com", "example.com");
+                            let mut aca = AccountingAnswer::new(&acr, result_code, &node);
+                            aca.acct_interim_interval = interim_interval;
+                            aca.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+        retransmitted
+    }
+
+    async fn connect(addr: &str) -> DiameterClient {
+        let mut client = DiameterClient::new(addr, client_config());
+        let mut handler = client.connect().await.unwrap();
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict()).await;
+        });
+        client
+    }
+
+    fn client_config() -> DiameterClientConfig {
+        DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        }
+    }
+
+    fn node() -> NodeConfig {
+        NodeConfig::new("ctf.example.com", "example.com")
+    }
+
+    fn drain(events: &mut mpsc::UnboundedReceiver<AcctSessionEvent>) -> Vec<AcctSessionEvent> {
+        let mut drained = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            drained.push(event);
+        }
+        drained
+    }
+
+    fn answer(outcome: AcctOutcome) -> AccountingAnswer {
+        match outcome {
+            AcctOutcome::Answer(aca) => *aca,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        start_server("0.0.0.0:3878", 2001, Duration::ZERO, None).await;
+        let client = connect("localhost:3878").await;
+        let acct_client =
+            AccountingClient::new(client, dict(), node(), AcctClientConfig::default());
+        let (mut session, mut events) = acct_client.session("ctf;1", "example.com");
+
+        let acr = session.record(AccountingRecordType::InterimRecord);
+        assert!(session.send(acr).await.is_err());
+
+        let acr = session.record(AccountingRecordType::StartRecord);
+        let aca = answer(session.send(acr).await.unwrap());
+        assert_eq!(aca.accounting_record_number, 0);
+        assert_eq!(session.state(), AcctSessionState::Open);
+
+        let acr = session.record(AccountingRecordType::InterimRecord);
+        let aca = answer(session.send(acr).await.unwrap());
+        assert_eq!(
+            aca.accounting_record_type,
+            AccountingRecordType::InterimRecord
+        );
+        assert_eq!(aca.accounting_record_number, 1);
+
+        let acr = session.record(AccountingRecordType::StopRecord);
+        let aca = answer(session.send(acr).await.unwrap());
+        assert_eq!(aca.accounting_record_number, 2);
+        assert_eq!(session.state(), AcctSessionState::Idle);
+
+        use AcctSessionState::*;
+        let changes: Vec<_> = drain(&mut events)
+            .into_iter()
+            .map(|event| match event {
+                AcctSessionEvent::StateChanged { to, .. } => to,
+                event => panic!("unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![PendingS, Open, PendingI, Open, PendingL, Idle]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interim_interval() {
+        start_server("0.0.0.0:3879", 2001, Duration::ZERO, Some(0)).await;
+        let client = connect("localhost:3879").await;
+        let config = AcctClientConfig {
+            interim_interval: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let acct_client = AccountingClient::new(client, dict(), node(), config);
+        let (mut session, mut events) = acct_client.session("ctf;2", "example.com");
+        assert_eq!(session.interim_interval(), Some(Duration::from_millis(50)));
+
+        // The server disables interim records in its answer
+        let acr = session.record(AccountingRecordType::StartRecord);
+        session.send(acr).await.unwrap();
+        assert_eq!(session.interim_interval(), None);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(!drain(&mut events).contains(&AcctSessionEvent::InterimDue));
+
+        // Without an override, interim records fall due while the session is open
+        let (mut session, mut events) = acct_client.session("ctf;3", "example.com");
+        session.interim_interval = Some(Duration::from_millis(50));
+        session.set_state(AcctSessionState::Open);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(drain(&mut events).contains(&AcctSessionEvent::InterimDue));
+
+        session.set_state(AcctSessionState::Idle);
+        drain(&mut events);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(drain(&mut events).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deliver_and_grant_terminates() {
+        start_server("0.0.0.0:3880", 2001, Duration::from_millis(300), None).await;
+        let client = connect("localhost:3880").await;
+        let config = AcctClientConfig {
+            tx_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let acct_client = AccountingClient::new(client, dict(), node(), config);
+        let (mut session, mut events) = acct_client.session("ctf;4", "example.com");
+
+        let acr = session.record(AccountingRecordType::StartRecord);
+        assert_eq!(session.send(acr).await.unwrap(), AcctOutcome::Terminate);
+        assert_eq!(session.state(), AcctSessionState::Idle);
+        assert_eq!(acct_client.buffered().await, 0);
+
+        let events = drain(&mut events);
+        assert!(events.contains(&AcctSessionEvent::TxExpired(
+            AccountingRecordType::StartRecord
+        )));
+        assert!(events.contains(&AcctSessionEvent::TerminateService));
+    }
+
+    #[tokio::test]
+    async fn test_grant_and_lose() {
+        let client = DiameterClient::new("localhost:3881", client_config());
+        let config = AcctClientConfig {
+            realtime_required: AccountingRealtimeRequired::GrantAndLose,
+            ..Default::default()
+        };
+        let acct_client = AccountingClient::new(client, dict(), node(), config);
+        let (mut session, mut events) = acct_client.session("ctf;5", "example.com");
+
+        let acr = session.record(AccountingRecordType::StartRecord);
+        assert_eq!(session.send(acr).await.unwrap(), AcctOutcome::Lost);
+        assert_eq!(session.state(), AcctSessionState::Open);
+        assert_eq!(acct_client.buffered().await, 0);
+        assert!(!drain(&mut events).contains(&AcctSessionEvent::TerminateService));
+    }
+
+    #[tokio::test]
+    async fn test_grant_and_store_replay() {
+        let path =
+            std::env::temp_dir().join(format!("diameter-acct-client-{}.bin", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        // The records are stored while the server is unreachable
+        let client = DiameterClient::new("localhost:3882", client_config());
+        let config = AcctClientConfig {
+            realtime_required: AccountingRealtimeRequired::GrantAndStore,
+            ..Default::default()
+        };
+        let mut acct_client = AccountingClient::new(client, dict(), node(), config);
+        acct_client.set_buffer(RecordBuffer::open(&path).unwrap());
+        let (mut session, _events) = acct_client.session("ctf;6", "example.com");
+
+        let acr = session.record(AccountingRecordType::StartRecord);
+        assert_eq!(session.send(acr).await.unwrap(), AcctOutcome::Buffered);
+        assert_eq!(session.state(), AcctSessionState::Open);
+        let acr = session.record(AccountingRecordType::StopRecord);
+        assert_eq!(session.send(acr).await.unwrap(), AcctOutcome::Buffered);
+        assert_eq!(session.state(), AcctSessionState::Idle);
+        assert_eq!(acct_client.buffered().await, 2);
+        drop(acct_client);
+        drop(session);
+
+        // After a restart, they are replayed once a record is delivered
+        let retransmitted = start_server("0.0.0.0:3882", 2001, Duration::ZERO, None).await;
+        let client = connect("localhost:3882").await;
+        let mut acct_client = AccountingClient::new(client, dict(), node(), config);
+        acct_client.set_buffer(RecordBuffer::open(&path).unwrap());
+        assert_eq!(acct_client.buffered().await, 2);
+
+        let (mut session, _events) = acct_client.session("ctf;7", "example.com");
+        let acr = session.record(AccountingRecordType::EventRecord);
+        answer(session.send(acr).await.unwrap());
+        assert_eq!(acct_client.buffered().await, 0);
+        assert_eq!(retransmitted.load(Ordering::SeqCst), 2);
+        assert!(RecordBuffer::open(&path).unwrap().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
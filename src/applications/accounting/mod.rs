@@ -0,0 +1,300 @@
+//! Diameter Base Accounting
+//!
+//! Typed Accounting-Request and Accounting-Answer messages, as defined in
+//! [RFC 6733 section 9](https://tools.ietf.org/html/rfc6733#section-9) and
+//! used by the 3GPP Rf interface, together with an accounting
+//! [`client`](client) and the [`buffer`](buffer) it stores undelivered
+//! records in.
+//!
+//! ```
+//! use diameter::applications::accounting::{
+//!     AccountingAnswer, AccountingRecordType, AccountingRequest,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//! let node = NodeConfig::new("ctf.example.com", "example.com");
+//!
+//! let mut acr = AccountingRequest::new(
+//!     "ctf.example.com;1;1",
+//!     &node,
+//!     "example.com",
+//!     AccountingRecordType::StartRecord,
+//!     0,
+//! );
+//! acr.service_context_id = Some("32260@3gpp.org".into());
+//!
+//! let msg = acr.to_message(1, 1, &dict).unwrap();
+//! assert_eq!(AccountingRequest::from_message(&msg).unwrap(), acr);
+//!
+//! let cdf = NodeConfig::new("cdf.example.com", "example.com");
+//! let aca = AccountingAnswer::new(&acr, 2001, &cdf);
+//! assert_eq!(aca.accounting_record_number, 0);
+//! ```
+
+pub mod buffer;
+pub mod client;
+
+use crate::applications::common::{ProxyInfo, VendorSpecificApplicationId};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::DiameterMessage;
+
+/// Acct-Application-Id of the base accounting application.
+pub const APPLICATION_ID: u32 = 3;
+
+enumerated! {
+    /// Values of the Accounting-Record-Type AVP (480).
+    pub enum AccountingRecordType ("Accounting-Record-Type") {
+        EventRecord = 1,
+        StartRecord = 2,
+        InterimRecord = 3,
+        StopRecord = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Accounting-Realtime-Required AVP (483).
+    pub enum AccountingRealtimeRequired ("Accounting-Realtime-Required") {
+        DeliverAndGrant = 1,
+        GrantAndStore = 2,
+        GrantAndLose = 3,
+    }
+}
+
+/// Accounting-Request (ACR), RFC 6733 section 9.7.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Accounting, application = Accounting, request, proxyable)]
+pub struct AccountingRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Accounting-Record-Type")]
+    pub accounting_record_type: AccountingRecordType,
+    #[avp(name = "Accounting-Record-Number")]
+    pub accounting_record_number: u32,
+    #[avp(name = "Acct-Application-Id")]
+    pub acct_application_id: Option<u32>,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Accounting-Sub-Session-Id")]
+    pub accounting_sub_session_id: Option<u64>,
+    #[avp(name = "Accounting-Session-Id")]
+    pub accounting_session_id: Option<Vec<u8>>,
+    #[avp(name = "Acct-Multi-Session-Id")]
+    pub acct_multi_session_id: Option<String>,
+    #[avp(name = "Acct-Interim-Interval")]
+    pub acct_interim_interval: Option<u32>,
+    #[avp(name = "Accounting-Realtime-Required")]
+    pub accounting_realtime_required: Option<AccountingRealtimeRequired>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Event-Timestamp")]
+    pub event_timestamp: Option<DateTime<Utc>>,
+    #[avp(name = "Service-Context-Id")]
+    pub service_context_id: Option<String>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Accounting-Answer (ACA), RFC 6733 section 9.7.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Accounting, application = Accounting, proxyable)]
+pub struct AccountingAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Accounting-Record-Type")]
+    pub accounting_record_type: AccountingRecordType,
+    #[avp(name = "Accounting-Record-Number")]
+    pub accounting_record_number: u32,
+    #[avp(name = "Acct-Application-Id")]
+    pub acct_application_id: Option<u32>,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Accounting-Sub-Session-Id")]
+    pub accounting_sub_session_id: Option<u64>,
+    #[avp(name = "Accounting-Session-Id")]
+    pub accounting_session_id: Option<Vec<u8>>,
+    #[avp(name = "Acct-Multi-Session-Id")]
+    pub acct_multi_session_id: Option<String>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Acct-Interim-Interval")]
+    pub acct_interim_interval: Option<u32>,
+    #[avp(name = "Accounting-Realtime-Required")]
+    pub accounting_realtime_required: Option<AccountingRealtimeRequired>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Event-Timestamp")]
+    pub event_timestamp: Option<DateTime<Utc>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl AccountingRequest {
+    /// Creates a request with the mandatory AVPs and the base accounting
+    /// Acct-Application-Id, taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        accounting_record_type: AccountingRecordType,
+        accounting_record_number: u32,
+    ) -> AccountingRequest {
+        AccountingRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            accounting_record_type,
+            accounting_record_number,
+            acct_application_id: Some(APPLICATION_ID),
+            vendor_specific_application_id: None,
+            user_name: None,
+            destination_host: None,
+            accounting_sub_session_id: None,
+            accounting_session_id: None,
+            acct_multi_session_id: None,
+            acct_interim_interval: None,
+            accounting_realtime_required: None,
+            origin_state_id: None,
+            event_timestamp: None,
+            service_context_id: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AccountingAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying the
+    /// Session-Id, the record type and number, the application and
+    /// sub-session identifiers and Proxy-Info, and taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        request: &AccountingRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> AccountingAnswer {
+        AccountingAnswer {
+            session_id: request.session_id.clone(),
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            accounting_record_type: request.accounting_record_type,
+            accounting_record_number: request.accounting_record_number,
+            acct_application_id: request.acct_application_id,
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            user_name: request.user_name.clone(),
+            accounting_sub_session_id: request.accounting_sub_session_id,
+            accounting_session_id: request.accounting_session_id.clone(),
+            acct_multi_session_id: request.acct_multi_session_id.clone(),
+            error_message: None,
+            error_reporting_host: None,
+            acct_interim_interval: None,
+            accounting_realtime_required: None,
+            origin_state_id: None,
+            event_timestamp: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    #[test]
+    fn test_acr_round_trip() {
+        let dict = dict();
+        let node = NodeConfig::new("ctf.example.com", "example.com");
+
+        let mut acr = AccountingRequest::new(
+            "ctf.example.com;1;2",
+            &node,
+            "example.com",
+            AccountingRecordType::InterimRecord,
+            3,
+        );
+        acr.accounting_session_id = Some(vec![0x01, 0x02]);
+        acr.accounting_sub_session_id = Some(7);
+        acr.acct_interim_interval = Some(300);
+        acr.accounting_realtime_required = Some(AccountingRealtimeRequired::GrantAndStore);
+        acr.event_timestamp = Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        acr.proxy_info.push(ProxyInfo {
+            proxy_host: "proxy.example.com".into(),
+            proxy_state: b"state".to_vec(),
+        });
+
+        let msg = acr.to_message(10, 20, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::Accounting);
+        assert_eq!(msg.get_application_id(), ApplicationId::Accounting);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        assert_eq!(msg.get::<i32, _>("Accounting-Record-Type").unwrap(), 3);
+        assert_eq!(msg.get::<u32, _>("Accounting-Record-Number").unwrap(), 3);
+        assert_eq!(msg.get::<u32, _>("Acct-Application-Id").unwrap(), 3);
+
+        let decoded = AccountingRequest::from_message(&msg).unwrap();
+        assert_eq!(decoded, acr);
+    }
+
+    #[test]
+    fn test_aca_copies_request() {
+        let dict = dict();
+        let node = NodeConfig::new("ctf.example.com", "example.com");
+        let mut acr = AccountingRequest::new(
+            "ctf.example.com;1;3",
+            &node,
+            "example.com",
+            AccountingRecordType::StopRecord,
+            5,
+        );
+        acr.accounting_sub_session_id = Some(9);
+
+        let cdf = NodeConfig::new("cdf.example.com", "example.com");
+        let mut aca = AccountingAnswer::new(&acr, 2001, &cdf);
+        aca.acct_interim_interval = Some(0);
+        assert_eq!(aca.session_id, acr.session_id);
+        assert_eq!(aca.accounting_record_type, AccountingRecordType::StopRecord);
+        assert_eq!(aca.accounting_record_number, 5);
+        assert_eq!(aca.accounting_sub_session_id, Some(9));
+        assert_eq!(aca.origin_host, "cdf.example.com");
+
+        let msg = aca.to_message(10, 20, &dict).unwrap();
+        assert!(!msg.is_request());
+        assert_eq!(AccountingAnswer::from_message(&msg).unwrap(), aca);
+    }
+}
@@ -0,0 +1,107 @@
+//! Base protocol and 3GPP AVPs shared by the applications.
+
+use crate::GroupedAvp;
+
+/// Vendor-Id of 3GPP, used by the AVPs and Experimental-Result codes of the
+/// 3GPP applications.
+pub const TGPP_VENDOR_ID: u32 = 10415;
+
+/// The Proxy-Info AVP (284), added by proxies and echoed back in answers.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ProxyInfo {
+    #[avp(name = "Proxy-Host")]
+    pub proxy_host: String,
+    #[avp(name = "Proxy-State")]
+    pub proxy_state: Vec<u8>,
+}
+
+enumerated! {
+    /// Values of the Termination-Cause AVP (295).
+    pub enum TerminationCause ("Termination-Cause") {
+        DiameterLogout = 1,
+        DiameterServiceNotProvided = 2,
+        DiameterBadAnswer = 3,
+        DiameterAdministrative = 4,
+        DiameterLinkBroken = 5,
+        DiameterAuthExpired = 6,
+        DiameterUserMoved = 7,
+        DiameterSessionTimeout = 8,
+    }
+}
+
+enumerated! {
+    /// Values of the Re-Auth-Request-Type AVP (285).
+    pub enum ReAuthRequestType ("Re-Auth-Request-Type") {
+        AuthorizeOnly = 0,
+        AuthorizeAuthenticate = 1,
+    }
+}
+
+/// The Experimental-Result AVP (297), carrying a vendor-specific result
+/// instead of a Result-Code.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ExperimentalResult {
+    #[avp(name = "Vendor-Id")]
+    pub vendor_id: u32,
+    #[avp(name = "Experimental-Result-Code")]
+    pub experimental_result_code: u32,
+}
+
+/// The Supported-Features AVP (628) of 3GPP TS 29.229, advertising the
+/// optional features of a 3GPP application.
+///
+/// It is defined by the 3GPP dictionaries in `dict/`, not the default one.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct SupportedFeatures {
+    #[avp(name = "Vendor-Id")]
+    pub vendor_id: u32,
+    #[avp(name = "Feature-List-ID")]
+    pub feature_list_id: u32,
+    #[avp(name = "Feature-List")]
+    pub feature_list: u32,
+}
+
+enumerated! {
+    /// Values of the Auth-Request-Type AVP (274).
+    pub enum AuthRequestType ("Auth-Request-Type") {
+        AuthenticateOnly = 1,
+        AuthorizeOnly = 2,
+        AuthorizeAuthenticate = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Auth-Session-State AVP (277).
+    pub enum AuthSessionState ("Auth-Session-State") {
+        StateMaintained = 0,
+        NoStateMaintained = 1,
+    }
+}
+
+/// The Vendor-Specific-Application-Id AVP (260), advertising a vendor
+/// application.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct VendorSpecificApplicationId {
+    #[avp(name = "Vendor-Id")]
+    pub vendor_id: u32,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: Option<u32>,
+    #[avp(name = "Acct-Application-Id")]
+    pub acct_application_id: Option<u32>,
+}
+
+impl VendorSpecificApplicationId {
+    /// Creates the AVP for the auth application `auth_application_id` of
+    /// `vendor_id`.
+    pub fn auth(vendor_id: u32, auth_application_id: u32) -> VendorSpecificApplicationId {
+        VendorSpecificApplicationId {
+            vendor_id,
+            auth_application_id: Some(auth_application_id),
+            acct_application_id: None,
+        }
+    }
+}
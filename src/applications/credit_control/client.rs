@@ -0,0 +1,684 @@
+//! Credit-Control Client Sessions
+//!
+//! The client side of the credit-control session state machine of
+//! [RFC 4006 section 7](https://tools.ietf.org/html/rfc4006#section-7),
+//! running on top of [`DiameterClient`] connections.
+//!
+//! A [`CreditControlClient`] holds the connection to the primary
+//! credit-control server and to any alternate servers. Each
+//! [`CcClientSession`] created from it numbers its requests, supervises them
+//! with the Tx timer, applies Credit-Control-Failure-Handling and
+//! Direct-Debiting-Failure-Handling when the server does not answer, and
+//! moves to an alternate server when failover is supported. State changes
+//! and other [`CcSessionEvent`]s are reported on the channel returned with
+//! the session.
+//!
+//! ```no_run
+//! use diameter::applications::credit_control::client::{CcClientConfig, CreditControlClient};
+//! use diameter::applications::credit_control::CcRequestType;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("localhost:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle(&mut handler, dict_ref).await;
+//!     });
+//!
+//!     let node = NodeConfig::new("pcef.example.com", "example.com");
+//!     let cc_client = CreditControlClient::new(client, dict, node, CcClientConfig::default());
+//!     let (mut session, _events) =
+//!         cc_client.session("pcef.example.com;1;1", "example.com", "32251@3gpp.org");
+//!
+//!     let ccr = session.request(CcRequestType::InitialRequest);
+//!     let outcome = session.send(ccr).await.unwrap();
+//!     println!("{:?} -> {:?}", outcome, session.state());
+//! }
+//! ```
+
+use super::result_code::DIAMETER_CREDIT_CONTROL_NOT_APPLICABLE;
+use super::{
+    CcRequestType, CcSessionFailover, CreditControlAnswer, CreditControlFailureHandling,
+    CreditControlRequest, DirectDebitingFailureHandling,
+};
+use crate::applications::common::TerminationCause;
+use crate::diameter::{flags, result_code, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::DiameterClient;
+use crate::typed::TypedMessage;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+
+/// States of a credit-control client session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcSessionState {
+    /// No request is outstanding and no session is open.
+    Idle,
+    /// Waiting for the answer to the initial request.
+    PendingI,
+    /// Waiting for the answer to an update request.
+    PendingU,
+    /// Waiting for the answer to the termination request.
+    PendingT,
+    /// Waiting for the answer to an event request.
+    PendingE,
+    /// The session is open at the server.
+    Open,
+}
+
+/// Events reported to the application while a session runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcSessionEvent {
+    /// The session moved from one state to another.
+    StateChanged {
+        from: CcSessionState,
+        to: CcSessionState,
+    },
+    /// The Tx timer expired before the request was answered.
+    TxExpired(CcRequestType),
+    /// The request was retransmitted to the alternate server with this index.
+    Failover(usize),
+    /// The end user may be granted service before, or without, an answer.
+    GrantService,
+    /// The end user's service must be terminated.
+    TerminateService,
+}
+
+/// Outcome of a request sent on a [`CcClientSession`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CcOutcome {
+    /// The server answered; the session state follows the Result-Code.
+    Answer(Box<CreditControlAnswer>),
+    /// No answer was received and the service continues without credit control.
+    Continue,
+    /// No answer was received and the end user's service must be terminated.
+    Terminate,
+    /// No answer was received and the event request was buffered, see
+    /// [`CcClientSession::take_buffered`].
+    Buffered,
+}
+
+/// Configuration of a [`CreditControlClient`].
+///
+/// The failure handling and failover values apply until the server overrides
+/// them in a Credit-Control-Answer.
+#[derive(Debug, Clone, Copy)]
+pub struct CcClientConfig {
+    pub tx_timeout: Duration,
+    pub credit_control_failure_handling: CreditControlFailureHandling,
+    pub direct_debiting_failure_handling: DirectDebitingFailureHandling,
+    pub session_failover: CcSessionFailover,
+}
+
+impl Default for CcClientConfig {
+    /// The RFC 4006 defaults with the recommended Tx timer of 10 seconds.
+    fn default() -> CcClientConfig {
+        CcClientConfig {
+            tx_timeout: Duration::from_secs(10),
+            credit_control_failure_handling: CreditControlFailureHandling::Terminate,
+            direct_debiting_failure_handling: DirectDebitingFailureHandling::TerminateOrBuffer,
+            session_failover: CcSessionFailover::FailoverNotSupported,
+        }
+    }
+}
+
+/// A credit-control client connected to a primary server and to the
+/// alternate servers its sessions may fail over to.
+///
+/// The connections are shared by all sessions created from the client; each
+/// `DiameterClient` must be connected and have its handler running.
+#[derive(Clone)]
+pub struct CreditControlClient {
+    servers: Vec<Arc<Mutex<DiameterClient>>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: CcClientConfig,
+}
+
+impl CreditControlClient {
+    /// Creates a client sending to `primary`.
+    pub fn new(
+        primary: DiameterClient,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: CcClientConfig,
+    ) -> CreditControlClient {
+        CreditControlClient {
+            servers: vec![Arc::new(Mutex::new(primary))],
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Adds an alternate server, tried after the ones added before it.
+    ///
+    /// Only sessions created afterwards use the new server.
+    pub fn add_alternate_server(&mut self, client: DiameterClient) {
+        self.servers.push(Arc::new(Mutex::new(client)));
+    }
+
+    /// Creates an idle session, returning it with the receiver of its events.
+    pub fn session(
+        &self,
+        session_id: &str,
+        destination_realm: &str,
+        service_context_id: &str,
+    ) -> (CcClientSession, mpsc::UnboundedReceiver<CcSessionEvent>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        let session = CcClientSession {
+            client: self.clone(),
+            session_id: session_id.into(),
+            destination_realm: destination_realm.into(),
+            service_context_id: service_context_id.into(),
+            state: CcSessionState::Idle,
+            request_number: 0,
+            server: 0,
+            credit_control_failure_handling: self.config.credit_control_failure_handling,
+            direct_debiting_failure_handling: self.config.direct_debiting_failure_handling,
+            session_failover: self.config.session_failover,
+            buffered: Vec::new(),
+            events,
+        };
+        (session, receiver)
+    }
+}
+
+/// Why a request was not answered successfully.
+#[derive(Debug)]
+enum Failure {
+    SendFailed(Error),
+    TxExpired,
+    ProtocolError(u32),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::SendFailed(e) => write!(f, "failed to send: {}", e),
+            Failure::TxExpired => write!(f, "Tx timer expired"),
+            Failure::ProtocolError(code) => write!(f, "Result-Code {}", code),
+        }
+    }
+}
+
+/// A credit-control session on the client side.
+pub struct CcClientSession {
+    client: CreditControlClient,
+    session_id: String,
+    destination_realm: String,
+    service_context_id: String,
+    state: CcSessionState,
+    request_number: u32,
+    server: usize,
+    credit_control_failure_handling: CreditControlFailureHandling,
+    direct_debiting_failure_handling: DirectDebitingFailureHandling,
+    session_failover: CcSessionFailover,
+    buffered: Vec<CreditControlRequest>,
+    events: mpsc::UnboundedSender<CcSessionEvent>,
+}
+
+impl CcClientSession {
+    /// Returns the Session-Id of the session.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Returns the current state of the session.
+    pub fn state(&self) -> CcSessionState {
+        self.state
+    }
+
+    /// Creates the next request of the session, for the application to
+    /// complete before passing it to [`send`](CcClientSession::send).
+    pub fn request(&self, cc_request_type: CcRequestType) -> CreditControlRequest {
+        CreditControlRequest::new(
+            &self.session_id,
+            &self.client.node,
+            &self.destination_realm,
+            &self.service_context_id,
+            cc_request_type,
+            self.request_number,
+        )
+    }
+
+    /// Sends a request and drives the session until it is answered or its
+    /// failure has been handled.
+    ///
+    /// Initial and event requests are sent from `Idle`, update and
+    /// termination requests from `Open`; Session-Id and CC-Request-Number
+    /// are set by the session. An error is returned for a request the state
+    /// does not allow, or for an answer that cannot be decoded, in which
+    /// case an open session is terminated.
+    pub async fn send(&mut self, mut ccr: CreditControlRequest) -> Result<CcOutcome> {
+        let pending = match (self.state, ccr.cc_request_type) {
+            (CcSessionState::Idle, CcRequestType::InitialRequest) => CcSessionState::PendingI,
+            (CcSessionState::Open, CcRequestType::UpdateRequest) => CcSessionState::PendingU,
+            (CcSessionState::Open, CcRequestType::TerminationRequest) => CcSessionState::PendingT,
+            (CcSessionState::Idle, CcRequestType::EventRequest) => CcSessionState::PendingE,
+            (state, request_type) => {
+                return Err(Error::ClientError(format!(
+                    "Cannot send {:?} in state {:?}",
+                    request_type, state
+                )))
+            }
+        };
+        ccr.session_id = self.session_id.clone();
+        ccr.cc_request_number = self.request_number;
+        ccr.to_message(0, 0, &self.client.dict)?;
+
+        let result = self.exchange(ccr, pending).await;
+        if result.is_err() {
+            if matches!(
+                self.state,
+                CcSessionState::PendingI | CcSessionState::PendingU
+            ) {
+                self.events.send(CcSessionEvent::TerminateService).ok();
+                let mut ccr = self.request(CcRequestType::TerminationRequest);
+                ccr.termination_cause = Some(TerminationCause::DiameterBadAnswer);
+                self.exchange(ccr, CcSessionState::PendingT).await.ok();
+            }
+            self.set_state(CcSessionState::Idle);
+        }
+        result
+    }
+
+    /// Takes the event requests buffered under TERMINATE_OR_BUFFER.
+    ///
+    /// They should be delivered later with the T flag set.
+    pub fn take_buffered(&mut self) -> Vec<CreditControlRequest> {
+        std::mem::take(&mut self.buffered)
+    }
+
+    async fn exchange(
+        &mut self,
+        mut ccr: CreditControlRequest,
+        pending: CcSessionState,
+    ) -> Result<CcOutcome> {
+        ccr.cc_request_number = self.request_number;
+        self.request_number += 1;
+        self.set_state(pending);
+
+        let mut server = self.server;
+        let mut end_to_end_id = None;
+        let mut granted = false;
+        loop {
+            let retransmit = end_to_end_id.is_some();
+            let failure = match self
+                .transmit(server, &ccr, &mut end_to_end_id, retransmit)
+                .await
+            {
+                Ok(answer) if answer.is_error() => Failure::ProtocolError(
+                    answer
+                        .get::<u32, _>("Result-Code")
+                        .unwrap_or(result_code::DIAMETER_UNABLE_TO_COMPLY),
+                ),
+                Ok(answer) => {
+                    let cca = CreditControlAnswer::from_message(&answer)?;
+                    match cca.result_code {
+                        result_code::DIAMETER_UNABLE_TO_DELIVER
+                        | result_code::DIAMETER_TOO_BUSY => Failure::ProtocolError(cca.result_code),
+                        _ => {
+                            self.server = server;
+                            return Ok(self.on_answer(cca));
+                        }
+                    }
+                }
+                Err(failure) => failure,
+            };
+            log::warn!(
+                "Credit-control request failed; session: {}, failure: {}",
+                self.session_id,
+                failure
+            );
+
+            // Whether service may be granted while retrying, whether to retry
+            // on an alternate server, and whether service continues after all
+            let event = pending == CcSessionState::PendingE;
+            let (grant, retry, continues) = if event {
+                let continues = self.direct_debiting_failure_handling
+                    == DirectDebitingFailureHandling::Continue;
+                (continues, true, continues)
+            } else {
+                match self.credit_control_failure_handling {
+                    CreditControlFailureHandling::Terminate
+                    | CreditControlFailureHandling::Unknown(_) => (false, false, false),
+                    CreditControlFailureHandling::Continue => (true, true, true),
+                    CreditControlFailureHandling::RetryAndTerminate => (true, true, false),
+                }
+            };
+
+            if let Failure::TxExpired = failure {
+                self.events
+                    .send(CcSessionEvent::TxExpired(ccr.cc_request_type))
+                    .ok();
+                if grant && !granted && pending != CcSessionState::PendingT {
+                    self.events.send(CcSessionEvent::GrantService).ok();
+                    granted = true;
+                }
+            }
+
+            if retry
+                && self.session_failover == CcSessionFailover::FailoverSupported
+                && server + 1 < self.client.servers.len()
+            {
+                server += 1;
+                ccr.destination_host = None;
+                self.events.send(CcSessionEvent::Failover(server)).ok();
+                continue;
+            }
+
+            let outcome = if pending == CcSessionState::PendingT {
+                CcOutcome::Terminate
+            } else if continues {
+                if !granted {
+                    self.events.send(CcSessionEvent::GrantService).ok();
+                }
+                CcOutcome::Continue
+            } else if event && matches!(failure, Failure::SendFailed(_)) {
+                self.buffered.push(ccr);
+                CcOutcome::Buffered
+            } else {
+                self.events.send(CcSessionEvent::TerminateService).ok();
+                CcOutcome::Terminate
+            };
+            self.set_state(CcSessionState::Idle);
+            return Ok(outcome);
+        }
+    }
+
+    async fn transmit(
+        &self,
+        server: usize,
+        ccr: &CreditControlRequest,
+        end_to_end_id: &mut Option<u32>,
+        retransmit: bool,
+    ) -> std::result::Result<DiameterMessage, Failure> {
+        let response = {
+            let mut client = self.client.servers[server].lock().await;
+            let hop_by_hop_id = client.get_next_seq_num();
+            let end_to_end_id = *end_to_end_id.get_or_insert(hop_by_hop_id);
+            let mut req = ccr
+                .to_message(hop_by_hop_id, end_to_end_id, &self.client.dict)
+                .map_err(Failure::SendFailed)?;
+            if retransmit {
+                req.set_flags(req.get_flags() | flags::RETRANSMIT);
+            }
+            client
+                .send_message(req)
+                .await
+                .map_err(Failure::SendFailed)?
+        };
+        match tokio::time::timeout(self.client.config.tx_timeout, response).await {
+            Ok(Ok(answer)) => Ok(answer),
+            Ok(Err(e)) => Err(Failure::SendFailed(e)),
+            Err(_) => Err(Failure::TxExpired),
+        }
+    }
+
+    fn on_answer(&mut self, cca: CreditControlAnswer) -> CcOutcome {
+        if let Some(handling) = cca.credit_control_failure_handling {
+            self.credit_control_failure_handling = handling;
+        }
+        if let Some(handling) = cca.direct_debiting_failure_handling {
+            self.direct_debiting_failure_handling = handling;
+        }
+        if let Some(failover) = cca.cc_session_failover {
+            self.session_failover = failover;
+        }
+
+        let success = (2000..3000).contains(&cca.result_code);
+        let next = match self.state {
+            CcSessionState::PendingI | CcSessionState::PendingU if success => CcSessionState::Open,
+            _ => CcSessionState::Idle,
+        };
+        if !success && self.state != CcSessionState::PendingT {
+            if cca.result_code == DIAMETER_CREDIT_CONTROL_NOT_APPLICABLE {
+                self.events.send(CcSessionEvent::GrantService).ok();
+            } else {
+                self.events.send(CcSessionEvent::TerminateService).ok();
+            }
+        }
+        self.set_state(next);
+        CcOutcome::Answer(Box::new(cca))
+    }
+
+    fn set_state(&mut self, state: CcSessionState) {
+        if self.state != state {
+            let from = std::mem::replace(&mut self.state, state);
+            self.events
+                .send(CcSessionEvent::StateChanged { from, to: state })
+                .ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::credit_control::result_code::DIAMETER_END_USER_SERVICE_DENIED;
+    use crate::dictionary;
+    use crate::transport::{DiameterClientConfig, DiameterServer, DiameterServerConfig};
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    async fn start_server(
+        addr: &str,
+        origin_host: &'static str,
+        result_code: u32,
+        delay: Duration,
+    ) {
+        let dict = dict();
+        let mut server = DiameterServer::new(addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| {
+                        let dict = Arc::clone(&dict);
+                        async move {
+                            tokio::time::sleep(delay).await;
+                            let ccr = CreditControlRequest::from_message(&req)?;
+                            let node = NodeConfig::new(origin_host, "example.com");
+                            let mut cca = CreditControlAnswer::new(&ccr, result_code, &node);
+                            cca.cc_session_failover = Some(CcSessionFailover::FailoverSupported);
+                            cca.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+    }
+
+    async fn connect(addr: &str) -> DiameterClient {
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new(addr, config);
+        let mut handler = client.connect().await.unwrap();
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict()).await;
+        });
+        client
+    }
+
+    fn node() -> NodeConfig {
+        NodeConfig::new("pcef.example.com", "example.com")
+    }
+
+    fn drain(events: &mut mpsc::UnboundedReceiver<CcSessionEvent>) -> Vec<CcSessionEvent> {
+        let mut drained = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            drained.push(event);
+        }
+        drained
+    }
+
+    fn answer(outcome: CcOutcome) -> CreditControlAnswer {
+        match outcome {
+            CcOutcome::Answer(cca) => *cca,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        start_server("0.0.0.0:3870", "ocs.example.com", 2001, Duration::ZERO).await;
+        let client = connect("localhost:3870").await;
+        let cc_client = CreditControlClient::new(client, dict(), node(), CcClientConfig::default());
+        let (mut session, mut events) = cc_client.session("ses;1", "example.com", "32251@3gpp.org");
+
+        let ccr = session.request(CcRequestType::UpdateRequest);
+        assert!(session.send(ccr).await.is_err());
+
+        let ccr = session.request(CcRequestType::InitialRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.cc_request_number, 0);
+        assert_eq!(session.state(), CcSessionState::Open);
+
+        let ccr = session.request(CcRequestType::UpdateRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.cc_request_type, CcRequestType::UpdateRequest);
+        assert_eq!(cca.cc_request_number, 1);
+        assert_eq!(session.state(), CcSessionState::Open);
+
+        let ccr = session.request(CcRequestType::TerminationRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.cc_request_number, 2);
+        assert_eq!(session.state(), CcSessionState::Idle);
+
+        use CcSessionState::*;
+        let changes: Vec<_> = drain(&mut events)
+            .into_iter()
+            .map(|event| match event {
+                CcSessionEvent::StateChanged { to, .. } => to,
+                event => panic!("unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![PendingI, Open, PendingU, Open, PendingT, Idle]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tx_expiry_terminate() {
+        start_server(
+            "0.0.0.0:3871",
+            "ocs.example.com",
+            2001,
+            Duration::from_millis(300),
+        )
+        .await;
+        let client = connect("localhost:3871").await;
+        let config = CcClientConfig {
+            tx_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let cc_client = CreditControlClient::new(client, dict(), node(), config);
+        let (mut session, mut events) = cc_client.session("ses;2", "example.com", "32251@3gpp.org");
+
+        let ccr = session.request(CcRequestType::InitialRequest);
+        assert_eq!(session.send(ccr).await.unwrap(), CcOutcome::Terminate);
+        assert_eq!(session.state(), CcSessionState::Idle);
+
+        let events = drain(&mut events);
+        assert!(events.contains(&CcSessionEvent::TxExpired(CcRequestType::InitialRequest)));
+        assert!(events.contains(&CcSessionEvent::TerminateService));
+        assert!(!events.contains(&CcSessionEvent::GrantService));
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_alternate_server() {
+        start_server(
+            "0.0.0.0:3872",
+            "ocs1.example.com",
+            2001,
+            Duration::from_millis(300),
+        )
+        .await;
+        start_server("0.0.0.0:3873", "ocs2.example.com", 2001, Duration::ZERO).await;
+        let config = CcClientConfig {
+            tx_timeout: Duration::from_millis(50),
+            credit_control_failure_handling: CreditControlFailureHandling::RetryAndTerminate,
+            session_failover: CcSessionFailover::FailoverSupported,
+            ..Default::default()
+        };
+        let mut cc_client =
+            CreditControlClient::new(connect("localhost:3872").await, dict(), node(), config);
+        cc_client.add_alternate_server(connect("localhost:3873").await);
+        let (mut session, mut events) = cc_client.session("ses;3", "example.com", "32251@3gpp.org");
+
+        let ccr = session.request(CcRequestType::InitialRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.origin_host, "ocs2.example.com");
+        assert_eq!(session.state(), CcSessionState::Open);
+
+        let events = drain(&mut events);
+        assert!(events.contains(&CcSessionEvent::GrantService));
+        assert!(events.contains(&CcSessionEvent::Failover(1)));
+
+        // The session stays on the alternate server
+        let ccr = session.request(CcRequestType::TerminationRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.origin_host, "ocs2.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_service_denied() {
+        start_server(
+            "0.0.0.0:3874",
+            "ocs.example.com",
+            DIAMETER_END_USER_SERVICE_DENIED,
+            Duration::ZERO,
+        )
+        .await;
+        let client = connect("localhost:3874").await;
+        let cc_client = CreditControlClient::new(client, dict(), node(), CcClientConfig::default());
+        let (mut session, mut events) = cc_client.session("ses;4", "example.com", "32251@3gpp.org");
+
+        let ccr = session.request(CcRequestType::InitialRequest);
+        let cca = answer(session.send(ccr).await.unwrap());
+        assert_eq!(cca.result_code, DIAMETER_END_USER_SERVICE_DENIED);
+        assert_eq!(session.state(), CcSessionState::Idle);
+        assert!(drain(&mut events).contains(&CcSessionEvent::TerminateService));
+    }
+
+    #[tokio::test]
+    async fn test_event_buffered_on_send_failure() {
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let client = DiameterClient::new("localhost:3875", config);
+        let cc_client = CreditControlClient::new(client, dict(), node(), CcClientConfig::default());
+        let (mut session, _events) = cc_client.session("ses;5", "example.com", "32251@3gpp.org");
+
+        let ccr = session.request(CcRequestType::EventRequest);
+        assert_eq!(session.send(ccr).await.unwrap(), CcOutcome::Buffered);
+        assert_eq!(session.state(), CcSessionState::Idle);
+
+        let buffered = session.take_buffered();
+        assert_eq!(buffered.len(), 1);
+        assert_eq!(buffered[0].cc_request_type, CcRequestType::EventRequest);
+        assert!(session.take_buffered().is_empty());
+    }
+}
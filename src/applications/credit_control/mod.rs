@@ -0,0 +1,693 @@
+//! Diameter Credit-Control Application
+//!
+//! Typed Credit-Control-Request and Credit-Control-Answer messages, as
+//! defined in [RFC 4006](https://tools.ietf.org/html/rfc4006) and used by the
+//! 3GPP Gy/Ro interface.
+//!
+//! ```
+//! use diameter::applications::credit_control::{
+//!     CcRequestType, CreditControlRequest, MultipleServicesCreditControl, ServiceUnit,
+//!     SubscriptionId, SubscriptionIdType,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//! let node = NodeConfig::new("host.example.com", "realm.example.com");
+//!
+//! let mut ccr = CreditControlRequest::new(
+//!     "ses;12345888",
+//!     &node,
+//!     "ocs.example.com",
+//!     "32251@3gpp.org",
+//!     CcRequestType::InitialRequest,
+//!     0,
+//! );
+//! ccr.subscription_id.push(SubscriptionId {
+//!     subscription_id_type: SubscriptionIdType::EndUserE164,
+//!     subscription_id_data: "6591234567".into(),
+//! });
+//! ccr.multiple_services_credit_control.push(MultipleServicesCreditControl {
+//!     rating_group: Some(100),
+//!     requested_service_unit: Some(ServiceUnit::default()),
+//!     ..Default::default()
+//! });
+//!
+//! let msg = ccr.to_message(1, 1, &dict).unwrap();
+//! assert_eq!(CreditControlRequest::from_message(&msg).unwrap(), ccr);
+//! ```
+
+pub mod client;
+pub mod server;
+
+use crate::applications::common::{ProxyInfo, TerminationCause};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::{DiameterMessage, GroupedAvp};
+
+/// Result-Code AVP values defined in RFC 4006 section 9.1.
+pub mod result_code {
+    // Transient Failures
+    pub const DIAMETER_END_USER_SERVICE_DENIED: u32 = 4010;
+    pub const DIAMETER_CREDIT_CONTROL_NOT_APPLICABLE: u32 = 4011;
+    pub const DIAMETER_CREDIT_LIMIT_REACHED: u32 = 4012;
+
+    // Permanent Failures
+    pub const DIAMETER_USER_UNKNOWN: u32 = 5030;
+    pub const DIAMETER_RATING_FAILED: u32 = 5031;
+}
+
+/// Auth-Application-Id of the Credit-Control application.
+pub const APPLICATION_ID: u32 = 4;
+
+enumerated! {
+    /// Values of the CC-Request-Type AVP (416).
+    pub enum CcRequestType ("CC-Request-Type") {
+        InitialRequest = 1,
+        UpdateRequest = 2,
+        TerminationRequest = 3,
+        EventRequest = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the CC-Session-Failover AVP (418).
+    pub enum CcSessionFailover ("CC-Session-Failover") {
+        FailoverNotSupported = 0,
+        FailoverSupported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Credit-Control-Failure-Handling AVP (427).
+    pub enum CreditControlFailureHandling ("Credit-Control-Failure-Handling") {
+        Terminate = 0,
+        Continue = 1,
+        RetryAndTerminate = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Direct-Debiting-Failure-Handling AVP (428).
+    pub enum DirectDebitingFailureHandling ("Direct-Debiting-Failure-Handling") {
+        TerminateOrBuffer = 0,
+        Continue = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Requested-Action AVP (436).
+    pub enum RequestedAction ("Requested-Action") {
+        DirectDebiting = 0,
+        RefundAccount = 1,
+        CheckBalance = 2,
+        PriceEnquiry = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Final-Unit-Action AVP (449).
+    pub enum FinalUnitAction ("Final-Unit-Action") {
+        Terminate = 0,
+        Redirect = 1,
+        RestrictAccess = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Redirect-Address-Type AVP (433).
+    pub enum RedirectAddressType ("Redirect-Address-Type") {
+        Ipv4Address = 0,
+        Ipv6Address = 1,
+        Url = 2,
+        SipUri = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Subscription-Id-Type AVP (450).
+    pub enum SubscriptionIdType ("Subscription-Id-Type") {
+        EndUserE164 = 0,
+        EndUserImsi = 1,
+        EndUserSipUri = 2,
+        EndUserNai = 3,
+        EndUserPrivate = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Tariff-Change-Usage AVP (452).
+    pub enum TariffChangeUsage ("Tariff-Change-Usage") {
+        UnitBeforeTariffChange = 0,
+        UnitAfterTariffChange = 1,
+        UnitIndeterminate = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the CC-Unit-Type AVP (454).
+    pub enum CcUnitType ("CC-Unit-Type") {
+        Time = 0,
+        Money = 1,
+        TotalOctets = 2,
+        InputOctets = 3,
+        OutputOctets = 4,
+        ServiceSpecificUnits = 5,
+    }
+}
+
+enumerated! {
+    /// Values of the Multiple-Services-Indicator AVP (455).
+    pub enum MultipleServicesIndicator ("Multiple-Services-Indicator") {
+        MultipleServicesNotSupported = 0,
+        MultipleServicesSupported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the User-Equipment-Info-Type AVP (459).
+    pub enum UserEquipmentInfoType ("User-Equipment-Info-Type") {
+        Imeisv = 0,
+        Mac = 1,
+        Eui64 = 2,
+        ModifiedEui64 = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Check-Balance-Result AVP (422).
+    pub enum CheckBalanceResult ("Check-Balance-Result") {
+        EnoughCredit = 0,
+        NoCredit = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Redirect-Host-Usage AVP (261).
+    pub enum RedirectHostUsage ("Redirect-Host-Usage") {
+        DontCache = 0,
+        AllSession = 1,
+        AllRealm = 2,
+        RealmAndApplication = 3,
+        AllApplication = 4,
+        AllHost = 5,
+        AllUser = 6,
+    }
+}
+
+/// The Subscription-Id AVP (443), identifying the end user.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct SubscriptionId {
+    #[avp(name = "Subscription-Id-Type")]
+    pub subscription_id_type: SubscriptionIdType,
+    #[avp(name = "Subscription-Id-Data")]
+    pub subscription_id_data: String,
+}
+
+/// The Unit-Value AVP (445), the value `value_digits * 10^exponent`.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct UnitValue {
+    #[avp(name = "Value-Digits")]
+    pub value_digits: i64,
+    #[avp(name = "Exponent")]
+    pub exponent: Option<i32>,
+}
+
+/// The CC-Money AVP (413).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct CcMoney {
+    #[avp(name = "Unit-Value", grouped)]
+    pub unit_value: UnitValue,
+    #[avp(name = "Currency-Code")]
+    pub currency_code: Option<u32>,
+}
+
+/// The Requested-Service-Unit (437), Used-Service-Unit (446) and
+/// Granted-Service-Unit (431) AVPs.
+///
+/// `tariff_time_change` only applies to granted units and
+/// `tariff_change_usage` only to used units.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ServiceUnit {
+    #[avp(name = "Tariff-Time-Change")]
+    pub tariff_time_change: Option<DateTime<Utc>>,
+    #[avp(name = "Tariff-Change-Usage")]
+    pub tariff_change_usage: Option<TariffChangeUsage>,
+    #[avp(name = "CC-Time")]
+    pub cc_time: Option<u32>,
+    #[avp(name = "CC-Money", grouped)]
+    pub cc_money: Option<CcMoney>,
+    #[avp(name = "CC-Total-Octets")]
+    pub cc_total_octets: Option<u64>,
+    #[avp(name = "CC-Input-Octets")]
+    pub cc_input_octets: Option<u64>,
+    #[avp(name = "CC-Output-Octets")]
+    pub cc_output_octets: Option<u64>,
+    #[avp(name = "CC-Service-Specific-Units")]
+    pub cc_service_specific_units: Option<u64>,
+}
+
+/// The G-S-U-Pool-Reference AVP (457).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct GsuPoolReference {
+    #[avp(name = "G-S-U-Pool-Identifier")]
+    pub g_s_u_pool_identifier: u32,
+    #[avp(name = "CC-Unit-Type")]
+    pub cc_unit_type: CcUnitType,
+    #[avp(name = "Unit-Value", grouped)]
+    pub unit_value: UnitValue,
+}
+
+/// The Redirect-Server AVP (434).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct RedirectServer {
+    #[avp(name = "Redirect-Address-Type")]
+    pub redirect_address_type: RedirectAddressType,
+    #[avp(name = "Redirect-Server-Address")]
+    pub redirect_server_address: String,
+}
+
+/// The Final-Unit-Indication AVP (430), telling the client what to do when
+/// the granted units are used up.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct FinalUnitIndication {
+    #[avp(name = "Final-Unit-Action")]
+    pub final_unit_action: FinalUnitAction,
+    #[avp(name = "Restriction-Filter-Rule")]
+    pub restriction_filter_rule: Vec<String>,
+    #[avp(name = "Filter-Id")]
+    pub filter_id: Vec<String>,
+    #[avp(name = "Redirect-Server", grouped)]
+    pub redirect_server: Option<RedirectServer>,
+}
+
+/// The Multiple-Services-Credit-Control AVP (456), carrying the units of one
+/// service or rating group.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct MultipleServicesCreditControl {
+    #[avp(name = "Granted-Service-Unit", grouped)]
+    pub granted_service_unit: Option<ServiceUnit>,
+    #[avp(name = "Requested-Service-Unit", grouped)]
+    pub requested_service_unit: Option<ServiceUnit>,
+    #[avp(name = "Used-Service-Unit", grouped)]
+    pub used_service_unit: Vec<ServiceUnit>,
+    #[avp(name = "Tariff-Change-Usage")]
+    pub tariff_change_usage: Option<TariffChangeUsage>,
+    #[avp(name = "Service-Identifier")]
+    pub service_identifier: Vec<u32>,
+    #[avp(name = "Rating-Group")]
+    pub rating_group: Option<u32>,
+    #[avp(name = "G-S-U-Pool-Reference", grouped)]
+    pub g_s_u_pool_reference: Vec<GsuPoolReference>,
+    #[avp(name = "Validity-Time")]
+    pub validity_time: Option<u32>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Final-Unit-Indication", grouped)]
+    pub final_unit_indication: Option<FinalUnitIndication>,
+}
+
+/// The User-Equipment-Info AVP (458).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct UserEquipmentInfo {
+    #[avp(name = "User-Equipment-Info-Type")]
+    pub user_equipment_info_type: UserEquipmentInfoType,
+    #[avp(name = "User-Equipment-Info-Value")]
+    pub user_equipment_info_value: Vec<u8>,
+}
+
+/// The Cost-Information AVP (423).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct CostInformation {
+    #[avp(name = "Unit-Value", grouped)]
+    pub unit_value: UnitValue,
+    #[avp(name = "Currency-Code")]
+    pub currency_code: u32,
+    #[avp(name = "Cost-Unit")]
+    pub cost_unit: Option<String>,
+}
+
+/// Credit-Control-Request (CCR), RFC 4006 section 3.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = CreditControl,
+    application = CreditControl,
+    request,
+    proxyable
+)]
+pub struct CreditControlRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Service-Context-Id")]
+    pub service_context_id: String,
+    #[avp(name = "CC-Request-Type")]
+    pub cc_request_type: CcRequestType,
+    #[avp(name = "CC-Request-Number")]
+    pub cc_request_number: u32,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "CC-Sub-Session-Id")]
+    pub cc_sub_session_id: Option<u64>,
+    #[avp(name = "Acct-Multi-Session-Id")]
+    pub acct_multi_session_id: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Event-Timestamp")]
+    pub event_timestamp: Option<DateTime<Utc>>,
+    #[avp(name = "Subscription-Id", grouped)]
+    pub subscription_id: Vec<SubscriptionId>,
+    #[avp(name = "Service-Identifier")]
+    pub service_identifier: Option<u32>,
+    #[avp(name = "Termination-Cause")]
+    pub termination_cause: Option<TerminationCause>,
+    #[avp(name = "Requested-Service-Unit", grouped)]
+    pub requested_service_unit: Option<ServiceUnit>,
+    #[avp(name = "Requested-Action")]
+    pub requested_action: Option<RequestedAction>,
+    #[avp(name = "Used-Service-Unit", grouped)]
+    pub used_service_unit: Vec<ServiceUnit>,
+    #[avp(name = "Multiple-Services-Indicator")]
+    pub multiple_services_indicator: Option<MultipleServicesIndicator>,
+    #[avp(name = "Multiple-Services-Credit-Control", grouped)]
+    pub multiple_services_credit_control: Vec<MultipleServicesCreditControl>,
+    #[avp(name = "CC-Correlation-Id")]
+    pub cc_correlation_id: Option<Vec<u8>>,
+    #[avp(name = "User-Equipment-Info", grouped)]
+    pub user_equipment_info: Option<UserEquipmentInfo>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Credit-Control-Answer (CCA), RFC 4006 section 3.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = CreditControl,
+    application = CreditControl,
+    proxyable
+)]
+pub struct CreditControlAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: Option<u32>,
+    #[avp(name = "CC-Request-Type")]
+    pub cc_request_type: CcRequestType,
+    #[avp(name = "CC-Request-Number")]
+    pub cc_request_number: u32,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "CC-Session-Failover")]
+    pub cc_session_failover: Option<CcSessionFailover>,
+    #[avp(name = "CC-Sub-Session-Id")]
+    pub cc_sub_session_id: Option<u64>,
+    #[avp(name = "Acct-Multi-Session-Id")]
+    pub acct_multi_session_id: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Event-Timestamp")]
+    pub event_timestamp: Option<DateTime<Utc>>,
+    #[avp(name = "Granted-Service-Unit", grouped)]
+    pub granted_service_unit: Option<ServiceUnit>,
+    #[avp(name = "Multiple-Services-Credit-Control", grouped)]
+    pub multiple_services_credit_control: Vec<MultipleServicesCreditControl>,
+    #[avp(name = "Cost-Information", grouped)]
+    pub cost_information: Option<CostInformation>,
+    #[avp(name = "Final-Unit-Indication", grouped)]
+    pub final_unit_indication: Option<FinalUnitIndication>,
+    #[avp(name = "Check-Balance-Result")]
+    pub check_balance_result: Option<CheckBalanceResult>,
+    #[avp(name = "Credit-Control-Failure-Handling")]
+    pub credit_control_failure_handling: Option<CreditControlFailureHandling>,
+    #[avp(name = "Direct-Debiting-Failure-Handling")]
+    pub direct_debiting_failure_handling: Option<DirectDebitingFailureHandling>,
+    #[avp(name = "Validity-Time")]
+    pub validity_time: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+impl CreditControlRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        service_context_id: &str,
+        cc_request_type: CcRequestType,
+        cc_request_number: u32,
+    ) -> CreditControlRequest {
+        CreditControlRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            auth_application_id: APPLICATION_ID,
+            service_context_id: service_context_id.into(),
+            cc_request_type,
+            cc_request_number,
+            destination_host: None,
+            user_name: None,
+            cc_sub_session_id: None,
+            acct_multi_session_id: None,
+            origin_state_id: None,
+            event_timestamp: None,
+            subscription_id: Vec::new(),
+            service_identifier: None,
+            termination_cause: None,
+            requested_service_unit: None,
+            requested_action: None,
+            used_service_unit: Vec::new(),
+            multiple_services_indicator: None,
+            multiple_services_credit_control: Vec::new(),
+            cc_correlation_id: None,
+            user_equipment_info: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl CreditControlAnswer {
+    /// Creates an answer to `request`, copying Session-Id, CC-Request-Type,
+    /// CC-Request-Number and Proxy-Info, and taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        request: &CreditControlRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> CreditControlAnswer {
+        CreditControlAnswer {
+            session_id: request.session_id.clone(),
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            auth_application_id: Some(APPLICATION_ID),
+            cc_request_type: request.cc_request_type,
+            cc_request_number: request.cc_request_number,
+            user_name: None,
+            cc_session_failover: None,
+            cc_sub_session_id: None,
+            acct_multi_session_id: None,
+            origin_state_id: None,
+            event_timestamp: None,
+            granted_service_unit: None,
+            multiple_services_credit_control: Vec::new(),
+            cost_information: None,
+            final_unit_indication: None,
+            check_balance_result: None,
+            credit_control_failure_handling: None,
+            direct_debiting_failure_handling: None,
+            validity_time: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::flags::M;
+    use crate::avp::{Enumerated, Identity, UTF8String, Unsigned32};
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode, Error};
+    use chrono::TimeZone;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    #[test]
+    fn test_ccr_round_trip() {
+        let dict = dict();
+        let node = NodeConfig::new("host.example.com", "realm.example.com");
+
+        let mut ccr = CreditControlRequest::new(
+            "ses;12345888",
+            &node,
+            "ocs.example.com",
+            "32251@3gpp.org",
+            CcRequestType::UpdateRequest,
+            1,
+        );
+        ccr.event_timestamp = Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        ccr.subscription_id.push(SubscriptionId {
+            subscription_id_type: SubscriptionIdType::EndUserImsi,
+            subscription_id_data: "525051234567890".into(),
+        });
+        ccr.multiple_services_indicator =
+            Some(MultipleServicesIndicator::MultipleServicesSupported);
+        ccr.multiple_services_credit_control
+            .push(MultipleServicesCreditControl {
+                rating_group: Some(100),
+                requested_service_unit: Some(ServiceUnit::default()),
+                used_service_unit: vec![ServiceUnit {
+                    tariff_change_usage: Some(TariffChangeUsage::UnitBeforeTariffChange),
+                    cc_total_octets: Some(1024),
+                    cc_time: Some(60),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            });
+        ccr.proxy_info.push(ProxyInfo {
+            proxy_host: "proxy.example.com".into(),
+            proxy_state: vec![1, 2, 3],
+        });
+
+        let msg = ccr.to_message(1234, 5678, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::CreditControl);
+        assert_eq!(msg.get_application_id(), ApplicationId::CreditControl);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        let msg = DiameterMessage::decode_from(&mut cursor, Arc::clone(&dict)).unwrap();
+        assert_eq!(
+            msg.query_values::<u64>(
+                "Multiple-Services-Credit-Control/Used-Service-Unit/CC-Total-Octets"
+            )
+            .unwrap(),
+            vec![1024]
+        );
+
+        let decoded = CreditControlRequest::from_message(&msg).unwrap();
+        assert_eq!(decoded, ccr);
+
+        let node = NodeConfig::new("ocs.example.com", "ocs.example.com");
+        let mut cca =
+            CreditControlAnswer::new(&decoded, crate::result_code::DIAMETER_SUCCESS, &node);
+        cca.validity_time = Some(3600);
+        cca.multiple_services_credit_control
+            .push(MultipleServicesCreditControl {
+                rating_group: Some(100),
+                granted_service_unit: Some(ServiceUnit {
+                    cc_total_octets: Some(1_000_000),
+                    ..Default::default()
+                }),
+                result_code: Some(crate::result_code::DIAMETER_SUCCESS),
+                final_unit_indication: Some(FinalUnitIndication {
+                    final_unit_action: FinalUnitAction::Redirect,
+                    restriction_filter_rule: vec!["permit in ip from any to 10.0.0.1".into()],
+                    filter_id: vec![],
+                    redirect_server: Some(RedirectServer {
+                        redirect_address_type: RedirectAddressType::Url,
+                        redirect_server_address: "http://topup.example.com".into(),
+                    }),
+                }),
+                ..Default::default()
+            });
+
+        let msg = cca.to_message(1234, 5678, &dict).unwrap();
+        assert_eq!(msg.get_flags(), flags::PROXYABLE);
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        let msg = DiameterMessage::decode_from(&mut cursor, dict).unwrap();
+
+        let decoded = CreditControlAnswer::from_message(&msg).unwrap();
+        assert_eq!(decoded, cca);
+        assert_eq!(decoded.session_id, "ses;12345888");
+        assert_eq!(decoded.cc_request_type, CcRequestType::UpdateRequest);
+        assert_eq!(decoded.proxy_info[0].proxy_host, "proxy.example.com");
+    }
+
+    #[test]
+    fn test_cca_decode_errors() {
+        let dict = dict();
+
+        let mut cca = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            0,
+            1,
+            1,
+            dict,
+        );
+        cca.add_avp(263, None, M, UTF8String::new("ses;1").into());
+        cca.add_avp(268, None, M, Unsigned32::new(2001).into());
+        cca.add_avp(264, None, M, Identity::new("ocs.example.com").into());
+        cca.add_avp(296, None, M, Identity::new("example.com").into());
+        cca.add_avp(415, None, M, Unsigned32::new(0).into());
+        match CreditControlAnswer::from_message(&cca) {
+            Err(Error::AvpNotFound(name)) => assert_eq!(name, "CC-Request-Type"),
+            _ => panic!("expected AvpNotFound"),
+        }
+
+        // Values not listed are kept rather than rejected
+        cca.add_avp(416, None, M, Enumerated::new(9).into());
+        let answer = CreditControlAnswer::from_message(&cca).unwrap();
+        assert_eq!(answer.cc_request_type, CcRequestType::Unknown(9));
+        assert_eq!(i32::from(answer.cc_request_type), 9);
+        assert_eq!(CcRequestType::from(3), CcRequestType::TerminationRequest);
+
+        assert!(CreditControlRequest::from_message(&cca).is_err());
+    }
+}
@@ -0,0 +1,728 @@
+//! Credit-Control Server Sessions
+//!
+//! The server side of the credit-control session state machine of
+//! [RFC 4006 section 7](https://tools.ietf.org/html/rfc4006#section-7).
+//!
+//! A [`CreditControlServer`] decodes Credit-Control-Requests, checks them
+//! against the sessions it keeps by Session-Id, and routes them to the
+//! methods of a [`CreditControlHandler`]. It sequences CC-Request-Number,
+//! answers retransmitted requests from the session, supervises open
+//! sessions with the Tcc timer, and sets the Result-Code of requests that
+//! cannot be processed. Given a [`SessionStore`] with
+//! [`set_store`](CreditControlServer::set_store), it persists its sessions
+//! there.
+//!
+//! ```no_run
+//! use diameter::applications::credit_control::server::{
+//!     CcServerConfig, CreditControlHandler, CreditControlServer,
+//! };
+//! use diameter::applications::credit_control::{
+//!     CreditControlAnswer, CreditControlRequest, ServiceUnit,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::{DiameterServer, DiameterServerConfig};
+//! use diameter::{NodeConfig, Result};
+//! use std::sync::Arc;
+//!
+//! struct Ocs;
+//!
+//! impl CreditControlHandler for Ocs {
+//!     async fn initial(
+//!         &self,
+//!         _ccr: &CreditControlRequest,
+//!         mut cca: CreditControlAnswer,
+//!     ) -> Result<CreditControlAnswer> {
+//!         cca.granted_service_unit = Some(ServiceUnit {
+//!             cc_time: Some(600),
+//!             ..Default::default()
+//!         });
+//!         Ok(cca)
+//!     }
+//!
+//!     async fn update(
+//!         &self,
+//!         ccr: &CreditControlRequest,
+//!         cca: CreditControlAnswer,
+//!     ) -> Result<CreditControlAnswer> {
+//!         self.initial(ccr, cca).await
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let node = NodeConfig::new("ocs.example.com", "example.com");
+//!     let ocs = CreditControlServer::new(Ocs, dict, node, CcServerConfig::default());
+//!
+//!     let mut server = DiameterServer::new("0.0.0.0:3868", DiameterServerConfig { native_tls: None })
+//!         .await
+//!         .unwrap();
+//!     ocs.listen(&mut server).await.unwrap();
+//! }
+//! ```
+
+use super::{CcRequestType, CreditControlAnswer, CreditControlRequest};
+use crate::diameter::result_code;
+use crate::diameter::{ApplicationId, CommandCode, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::session::store::{RecordReader, RecordWriter, SessionStore};
+use crate::transport::DiameterServer;
+use crate::typed::TypedMessage;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Application callbacks of a [`CreditControlServer`].
+///
+/// Each request method receives the decoded request and an answer prepared
+/// with DIAMETER_SUCCESS, and returns the answer to send. Returning an error
+/// answers with DIAMETER_UNABLE_TO_COMPLY; returning a failure Result-Code
+/// to an initial or update request closes the session.
+pub trait CreditControlHandler: Send + Sync + 'static {
+    /// Handles a CCR-I, opening the session on success.
+    fn initial(
+        &self,
+        ccr: &CreditControlRequest,
+        cca: CreditControlAnswer,
+    ) -> impl Future<Output = Result<CreditControlAnswer>> + Send;
+
+    /// Handles a CCR-U of an open session.
+    fn update(
+        &self,
+        ccr: &CreditControlRequest,
+        cca: CreditControlAnswer,
+    ) -> impl Future<Output = Result<CreditControlAnswer>> + Send;
+
+    /// Handles a CCR-T, which closes the session whatever the answer.
+    fn terminate(
+        &self,
+        _ccr: &CreditControlRequest,
+        cca: CreditControlAnswer,
+    ) -> impl Future<Output = Result<CreditControlAnswer>> + Send {
+        async move { Ok(cca) }
+    }
+
+    /// Handles a CCR-E. Event requests are rejected unless overridden.
+    fn event(
+        &self,
+        _ccr: &CreditControlRequest,
+        mut cca: CreditControlAnswer,
+    ) -> impl Future<Output = Result<CreditControlAnswer>> + Send {
+        async move {
+            cca.result_code = result_code::DIAMETER_UNABLE_TO_COMPLY;
+            Ok(cca)
+        }
+    }
+
+    /// Called when the Tcc timer of an open session expires and the session
+    /// is released without a CCR-T.
+    fn session_expired(&self, _session_id: &str) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// Configuration of a [`CreditControlServer`].
+#[derive(Debug, Clone, Copy)]
+pub struct CcServerConfig {
+    /// Tcc timer of sessions whose answers carry no Validity-Time. When a
+    /// Validity-Time is sent, Tcc is twice its value.
+    pub tcc_timeout: Duration,
+    /// Validity-Time added to successful CCA-I and CCA-U that have none.
+    pub validity_time: Option<u32>,
+}
+
+impl Default for CcServerConfig {
+    fn default() -> CcServerConfig {
+        CcServerConfig {
+            tcc_timeout: Duration::from_secs(3600),
+            validity_time: None,
+        }
+    }
+}
+
+/// An open session, with the last request number and answer.
+struct CcServerSession {
+    request_number: u32,
+    deadline: Instant,
+    answer: CreditControlAnswer,
+}
+
+/// A credit-control server routing requests to a [`CreditControlHandler`].
+pub struct CreditControlServer<H> {
+    handler: Arc<H>,
+    sessions: Arc<Mutex<HashMap<String, CcServerSession>>>,
+    store: Option<Arc<dyn SessionStore>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: CcServerConfig,
+}
+
+impl<H> Clone for CreditControlServer<H> {
+    fn clone(&self) -> Self {
+        CreditControlServer {
+            handler: Arc::clone(&self.handler),
+            sessions: Arc::clone(&self.sessions),
+            store: self.store.clone(),
+            dict: Arc::clone(&self.dict),
+            node: self.node.clone(),
+            config: self.config,
+        }
+    }
+}
+
+impl<H: CreditControlHandler> CreditControlServer<H> {
+    /// Creates a server answering as `node`.
+    pub fn new(
+        handler: H,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: CcServerConfig,
+    ) -> CreditControlServer<H> {
+        CreditControlServer {
+            handler: Arc::new(handler),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            store: None,
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Persists the sessions in `store`, taking over the sessions it holds,
+    /// such as the sessions left open by a previous run of the server.
+    pub fn set_store(&mut self, store: Arc<dyn SessionStore>) -> Result<()> {
+        let mut sessions = self.sessions.lock()?;
+        for (session_id, record) in store.records()? {
+            sessions.insert(session_id, self.decode_session(&record)?);
+        }
+        for (session_id, session) in sessions.iter() {
+            store.put(session_id, &self.encode_session(session)?)?;
+        }
+        drop(sessions);
+        self.store = Some(store);
+        Ok(())
+    }
+
+    /// Serves requests received by `server`, supervising sessions until
+    /// the server stops listening.
+    pub async fn listen(&self, server: &mut DiameterServer) -> Result<()> {
+        server.set_node(self.node.clone());
+        let this = self.clone();
+        let supervisor = tokio::spawn(async move {
+            loop {
+                if let Err(e) = this.expire_sessions().await {
+                    log::error!("Failed to expire sessions; error: {}", e);
+                }
+                let latest = Instant::now() + Duration::from_secs(1);
+                let next = this.next_deadline().unwrap_or(latest).min(latest);
+                tokio::time::sleep_until(next).await;
+            }
+        });
+
+        let this = self.clone();
+        let result = server
+            .listen(
+                move |req| {
+                    let this = this.clone();
+                    async move { this.handle(req).await }
+                },
+                Arc::clone(&self.dict),
+            )
+            .await;
+        supervisor.abort();
+        result
+    }
+
+    /// Handles a request, returning the answer to send.
+    ///
+    /// Requests other than Credit-Control-Requests are answered with a
+    /// protocol error, and requests that cannot be decoded with
+    /// DIAMETER_MISSING_AVP, DIAMETER_INVALID_AVP_VALUE or
+    /// DIAMETER_UNABLE_TO_COMPLY.
+    pub async fn handle(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        if req.get_command_code() != CommandCode::CreditControl || !req.is_request() {
+            return Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_COMMAND_UNSUPPORTED,
+                None,
+                &self.node,
+            ));
+        }
+        if req.get_application_id() != ApplicationId::CreditControl {
+            return Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_APPLICATION_UNSUPPORTED,
+                None,
+                &self.node,
+            ));
+        }
+
+        let ccr = match CreditControlRequest::from_message(&req) {
+            Ok(ccr) => ccr,
+            Err(e) => {
+                log::warn!("Invalid Credit-Control-Request; error: {}", e);
+                let result_code = match e {
+                    Error::AvpNotFound(_) => result_code::DIAMETER_MISSING_AVP,
+                    Error::InvalidAvpValue(_) => result_code::DIAMETER_INVALID_AVP_VALUE,
+                    _ => result_code::DIAMETER_UNABLE_TO_COMPLY,
+                };
+                return Ok(DiameterMessage::create_answer(
+                    &req,
+                    result_code,
+                    &self.node,
+                ));
+            }
+        };
+
+        let cca = self.process(ccr).await?;
+        cca.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &self.dict)
+    }
+
+    /// Releases the sessions whose Tcc timer has expired, notifying the
+    /// handler, and returns their Session-Ids.
+    pub async fn expire_sessions(&self) -> Result<Vec<String>> {
+        let expired = {
+            let mut sessions = self.sessions.lock()?;
+            let now = Instant::now();
+            let expired: Vec<String> = sessions
+                .iter()
+                .filter(|(_, session)| session.deadline <= now)
+                .map(|(session_id, _)| session_id.clone())
+                .collect();
+            for session_id in &expired {
+                sessions.remove(session_id);
+                self.discard(session_id);
+            }
+            expired
+        };
+        for session_id in &expired {
+            log::info!("Tcc expired; session: {}", session_id);
+            self.handler.session_expired(session_id).await;
+        }
+        Ok(expired)
+    }
+
+    /// Returns the Session-Ids of the open sessions.
+    pub fn session_ids(&self) -> Result<Vec<String>> {
+        Ok(self.sessions.lock()?.keys().cloned().collect())
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let sessions = self.sessions.lock().ok()?;
+        sessions.values().map(|session| session.deadline).min()
+    }
+
+    fn encode_session(&self, session: &CcServerSession) -> Result<Vec<u8>> {
+        let mut answer = Vec::new();
+        session
+            .answer
+            .to_message(0, 0, &self.dict)?
+            .encode_to(&mut answer)?;
+        let mut writer = RecordWriter::new();
+        writer.put_u32(session.request_number);
+        writer.put_deadline(Some(session.deadline));
+        writer.put_bytes(&answer);
+        Ok(writer.into_bytes())
+    }
+
+    fn decode_session(&self, record: &[u8]) -> Result<CcServerSession> {
+        let mut reader = RecordReader::new(record);
+        let request_number = reader.get_u32()?;
+        let deadline = reader
+            .get_deadline()?
+            .ok_or_else(|| Error::DecodeError("Session record without Tcc".into()))?;
+        let answer = reader.get_bytes()?;
+        let answer =
+            DiameterMessage::decode_from(&mut Cursor::new(answer), Arc::clone(&self.dict))?;
+        Ok(CcServerSession {
+            request_number,
+            deadline,
+            answer: CreditControlAnswer::from_message(&answer)?,
+        })
+    }
+
+    fn save(&self, session_id: &str, session: &CcServerSession) {
+        if let Some(store) = &self.store {
+            let result = self
+                .encode_session(session)
+                .and_then(|record| store.put(session_id, &record));
+            if let Err(e) = result {
+                log::error!("Failed to store session {}; error: {}", session_id, e);
+            }
+        }
+    }
+
+    fn discard(&self, session_id: &str) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(session_id) {
+                log::error!(
+                    "Failed to remove stored session {}; error: {}",
+                    session_id,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn process(&self, ccr: CreditControlRequest) -> Result<CreditControlAnswer> {
+        let mut cca = CreditControlAnswer::new(&ccr, result_code::DIAMETER_SUCCESS, &self.node);
+        if let CcRequestType::Unknown(value) = ccr.cc_request_type {
+            log::warn!(
+                "Unknown CC-Request-Type {}; session: {}",
+                value,
+                ccr.session_id
+            );
+            cca.result_code = result_code::DIAMETER_INVALID_AVP_VALUE;
+            return Ok(cca);
+        }
+
+        // Check the request against the session before calling the handler
+        let expired = {
+            let mut sessions = self.sessions.lock()?;
+            let expired = match sessions.get(&ccr.session_id) {
+                Some(session) if session.deadline <= Instant::now() => {
+                    sessions.remove(&ccr.session_id);
+                    self.discard(&ccr.session_id);
+                    true
+                }
+                _ => false,
+            };
+            match (ccr.cc_request_type, sessions.get(&ccr.session_id)) {
+                (_, Some(session)) if ccr.cc_request_number == session.request_number => {
+                    log::debug!("Duplicate request; session: {}", ccr.session_id);
+                    let mut answer = session.answer.clone();
+                    answer.proxy_info = ccr.proxy_info.clone();
+                    return Ok(answer);
+                }
+                (CcRequestType::InitialRequest | CcRequestType::EventRequest, Some(_)) => {
+                    cca.result_code = result_code::DIAMETER_INVALID_AVP_VALUE;
+                }
+                (_, Some(session)) if ccr.cc_request_number < session.request_number => {
+                    cca.result_code = result_code::DIAMETER_INVALID_AVP_VALUE;
+                }
+                (CcRequestType::UpdateRequest | CcRequestType::TerminationRequest, None) => {
+                    cca.result_code = result_code::DIAMETER_UNKNOWN_SESSION_ID;
+                }
+                _ => {}
+            }
+            expired
+        };
+        if expired {
+            self.handler.session_expired(&ccr.session_id).await;
+        }
+        if cca.result_code != result_code::DIAMETER_SUCCESS {
+            log::warn!(
+                "Rejected {:?}; session: {}, CC-Request-Number: {}, Result-Code: {}",
+                ccr.cc_request_type,
+                ccr.session_id,
+                ccr.cc_request_number,
+                cca.result_code
+            );
+            return Ok(cca);
+        }
+
+        let result = match ccr.cc_request_type {
+            CcRequestType::InitialRequest => self.handler.initial(&ccr, cca).await,
+            CcRequestType::UpdateRequest => self.handler.update(&ccr, cca).await,
+            CcRequestType::TerminationRequest => self.handler.terminate(&ccr, cca).await,
+            CcRequestType::EventRequest => self.handler.event(&ccr, cca).await,
+            // Answered above
+            CcRequestType::Unknown(_) => Ok(cca),
+        };
+        let mut cca = match result {
+            Ok(cca) => cca,
+            Err(e) => {
+                log::error!("Failed to handle {:?}; error: {}", ccr.cc_request_type, e);
+                CreditControlAnswer::new(&ccr, result_code::DIAMETER_UNABLE_TO_COMPLY, &self.node)
+            }
+        };
+        cca.session_id = ccr.session_id.clone();
+        cca.cc_request_type = ccr.cc_request_type;
+        cca.cc_request_number = ccr.cc_request_number;
+
+        let success = (2000..3000).contains(&cca.result_code);
+        let mut sessions = self.sessions.lock()?;
+        match ccr.cc_request_type {
+            CcRequestType::InitialRequest | CcRequestType::UpdateRequest if success => {
+                if cca.validity_time.is_none() {
+                    cca.validity_time = self.config.validity_time;
+                }
+                let tcc = cca
+                    .validity_time
+                    .map(|validity_time| Duration::from_secs(2 * u64::from(validity_time)))
+                    .unwrap_or(self.config.tcc_timeout);
+                let session = CcServerSession {
+                    request_number: ccr.cc_request_number,
+                    deadline: Instant::now() + tcc,
+                    answer: cca.clone(),
+                };
+                self.save(&ccr.session_id, &session);
+                sessions.insert(ccr.session_id.clone(), session);
+            }
+            CcRequestType::EventRequest => {}
+            _ => {
+                sessions.remove(&ccr.session_id);
+                self.discard(&ccr.session_id);
+            }
+        }
+        Ok(cca)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::credit_control::result_code::DIAMETER_CREDIT_LIMIT_REACHED;
+    use crate::applications::credit_control::ServiceUnit;
+    use crate::dictionary;
+    use crate::flags;
+    use crate::session::store::file::FileStore;
+    use crate::transport::{DiameterClient, DiameterClientConfig, DiameterServerConfig};
+
+    struct Ocs {
+        expired: Mutex<Vec<String>>,
+    }
+
+    impl CreditControlHandler for Ocs {
+        async fn initial(
+            &self,
+            ccr: &CreditControlRequest,
+            mut cca: CreditControlAnswer,
+        ) -> Result<CreditControlAnswer> {
+            if ccr.user_name.as_deref() == Some("broken") {
+                return Err(Error::ServerError("rating engine unavailable".into()));
+            }
+            cca.granted_service_unit = Some(ServiceUnit {
+                cc_time: Some(600),
+                ..Default::default()
+            });
+            Ok(cca)
+        }
+
+        async fn update(
+            &self,
+            ccr: &CreditControlRequest,
+            mut cca: CreditControlAnswer,
+        ) -> Result<CreditControlAnswer> {
+            if ccr.user_name.as_deref() == Some("exhausted") {
+                cca.result_code = DIAMETER_CREDIT_LIMIT_REACHED;
+            }
+            Ok(cca)
+        }
+
+        async fn session_expired(&self, session_id: &str) {
+            self.expired.lock().unwrap().push(session_id.into());
+        }
+    }
+
+    fn server(config: CcServerConfig) -> CreditControlServer<Ocs> {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let node = NodeConfig::new("ocs.example.com", "example.com");
+        let ocs = Ocs {
+            expired: Mutex::new(Vec::new()),
+        };
+        CreditControlServer::new(ocs, dict, node, config)
+    }
+
+    fn ccr(session_id: &str, cc_request_type: CcRequestType, number: u32) -> CreditControlRequest {
+        let node = NodeConfig::new("pcef.example.com", "example.com");
+        CreditControlRequest::new(
+            session_id,
+            &node,
+            "example.com",
+            "32251@3gpp.org",
+            cc_request_type,
+            number,
+        )
+    }
+
+    async fn send(
+        server: &CreditControlServer<Ocs>,
+        ccr: &CreditControlRequest,
+    ) -> CreditControlAnswer {
+        let req = ccr.to_message(1, 1, &server.dict).unwrap();
+        let res = server.handle(req).await.unwrap();
+        CreditControlAnswer::from_message(&res).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_sequencing() {
+        let server = server(CcServerConfig {
+            validity_time: Some(60),
+            ..Default::default()
+        });
+
+        let cca = send(&server, &ccr("ses;1", CcRequestType::InitialRequest, 0)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert_eq!(cca.validity_time, Some(60));
+        assert_eq!(server.session_ids().unwrap(), vec!["ses;1".to_string()]);
+
+        // A retransmission is answered from the session
+        let cca = send(&server, &ccr("ses;1", CcRequestType::InitialRequest, 0)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert!(cca.granted_service_unit.is_some());
+
+        let cca = send(&server, &ccr("ses;1", CcRequestType::UpdateRequest, 1)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert_eq!(cca.cc_request_number, 1);
+
+        let cca = send(&server, &ccr("ses;1", CcRequestType::UpdateRequest, 0)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_INVALID_AVP_VALUE);
+        let cca = send(&server, &ccr("ses;1", CcRequestType::InitialRequest, 2)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_INVALID_AVP_VALUE);
+
+        // An unknown request type leaves the session as it is
+        let cca = send(&server, &ccr("ses;1", CcRequestType::Unknown(5), 2)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_INVALID_AVP_VALUE);
+        assert_eq!(server.session_ids().unwrap().len(), 1);
+
+        let cca = send(&server, &ccr("ses;1", CcRequestType::TerminationRequest, 2)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert!(server.session_ids().unwrap().is_empty());
+
+        let cca = send(&server, &ccr("ses;1", CcRequestType::UpdateRequest, 3)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_UNKNOWN_SESSION_ID);
+    }
+
+    #[tokio::test]
+    async fn test_handler_result_codes() {
+        let server = server(CcServerConfig::default());
+
+        let mut request = ccr("ses;2", CcRequestType::InitialRequest, 0);
+        request.user_name = Some("broken".into());
+        let cca = send(&server, &request).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_UNABLE_TO_COMPLY);
+        assert!(server.session_ids().unwrap().is_empty());
+
+        send(&server, &ccr("ses;3", CcRequestType::InitialRequest, 0)).await;
+        let mut request = ccr("ses;3", CcRequestType::UpdateRequest, 1);
+        request.user_name = Some("exhausted".into());
+        let cca = send(&server, &request).await;
+        assert_eq!(cca.result_code, DIAMETER_CREDIT_LIMIT_REACHED);
+        assert!(server.session_ids().unwrap().is_empty());
+
+        let cca = send(&server, &ccr("ses;4", CcRequestType::EventRequest, 0)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_UNABLE_TO_COMPLY);
+
+        // Missing CC-Request-Number
+        let mut req = ccr("ses;5", CcRequestType::InitialRequest, 0)
+            .to_message(1, 1, &server.dict)
+            .unwrap();
+        req.remove_avp(415);
+        let res = server.handle(req).await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_MISSING_AVP
+        );
+
+        // Not a Credit-Control-Request
+        let req = DiameterMessage::new(
+            CommandCode::CapabilitiesExchange,
+            ApplicationId::Common,
+            flags::REQUEST,
+            1,
+            1,
+            Arc::clone(&server.dict),
+        );
+        let res = server.handle(req).await.unwrap();
+        assert!(res.is_error());
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_COMMAND_UNSUPPORTED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tcc_supervision() {
+        let server = server(CcServerConfig {
+            tcc_timeout: Duration::from_millis(50),
+            validity_time: None,
+        });
+
+        send(&server, &ccr("ses;6", CcRequestType::InitialRequest, 0)).await;
+        send(&server, &ccr("ses;7", CcRequestType::InitialRequest, 0)).await;
+        assert!(server.expire_sessions().await.unwrap().is_empty());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // An expired session is released when its next request arrives
+        let cca = send(&server, &ccr("ses;7", CcRequestType::UpdateRequest, 1)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_UNKNOWN_SESSION_ID);
+
+        assert_eq!(server.expire_sessions().await.unwrap(), vec!["ses;6"]);
+        assert_eq!(
+            *server.handler.expired.lock().unwrap(),
+            vec!["ses;7", "ses;6"]
+        );
+        assert!(server.session_ids().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store() {
+        let path = std::env::temp_dir().join(format!("diameter-ocs-{}.log", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let store: Arc<dyn SessionStore> = Arc::new(FileStore::open(&path).unwrap());
+        let mut ocs = server(CcServerConfig::default());
+        ocs.set_store(Arc::clone(&store)).unwrap();
+        send(&ocs, &ccr("ses;8", CcRequestType::InitialRequest, 0)).await;
+        send(&ocs, &ccr("ses;8", CcRequestType::UpdateRequest, 1)).await;
+        send(&ocs, &ccr("ses;9", CcRequestType::InitialRequest, 0)).await;
+        send(&ocs, &ccr("ses;9", CcRequestType::TerminationRequest, 1)).await;
+        drop(ocs);
+        drop(store);
+
+        // A restarted server answers retransmissions and goes on sequencing
+        let mut ocs = server(CcServerConfig::default());
+        ocs.set_store(Arc::new(FileStore::open(&path).unwrap()))
+            .unwrap();
+        assert_eq!(ocs.session_ids().unwrap(), vec!["ses;8".to_string()]);
+        let cca = send(&ocs, &ccr("ses;8", CcRequestType::UpdateRequest, 1)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert_eq!(cca.cc_request_number, 1);
+        let cca = send(&ocs, &ccr("ses;8", CcRequestType::UpdateRequest, 0)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_INVALID_AVP_VALUE);
+        let cca = send(&ocs, &ccr("ses;8", CcRequestType::TerminationRequest, 2)).await;
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        drop(ocs);
+
+        let store = FileStore::open(&path).unwrap();
+        assert!(store.records().unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_listen() {
+        let ocs = server(CcServerConfig::default());
+        let mut diameter_server =
+            DiameterServer::new("0.0.0.0:3876", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let ocs_ref = ocs.clone();
+        tokio::spawn(async move {
+            ocs_ref.listen(&mut diameter_server).await.unwrap();
+        });
+
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3876", config);
+        let mut handler = client.connect().await.unwrap();
+        let dict = Arc::clone(&ocs.dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict).await;
+        });
+
+        let req = ccr("ses;8", CcRequestType::InitialRequest, 0)
+            .to_message(1, 1, &ocs.dict)
+            .unwrap();
+        let res = client.send_message(req).await.unwrap().await.unwrap();
+        let cca = CreditControlAnswer::from_message(&res).unwrap();
+        assert_eq!(cca.result_code, result_code::DIAMETER_SUCCESS);
+        assert_eq!(ocs.session_ids().unwrap(), vec!["ses;8"]);
+    }
+}
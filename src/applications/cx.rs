@@ -0,0 +1,1221 @@
+//! Cx/Dx Application
+//!
+//! Typed messages of the Cx and Dx interfaces between the I-CSCF/S-CSCF and
+//! the HSS or SLF, as defined in
+//! [3GPP TS 29.229](https://www.3gpp.org/DynaReport/29229.htm).
+//!
+//! The Cx AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary. The User-Data AVP carries the IMS subscription as an
+//! XML document (3GPP TS 29.228 annex E), kept as raw bytes.
+//!
+//! ```
+//! use diameter::applications::cx::{
+//!     self, ServerAssignmentAnswer, ServerAssignmentRequest, ServerAssignmentType,
+//!     UserDataAlreadyAvailable,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, cx::DICT_XML]));
+//! let scscf = NodeConfig::new("scscf.ims.example.com", "ims.example.com");
+//! let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+//!
+//! let mut sar = ServerAssignmentRequest::new(
+//!     "scscf.ims.example.com;1;1",
+//!     &scscf,
+//!     "ims.example.com",
+//!     "sip:scscf.ims.example.com",
+//!     ServerAssignmentType::Registration,
+//!     UserDataAlreadyAvailable::UserDataNotAvailable,
+//! );
+//! sar.public_identity.push("sip:alice@ims.example.com".into());
+//!
+//! let mut saa = ServerAssignmentAnswer::new(&sar, 2001, &hss);
+//! saa.user_data = Some(b"<IMSSubscription>...</IMSSubscription>".to_vec());
+//!
+//! let msg = saa.to_message(1, 1, &dict).unwrap();
+//! assert_eq!(ServerAssignmentAnswer::from_message(&msg).unwrap(), saa);
+//! ```
+
+use crate::applications::common::{
+    AuthSessionState, ExperimentalResult, ProxyInfo, SupportedFeatures,
+    VendorSpecificApplicationId, TGPP_VENDOR_ID,
+};
+use crate::node::NodeConfig;
+use crate::{DiameterMessage, GroupedAvp};
+
+pub use crate::applications::gx::ChargingInformation;
+
+/// Dictionary of the Cx/Dx application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-cx.xml");
+
+/// Auth-Application-Id of the Cx/Dx application.
+pub const APPLICATION_ID: u32 = 16777216;
+
+/// Experimental-Result-Code values defined in 3GPP TS 29.229 section 6.2.
+pub mod result_code {
+    // Success
+    pub const DIAMETER_FIRST_REGISTRATION: u32 = 2001;
+    pub const DIAMETER_SUBSEQUENT_REGISTRATION: u32 = 2002;
+    pub const DIAMETER_UNREGISTERED_SERVICE: u32 = 2003;
+    pub const DIAMETER_SUCCESS_SERVER_NAME_NOT_STORED: u32 = 2004;
+
+    // Permanent Failures
+    pub const DIAMETER_ERROR_USER_UNKNOWN: u32 = 5001;
+    pub const DIAMETER_ERROR_IDENTITIES_DONT_MATCH: u32 = 5002;
+    pub const DIAMETER_ERROR_IDENTITY_NOT_REGISTERED: u32 = 5003;
+    pub const DIAMETER_ERROR_ROAMING_NOT_ALLOWED: u32 = 5004;
+    pub const DIAMETER_ERROR_IDENTITY_ALREADY_REGISTERED: u32 = 5005;
+    pub const DIAMETER_ERROR_AUTH_SCHEME_NOT_SUPPORTED: u32 = 5006;
+    pub const DIAMETER_ERROR_IN_ASSIGNMENT_TYPE: u32 = 5007;
+    pub const DIAMETER_ERROR_TOO_MUCH_DATA: u32 = 5008;
+    pub const DIAMETER_ERROR_NOT_SUPPORTED_USER_DATA: u32 = 5009;
+    pub const DIAMETER_ERROR_FEATURE_UNSUPPORTED: u32 = 5011;
+    pub const DIAMETER_ERROR_SERVING_NODE_FEATURE_UNSUPPORTED: u32 = 5012;
+}
+
+enumerated! {
+    /// Values of the Loose-Route-Indication AVP (638).
+    pub enum LooseRouteIndication ("Loose-Route-Indication") {
+        LooseRouteNotRequired = 0,
+        LooseRouteRequired = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Originating-Request AVP (633).
+    pub enum OriginatingRequest ("Originating-Request") {
+        Originating = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the Reason-Code AVP (616).
+    pub enum ReasonCode ("Reason-Code") {
+        PermanentTermination = 0,
+        NewServerAssigned = 1,
+        ServerChange = 2,
+        RemoveScscf = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Server-Assignment-Type AVP (614).
+    pub enum ServerAssignmentType ("Server-Assignment-Type") {
+        NoAssignment = 0,
+        Registration = 1,
+        ReRegistration = 2,
+        UnregisteredUser = 3,
+        TimeoutDeregistration = 4,
+        UserDeregistration = 5,
+        TimeoutDeregistrationStoreServerName = 6,
+        UserDeregistrationStoreServerName = 7,
+        AdministrativeDeregistration = 8,
+        AuthenticationFailure = 9,
+        AuthenticationTimeout = 10,
+        DeregistrationTooMuchData = 11,
+        AaaUserDataRequest = 12,
+        PgwUpdate = 13,
+        Restoration = 14,
+    }
+}
+
+enumerated! {
+    /// Values of the Session-Priority AVP (650).
+    pub enum SessionPriority ("Session-Priority") {
+        Priority0 = 0,
+        Priority1 = 1,
+        Priority2 = 2,
+        Priority3 = 3,
+        Priority4 = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the User-Authorization-Type AVP (623).
+    pub enum UserAuthorizationType ("User-Authorization-Type") {
+        Registration = 0,
+        DeRegistration = 1,
+        RegistrationAndCapabilities = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the User-Data-Already-Available AVP (624).
+    pub enum UserDataAlreadyAvailable ("User-Data-Already-Available") {
+        UserDataNotAvailable = 0,
+        UserDataAlreadyAvailable = 1,
+    }
+}
+
+/// The Server-Capabilities AVP (603), used by the I-CSCF to select an
+/// S-CSCF.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ServerCapabilities {
+    #[avp(name = "Mandatory-Capability")]
+    pub mandatory_capability: Vec<u32>,
+    #[avp(name = "Optional-Capability")]
+    pub optional_capability: Vec<u32>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Vec<String>,
+}
+
+/// The SIP-Digest-Authenticate AVP (635).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct SipDigestAuthenticate {
+    #[avp(name = "Digest-Realm")]
+    pub digest_realm: String,
+    #[avp(name = "Digest-Algorithm")]
+    pub digest_algorithm: Option<String>,
+    #[avp(name = "Digest-QoP")]
+    pub digest_qop: String,
+    #[avp(name = "Digest-HA1")]
+    pub digest_ha1: String,
+}
+
+/// The SIP-Auth-Data-Item AVP (612), one authentication vector.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct SipAuthDataItem {
+    #[avp(name = "SIP-Item-Number")]
+    pub sip_item_number: Option<u32>,
+    #[avp(name = "SIP-Authentication-Scheme")]
+    pub sip_authentication_scheme: Option<String>,
+    #[avp(name = "SIP-Authenticate")]
+    pub sip_authenticate: Option<Vec<u8>>,
+    #[avp(name = "SIP-Authorization")]
+    pub sip_authorization: Option<Vec<u8>>,
+    #[avp(name = "SIP-Authentication-Context")]
+    pub sip_authentication_context: Option<Vec<u8>>,
+    #[avp(name = "Confidentiality-Key")]
+    pub confidentiality_key: Option<Vec<u8>>,
+    #[avp(name = "Integrity-Key")]
+    pub integrity_key: Option<Vec<u8>>,
+    #[avp(name = "SIP-Digest-Authenticate", grouped)]
+    pub sip_digest_authenticate: Option<SipDigestAuthenticate>,
+}
+
+/// The Deregistration-Reason AVP (615).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct DeregistrationReason {
+    #[avp(name = "Reason-Code")]
+    pub reason_code: ReasonCode,
+    #[avp(name = "Reason-Info")]
+    pub reason_info: Option<String>,
+}
+
+/// The Associated-Identities AVP (632), the private identities sharing
+/// the registration.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AssociatedIdentities {
+    #[avp(name = "User-Name")]
+    pub user_name: Vec<String>,
+}
+
+/// Cx User-Authorization-Request (UAR), 3GPP TS 29.229 section 6.1.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UserAuthorization, application = Cx, request, proxyable)]
+pub struct UserAuthorizationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: String,
+    #[avp(name = "Visited-Network-Identifier")]
+    pub visited_network_identifier: Vec<u8>,
+    #[avp(name = "User-Authorization-Type")]
+    pub user_authorization_type: Option<UserAuthorizationType>,
+    #[avp(name = "UAR-Flags")]
+    pub uar_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx User-Authorization-Answer (UAA), 3GPP TS 29.229 section 6.1.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UserAuthorization, application = Cx, proxyable)]
+pub struct UserAuthorizationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Option<String>,
+    #[avp(name = "Server-Capabilities", grouped)]
+    pub server_capabilities: Option<ServerCapabilities>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Server-Assignment-Request (SAR), 3GPP TS 29.229 section 6.1.3.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ServerAssignment, application = Cx, request, proxyable)]
+pub struct ServerAssignmentRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: Vec<String>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Server-Name")]
+    pub server_name: String,
+    #[avp(name = "Server-Assignment-Type")]
+    pub server_assignment_type: ServerAssignmentType,
+    #[avp(name = "User-Data-Already-Available")]
+    pub user_data_already_available: UserDataAlreadyAvailable,
+    #[avp(name = "Session-Priority")]
+    pub session_priority: Option<SessionPriority>,
+    #[avp(name = "SAR-Flags")]
+    pub sar_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Server-Assignment-Answer (SAA), 3GPP TS 29.229 section 6.1.4.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ServerAssignment, application = Cx, proxyable)]
+pub struct ServerAssignmentAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "User-Data")]
+    pub user_data: Option<Vec<u8>>,
+    #[avp(name = "Charging-Information", grouped)]
+    pub charging_information: Option<ChargingInformation>,
+    #[avp(name = "Associated-Identities", grouped)]
+    pub associated_identities: Option<AssociatedIdentities>,
+    #[avp(name = "Loose-Route-Indication")]
+    pub loose_route_indication: Option<LooseRouteIndication>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Option<String>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Location-Info-Request (LIR), 3GPP TS 29.229 section 6.1.5.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = LocationInfo, application = Cx, request, proxyable)]
+pub struct LocationInfoRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Originating-Request")]
+    pub originating_request: Option<OriginatingRequest>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: String,
+    #[avp(name = "User-Authorization-Type")]
+    pub user_authorization_type: Option<UserAuthorizationType>,
+    #[avp(name = "Session-Priority")]
+    pub session_priority: Option<SessionPriority>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Location-Info-Answer (LIA), 3GPP TS 29.229 section 6.1.6.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = LocationInfo, application = Cx, proxyable)]
+pub struct LocationInfoAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Option<String>,
+    #[avp(name = "Server-Capabilities", grouped)]
+    pub server_capabilities: Option<ServerCapabilities>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "LIA-Flags")]
+    pub lia_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Multimedia-Auth-Request (MAR), 3GPP TS 29.229 section 6.1.7.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = MultimediaAuth, application = Cx, request, proxyable)]
+pub struct MultimediaAuthRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: String,
+    #[avp(name = "SIP-Auth-Data-Item", grouped)]
+    pub sip_auth_data_item: SipAuthDataItem,
+    #[avp(name = "SIP-Number-Auth-Items")]
+    pub sip_number_auth_items: u32,
+    #[avp(name = "Server-Name")]
+    pub server_name: String,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Multimedia-Auth-Answer (MAA), 3GPP TS 29.229 section 6.1.8.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = MultimediaAuth, application = Cx, proxyable)]
+pub struct MultimediaAuthAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: Option<String>,
+    #[avp(name = "SIP-Number-Auth-Items")]
+    pub sip_number_auth_items: Option<u32>,
+    #[avp(name = "SIP-Auth-Data-Item", grouped)]
+    pub sip_auth_data_item: Vec<SipAuthDataItem>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Registration-Termination-Request (RTR), 3GPP TS 29.229 section 6.1.9.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = RegistrationTermination,
+    application = Cx,
+    request,
+    proxyable,
+)]
+pub struct RegistrationTerminationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Associated-Identities", grouped)]
+    pub associated_identities: Option<AssociatedIdentities>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Public-Identity")]
+    pub public_identity: Vec<String>,
+    #[avp(name = "Deregistration-Reason", grouped)]
+    pub deregistration_reason: DeregistrationReason,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Registration-Termination-Answer (RTA), 3GPP TS 29.229 section 6.1.10.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = RegistrationTermination, application = Cx, proxyable)]
+pub struct RegistrationTerminationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Associated-Identities", grouped)]
+    pub associated_identities: Option<AssociatedIdentities>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Push-Profile-Request (PPR), 3GPP TS 29.229 section 6.1.11.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PushProfile, application = Cx, request, proxyable)]
+pub struct PushProfileRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Data")]
+    pub user_data: Option<Vec<u8>>,
+    #[avp(name = "Charging-Information", grouped)]
+    pub charging_information: Option<ChargingInformation>,
+    #[avp(name = "SIP-Auth-Data-Item", grouped)]
+    pub sip_auth_data_item: Option<SipAuthDataItem>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Cx Push-Profile-Answer (PPA), 3GPP TS 29.229 section 6.1.12.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PushProfile, application = Cx, proxyable)]
+pub struct PushProfileAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+impl UserAuthorizationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+        public_identity: &str,
+        visited_network_identifier: &[u8],
+    ) -> UserAuthorizationRequest {
+        UserAuthorizationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            public_identity: public_identity.into(),
+            visited_network_identifier: visited_network_identifier.to_vec(),
+            user_authorization_type: None,
+            uar_flags: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl UserAuthorizationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &UserAuthorizationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> UserAuthorizationAnswer {
+        UserAuthorizationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            server_name: None,
+            server_capabilities: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ServerAssignmentRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        server_name: &str,
+        server_assignment_type: ServerAssignmentType,
+        user_data_already_available: UserDataAlreadyAvailable,
+    ) -> ServerAssignmentRequest {
+        ServerAssignmentRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: None,
+            supported_features: Vec::new(),
+            public_identity: Vec::new(),
+            wildcarded_public_identity: None,
+            server_name: server_name.into(),
+            server_assignment_type,
+            user_data_already_available,
+            session_priority: None,
+            sar_flags: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ServerAssignmentAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &ServerAssignmentRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> ServerAssignmentAnswer {
+        ServerAssignmentAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            user_name: None,
+            user_data: None,
+            charging_information: None,
+            associated_identities: None,
+            loose_route_indication: None,
+            server_name: None,
+            wildcarded_public_identity: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl LocationInfoRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        public_identity: &str,
+    ) -> LocationInfoRequest {
+        LocationInfoRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            originating_request: None,
+            supported_features: Vec::new(),
+            public_identity: public_identity.into(),
+            user_authorization_type: None,
+            session_priority: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl LocationInfoAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &LocationInfoRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> LocationInfoAnswer {
+        LocationInfoAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            server_name: None,
+            server_capabilities: None,
+            wildcarded_public_identity: None,
+            lia_flags: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl MultimediaAuthRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    ///
+    /// One authentication vector is requested; set `sip_number_auth_items`
+    /// to request more.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+        public_identity: &str,
+        sip_auth_data_item: SipAuthDataItem,
+        server_name: &str,
+    ) -> MultimediaAuthRequest {
+        MultimediaAuthRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: None,
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            public_identity: public_identity.into(),
+            sip_auth_data_item,
+            sip_number_auth_items: 1,
+            server_name: server_name.into(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl MultimediaAuthAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &MultimediaAuthRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> MultimediaAuthAnswer {
+        MultimediaAuthAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            user_name: None,
+            public_identity: None,
+            sip_number_auth_items: None,
+            sip_auth_data_item: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl RegistrationTerminationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_name: &str,
+        deregistration_reason: DeregistrationReason,
+    ) -> RegistrationTerminationRequest {
+        RegistrationTerminationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            associated_identities: None,
+            supported_features: Vec::new(),
+            public_identity: Vec::new(),
+            deregistration_reason,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl RegistrationTerminationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &RegistrationTerminationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> RegistrationTerminationAnswer {
+        RegistrationTerminationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            associated_identities: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PushProfileRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_name: &str,
+    ) -> PushProfileRequest {
+        PushProfileRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            user_data: None,
+            charging_information: None,
+            sip_auth_data_item: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PushProfileAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &PushProfileRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> PushProfileAnswer {
+        PushProfileAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    #[test]
+    fn test_uar_uaa_lir_lia_round_trip() {
+        let dict = dict();
+        let icscf = NodeConfig::new("icscf.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+
+        let mut uar = UserAuthorizationRequest::new(
+            "icscf.ims.example.com;1;1",
+            &icscf,
+            "ims.example.com",
+            "alice@ims.example.com",
+            "sip:alice@ims.example.com",
+            b"ims.example.com",
+        );
+        uar.user_authorization_type = Some(UserAuthorizationType::Registration);
+
+        let msg = uar.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::UserAuthorization);
+        assert_eq!(msg.get_application_id(), ApplicationId::Cx);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Vendor-Specific-Application-Id/Auth-Application-Id")
+                .unwrap(),
+            vec![APPLICATION_ID]
+        );
+        assert_eq!(UserAuthorizationRequest::from_message(&msg).unwrap(), uar);
+
+        let mut uaa = UserAuthorizationAnswer::new(&uar, 2001, &hss);
+        uaa.result_code = None;
+        uaa.experimental_result = Some(ExperimentalResult {
+            vendor_id: TGPP_VENDOR_ID,
+            experimental_result_code: result_code::DIAMETER_FIRST_REGISTRATION,
+        });
+        uaa.server_capabilities = Some(ServerCapabilities {
+            mandatory_capability: vec![1, 2],
+            optional_capability: vec![3],
+            server_name: Vec::new(),
+        });
+        let msg = round_trip(uaa.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Server-Capabilities/Mandatory-Capability[*]")
+                .unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(UserAuthorizationAnswer::from_message(&msg).unwrap(), uaa);
+
+        let mut lir = LocationInfoRequest::new(
+            "icscf.ims.example.com;1;2",
+            &icscf,
+            "ims.example.com",
+            "sip:bob@ims.example.com",
+        );
+        lir.originating_request = Some(OriginatingRequest::Originating);
+        lir.session_priority = Some(SessionPriority::Priority2);
+        let msg = round_trip(lir.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(LocationInfoRequest::from_message(&msg).unwrap(), lir);
+
+        let mut lia = LocationInfoAnswer::new(&lir, 2001, &hss);
+        lia.server_name = Some("sip:scscf.ims.example.com".into());
+        let msg = round_trip(lia.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(LocationInfoAnswer::from_message(&msg).unwrap(), lia);
+    }
+
+    #[test]
+    fn test_mar_maa_sar_saa_round_trip() {
+        let dict = dict();
+        let scscf = NodeConfig::new("scscf.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+
+        let mar = MultimediaAuthRequest::new(
+            "scscf.ims.example.com;1;1",
+            &scscf,
+            "ims.example.com",
+            "alice@ims.example.com",
+            "sip:alice@ims.example.com",
+            SipAuthDataItem {
+                sip_authentication_scheme: Some("Digest-AKAv1-MD5".into()),
+                ..Default::default()
+            },
+            "sip:scscf.ims.example.com",
+        );
+        let msg = round_trip(mar.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::MultimediaAuth);
+        assert_eq!(MultimediaAuthRequest::from_message(&msg).unwrap(), mar);
+
+        let mut maa = MultimediaAuthAnswer::new(&mar, 2001, &hss);
+        maa.user_name = Some("alice@ims.example.com".into());
+        maa.public_identity = Some("sip:alice@ims.example.com".into());
+        maa.sip_number_auth_items = Some(2);
+        maa.sip_auth_data_item = vec![
+            SipAuthDataItem {
+                sip_item_number: Some(1),
+                sip_authentication_scheme: Some("Digest-AKAv1-MD5".into()),
+                sip_authenticate: Some(vec![0x01; 32]),
+                sip_authorization: Some(vec![0x02; 8]),
+                confidentiality_key: Some(vec![0x03; 16]),
+                integrity_key: Some(vec![0x04; 16]),
+                ..Default::default()
+            },
+            SipAuthDataItem {
+                sip_item_number: Some(2),
+                sip_authentication_scheme: Some("SIP Digest".into()),
+                sip_digest_authenticate: Some(SipDigestAuthenticate {
+                    digest_realm: "ims.example.com".into(),
+                    digest_algorithm: Some("MD5".into()),
+                    digest_qop: "auth".into(),
+                    digest_ha1: "0123456789abcdef0123456789abcdef".into(),
+                }),
+                ..Default::default()
+            },
+        ];
+        let msg = round_trip(maa.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<u32>("SIP-Auth-Data-Item[*]/SIP-Item-Number")
+                .unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(MultimediaAuthAnswer::from_message(&msg).unwrap(), maa);
+
+        let mut sar = ServerAssignmentRequest::new(
+            "scscf.ims.example.com;1;2",
+            &scscf,
+            "ims.example.com",
+            "sip:scscf.ims.example.com",
+            ServerAssignmentType::Registration,
+            UserDataAlreadyAvailable::UserDataNotAvailable,
+        );
+        sar.user_name = Some("alice@ims.example.com".into());
+        sar.public_identity.push("sip:alice@ims.example.com".into());
+        let msg = round_trip(sar.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(ServerAssignmentRequest::from_message(&msg).unwrap(), sar);
+
+        let xml = "<?xml version=\"1.0\"?><IMSSubscription><PrivateID>alice@ims.example.com\
+                   </PrivateID></IMSSubscription>";
+        let mut saa = ServerAssignmentAnswer::new(&sar, 2001, &hss);
+        saa.user_data = Some(xml.as_bytes().to_vec());
+        saa.charging_information = Some(ChargingInformation {
+            primary_event_charging_function_name: Some("aaa://ecf.ims.example.com".into()),
+            ..Default::default()
+        });
+        saa.associated_identities = Some(AssociatedIdentities {
+            user_name: vec!["alice@ims.example.com".into()],
+        });
+        let msg = round_trip(saa.to_message(2, 2, &dict).unwrap(), &dict);
+        let decoded = ServerAssignmentAnswer::from_message(&msg).unwrap();
+        assert_eq!(decoded.user_data.as_deref(), Some(xml.as_bytes()));
+        assert_eq!(decoded, saa);
+    }
+
+    #[test]
+    fn test_rtr_rta_ppr_ppa_round_trip() {
+        let dict = dict();
+        let scscf = NodeConfig::new("scscf.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+
+        let rtr = RegistrationTerminationRequest::new(
+            "hss.ims.example.com;1;1",
+            &hss,
+            "ims.example.com",
+            "scscf.ims.example.com",
+            "alice@ims.example.com",
+            DeregistrationReason {
+                reason_code: ReasonCode::PermanentTermination,
+                reason_info: Some("subscription removed".into()),
+            },
+        );
+        let msg = round_trip(rtr.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<i32>("Deregistration-Reason/Reason-Code")
+                .unwrap(),
+            vec![0]
+        );
+        assert_eq!(
+            RegistrationTerminationRequest::from_message(&msg).unwrap(),
+            rtr
+        );
+        let rta = RegistrationTerminationAnswer::new(&rtr, 2001, &scscf);
+        let msg = round_trip(rta.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(
+            RegistrationTerminationAnswer::from_message(&msg).unwrap(),
+            rta
+        );
+
+        let mut ppr = PushProfileRequest::new(
+            "hss.ims.example.com;1;2",
+            &hss,
+            "ims.example.com",
+            "scscf.ims.example.com",
+            "alice@ims.example.com",
+        );
+        ppr.user_data = Some(b"<IMSSubscription/>".to_vec());
+        let msg = round_trip(ppr.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::PushProfile);
+        assert_eq!(PushProfileRequest::from_message(&msg).unwrap(), ppr);
+        let ppa = PushProfileAnswer::new(&ppr, 2001, &scscf);
+        let msg = round_trip(ppa.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(PushProfileAnswer::from_message(&msg).unwrap(), ppa);
+    }
+}
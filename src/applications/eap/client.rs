@@ -0,0 +1,469 @@
+//! Diameter EAP Client Sessions
+//!
+//! Runs the multi-round Diameter-EAP exchanges of
+//! [RFC 4072 section 2](https://tools.ietf.org/html/rfc4072#section-2) on
+//! top of a [`DiameterClient`] connection.
+//!
+//! Each [`EapClientSession`] is identified by its Session-Id. While the
+//! server answers with DIAMETER_MULTI_ROUND_AUTH, the session echoes the
+//! State AVP of the last answer, addresses the server that answered it
+//! through Destination-Host and enforces its Multi-Round-Time-Out, so the
+//! application only relays EAP packets between the peer and the server.
+//!
+//! ```no_run
+//! use diameter::applications::eap::client::{EapClient, EapClientConfig, EapOutcome};
+//! use diameter::applications::eap::{self, EapPacket};
+//! use diameter::applications::nasreq;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[
+//!         &dictionary::DEFAULT_DICT_XML,
+//!         nasreq::DICT_XML,
+//!         eap::DICT_XML,
+//!     ]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("localhost:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle(&mut handler, dict_ref).await;
+//!     });
+//!
+//!     let node = NodeConfig::new("nas.example.com", "example.com");
+//!     let eap_client = EapClient::new(client, dict, node, EapClientConfig::default());
+//!     let mut session = eap_client.session("nas.example.com;1;1", "example.com");
+//!
+//!     let mut payload = EapPacket::identity_response(1, "alice@example.com").encode();
+//!     loop {
+//!         let der = session.request(&payload);
+//!         match session.send(der).await.unwrap() {
+//!             EapOutcome::Challenge(dea) => {
+//!                 // Relay dea.eap_payload to the peer and its response back
+//!                 payload = dea.eap_payload.unwrap_or_default();
+//!             }
+//!             outcome => {
+//!                 println!("{:?}", outcome);
+//!                 break;
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use super::{DiameterEapAnswer, DiameterEapRequest};
+use crate::diameter::result_code;
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::DiameterClient;
+use crate::typed::TypedMessage;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Outcome of a round of an [`EapClientSession`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EapOutcome {
+    /// The server answered DIAMETER_MULTI_ROUND_AUTH with an EAP-Request to
+    /// relay to the peer; the peer's response is sent in the next round.
+    Challenge(Box<DiameterEapAnswer>),
+    /// The server authenticated and authorized the peer.
+    Success(Box<DiameterEapAnswer>),
+    /// The server rejected the peer.
+    Failure(Box<DiameterEapAnswer>),
+}
+
+/// Configuration of an [`EapClient`].
+#[derive(Debug, Clone, Copy)]
+pub struct EapClientConfig {
+    pub tx_timeout: Duration,
+    pub max_rounds: u32,
+}
+
+impl Default for EapClientConfig {
+    /// A Tx timer of 10 seconds and at most 50 rounds per session.
+    fn default() -> EapClientConfig {
+        EapClientConfig {
+            tx_timeout: Duration::from_secs(10),
+            max_rounds: 50,
+        }
+    }
+}
+
+/// A Diameter EAP client sending to an EAP server.
+///
+/// The connection is shared by all sessions created from the client; the
+/// `DiameterClient` must be connected and have its handler running.
+#[derive(Clone)]
+pub struct EapClient {
+    client: Arc<Mutex<DiameterClient>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: EapClientConfig,
+}
+
+impl EapClient {
+    /// Creates a client sending to `client`.
+    pub fn new(
+        client: DiameterClient,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: EapClientConfig,
+    ) -> EapClient {
+        EapClient {
+            client: Arc::new(Mutex::new(client)),
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Creates a session that has not sent any round yet.
+    pub fn session(&self, session_id: &str, destination_realm: &str) -> EapClientSession {
+        EapClientSession {
+            client: self.clone(),
+            session_id: session_id.into(),
+            destination_realm: destination_realm.into(),
+            destination_host: None,
+            state: None,
+            rounds: 0,
+            deadline: None,
+            complete: false,
+        }
+    }
+}
+
+/// A Diameter EAP session on the NAS side.
+pub struct EapClientSession {
+    client: EapClient,
+    session_id: String,
+    destination_realm: String,
+    destination_host: Option<String>,
+    state: Option<Vec<u8>>,
+    rounds: u32,
+    deadline: Option<Instant>,
+    complete: bool,
+}
+
+impl EapClientSession {
+    /// Returns the Session-Id of the session.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Returns the number of rounds answered so far.
+    pub fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    /// Returns true once the server has accepted or rejected the peer, or
+    /// the exchange was abandoned.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Creates the request of the next round carrying `eap_payload`, for the
+    /// application to complete before passing it to
+    /// [`send`](EapClientSession::send).
+    pub fn request(&self, eap_payload: &[u8]) -> DiameterEapRequest {
+        let mut der = DiameterEapRequest::new(
+            &self.session_id,
+            &self.client.node,
+            &self.destination_realm,
+            eap_payload,
+        );
+        der.destination_host = self.destination_host.clone();
+        der.state = self.state.clone();
+        der
+    }
+
+    /// Sends a round and waits for its answer.
+    ///
+    /// Session-Id is set by the session, as are State and Destination-Host
+    /// when the request does not carry them. An error is returned once the
+    /// session is complete, when the Multi-Round-Time-Out of the previous
+    /// answer or the maximum number of rounds is exceeded, which abandons
+    /// the exchange, and when no valid answer for the session is received.
+    pub async fn send(&mut self, mut der: DiameterEapRequest) -> Result<EapOutcome> {
+        if self.complete {
+            return Err(Error::ClientError(format!(
+                "EAP session {} is complete",
+                self.session_id
+            )));
+        }
+        if self.rounds >= self.client.config.max_rounds {
+            self.complete = true;
+            return Err(Error::ClientError(format!(
+                "EAP session {} exceeded {} rounds",
+                self.session_id, self.client.config.max_rounds
+            )));
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+        {
+            self.complete = true;
+            return Err(Error::ClientError(format!(
+                "EAP session {} exceeded its Multi-Round-Time-Out",
+                self.session_id
+            )));
+        }
+        der.session_id = self.session_id.clone();
+        if der.state.is_none() {
+            der.state = self.state.clone();
+        }
+        if der.destination_host.is_none() {
+            der.destination_host = self.destination_host.clone();
+        }
+
+        let answer = self.transmit(&der).await?;
+        if answer.is_error() {
+            self.complete = true;
+            return Err(Error::ClientError(format!(
+                "EAP session {} failed with Result-Code {}",
+                self.session_id,
+                answer
+                    .get::<u32, _>("Result-Code")
+                    .unwrap_or(result_code::DIAMETER_UNABLE_TO_COMPLY)
+            )));
+        }
+        let dea = DiameterEapAnswer::from_message(&answer)?;
+        if dea.session_id != self.session_id {
+            return Err(Error::ClientError(format!(
+                "Answer for session {} received on session {}",
+                dea.session_id, self.session_id
+            )));
+        }
+        self.rounds += 1;
+
+        let outcome = match dea.result_code {
+            result_code::DIAMETER_MULTI_ROUND_AUTH => {
+                self.state = dea.state.clone();
+                self.destination_host = Some(dea.origin_host.clone());
+                self.deadline = dea
+                    .multi_round_time_out
+                    .map(|seconds| Instant::now() + Duration::from_secs(seconds.into()));
+                EapOutcome::Challenge(Box::new(dea))
+            }
+            code if (2000..3000).contains(&code) => {
+                self.complete = true;
+                EapOutcome::Success(Box::new(dea))
+            }
+            _ => {
+                self.complete = true;
+                EapOutcome::Failure(Box::new(dea))
+            }
+        };
+        Ok(outcome)
+    }
+
+    async fn transmit(&self, der: &DiameterEapRequest) -> Result<crate::DiameterMessage> {
+        let response = {
+            let mut client = self.client.client.lock().await;
+            let seq_num = client.get_next_seq_num();
+            let req = der.to_message(seq_num, seq_num, &self.client.dict)?;
+            client.send_message(req).await?
+        };
+        match tokio::time::timeout(self.client.config.tx_timeout, response).await {
+            Ok(answer) => answer,
+            Err(_) => Err(Error::ClientError(format!(
+                "Tx timer expired on EAP session {}",
+                self.session_id
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::eap::{eap_type, EapCode, EapPacket, DICT_XML};
+    use crate::applications::nasreq;
+    use crate::dictionary;
+    use crate::transport::{DiameterClientConfig, DiameterServer, DiameterServerConfig};
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            nasreq::DICT_XML,
+            DICT_XML,
+        ]))
+    }
+
+    /// Starts a server running a two-round EAP-MD5 exchange: the identity
+    /// response is challenged, and the challenge response is accepted if it
+    /// echoes the State of the challenge.
+    async fn start_server(addr: &str, session_id_override: Option<&'static str>) {
+        let dict = dict();
+        let mut server = DiameterServer::new(addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| {
+                        let dict = Arc::clone(&dict);
+                        async move {
+                            let der = DiameterEapRequest::from_message(&req)?;
+                            let node = NodeConfig::new("aaa.example.com", "example.com");
+                            let packet = EapPacket::decode(&der.eap_payload)?;
+                            let mut dea = match packet.eap_type {
+                                Some(eap_type::IDENTITY) => {
+                                    let mut dea = DiameterEapAnswer::new(
+                                        &der,
+                                        result_code::DIAMETER_MULTI_ROUND_AUTH,
+                                        &node,
+                                    );
+                                    let challenge = EapPacket::request(
+                                        packet.identifier + 1,
+                                        eap_type::MD5_CHALLENGE,
+                                        b"challenge",
+                                    );
+                                    dea.eap_payload = Some(challenge.encode());
+                                    dea.state = Some(b"round-1".to_vec());
+                                    dea.multi_round_time_out = Some(30);
+                                    dea
+                                }
+                                _ if der.state.as_deref() == Some(b"round-1")
+                                    && der.destination_host.as_deref()
+                                        == Some("aaa.example.com") =>
+                                {
+                                    let mut dea = DiameterEapAnswer::new(&der, 2001, &node);
+                                    let success = EapPacket {
+                                        code: EapCode::Success,
+                                        identifier: packet.identifier,
+                                        eap_type: None,
+                                        type_data: Vec::new(),
+                                    };
+                                    dea.eap_payload = Some(success.encode());
+                                    dea.eap_master_session_key = Some(vec![0x5a; 64]);
+                                    dea
+                                }
+                                _ => DiameterEapAnswer::new(
+                                    &der,
+                                    result_code::DIAMETER_AUTHENTICATION_REJECTED,
+                                    &node,
+                                ),
+                            };
+                            if let Some(session_id) = session_id_override {
+                                dea.session_id = session_id.into();
+                            }
+                            dea.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+    }
+
+    async fn connect(addr: &str) -> DiameterClient {
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new(addr, config);
+        let mut handler = client.connect().await.unwrap();
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict()).await;
+        });
+        client
+    }
+
+    fn node() -> NodeConfig {
+        NodeConfig::new("nas.example.com", "example.com")
+    }
+
+    #[tokio::test]
+    async fn test_multi_round_exchange() {
+        start_server("0.0.0.0:3883", None).await;
+        let client = connect("localhost:3883").await;
+        let eap_client = EapClient::new(client, dict(), node(), EapClientConfig::default());
+        let mut session = eap_client.session("nas;1", "example.com");
+
+        let identity = EapPacket::identity_response(1, "alice").encode();
+        let dea = match session.send(session.request(&identity)).await.unwrap() {
+            EapOutcome::Challenge(dea) => dea,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        let challenge = dea.eap_packet().unwrap().unwrap();
+        assert_eq!(challenge.code, EapCode::Request);
+        assert_eq!(challenge.eap_type, Some(eap_type::MD5_CHALLENGE));
+        assert!(!session.is_complete());
+
+        let response = EapPacket::response(challenge.identifier, eap_type::MD5_CHALLENGE, b"hash");
+        let der = session.request(&response.encode());
+        assert_eq!(der.state.as_deref(), Some(&b"round-1"[..]));
+        assert_eq!(der.destination_host.as_deref(), Some("aaa.example.com"));
+        let dea = match session.send(der).await.unwrap() {
+            EapOutcome::Success(dea) => dea,
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        };
+        assert_eq!(dea.eap_packet().unwrap().unwrap().code, EapCode::Success);
+        assert_eq!(dea.eap_master_session_key, Some(vec![0x5a; 64]));
+        assert_eq!(session.rounds(), 2);
+        assert!(session.is_complete());
+
+        assert!(session.send(session.request(&identity)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_without_state() {
+        start_server("0.0.0.0:3884", None).await;
+        let client = connect("localhost:3884").await;
+        let eap_client = EapClient::new(client, dict(), node(), EapClientConfig::default());
+        let mut session = eap_client.session("nas;2", "example.com");
+
+        // A response sent without the State of a challenge is rejected
+        let response = EapPacket::response(2, eap_type::MD5_CHALLENGE, b"hash").encode();
+        match session.send(session.request(&response)).await.unwrap() {
+            EapOutcome::Failure(dea) => assert_eq!(
+                dea.result_code,
+                result_code::DIAMETER_AUTHENTICATION_REJECTED
+            ),
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+        assert!(session.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_answer_for_other_session() {
+        start_server("0.0.0.0:3885", Some("nas;other")).await;
+        let client = connect("localhost:3885").await;
+        let eap_client = EapClient::new(client, dict(), node(), EapClientConfig::default());
+        let mut session = eap_client.session("nas;3", "example.com");
+
+        let identity = EapPacket::identity_response(1, "alice").encode();
+        assert!(session.send(session.request(&identity)).await.is_err());
+        assert_eq!(session.rounds(), 0);
+        assert!(!session.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_max_rounds() {
+        start_server("0.0.0.0:3886", None).await;
+        let client = connect("localhost:3886").await;
+        let config = EapClientConfig {
+            max_rounds: 1,
+            ..Default::default()
+        };
+        let eap_client = EapClient::new(client, dict(), node(), config);
+        let mut session = eap_client.session("nas;4", "example.com");
+
+        let identity = EapPacket::identity_response(1, "alice").encode();
+        let outcome = session.send(session.request(&identity)).await.unwrap();
+        assert!(matches!(outcome, EapOutcome::Challenge(_)));
+
+        let response = EapPacket::response(2, eap_type::MD5_CHALLENGE, b"hash").encode();
+        assert!(session.send(session.request(&response)).await.is_err());
+        assert!(session.is_complete());
+    }
+}
@@ -0,0 +1,574 @@
+//! Diameter EAP Application
+//!
+//! Typed Diameter-EAP-Request and Diameter-EAP-Answer messages, as defined
+//! in [RFC 4072](https://tools.ietf.org/html/rfc4072), carrying EAP packets
+//! between a NAS and a back-end authentication server, together with
+//! [`EapPacket`] to read and build the EAP-Payload and a [`client`] that
+//! runs the multi-round exchanges of a session.
+//!
+//! The EAP AVPs are defined in [`DICT_XML`]; the NAS AVPs it shares with
+//! [`nasreq`](super::nasreq) are defined in
+//! [`nasreq::DICT_XML`](super::nasreq::DICT_XML), which must be loaded as
+//! well.
+//!
+//! ```
+//! use diameter::applications::eap::{
+//!     self, eap_type, DiameterEapAnswer, DiameterEapRequest, EapCode, EapPacket,
+//! };
+//! use diameter::applications::nasreq;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[
+//!     &dictionary::DEFAULT_DICT_XML,
+//!     nasreq::DICT_XML,
+//!     eap::DICT_XML,
+//! ]));
+//! let nas = NodeConfig::new("nas.example.com", "example.com");
+//! let server = NodeConfig::new("aaa.example.com", "example.com");
+//!
+//! let identity = EapPacket::identity_response(1, "alice@example.com");
+//! let payload = identity.encode();
+//! let der = DiameterEapRequest::new("nas.example.com;1;1", &nas, "example.com", &payload);
+//!
+//! let msg = der.to_message(1, 1, &dict).unwrap();
+//! let der = DiameterEapRequest::from_message(&msg).unwrap();
+//! let packet = EapPacket::decode(&der.eap_payload).unwrap();
+//! assert_eq!(packet.eap_type, Some(eap_type::IDENTITY));
+//!
+//! let mut dea = DiameterEapAnswer::new(&der, 1001, &server);
+//! dea.eap_payload = Some(EapPacket::request(2, eap_type::MD5_CHALLENGE, b"challenge").encode());
+//! assert_eq!(dea.eap_packet().unwrap().unwrap().code, EapCode::Request);
+//! ```
+
+pub mod client;
+
+use crate::applications::common::{
+    AuthRequestType, AuthSessionState, ProxyInfo, ReAuthRequestType,
+};
+use crate::applications::credit_control::RedirectHostUsage;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::DiameterMessage;
+
+pub use crate::applications::nasreq::{
+    FramedCompression, FramedProtocol, FramedRouting, NasPortType, ServiceType,
+};
+
+/// Dictionary of the Diameter EAP application.
+pub const DICT_XML: &str = include_str!("../../../dict/eap.xml");
+
+/// Auth-Application-Id of the Diameter EAP application.
+pub const APPLICATION_ID: u32 = 5;
+
+/// EAP method types, as registered by IANA for the Type field of EAP
+/// Request and Response packets.
+pub mod eap_type {
+    pub const IDENTITY: u8 = 1;
+    pub const NOTIFICATION: u8 = 2;
+    pub const NAK: u8 = 3;
+    pub const MD5_CHALLENGE: u8 = 4;
+    pub const TLS: u8 = 13;
+    pub const SIM: u8 = 18;
+    pub const TTLS: u8 = 21;
+    pub const AKA: u8 = 23;
+    pub const PEAP: u8 = 25;
+    pub const MSCHAP_V2: u8 = 26;
+    pub const AKA_PRIME: u8 = 50;
+}
+
+/// Code field of an EAP packet, RFC 3748 section 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EapCode {
+    Request = 1,
+    Response = 2,
+    Success = 3,
+    Failure = 4,
+}
+
+impl EapCode {
+    /// Returns the code with the given value.
+    pub fn from_u8(value: u8) -> Option<EapCode> {
+        match value {
+            1 => Some(EapCode::Request),
+            2 => Some(EapCode::Response),
+            3 => Some(EapCode::Success),
+            4 => Some(EapCode::Failure),
+            _ => None,
+        }
+    }
+}
+
+/// An EAP packet, RFC 3748 section 4, as carried in the EAP-Payload AVP.
+///
+/// Request and Response packets have a type; Success and Failure packets
+/// have neither a type nor data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EapPacket {
+    pub code: EapCode,
+    pub identifier: u8,
+    pub eap_type: Option<u8>,
+    pub type_data: Vec<u8>,
+}
+
+impl EapPacket {
+    /// Creates a Request packet.
+    pub fn request(identifier: u8, eap_type: u8, type_data: &[u8]) -> EapPacket {
+        EapPacket {
+            code: EapCode::Request,
+            identifier,
+            eap_type: Some(eap_type),
+            type_data: type_data.to_vec(),
+        }
+    }
+
+    /// Creates a Response packet.
+    pub fn response(identifier: u8, eap_type: u8, type_data: &[u8]) -> EapPacket {
+        EapPacket {
+            code: EapCode::Response,
+            identifier,
+            eap_type: Some(eap_type),
+            type_data: type_data.to_vec(),
+        }
+    }
+
+    /// Creates the Response/Identity packet a NAS starts the exchange with.
+    pub fn identity_response(identifier: u8, identity: &str) -> EapPacket {
+        EapPacket::response(identifier, eap_type::IDENTITY, identity.as_bytes())
+    }
+
+    /// Decodes a packet, checking its Length field against `data`.
+    pub fn decode(data: &[u8]) -> Result<EapPacket> {
+        if data.len() < 4 {
+            return Err(Error::DecodeError(format!(
+                "EAP packet too short: {} bytes",
+                data.len()
+            )));
+        }
+        let code = EapCode::from_u8(data[0])
+            .ok_or_else(|| Error::DecodeError(format!("invalid EAP code: {}", data[0])))?;
+        let length = u16::from_be_bytes([data[2], data[3]]) as usize;
+        if length < 4 || length > data.len() {
+            return Err(Error::DecodeError(format!(
+                "invalid EAP packet length: {}",
+                length
+            )));
+        }
+        let (eap_type, type_data) = match code {
+            EapCode::Request | EapCode::Response => {
+                if length < 5 {
+                    return Err(Error::DecodeError("EAP packet without type".into()));
+                }
+                (Some(data[4]), data[5..length].to_vec())
+            }
+            EapCode::Success | EapCode::Failure => (None, Vec::new()),
+        };
+        Ok(EapPacket {
+            code,
+            identifier: data[1],
+            eap_type,
+            type_data,
+        })
+    }
+
+    /// Encodes the packet, setting its Length field.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = vec![self.code as u8, self.identifier, 0, 0];
+        if let Some(eap_type) = self.eap_type {
+            data.push(eap_type);
+            data.extend_from_slice(&self.type_data);
+        }
+        let length = (data.len() as u16).to_be_bytes();
+        data[2..4].copy_from_slice(&length);
+        data
+    }
+}
+
+/// Diameter-EAP-Request (DER), RFC 4072 section 3.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = DiameterEap, application = Eap, request, proxyable)]
+pub struct DiameterEapRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Auth-Request-Type")]
+    pub auth_request_type: AuthRequestType,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "NAS-Identifier")]
+    pub nas_identifier: Option<String>,
+    #[avp(name = "NAS-IP-Address")]
+    pub nas_ip_address: Option<Vec<u8>>,
+    #[avp(name = "NAS-IPv6-Address")]
+    pub nas_ipv6_address: Option<Vec<u8>>,
+    #[avp(name = "NAS-Port")]
+    pub nas_port: Option<u32>,
+    #[avp(name = "NAS-Port-Id")]
+    pub nas_port_id: Option<String>,
+    #[avp(name = "NAS-Port-Type")]
+    pub nas_port_type: Option<NasPortType>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Port-Limit")]
+    pub port_limit: Option<u32>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "EAP-Payload")]
+    pub eap_payload: Vec<u8>,
+    #[avp(name = "EAP-Key-Name")]
+    pub eap_key_name: Option<Vec<u8>>,
+    #[avp(name = "Service-Type")]
+    pub service_type: Option<ServiceType>,
+    #[avp(name = "State")]
+    pub state: Option<Vec<u8>>,
+    #[avp(name = "Authorization-Lifetime")]
+    pub authorization_lifetime: Option<u32>,
+    #[avp(name = "Auth-Grace-Period")]
+    pub auth_grace_period: Option<u32>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: Option<AuthSessionState>,
+    #[avp(name = "Callback-Number")]
+    pub callback_number: Option<String>,
+    #[avp(name = "Called-Station-Id")]
+    pub called_station_id: Option<String>,
+    #[avp(name = "Calling-Station-Id")]
+    pub calling_station_id: Option<String>,
+    #[avp(name = "Originating-Line-Info")]
+    pub originating_line_info: Option<Vec<u8>>,
+    #[avp(name = "Connect-Info")]
+    pub connect_info: Option<String>,
+    #[avp(name = "Framed-Compression")]
+    pub framed_compression: Vec<FramedCompression>,
+    #[avp(name = "Framed-Interface-Id")]
+    pub framed_interface_id: Option<u64>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Vec<Vec<u8>>,
+    #[avp(name = "Framed-IP-Netmask")]
+    pub framed_ip_netmask: Option<Vec<u8>>,
+    #[avp(name = "Framed-MTU")]
+    pub framed_mtu: Option<u32>,
+    #[avp(name = "Framed-Protocol")]
+    pub framed_protocol: Option<FramedProtocol>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Diameter-EAP-Answer (DEA), RFC 4072 section 3.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = DiameterEap, application = Eap, proxyable)]
+pub struct DiameterEapAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Auth-Request-Type")]
+    pub auth_request_type: AuthRequestType,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "EAP-Payload")]
+    pub eap_payload: Option<Vec<u8>>,
+    #[avp(name = "EAP-Reissued-Payload")]
+    pub eap_reissued_payload: Option<Vec<u8>>,
+    #[avp(name = "EAP-Master-Session-Key")]
+    pub eap_master_session_key: Option<Vec<u8>>,
+    #[avp(name = "EAP-Key-Name")]
+    pub eap_key_name: Option<Vec<u8>>,
+    #[avp(name = "Accounting-EAP-Auth-Method")]
+    pub accounting_eap_auth_method: Vec<u64>,
+    #[avp(name = "Service-Type")]
+    pub service_type: Option<ServiceType>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Configuration-Token")]
+    pub configuration_token: Vec<Vec<u8>>,
+    #[avp(name = "Acct-Interim-Interval")]
+    pub acct_interim_interval: Option<u32>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Idle-Timeout")]
+    pub idle_timeout: Option<u32>,
+    #[avp(name = "Authorization-Lifetime")]
+    pub authorization_lifetime: Option<u32>,
+    #[avp(name = "Auth-Grace-Period")]
+    pub auth_grace_period: Option<u32>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: Option<AuthSessionState>,
+    #[avp(name = "Re-Auth-Request-Type")]
+    pub re_auth_request_type: Option<ReAuthRequestType>,
+    #[avp(name = "Multi-Round-Time-Out")]
+    pub multi_round_time_out: Option<u32>,
+    #[avp(name = "Session-Timeout")]
+    pub session_timeout: Option<u32>,
+    #[avp(name = "State")]
+    pub state: Option<Vec<u8>>,
+    #[avp(name = "Reply-Message")]
+    pub reply_message: Vec<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Filter-Id")]
+    pub filter_id: Vec<String>,
+    #[avp(name = "Port-Limit")]
+    pub port_limit: Option<u32>,
+    #[avp(name = "Callback-Id")]
+    pub callback_id: Option<String>,
+    #[avp(name = "Callback-Number")]
+    pub callback_number: Option<String>,
+    #[avp(name = "Framed-Compression")]
+    pub framed_compression: Vec<FramedCompression>,
+    #[avp(name = "Framed-Interface-Id")]
+    pub framed_interface_id: Option<u64>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Vec<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Pool")]
+    pub framed_ipv6_pool: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Route")]
+    pub framed_ipv6_route: Vec<String>,
+    #[avp(name = "Framed-IP-Netmask")]
+    pub framed_ip_netmask: Option<Vec<u8>>,
+    #[avp(name = "Framed-Route")]
+    pub framed_route: Vec<String>,
+    #[avp(name = "Framed-Pool")]
+    pub framed_pool: Option<Vec<u8>>,
+    #[avp(name = "Framed-MTU")]
+    pub framed_mtu: Option<u32>,
+    #[avp(name = "Framed-Protocol")]
+    pub framed_protocol: Option<FramedProtocol>,
+    #[avp(name = "Framed-Routing")]
+    pub framed_routing: Option<FramedRouting>,
+    #[avp(name = "NAS-Filter-Rule")]
+    pub nas_filter_rule: Vec<String>,
+    #[avp(name = "QoS-Filter-Rule")]
+    pub qos_filter_rule: Vec<String>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl DiameterEapRequest {
+    /// Creates an AUTHORIZE_AUTHENTICATE request carrying `eap_payload`, taking
+    /// Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        eap_payload: &[u8],
+    ) -> DiameterEapRequest {
+        DiameterEapRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            auth_request_type: AuthRequestType::AuthorizeAuthenticate,
+            destination_host: None,
+            nas_identifier: None,
+            nas_ip_address: None,
+            nas_ipv6_address: None,
+            nas_port: None,
+            nas_port_id: None,
+            nas_port_type: None,
+            origin_state_id: None,
+            port_limit: None,
+            user_name: None,
+            eap_payload: eap_payload.to_vec(),
+            eap_key_name: None,
+            service_type: None,
+            state: None,
+            authorization_lifetime: None,
+            auth_grace_period: None,
+            auth_session_state: None,
+            callback_number: None,
+            called_station_id: None,
+            calling_station_id: None,
+            originating_line_info: None,
+            connect_info: None,
+            framed_compression: Vec::new(),
+            framed_interface_id: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: Vec::new(),
+            framed_ip_netmask: None,
+            framed_mtu: None,
+            framed_protocol: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl DiameterEapAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Auth-Request-Type, User-Name and Proxy-Info, and taking Origin-Host
+    /// and Origin-Realm from `node`.
+    pub fn new(
+        request: &DiameterEapRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> DiameterEapAnswer {
+        DiameterEapAnswer {
+            session_id: request.session_id.clone(),
+            auth_application_id: APPLICATION_ID,
+            auth_request_type: request.auth_request_type,
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            user_name: request.user_name.clone(),
+            eap_payload: None,
+            eap_reissued_payload: None,
+            eap_master_session_key: None,
+            eap_key_name: None,
+            accounting_eap_auth_method: Vec::new(),
+            service_type: None,
+            class: Vec::new(),
+            configuration_token: Vec::new(),
+            acct_interim_interval: None,
+            error_message: None,
+            error_reporting_host: None,
+            idle_timeout: None,
+            authorization_lifetime: None,
+            auth_grace_period: None,
+            auth_session_state: None,
+            re_auth_request_type: None,
+            multi_round_time_out: None,
+            session_timeout: None,
+            state: None,
+            reply_message: Vec::new(),
+            origin_state_id: None,
+            filter_id: Vec::new(),
+            port_limit: None,
+            callback_id: None,
+            callback_number: None,
+            framed_compression: Vec::new(),
+            framed_interface_id: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: Vec::new(),
+            framed_ipv6_pool: None,
+            framed_ipv6_route: Vec::new(),
+            framed_ip_netmask: None,
+            framed_route: Vec::new(),
+            framed_pool: None,
+            framed_mtu: None,
+            framed_protocol: None,
+            framed_routing: None,
+            nas_filter_rule: Vec::new(),
+            qos_filter_rule: Vec::new(),
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+impl DiameterEapAnswer {
+    /// Decodes the EAP-Payload, if the answer carries one.
+    pub fn eap_packet(&self) -> Result<Option<EapPacket>> {
+        self.eap_payload
+            .as_deref()
+            .map(EapPacket::decode)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::nasreq;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            nasreq::DICT_XML,
+            DICT_XML,
+        ]))
+    }
+
+    #[test]
+    fn test_eap_packet() {
+        let packet = EapPacket::identity_response(7, "alice");
+        let data = packet.encode();
+        assert_eq!(data, [2, 7, 0, 10, 1, b'a', b'l', b'i', b'c', b'e']);
+        assert_eq!(EapPacket::decode(&data).unwrap(), packet);
+
+        let success = EapPacket::decode(&[3, 8, 0, 4]).unwrap();
+        assert_eq!(success.code, EapCode::Success);
+        assert_eq!(success.eap_type, None);
+        assert_eq!(success.encode(), [3, 8, 0, 4]);
+
+        // Trailing bytes beyond the Length field are ignored
+        let packet = EapPacket::decode(&[1, 9, 0, 6, 4, 0xaa, 0xff]).unwrap();
+        assert_eq!(packet.type_data, vec![0xaa]);
+
+        assert!(EapPacket::decode(&[1, 1, 0]).is_err());
+        assert!(EapPacket::decode(&[5, 1, 0, 4]).is_err());
+        assert!(EapPacket::decode(&[1, 1, 0, 9, 1]).is_err());
+        assert!(EapPacket::decode(&[1, 1, 0, 4]).is_err());
+    }
+
+    #[test]
+    fn test_der_dea_round_trip() {
+        let dict = dict();
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let server = NodeConfig::new("aaa.example.com", "example.com");
+
+        let identity = EapPacket::identity_response(1, "alice@example.com").encode();
+        let mut der =
+            DiameterEapRequest::new("nas.example.com;1;2", &nas, "example.com", &identity);
+        der.user_name = Some("alice@example.com".into());
+        der.nas_port_type = Some(NasPortType::WirelessIeee80211);
+
+        let msg = der.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::DiameterEap);
+        assert_eq!(msg.get_application_id(), ApplicationId::Eap);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        assert_eq!(msg.get::<u32, _>("Auth-Application-Id").unwrap(), 5);
+        assert_eq!(msg.get::<i32, _>("Auth-Request-Type").unwrap(), 3);
+        assert_eq!(DiameterEapRequest::from_message(&msg).unwrap(), der);
+
+        let mut dea = DiameterEapAnswer::new(&der, 2001, &server);
+        dea.eap_payload = Some(EapPacket::decode(&[3, 2, 0, 4]).unwrap().encode());
+        dea.eap_master_session_key = Some(vec![0x11; 64]);
+        dea.accounting_eap_auth_method.push(eap_type::AKA as u64);
+        dea.session_timeout = Some(3600);
+
+        let msg = dea.to_message(1, 2, &dict).unwrap();
+        assert!(!msg.is_request());
+        let decoded = DiameterEapAnswer::from_message(&msg).unwrap();
+        assert_eq!(decoded, dea);
+        assert_eq!(
+            decoded.eap_packet().unwrap().map(|packet| packet.code),
+            Some(EapCode::Success)
+        );
+    }
+}
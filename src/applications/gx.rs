@@ -0,0 +1,1230 @@
+//! Gx Application
+//!
+//! Typed Credit-Control and Re-Auth messages of the Gx reference point
+//! between the PCEF and the PCRF, as defined in
+//! [3GPP TS 29.212](https://www.3gpp.org/DynaReport/29212.htm).
+//!
+//! The Gx AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary.
+//!
+//! ```
+//! use diameter::applications::credit_control::CcRequestType;
+//! use diameter::applications::gx::{
+//!     self, ChargingRuleInstall, CreditControlAnswer, CreditControlRequest, EventTrigger,
+//!     IpCanType,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, gx::DICT_XML]));
+//! let pcef = NodeConfig::new("pgw.example.com", "example.com");
+//! let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+//!
+//! let mut ccr = CreditControlRequest::new(
+//!     "pgw.example.com;1;1",
+//!     &pcef,
+//!     "example.com",
+//!     CcRequestType::InitialRequest,
+//!     0,
+//! );
+//! ccr.ip_can_type = Some(IpCanType::TgppEps);
+//!
+//! let mut cca = CreditControlAnswer::new(&ccr, 2001, &pcrf);
+//! cca.event_trigger.push(EventTrigger::UsageReport);
+//! cca.charging_rule_install.push(ChargingRuleInstall {
+//!     charging_rule_base_name: vec!["internet".into()],
+//!     ..Default::default()
+//! });
+//!
+//! let msg = cca.to_message(1, 1, &dict).unwrap();
+//! assert_eq!(CreditControlAnswer::from_message(&msg).unwrap(), cca);
+//! ```
+
+use crate::applications::common::{
+    ExperimentalResult, ProxyInfo, ReAuthRequestType, SupportedFeatures, TerminationCause,
+};
+use crate::applications::credit_control::{
+    CcRequestType, FinalUnitAction, FinalUnitIndication, RedirectHostUsage, ServiceUnit,
+    SubscriptionId, UserEquipmentInfo,
+};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::{DiameterMessage, GroupedAvp};
+use std::net::IpAddr;
+
+/// Dictionary of the Gx application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-gx.xml");
+
+/// Auth-Application-Id of the Gx application.
+pub const APPLICATION_ID: u32 = 16777238;
+
+/// Experimental-Result-Code values defined in 3GPP TS 29.212 section 5.5.
+pub mod result_code {
+    // Transient Failures
+    pub const DIAMETER_PCC_BEARER_EVENT: u32 = 4141;
+    pub const DIAMETER_AN_GW_FAILED: u32 = 4143;
+    pub const DIAMETER_PENDING_TRANSACTION: u32 = 4144;
+    pub const DIAMETER_UE_STATUS_SUSPEND: u32 = 4145;
+
+    // Permanent Failures
+    pub const DIAMETER_ERROR_INITIAL_PARAMETERS: u32 = 5140;
+    pub const DIAMETER_ERROR_TRIGGER_EVENT: u32 = 5141;
+    pub const DIAMETER_PCC_RULE_EVENT: u32 = 5142;
+    pub const DIAMETER_ERROR_BEARER_NOT_AUTHORIZED: u32 = 5143;
+    pub const DIAMETER_ERROR_TRAFFIC_MAPPING_INFO_REJECTED: u32 = 5144;
+    pub const DIAMETER_ERROR_CONFLICTING_REQUEST: u32 = 5147;
+    pub const DIAMETER_ADC_RULE_EVENT: u32 = 5148;
+    pub const DIAMETER_ERROR_NBIFOM_NOT_AUTHORIZED: u32 = 5149;
+}
+
+enumerated! {
+    /// Values of the Bearer-Control-Mode AVP (1023).
+    pub enum BearerControlMode ("Bearer-Control-Mode") {
+        UeOnly = 0,
+        Reserved = 1,
+        UeNw = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Bearer-Operation AVP (1021).
+    pub enum BearerOperation ("Bearer-Operation") {
+        Termination = 0,
+        Establishment = 1,
+        Modification = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Bearer-Usage AVP (1000).
+    pub enum BearerUsage ("Bearer-Usage") {
+        General = 0,
+        ImsSignalling = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Event-Trigger AVP (1006).
+    pub enum EventTrigger ("Event-Trigger") {
+        SgsnChange = 0,
+        QosChange = 1,
+        RatChange = 2,
+        TftChange = 3,
+        PlmnChange = 4,
+        LossOfBearer = 5,
+        RecoveryOfBearer = 6,
+        IpCanChange = 7,
+        QosChangeExceedingAuthorization = 11,
+        RaiChange = 12,
+        UserLocationChange = 13,
+        NoEventTriggers = 14,
+        OutOfCredit = 15,
+        ReallocationOfCredit = 16,
+        RevalidationTimeout = 17,
+        UeIpAddressAllocate = 18,
+        UeIpAddressRelease = 19,
+        DefaultEpsBearerQosChange = 20,
+        AnGwChange = 21,
+        SuccessfulResourceAllocation = 22,
+        ResourceModificationRequest = 23,
+        PgwTraceControl = 24,
+        UeTimeZoneChange = 25,
+        TaiChange = 26,
+        EcgiChange = 27,
+        ChargingCorrelationExchange = 28,
+        ApnAmbrModificationFailure = 29,
+        UserCsgInformationChange = 30,
+        UsageReport = 33,
+        DefaultEpsBearerQosModificationFailure = 34,
+        UserCsgHybridSubscribedInformationChange = 35,
+        UserCsgHybridUnsubscribedInformationChange = 36,
+        RoutingRuleChange = 37,
+        ApplicationStart = 39,
+        ApplicationStop = 40,
+        CsToPsHandover = 42,
+        UeLocalIpAddressChange = 43,
+        HenbLocalIpAddressChange = 44,
+        AccessNetworkInfoReport = 45,
+        CreditManagementSessionFailure = 46,
+        DefaultQosChange = 47,
+        ChangeOfUePresenceInPresenceReportingAreaReport = 48,
+    }
+}
+
+enumerated! {
+    /// Values of the Flow-Direction AVP (1080).
+    pub enum FlowDirection ("Flow-Direction") {
+        Unspecified = 0,
+        Downlink = 1,
+        Uplink = 2,
+        Bidirectional = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Flow-Status AVP (511).
+    pub enum FlowStatus ("Flow-Status") {
+        EnabledUplink = 0,
+        EnabledDownlink = 1,
+        Enabled = 2,
+        Disabled = 3,
+        Removed = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the IP-CAN-Type AVP (1027).
+    pub enum IpCanType ("IP-CAN-Type") {
+        TgppGprs = 0,
+        Docsis = 1,
+        Xdsl = 2,
+        Wimax = 3,
+        Tgpp2 = 4,
+        TgppEps = 5,
+        Non3gppEps = 6,
+        Fba = 7,
+        Tgpp5gs = 8,
+        Non3gpp5gs = 9,
+    }
+}
+
+enumerated! {
+    /// Values of the Metering-Method AVP (1007).
+    pub enum MeteringMethod ("Metering-Method") {
+        Duration = 0,
+        Volume = 1,
+        DurationVolume = 2,
+        Event = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Network-Request-Support AVP (1024).
+    pub enum NetworkRequestSupport ("Network-Request-Support") {
+        NetworkRequestNotSupported = 0,
+        NetworkRequestSupported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Offline AVP (1008).
+    pub enum Offline ("Offline") {
+        DisableOffline = 0,
+        EnableOffline = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Online AVP (1009).
+    pub enum Online ("Online") {
+        DisableOnline = 0,
+        EnableOnline = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the PCC-Rule-Status AVP (1019).
+    pub enum PccRuleStatus ("PCC-Rule-Status") {
+        Active = 0,
+        Inactive = 1,
+        TemporarilyInactive = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Pre-emption-Capability AVP (1047).
+    pub enum PreemptionCapability ("Pre-emption-Capability") {
+        Enabled = 0,
+        Disabled = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Pre-emption-Vulnerability AVP (1048).
+    pub enum PreemptionVulnerability ("Pre-emption-Vulnerability") {
+        Enabled = 0,
+        Disabled = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the QoS-Class-Identifier AVP (1028).
+    pub enum QosClassIdentifier ("QoS-Class-Identifier") {
+        Qci1 = 1,
+        Qci2 = 2,
+        Qci3 = 3,
+        Qci4 = 4,
+        Qci5 = 5,
+        Qci6 = 6,
+        Qci7 = 7,
+        Qci8 = 8,
+        Qci9 = 9,
+        Qci65 = 65,
+        Qci66 = 66,
+        Qci67 = 67,
+        Qci69 = 69,
+        Qci70 = 70,
+        Qci71 = 71,
+        Qci72 = 72,
+        Qci73 = 73,
+        Qci74 = 74,
+        Qci75 = 75,
+        Qci76 = 76,
+        Qci79 = 79,
+        Qci80 = 80,
+        Qci82 = 82,
+        Qci83 = 83,
+        Qci84 = 84,
+        Qci85 = 85,
+    }
+}
+
+enumerated! {
+    /// Values of the RAT-Type AVP (1032).
+    pub enum RatType ("RAT-Type") {
+        Wlan = 0,
+        Virtual = 1,
+        TrustedN3ga = 2,
+        Utran = 1000,
+        Geran = 1001,
+        Gan = 1002,
+        HspaEvolution = 1003,
+        Eutran = 1004,
+        EutranNbIot = 1005,
+        Nr = 1006,
+        LteM = 1007,
+        NrU = 1008,
+        Cdma20001x = 2000,
+        Hrpd = 2001,
+        Umb = 2002,
+        Ehrpd = 2003,
+    }
+}
+
+enumerated! {
+    /// Values of the Reporting-Level AVP (1011).
+    pub enum ReportingLevel ("Reporting-Level") {
+        ServiceIdentifierLevel = 0,
+        RatingGroupLevel = 1,
+        SponsoredConnectivityLevel = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Resource-Allocation-Notification AVP (1063).
+    pub enum ResourceAllocationNotification ("Resource-Allocation-Notification") {
+        EnableNotification = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the Rule-Failure-Code AVP (1031).
+    pub enum RuleFailureCode ("Rule-Failure-Code") {
+        UnknownRuleName = 1,
+        RatingGroupError = 2,
+        ServiceIdentifierError = 3,
+        GwPcefMalfunction = 4,
+        ResourcesLimitation = 5,
+        MaxNrBearersReached = 6,
+        UnknownBearerId = 7,
+        MissingBearerId = 8,
+        MissingFlowInformation = 9,
+        ResourceAllocationFailure = 10,
+        UnsuccessfulQosValidation = 11,
+        IncorrectFlowInformation = 12,
+        PsToCsHandover = 13,
+        TdfApplicationIdentifierError = 14,
+        NoBearerBound = 15,
+        FilterRestrictions = 16,
+        AnGwFailed = 17,
+        MissingRedirectServerAddress = 18,
+        CmEndUserServiceDenied = 19,
+        CmCreditControlNotApplicable = 20,
+        CmAuthorizationRejected = 21,
+        CmUserUnknown = 22,
+        CmRatingFailed = 23,
+        RoutingRuleRejection = 24,
+        UnknownRoutingAccessInformation = 25,
+        NoNbifomSupport = 26,
+    }
+}
+
+enumerated! {
+    /// Values of the Session-Release-Cause AVP (1045).
+    pub enum SessionReleaseCause ("Session-Release-Cause") {
+        UnspecifiedReason = 0,
+        UeSubscriptionReason = 1,
+        InsufficientServerResources = 2,
+        IpCanSessionTermination = 3,
+        UeIpAddressRelease = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Usage-Monitoring-Level AVP (1068).
+    pub enum UsageMonitoringLevel ("Usage-Monitoring-Level") {
+        SessionLevel = 0,
+        PccRuleLevel = 1,
+        AdcRuleLevel = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Usage-Monitoring-Report AVP (1069).
+    pub enum UsageMonitoringReport ("Usage-Monitoring-Report") {
+        UsageMonitoringReportRequired = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the Usage-Monitoring-Support AVP (1070).
+    pub enum UsageMonitoringSupport ("Usage-Monitoring-Support") {
+        UsageMonitoringDisabled = 0,
+    }
+}
+
+/// The Allocation-Retention-Priority AVP (1034).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AllocationRetentionPriority {
+    #[avp(name = "Priority-Level")]
+    pub priority_level: u32,
+    #[avp(name = "Pre-emption-Capability")]
+    pub pre_emption_capability: Option<PreemptionCapability>,
+    #[avp(name = "Pre-emption-Vulnerability")]
+    pub pre_emption_vulnerability: Option<PreemptionVulnerability>,
+}
+
+/// The QoS-Information AVP (1016), the QoS authorized for a bearer, a PCC
+/// rule or the APN.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct QosInformation {
+    #[avp(name = "QoS-Class-Identifier")]
+    pub qos_class_identifier: Option<QosClassIdentifier>,
+    #[avp(name = "Max-Requested-Bandwidth-UL")]
+    pub max_requested_bandwidth_ul: Option<u32>,
+    #[avp(name = "Max-Requested-Bandwidth-DL")]
+    pub max_requested_bandwidth_dl: Option<u32>,
+    #[avp(name = "Guaranteed-Bitrate-UL")]
+    pub guaranteed_bitrate_ul: Option<u32>,
+    #[avp(name = "Guaranteed-Bitrate-DL")]
+    pub guaranteed_bitrate_dl: Option<u32>,
+    #[avp(name = "Bearer-Identifier")]
+    pub bearer_identifier: Option<Vec<u8>>,
+    #[avp(name = "Allocation-Retention-Priority", grouped)]
+    pub allocation_retention_priority: Option<AllocationRetentionPriority>,
+    #[avp(name = "APN-Aggregate-Max-Bitrate-UL")]
+    pub apn_aggregate_max_bitrate_ul: Option<u32>,
+    #[avp(name = "APN-Aggregate-Max-Bitrate-DL")]
+    pub apn_aggregate_max_bitrate_dl: Option<u32>,
+}
+
+/// The Default-EPS-Bearer-QoS AVP (1049).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct DefaultEpsBearerQos {
+    #[avp(name = "QoS-Class-Identifier")]
+    pub qos_class_identifier: Option<QosClassIdentifier>,
+    #[avp(name = "Allocation-Retention-Priority", grouped)]
+    pub allocation_retention_priority: Option<AllocationRetentionPriority>,
+}
+
+/// The Flow-Information AVP (1058), one packet filter of a PCC rule.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct FlowInformation {
+    #[avp(name = "Flow-Description")]
+    pub flow_description: Option<String>,
+    #[avp(name = "Packet-Filter-Identifier")]
+    pub packet_filter_identifier: Option<Vec<u8>>,
+    #[avp(name = "ToS-Traffic-Class")]
+    pub tos_traffic_class: Option<Vec<u8>>,
+    #[avp(name = "Flow-Direction")]
+    pub flow_direction: Option<FlowDirection>,
+}
+
+/// The Flows AVP (510), the IP flows of a media component.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct Flows {
+    #[avp(name = "Media-Component-Number")]
+    pub media_component_number: u32,
+    #[avp(name = "Flow-Number")]
+    pub flow_number: Vec<u32>,
+    #[avp(name = "Final-Unit-Action")]
+    pub final_unit_action: Option<FinalUnitAction>,
+}
+
+/// The Charging-Rule-Definition AVP (1003), a PCC rule provided by the PCRF.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChargingRuleDefinition {
+    #[avp(name = "Charging-Rule-Name")]
+    pub charging_rule_name: Vec<u8>,
+    #[avp(name = "Service-Identifier")]
+    pub service_identifier: Option<u32>,
+    #[avp(name = "Rating-Group")]
+    pub rating_group: Option<u32>,
+    #[avp(name = "Flow-Information", grouped)]
+    pub flow_information: Vec<FlowInformation>,
+    #[avp(name = "Flow-Status")]
+    pub flow_status: Option<FlowStatus>,
+    #[avp(name = "QoS-Information", grouped)]
+    pub qos_information: Option<QosInformation>,
+    #[avp(name = "Reporting-Level")]
+    pub reporting_level: Option<ReportingLevel>,
+    #[avp(name = "Online")]
+    pub online: Option<Online>,
+    #[avp(name = "Offline")]
+    pub offline: Option<Offline>,
+    #[avp(name = "Metering-Method")]
+    pub metering_method: Option<MeteringMethod>,
+    #[avp(name = "Precedence")]
+    pub precedence: Option<u32>,
+    #[avp(name = "AF-Charging-Identifier")]
+    pub af_charging_identifier: Option<Vec<u8>>,
+    #[avp(name = "Flows", grouped)]
+    pub flows: Vec<Flows>,
+    #[avp(name = "Monitoring-Key")]
+    pub monitoring_key: Option<Vec<u8>>,
+}
+
+/// The Charging-Rule-Install AVP (1001), activating PCC rules.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChargingRuleInstall {
+    #[avp(name = "Charging-Rule-Definition", grouped)]
+    pub charging_rule_definition: Vec<ChargingRuleDefinition>,
+    #[avp(name = "Charging-Rule-Name")]
+    pub charging_rule_name: Vec<Vec<u8>>,
+    #[avp(name = "Charging-Rule-Base-Name")]
+    pub charging_rule_base_name: Vec<String>,
+    #[avp(name = "Bearer-Identifier")]
+    pub bearer_identifier: Option<Vec<u8>>,
+    #[avp(name = "Rule-Activation-Time")]
+    pub rule_activation_time: Option<DateTime<Utc>>,
+    #[avp(name = "Rule-Deactivation-Time")]
+    pub rule_deactivation_time: Option<DateTime<Utc>>,
+    #[avp(name = "Resource-Allocation-Notification")]
+    pub resource_allocation_notification: Option<ResourceAllocationNotification>,
+}
+
+/// The Charging-Rule-Remove AVP (1002), deactivating PCC rules.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChargingRuleRemove {
+    #[avp(name = "Charging-Rule-Name")]
+    pub charging_rule_name: Vec<Vec<u8>>,
+    #[avp(name = "Charging-Rule-Base-Name")]
+    pub charging_rule_base_name: Vec<String>,
+}
+
+/// The Charging-Rule-Report AVP (1018), reporting the status of PCC rules.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChargingRuleReport {
+    #[avp(name = "Charging-Rule-Name")]
+    pub charging_rule_name: Vec<Vec<u8>>,
+    #[avp(name = "Charging-Rule-Base-Name")]
+    pub charging_rule_base_name: Vec<String>,
+    #[avp(name = "Bearer-Identifier")]
+    pub bearer_identifier: Option<Vec<u8>>,
+    #[avp(name = "PCC-Rule-Status")]
+    pub pcc_rule_status: Option<PccRuleStatus>,
+    #[avp(name = "Rule-Failure-Code")]
+    pub rule_failure_code: Option<RuleFailureCode>,
+    #[avp(name = "Final-Unit-Indication", grouped)]
+    pub final_unit_indication: Option<FinalUnitIndication>,
+}
+
+/// The Charging-Information AVP (618), the addresses of the charging
+/// functions.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChargingInformation {
+    #[avp(name = "Primary-Event-Charging-Function-Name")]
+    pub primary_event_charging_function_name: Option<String>,
+    #[avp(name = "Secondary-Event-Charging-Function-Name")]
+    pub secondary_event_charging_function_name: Option<String>,
+    #[avp(name = "Primary-Charging-Collection-Function-Name")]
+    pub primary_charging_collection_function_name: Option<String>,
+    #[avp(name = "Secondary-Charging-Collection-Function-Name")]
+    pub secondary_charging_collection_function_name: Option<String>,
+}
+
+/// The Access-Network-Charging-Identifier-Gx AVP (1022).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AccessNetworkChargingIdentifierGx {
+    #[avp(name = "Access-Network-Charging-Identifier-Value")]
+    pub access_network_charging_identifier_value: Vec<u8>,
+    #[avp(name = "Charging-Rule-Base-Name")]
+    pub charging_rule_base_name: Vec<String>,
+    #[avp(name = "Charging-Rule-Name")]
+    pub charging_rule_name: Vec<Vec<u8>>,
+}
+
+/// The Event-Report-Indication AVP (1033).
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct EventReportIndication {
+    #[avp(name = "Event-Trigger")]
+    pub event_trigger: Vec<EventTrigger>,
+}
+
+/// The Usage-Monitoring-Information AVP (1067), the volume thresholds
+/// granted for, and the usage reported against, a monitoring key.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct UsageMonitoringInformation {
+    #[avp(name = "Monitoring-Key")]
+    pub monitoring_key: Option<Vec<u8>>,
+    #[avp(name = "Granted-Service-Unit", grouped)]
+    pub granted_service_unit: Vec<ServiceUnit>,
+    #[avp(name = "Used-Service-Unit", grouped)]
+    pub used_service_unit: Vec<ServiceUnit>,
+    #[avp(name = "Usage-Monitoring-Level")]
+    pub usage_monitoring_level: Option<UsageMonitoringLevel>,
+    #[avp(name = "Usage-Monitoring-Report")]
+    pub usage_monitoring_report: Option<UsageMonitoringReport>,
+    #[avp(name = "Usage-Monitoring-Support")]
+    pub usage_monitoring_support: Option<UsageMonitoringSupport>,
+}
+
+/// Gx Credit-Control-Request (CCR), 3GPP TS 29.212 section 5.6.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = CreditControl,
+    application = Gx,
+    request,
+    proxyable
+)]
+pub struct CreditControlRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "CC-Request-Type")]
+    pub cc_request_type: CcRequestType,
+    #[avp(name = "CC-Request-Number")]
+    pub cc_request_number: u32,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Subscription-Id", grouped)]
+    pub subscription_id: Vec<SubscriptionId>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Network-Request-Support")]
+    pub network_request_support: Option<NetworkRequestSupport>,
+    #[avp(name = "Bearer-Identifier")]
+    pub bearer_identifier: Option<Vec<u8>>,
+    #[avp(name = "Bearer-Operation")]
+    pub bearer_operation: Option<BearerOperation>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Option<Vec<u8>>,
+    #[avp(name = "IP-CAN-Type")]
+    pub ip_can_type: Option<IpCanType>,
+    #[avp(name = "TGPP-RAT-Type")]
+    pub tgpp_rat_type: Option<Vec<u8>>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: Option<RatType>,
+    #[avp(name = "Termination-Cause")]
+    pub termination_cause: Option<TerminationCause>,
+    #[avp(name = "User-Equipment-Info", grouped)]
+    pub user_equipment_info: Option<UserEquipmentInfo>,
+    #[avp(name = "QoS-Information", grouped)]
+    pub qos_information: Option<QosInformation>,
+    #[avp(name = "Default-EPS-Bearer-QoS", grouped)]
+    pub default_eps_bearer_qos: Option<DefaultEpsBearerQos>,
+    #[avp(name = "AN-GW-Address")]
+    pub an_gw_address: Vec<IpAddr>,
+    #[avp(name = "TGPP-SGSN-MCC-MNC")]
+    pub tgpp_sgsn_mcc_mnc: Option<String>,
+    #[avp(name = "TGPP-User-Location-Info")]
+    pub tgpp_user_location_info: Option<Vec<u8>>,
+    #[avp(name = "TGPP-MS-TimeZone")]
+    pub tgpp_ms_timezone: Option<Vec<u8>>,
+    #[avp(name = "Called-Station-Id")]
+    pub called_station_id: Option<String>,
+    #[avp(name = "PDN-Connection-ID")]
+    pub pdn_connection_id: Option<Vec<u8>>,
+    #[avp(name = "Bearer-Usage")]
+    pub bearer_usage: Option<BearerUsage>,
+    #[avp(name = "Online")]
+    pub online: Option<Online>,
+    #[avp(name = "Offline")]
+    pub offline: Option<Offline>,
+    #[avp(name = "Charging-Rule-Report", grouped)]
+    pub charging_rule_report: Vec<ChargingRuleReport>,
+    #[avp(name = "Event-Trigger")]
+    pub event_trigger: Vec<EventTrigger>,
+    #[avp(name = "Event-Report-Indication", grouped)]
+    pub event_report_indication: Option<EventReportIndication>,
+    #[avp(name = "Access-Network-Charging-Address")]
+    pub access_network_charging_address: Option<IpAddr>,
+    #[avp(name = "Access-Network-Charging-Identifier-Gx", grouped)]
+    pub access_network_charging_identifier_gx: Vec<AccessNetworkChargingIdentifierGx>,
+    #[avp(name = "Usage-Monitoring-Information", grouped)]
+    pub usage_monitoring_information: Vec<UsageMonitoringInformation>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Gx Credit-Control-Answer (CCA), 3GPP TS 29.212 section 5.6.3.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = CreditControl, application = Gx, proxyable)]
+pub struct CreditControlAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "CC-Request-Type")]
+    pub cc_request_type: CcRequestType,
+    #[avp(name = "CC-Request-Number")]
+    pub cc_request_number: u32,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Bearer-Control-Mode")]
+    pub bearer_control_mode: Option<BearerControlMode>,
+    #[avp(name = "Event-Trigger")]
+    pub event_trigger: Vec<EventTrigger>,
+    #[avp(name = "Event-Report-Indication", grouped)]
+    pub event_report_indication: Option<EventReportIndication>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Charging-Rule-Remove", grouped)]
+    pub charging_rule_remove: Vec<ChargingRuleRemove>,
+    #[avp(name = "Charging-Rule-Install", grouped)]
+    pub charging_rule_install: Vec<ChargingRuleInstall>,
+    #[avp(name = "Charging-Information", grouped)]
+    pub charging_information: Option<ChargingInformation>,
+    #[avp(name = "Online")]
+    pub online: Option<Online>,
+    #[avp(name = "Offline")]
+    pub offline: Option<Offline>,
+    #[avp(name = "QoS-Information", grouped)]
+    pub qos_information: Vec<QosInformation>,
+    #[avp(name = "Revalidation-Time")]
+    pub revalidation_time: Option<DateTime<Utc>>,
+    #[avp(name = "Default-EPS-Bearer-QoS", grouped)]
+    pub default_eps_bearer_qos: Option<DefaultEpsBearerQos>,
+    #[avp(name = "Bearer-Usage")]
+    pub bearer_usage: Option<BearerUsage>,
+    #[avp(name = "Usage-Monitoring-Information", grouped)]
+    pub usage_monitoring_information: Vec<UsageMonitoringInformation>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Gx Re-Auth-Request (RAR), 3GPP TS 29.212 section 5.6.4, sent by the PCRF
+/// to push policy to the PCEF.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ReAuth, application = Gx, request, proxyable)]
+pub struct ReAuthRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Re-Auth-Request-Type")]
+    pub re_auth_request_type: ReAuthRequestType,
+    #[avp(name = "Session-Release-Cause")]
+    pub session_release_cause: Option<SessionReleaseCause>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Event-Trigger")]
+    pub event_trigger: Vec<EventTrigger>,
+    #[avp(name = "Event-Report-Indication", grouped)]
+    pub event_report_indication: Option<EventReportIndication>,
+    #[avp(name = "Charging-Rule-Remove", grouped)]
+    pub charging_rule_remove: Vec<ChargingRuleRemove>,
+    #[avp(name = "Charging-Rule-Install", grouped)]
+    pub charging_rule_install: Vec<ChargingRuleInstall>,
+    #[avp(name = "Charging-Information", grouped)]
+    pub charging_information: Option<ChargingInformation>,
+    #[avp(name = "Online")]
+    pub online: Option<Online>,
+    #[avp(name = "Offline")]
+    pub offline: Option<Offline>,
+    #[avp(name = "QoS-Information", grouped)]
+    pub qos_information: Vec<QosInformation>,
+    #[avp(name = "Revalidation-Time")]
+    pub revalidation_time: Option<DateTime<Utc>>,
+    #[avp(name = "Default-EPS-Bearer-QoS", grouped)]
+    pub default_eps_bearer_qos: Option<DefaultEpsBearerQos>,
+    #[avp(name = "Usage-Monitoring-Information", grouped)]
+    pub usage_monitoring_information: Vec<UsageMonitoringInformation>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Gx Re-Auth-Answer (RAA), 3GPP TS 29.212 section 5.6.5.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ReAuth, application = Gx, proxyable)]
+pub struct ReAuthAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "IP-CAN-Type")]
+    pub ip_can_type: Option<IpCanType>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: Option<RatType>,
+    #[avp(name = "AN-GW-Address")]
+    pub an_gw_address: Vec<IpAddr>,
+    #[avp(name = "TGPP-SGSN-MCC-MNC")]
+    pub tgpp_sgsn_mcc_mnc: Option<String>,
+    #[avp(name = "TGPP-User-Location-Info")]
+    pub tgpp_user_location_info: Option<Vec<u8>>,
+    #[avp(name = "TGPP-MS-TimeZone")]
+    pub tgpp_ms_timezone: Option<Vec<u8>>,
+    #[avp(name = "Charging-Rule-Report", grouped)]
+    pub charging_rule_report: Vec<ChargingRuleReport>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl CreditControlRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        cc_request_type: CcRequestType,
+        cc_request_number: u32,
+    ) -> CreditControlRequest {
+        CreditControlRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            cc_request_type,
+            cc_request_number,
+            destination_host: None,
+            origin_state_id: None,
+            subscription_id: Vec::new(),
+            supported_features: Vec::new(),
+            network_request_support: None,
+            bearer_identifier: None,
+            bearer_operation: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: None,
+            ip_can_type: None,
+            tgpp_rat_type: None,
+            rat_type: None,
+            termination_cause: None,
+            user_equipment_info: None,
+            qos_information: None,
+            default_eps_bearer_qos: None,
+            an_gw_address: Vec::new(),
+            tgpp_sgsn_mcc_mnc: None,
+            tgpp_user_location_info: None,
+            tgpp_ms_timezone: None,
+            called_station_id: None,
+            pdn_connection_id: None,
+            bearer_usage: None,
+            online: None,
+            offline: None,
+            charging_rule_report: Vec::new(),
+            event_trigger: Vec::new(),
+            event_report_indication: None,
+            access_network_charging_address: None,
+            access_network_charging_identifier_gx: Vec::new(),
+            usage_monitoring_information: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl CreditControlAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying
+    /// Session-Id, CC-Request-Type, CC-Request-Number and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &CreditControlRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> CreditControlAnswer {
+        CreditControlAnswer {
+            session_id: request.session_id.clone(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            cc_request_type: request.cc_request_type,
+            cc_request_number: request.cc_request_number,
+            supported_features: Vec::new(),
+            bearer_control_mode: None,
+            event_trigger: Vec::new(),
+            event_report_indication: None,
+            origin_state_id: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            charging_rule_remove: Vec::new(),
+            charging_rule_install: Vec::new(),
+            charging_information: None,
+            online: None,
+            offline: None,
+            qos_information: Vec::new(),
+            revalidation_time: None,
+            default_eps_bearer_qos: None,
+            bearer_usage: None,
+            usage_monitoring_information: Vec::new(),
+            error_message: None,
+            error_reporting_host: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ReAuthRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        re_auth_request_type: ReAuthRequestType,
+    ) -> ReAuthRequest {
+        ReAuthRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: destination_host.into(),
+            re_auth_request_type,
+            session_release_cause: None,
+            origin_state_id: None,
+            event_trigger: Vec::new(),
+            event_report_indication: None,
+            charging_rule_remove: Vec::new(),
+            charging_rule_install: Vec::new(),
+            charging_information: None,
+            online: None,
+            offline: None,
+            qos_information: Vec::new(),
+            revalidation_time: None,
+            default_eps_bearer_qos: None,
+            usage_monitoring_information: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ReAuthAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &ReAuthRequest, result_code: u32, node: &NodeConfig) -> ReAuthAnswer {
+        ReAuthAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            origin_state_id: None,
+            ip_can_type: None,
+            rat_type: None,
+            an_gw_address: Vec::new(),
+            tgpp_sgsn_mcc_mnc: None,
+            tgpp_user_location_info: None,
+            tgpp_ms_timezone: None,
+            charging_rule_report: Vec::new(),
+            error_message: None,
+            error_reporting_host: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::common::TGPP_VENDOR_ID;
+    use crate::applications::credit_control::SubscriptionIdType;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use chrono::TimeZone;
+    use std::fs;
+    use std::io::Cursor;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    #[test]
+    fn test_ccr_cca_round_trip() {
+        let dict = dict();
+        let pcef = NodeConfig::new("pgw.example.com", "example.com");
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+
+        let mut ccr = CreditControlRequest::new(
+            "pgw.example.com;1;1",
+            &pcef,
+            "example.com",
+            CcRequestType::UpdateRequest,
+            1,
+        );
+        ccr.subscription_id.push(SubscriptionId {
+            subscription_id_type: SubscriptionIdType::EndUserImsi,
+            subscription_id_data: "525051234567890".into(),
+        });
+        ccr.supported_features.push(SupportedFeatures {
+            vendor_id: TGPP_VENDOR_ID,
+            feature_list_id: 1,
+            feature_list: 0x3,
+        });
+        ccr.framed_ip_address = Some(vec![10, 0, 0, 1]);
+        ccr.ip_can_type = Some(IpCanType::TgppEps);
+        ccr.rat_type = Some(RatType::Eutran);
+        ccr.an_gw_address
+            .push(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        ccr.tgpp_user_location_info = Some(vec![0x82, 0x25, 0xf0, 0x51]);
+        ccr.called_station_id = Some("internet".into());
+        ccr.event_trigger.push(EventTrigger::UsageReport);
+        ccr.charging_rule_report.push(ChargingRuleReport {
+            charging_rule_name: vec![b"video".to_vec()],
+            pcc_rule_status: Some(PccRuleStatus::Inactive),
+            rule_failure_code: Some(RuleFailureCode::ResourceAllocationFailure),
+            final_unit_indication: Some(FinalUnitIndication {
+                final_unit_action: FinalUnitAction::Terminate,
+                restriction_filter_rule: Vec::new(),
+                filter_id: Vec::new(),
+                redirect_server: None,
+            }),
+            ..Default::default()
+        });
+        ccr.usage_monitoring_information
+            .push(UsageMonitoringInformation {
+                monitoring_key: Some(b"mk1".to_vec()),
+                used_service_unit: vec![ServiceUnit {
+                    cc_total_octets: Some(1_000_000),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            });
+
+        let msg = ccr.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::CreditControl);
+        assert_eq!(msg.get_application_id(), ApplicationId::Gx);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(
+            msg.query_values::<i32>("Charging-Rule-Report/Rule-Failure-Code")
+                .unwrap(),
+            vec![10]
+        );
+        assert_eq!(CreditControlRequest::from_message(&msg).unwrap(), ccr);
+
+        let mut cca = CreditControlAnswer::new(&ccr, 2001, &pcrf);
+        cca.bearer_control_mode = Some(BearerControlMode::UeNw);
+        cca.event_trigger.push(EventTrigger::RatChange);
+        cca.charging_rule_remove.push(ChargingRuleRemove {
+            charging_rule_name: vec![b"video".to_vec()],
+            ..Default::default()
+        });
+        cca.charging_rule_install.push(ChargingRuleInstall {
+            charging_rule_definition: vec![ChargingRuleDefinition {
+                charging_rule_name: b"voice".to_vec(),
+                rating_group: Some(10),
+                flow_information: vec![FlowInformation {
+                    flow_description: Some(
+                        "permit out 17 from 10.0.0.2 5060 to 10.0.0.1 5060".into(),
+                    ),
+                    flow_direction: Some(FlowDirection::Bidirectional),
+                    ..Default::default()
+                }],
+                flow_status: Some(FlowStatus::Enabled),
+                qos_information: Some(QosInformation {
+                    qos_class_identifier: Some(QosClassIdentifier::Qci1),
+                    guaranteed_bitrate_ul: Some(64_000),
+                    guaranteed_bitrate_dl: Some(64_000),
+                    allocation_retention_priority: Some(AllocationRetentionPriority {
+                        priority_level: 2,
+                        pre_emption_capability: Some(PreemptionCapability::Enabled),
+                        pre_emption_vulnerability: Some(PreemptionVulnerability::Disabled),
+                    }),
+                    ..Default::default()
+                }),
+                online: Some(Online::EnableOnline),
+                offline: Some(Offline::DisableOffline),
+                precedence: Some(100),
+                flows: vec![Flows {
+                    media_component_number: 1,
+                    flow_number: vec![1, 2],
+                    final_unit_action: None,
+                }],
+                ..Default::default()
+            }],
+            rule_activation_time: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        });
+        cca.default_eps_bearer_qos = Some(DefaultEpsBearerQos {
+            qos_class_identifier: Some(QosClassIdentifier::Qci9),
+            allocation_retention_priority: None,
+        });
+        cca.charging_information = Some(ChargingInformation {
+            primary_charging_collection_function_name: Some("aaa://cgf.example.com".into()),
+            ..Default::default()
+        });
+
+        let msg = round_trip(cca.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_flags(), flags::PROXYABLE);
+        assert_eq!(
+            msg.query_values::<u32>(
+                "Charging-Rule-Install/Charging-Rule-Definition/QoS-Information/Allocation-Retention-Priority/Priority-Level"
+            )
+            .unwrap(),
+            vec![2]
+        );
+        assert_eq!(CreditControlAnswer::from_message(&msg).unwrap(), cca);
+    }
+
+    #[test]
+    fn test_rar_raa_round_trip() {
+        let dict = dict();
+        let pcef = NodeConfig::new("pgw.example.com", "example.com");
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+
+        let mut rar = ReAuthRequest::new(
+            "pgw.example.com;1;1",
+            &pcrf,
+            "example.com",
+            "pgw.example.com",
+            ReAuthRequestType::AuthorizeOnly,
+        );
+        rar.charging_rule_install.push(ChargingRuleInstall {
+            charging_rule_base_name: vec!["throttled".into()],
+            ..Default::default()
+        });
+        rar.qos_information.push(QosInformation {
+            apn_aggregate_max_bitrate_ul: Some(1_000_000),
+            apn_aggregate_max_bitrate_dl: Some(5_000_000),
+            ..Default::default()
+        });
+
+        let msg = round_trip(rar.to_message(7, 8, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::ReAuth);
+        assert_eq!(msg.get_application_id(), ApplicationId::Gx);
+        assert_eq!(ReAuthRequest::from_message(&msg).unwrap(), rar);
+
+        let mut raa = ReAuthAnswer::new(&rar, 2001, &pcef);
+        raa.result_code = None;
+        raa.experimental_result = Some(ExperimentalResult {
+            vendor_id: TGPP_VENDOR_ID,
+            experimental_result_code: result_code::DIAMETER_PCC_RULE_EVENT,
+        });
+        raa.charging_rule_report.push(ChargingRuleReport {
+            charging_rule_base_name: vec!["throttled".into()],
+            pcc_rule_status: Some(PccRuleStatus::Inactive),
+            rule_failure_code: Some(RuleFailureCode::UnknownRuleName),
+            ..Default::default()
+        });
+
+        let msg = round_trip(raa.to_message(7, 8, &dict).unwrap(), &dict);
+        let decoded = ReAuthAnswer::from_message(&msg).unwrap();
+        assert_eq!(decoded, raa);
+        assert!(ReAuthRequest::from_message(&msg).is_err());
+    }
+
+    #[test]
+    fn test_dictionary_with_ro_rf() {
+        // The Gx dictionary shares 3GPP AVPs with the Ro/Rf one
+        let dict = Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            &fs::read_to_string("dict/3gpp-ro-rf.xml").unwrap(),
+            DICT_XML,
+        ]));
+        let pcef = NodeConfig::new("pgw.example.com", "example.com");
+        let mut ccr = CreditControlRequest::new(
+            "pgw.example.com;1;1",
+            &pcef,
+            "example.com",
+            CcRequestType::InitialRequest,
+            0,
+        );
+        ccr.qos_information = Some(QosInformation {
+            qos_class_identifier: Some(QosClassIdentifier::Qci5),
+            ..Default::default()
+        });
+        ccr.tgpp_ms_timezone = Some(vec![0x40, 0x00]);
+
+        let msg = round_trip(ccr.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(CreditControlRequest::from_message(&msg).unwrap(), ccr);
+    }
+}
@@ -0,0 +1,89 @@
+//! Diameter Applications
+//!
+//! Typed requests, answers and grouped AVPs of Diameter applications, built
+//! on the [`TypedMessage`](crate::typed::TypedMessage) and
+//! [`TypedGroup`](crate::typed::TypedGroup) traits.
+
+/// Defines the values of an `Enumerated` AVP as an enum that converts to and
+/// from AVPs.
+///
+/// Values the enum does not list, such as those of a later release of the
+/// application, are kept as `Unknown` rather than rejecting the message, as
+/// [RFC 6733 section 4.3.1](https://tools.ietf.org/html/rfc6733#section-4.3.1)
+/// allows new values to be added.
+///
+/// Exported for the code generated by `diameter-codegen`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! enumerated {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident ($avp:literal) {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            #[doc = concat!("A value of ", $avp, " not listed above.")]
+            Unknown(i32),
+        }
+
+        impl $name {
+            /// Returns the variant with the given value, `None` for the
+            /// values not listed.
+            pub fn from_i32(value: i32) -> Option<$name> {
+                match value {
+                    $($value => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<i32> for $name {
+            fn from(value: i32) -> $name {
+                $name::from_i32(value).unwrap_or($name::Unknown(value))
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> i32 {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl $crate::avp::IntoAvpValue for $name {
+            fn into_avp_value(
+                self,
+                avp_type: $crate::avp::AvpType,
+            ) -> $crate::error::Result<$crate::avp::AvpValue> {
+                $crate::avp::IntoAvpValue::into_avp_value(i32::from(self), avp_type)
+            }
+        }
+
+        impl std::convert::TryFrom<&$crate::avp::Avp> for $name {
+            type Error = $crate::error::Error;
+
+            fn try_from(avp: &$crate::avp::Avp) -> $crate::error::Result<$name> {
+                let value = <i32 as std::convert::TryFrom<&$crate::avp::Avp>>::try_from(avp)?;
+                Ok($name::from(value))
+            }
+        }
+    };
+}
+
+pub mod accounting;
+pub mod common;
+pub mod credit_control;
+pub mod cx;
+pub mod eap;
+pub mod gx;
+pub mod nasreq;
+pub mod rx;
+pub mod s6a;
+pub mod sh;
+pub mod sy;
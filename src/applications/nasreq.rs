@@ -0,0 +1,534 @@
+//! NASREQ Application
+//!
+//! Typed AA-Request and AA-Answer messages of the Diameter Network Access
+//! Server application, as defined in
+//! [RFC 7155](https://tools.ietf.org/html/rfc7155), used by a NAS to
+//! authenticate and authorize dial-up, DSL or WLAN users with PAP or CHAP.
+//!
+//! The NASREQ AVPs are defined in [`DICT_XML`], which is loaded on top of
+//! the default dictionary. They are also used by the [`eap`](super::eap)
+//! application.
+//!
+//! ```
+//! use diameter::applications::common::AuthRequestType;
+//! use diameter::applications::nasreq::{self, AaAnswer, AaRequest, FramedProtocol, ServiceType};
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, nasreq::DICT_XML]));
+//! let nas = NodeConfig::new("nas.example.com", "example.com");
+//! let server = NodeConfig::new("aaa.example.com", "example.com");
+//!
+//! let mut aar = AaRequest::new(
+//!     "nas.example.com;1;1",
+//!     &nas,
+//!     "example.com",
+//!     AuthRequestType::AuthorizeAuthenticate,
+//! );
+//! aar.user_name = Some("alice@example.com".into());
+//! aar.user_password = Some(b"secret".to_vec());
+//! aar.service_type = Some(ServiceType::Framed);
+//! aar.framed_protocol = Some(FramedProtocol::Ppp);
+//!
+//! let msg = aar.to_message(1, 1, &dict).unwrap();
+//! let aar = AaRequest::from_message(&msg).unwrap();
+//! let mut aaa = AaAnswer::new(&aar, 2001, &server);
+//! aaa.framed_ip_address = Some(vec![10, 0, 0, 2]);
+//! assert_eq!(aaa.user_name.as_deref(), Some("alice@example.com"));
+//! ```
+
+use crate::applications::common::{
+    AuthRequestType, AuthSessionState, ProxyInfo, ReAuthRequestType,
+};
+use crate::applications::credit_control::RedirectHostUsage;
+use crate::node::NodeConfig;
+use crate::{DiameterMessage, GroupedAvp};
+
+/// Dictionary of the NASREQ application.
+pub const DICT_XML: &str = include_str!("../../dict/nasreq.xml");
+
+/// Auth-Application-Id of the NASREQ application.
+pub const APPLICATION_ID: u32 = 1;
+
+enumerated! {
+    /// Values of the CHAP-Algorithm AVP (403).
+    pub enum ChapAlgorithm ("CHAP-Algorithm") {
+        ChapWithMd5 = 5,
+    }
+}
+
+enumerated! {
+    /// Values of the Framed-Compression AVP (13).
+    pub enum FramedCompression ("Framed-Compression") {
+        None = 0,
+        VjTcpIpHeaderCompression = 1,
+        IpxHeaderCompression = 2,
+        StacLzsCompression = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the Framed-Protocol AVP (7).
+    pub enum FramedProtocol ("Framed-Protocol") {
+        Ppp = 1,
+        Slip = 2,
+        Arap = 3,
+        Gandalf = 4,
+        Xylogics = 5,
+        X75 = 6,
+    }
+}
+
+enumerated! {
+    /// Values of the Framed-Routing AVP (10).
+    pub enum FramedRouting ("Framed-Routing") {
+        None = 0,
+        SendRoutingPackets = 1,
+        ListenForRoutingPackets = 2,
+        SendAndListen = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the NAS-Port-Type AVP (61), as registered for the RADIUS
+    /// attribute.
+    pub enum NasPortType ("NAS-Port-Type") {
+        Async = 0,
+        Sync = 1,
+        IsdnSync = 2,
+        IsdnAsyncV120 = 3,
+        IsdnAsyncV110 = 4,
+        Virtual = 5,
+        Ethernet = 15,
+        Xdsl = 16,
+        Cable = 17,
+        WirelessOther = 18,
+        WirelessIeee80211 = 19,
+    }
+}
+
+enumerated! {
+    /// Values of the Origin-AAA-Protocol AVP (408).
+    pub enum OriginAaaProtocol ("Origin-AAA-Protocol") {
+        Radius = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Prompt AVP (76).
+    pub enum Prompt ("Prompt") {
+        NoEcho = 0,
+        Echo = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Service-Type AVP (6).
+    pub enum ServiceType ("Service-Type") {
+        Login = 1,
+        Framed = 2,
+        CallbackLogin = 3,
+        CallbackFramed = 4,
+        Outbound = 5,
+        Administrative = 6,
+        NasPrompt = 7,
+        AuthenticateOnly = 8,
+        CallbackNasPrompt = 9,
+        CallCheck = 10,
+        CallbackAdministrative = 11,
+    }
+}
+
+/// The CHAP-Auth AVP (402), carrying the user's CHAP response.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ChapAuth {
+    #[avp(name = "CHAP-Algorithm")]
+    pub chap_algorithm: ChapAlgorithm,
+    #[avp(name = "CHAP-Ident")]
+    pub chap_ident: Vec<u8>,
+    #[avp(name = "CHAP-Response")]
+    pub chap_response: Option<Vec<u8>>,
+}
+
+/// NASREQ AA-Request (AAR), RFC 7155 section 3.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AA, application = Nasreq, request, proxyable)]
+pub struct AaRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Auth-Request-Type")]
+    pub auth_request_type: AuthRequestType,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "NAS-Identifier")]
+    pub nas_identifier: Option<String>,
+    #[avp(name = "NAS-IP-Address")]
+    pub nas_ip_address: Option<Vec<u8>>,
+    #[avp(name = "NAS-IPv6-Address")]
+    pub nas_ipv6_address: Option<Vec<u8>>,
+    #[avp(name = "NAS-Port")]
+    pub nas_port: Option<u32>,
+    #[avp(name = "NAS-Port-Id")]
+    pub nas_port_id: Option<String>,
+    #[avp(name = "NAS-Port-Type")]
+    pub nas_port_type: Option<NasPortType>,
+    #[avp(name = "Origin-AAA-Protocol")]
+    pub origin_aaa_protocol: Option<OriginAaaProtocol>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Port-Limit")]
+    pub port_limit: Option<u32>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "User-Password")]
+    pub user_password: Option<Vec<u8>>,
+    #[avp(name = "Service-Type")]
+    pub service_type: Option<ServiceType>,
+    #[avp(name = "State")]
+    pub state: Option<Vec<u8>>,
+    #[avp(name = "Authorization-Lifetime")]
+    pub authorization_lifetime: Option<u32>,
+    #[avp(name = "Auth-Grace-Period")]
+    pub auth_grace_period: Option<u32>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: Option<AuthSessionState>,
+    #[avp(name = "Callback-Number")]
+    pub callback_number: Option<String>,
+    #[avp(name = "Called-Station-Id")]
+    pub called_station_id: Option<String>,
+    #[avp(name = "Calling-Station-Id")]
+    pub calling_station_id: Option<String>,
+    #[avp(name = "Originating-Line-Info")]
+    pub originating_line_info: Option<Vec<u8>>,
+    #[avp(name = "Connect-Info")]
+    pub connect_info: Option<String>,
+    #[avp(name = "CHAP-Auth", grouped)]
+    pub chap_auth: Option<ChapAuth>,
+    #[avp(name = "CHAP-Challenge")]
+    pub chap_challenge: Option<Vec<u8>>,
+    #[avp(name = "Framed-Compression")]
+    pub framed_compression: Vec<FramedCompression>,
+    #[avp(name = "Framed-Interface-Id")]
+    pub framed_interface_id: Option<u64>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Vec<Vec<u8>>,
+    #[avp(name = "Framed-IP-Netmask")]
+    pub framed_ip_netmask: Option<Vec<u8>>,
+    #[avp(name = "Framed-MTU")]
+    pub framed_mtu: Option<u32>,
+    #[avp(name = "Framed-Protocol")]
+    pub framed_protocol: Option<FramedProtocol>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// NASREQ AA-Answer (AAA), RFC 7155 section 3.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AA, application = Nasreq, proxyable)]
+pub struct AaAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Auth-Request-Type")]
+    pub auth_request_type: AuthRequestType,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Service-Type")]
+    pub service_type: Option<ServiceType>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Configuration-Token")]
+    pub configuration_token: Vec<Vec<u8>>,
+    #[avp(name = "Acct-Interim-Interval")]
+    pub acct_interim_interval: Option<u32>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Idle-Timeout")]
+    pub idle_timeout: Option<u32>,
+    #[avp(name = "Authorization-Lifetime")]
+    pub authorization_lifetime: Option<u32>,
+    #[avp(name = "Auth-Grace-Period")]
+    pub auth_grace_period: Option<u32>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: Option<AuthSessionState>,
+    #[avp(name = "Re-Auth-Request-Type")]
+    pub re_auth_request_type: Option<ReAuthRequestType>,
+    #[avp(name = "Multi-Round-Time-Out")]
+    pub multi_round_time_out: Option<u32>,
+    #[avp(name = "Session-Timeout")]
+    pub session_timeout: Option<u32>,
+    #[avp(name = "State")]
+    pub state: Option<Vec<u8>>,
+    #[avp(name = "Reply-Message")]
+    pub reply_message: Vec<String>,
+    #[avp(name = "Origin-AAA-Protocol")]
+    pub origin_aaa_protocol: Option<OriginAaaProtocol>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Filter-Id")]
+    pub filter_id: Vec<String>,
+    #[avp(name = "Password-Retry")]
+    pub password_retry: Option<u32>,
+    #[avp(name = "Port-Limit")]
+    pub port_limit: Option<u32>,
+    #[avp(name = "Prompt")]
+    pub prompt: Option<Prompt>,
+    #[avp(name = "Callback-Id")]
+    pub callback_id: Option<String>,
+    #[avp(name = "Callback-Number")]
+    pub callback_number: Option<String>,
+    #[avp(name = "Framed-Compression")]
+    pub framed_compression: Vec<FramedCompression>,
+    #[avp(name = "Framed-Interface-Id")]
+    pub framed_interface_id: Option<u64>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Vec<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Pool")]
+    pub framed_ipv6_pool: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Route")]
+    pub framed_ipv6_route: Vec<String>,
+    #[avp(name = "Framed-IP-Netmask")]
+    pub framed_ip_netmask: Option<Vec<u8>>,
+    #[avp(name = "Framed-Route")]
+    pub framed_route: Vec<String>,
+    #[avp(name = "Framed-Pool")]
+    pub framed_pool: Option<Vec<u8>>,
+    #[avp(name = "Framed-MTU")]
+    pub framed_mtu: Option<u32>,
+    #[avp(name = "Framed-Protocol")]
+    pub framed_protocol: Option<FramedProtocol>,
+    #[avp(name = "Framed-Routing")]
+    pub framed_routing: Option<FramedRouting>,
+    #[avp(name = "NAS-Filter-Rule")]
+    pub nas_filter_rule: Vec<String>,
+    #[avp(name = "QoS-Filter-Rule")]
+    pub qos_filter_rule: Vec<String>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl AaRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        auth_request_type: AuthRequestType,
+    ) -> AaRequest {
+        AaRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            auth_request_type,
+            destination_host: None,
+            nas_identifier: None,
+            nas_ip_address: None,
+            nas_ipv6_address: None,
+            nas_port: None,
+            nas_port_id: None,
+            nas_port_type: None,
+            origin_aaa_protocol: None,
+            origin_state_id: None,
+            port_limit: None,
+            user_name: None,
+            user_password: None,
+            service_type: None,
+            state: None,
+            authorization_lifetime: None,
+            auth_grace_period: None,
+            auth_session_state: None,
+            callback_number: None,
+            called_station_id: None,
+            calling_station_id: None,
+            originating_line_info: None,
+            connect_info: None,
+            chap_auth: None,
+            chap_challenge: None,
+            framed_compression: Vec::new(),
+            framed_interface_id: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: Vec::new(),
+            framed_ip_netmask: None,
+            framed_mtu: None,
+            framed_protocol: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AaAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Auth-Request-Type, User-Name and Proxy-Info, and taking Origin-Host
+    /// and Origin-Realm from `node`.
+    pub fn new(request: &AaRequest, result_code: u32, node: &NodeConfig) -> AaAnswer {
+        AaAnswer {
+            session_id: request.session_id.clone(),
+            auth_application_id: APPLICATION_ID,
+            auth_request_type: request.auth_request_type,
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            user_name: request.user_name.clone(),
+            service_type: None,
+            class: Vec::new(),
+            configuration_token: Vec::new(),
+            acct_interim_interval: None,
+            error_message: None,
+            error_reporting_host: None,
+            idle_timeout: None,
+            authorization_lifetime: None,
+            auth_grace_period: None,
+            auth_session_state: None,
+            re_auth_request_type: None,
+            multi_round_time_out: None,
+            session_timeout: None,
+            state: None,
+            reply_message: Vec::new(),
+            origin_aaa_protocol: None,
+            origin_state_id: None,
+            filter_id: Vec::new(),
+            password_retry: None,
+            port_limit: None,
+            prompt: None,
+            callback_id: None,
+            callback_number: None,
+            framed_compression: Vec::new(),
+            framed_interface_id: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: Vec::new(),
+            framed_ipv6_pool: None,
+            framed_ipv6_route: Vec::new(),
+            framed_ip_netmask: None,
+            framed_route: Vec::new(),
+            framed_pool: None,
+            framed_mtu: None,
+            framed_protocol: None,
+            framed_routing: None,
+            nas_filter_rule: Vec::new(),
+            qos_filter_rule: Vec::new(),
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    #[test]
+    fn test_aar_chap_round_trip() {
+        let dict = dict();
+        let node = NodeConfig::new("nas.example.com", "example.com");
+
+        let mut aar = AaRequest::new(
+            "nas.example.com;1;2",
+            &node,
+            "example.com",
+            AuthRequestType::AuthorizeAuthenticate,
+        );
+        aar.user_name = Some("bob".into());
+        aar.nas_identifier = Some("nas1".into());
+        aar.nas_ip_address = Some(vec![192, 168, 0, 1]);
+        aar.nas_port = Some(7);
+        aar.nas_port_type = Some(NasPortType::WirelessIeee80211);
+        aar.calling_station_id = Some("00-11-22-33-44-55".into());
+        aar.chap_auth = Some(ChapAuth {
+            chap_algorithm: ChapAlgorithm::ChapWithMd5,
+            chap_ident: vec![1],
+            chap_response: Some(vec![0xab; 16]),
+        });
+        aar.chap_challenge = Some(vec![0xcd; 16]);
+        aar.framed_compression
+            .push(FramedCompression::VjTcpIpHeaderCompression);
+
+        let msg = aar.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::AA);
+        assert_eq!(msg.get_application_id(), ApplicationId::Nasreq);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        assert_eq!(msg.get::<u32, _>("Auth-Application-Id").unwrap(), 1);
+        assert_eq!(
+            msg.query_values::<i32>("CHAP-Auth/CHAP-Algorithm").unwrap(),
+            vec![5]
+        );
+
+        let decoded = AaRequest::from_message(&msg).unwrap();
+        assert_eq!(decoded, aar);
+    }
+
+    #[test]
+    fn test_aaa_round_trip() {
+        let dict = dict();
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let server = NodeConfig::new("aaa.example.com", "example.com");
+        let mut aar = AaRequest::new(
+            "nas.example.com;1;3",
+            &nas,
+            "example.com",
+            AuthRequestType::AuthenticateOnly,
+        );
+        aar.user_name = Some("carol".into());
+
+        let mut aaa = AaAnswer::new(&aar, 2001, &server);
+        aaa.session_timeout = Some(3600);
+        aaa.auth_session_state = Some(AuthSessionState::StateMaintained);
+        aaa.framed_ip_address = Some(vec![10, 0, 0, 9]);
+        aaa.framed_route.push("10.1.0.0/16 10.0.0.1 1".into());
+        aaa.nas_filter_rule
+            .push("permit in ip from any to 10.0.0.0/8".into());
+        aaa.reply_message.push("Welcome".into());
+        aaa.class.push(b"class-1".to_vec());
+        assert_eq!(aaa.auth_request_type, AuthRequestType::AuthenticateOnly);
+        assert_eq!(aaa.user_name.as_deref(), Some("carol"));
+
+        let msg = aaa.to_message(1, 2, &dict).unwrap();
+        assert!(!msg.is_request());
+        assert_eq!(AaAnswer::from_message(&msg).unwrap(), aaa);
+    }
+}
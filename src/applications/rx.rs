@@ -0,0 +1,1055 @@
+//! Rx Application
+//!
+//! Typed AA, Re-Auth, Session-Termination and Abort-Session messages of the
+//! Rx reference point between an AF, such as a P-CSCF, and the PCRF, as
+//! defined in [3GPP TS 29.214](https://www.3gpp.org/DynaReport/29214.htm).
+//!
+//! The Rx AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary. The types shared with Gx, such as [`Flows`] and
+//! [`FlowStatus`], are re-exported from the [`gx`](super::gx) module.
+//!
+//! ```
+//! use diameter::applications::rx::{
+//!     self, AaAnswer, AaRequest, FlowStatus, MediaComponentDescription, MediaSubComponent,
+//!     MediaType, SpecificAction,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, rx::DICT_XML]));
+//! let pcscf = NodeConfig::new("pcscf.example.com", "example.com");
+//! let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+//!
+//! let mut aar = AaRequest::new("pcscf.example.com;1;1", &pcscf, "example.com");
+//! aar.media_component_description.push(MediaComponentDescription {
+//!     media_component_number: 1,
+//!     media_type: Some(MediaType::Audio),
+//!     flow_status: Some(FlowStatus::Enabled),
+//!     media_sub_component: vec![MediaSubComponent {
+//!         flow_number: 1,
+//!         flow_description: vec![
+//!             "permit out 17 from 10.0.0.2 49152 to 10.0.0.1 49152".into(),
+//!             "permit in 17 from 10.0.0.1 49152 to 10.0.0.2 49152".into(),
+//!         ],
+//!         ..Default::default()
+//!     }],
+//!     ..Default::default()
+//! });
+//! aar.specific_action.push(SpecificAction::IndicationOfLossOfBearer);
+//!
+//! let msg = aar.to_message(1, 1, &dict).unwrap();
+//! let aar = AaRequest::from_message(&msg).unwrap();
+//! let aaa = AaAnswer::new(&aar, 2001, &pcrf);
+//! assert_eq!(aaa.session_id, "pcscf.example.com;1;1");
+//! ```
+
+use crate::applications::common::{ExperimentalResult, ProxyInfo, TerminationCause};
+use crate::applications::credit_control::{RedirectHostUsage, SubscriptionId};
+use crate::node::NodeConfig;
+use crate::{DiameterMessage, GroupedAvp};
+use std::net::IpAddr;
+
+pub use crate::applications::common::SupportedFeatures;
+pub use crate::applications::gx::{FlowStatus, Flows, IpCanType, RatType};
+
+/// Dictionary of the Rx application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-rx.xml");
+
+/// Auth-Application-Id of the Rx application.
+pub const APPLICATION_ID: u32 = 16777236;
+
+/// Experimental-Result-Code values defined in 3GPP TS 29.214 section 5.5.
+pub mod result_code {
+    // Transient Failures
+    pub const REQUESTED_SERVICE_TEMPORARILY_NOT_AUTHORIZED: u32 = 4261;
+
+    // Permanent Failures
+    pub const INVALID_SERVICE_INFORMATION: u32 = 5061;
+    pub const FILTER_RESTRICTIONS: u32 = 5062;
+    pub const REQUESTED_SERVICE_NOT_AUTHORIZED: u32 = 5063;
+    pub const DUPLICATED_AF_SESSION: u32 = 5064;
+    pub const IP_CAN_SESSION_NOT_AVAILABLE: u32 = 5065;
+    pub const UNAUTHORIZED_NON_EMERGENCY_SESSION: u32 = 5066;
+    pub const UNAUTHORIZED_SPONSORED_DATA_CONNECTIVITY: u32 = 5067;
+    pub const TEMPORARY_NETWORK_FAILURE: u32 = 5068;
+}
+
+enumerated! {
+    /// Values of the Abort-Cause AVP (500).
+    pub enum AbortCause ("Abort-Cause") {
+        BearerReleased = 0,
+        InsufficientServerResources = 1,
+        InsufficientBearerResources = 2,
+        PsToCsHandover = 3,
+        SponsoredDataConnectivityDisallowed = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the AF-Signalling-Protocol AVP (529).
+    pub enum AfSignallingProtocol ("AF-Signalling-Protocol") {
+        NoInformation = 0,
+        Sip = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Flow-Usage AVP (512).
+    pub enum FlowUsage ("Flow-Usage") {
+        NoInformation = 0,
+        Rtcp = 1,
+        AfSignalling = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Media-Type AVP (520).
+    pub enum MediaType ("Media-Type") {
+        Audio = 0,
+        Video = 1,
+        Data = 2,
+        Application = 3,
+        Control = 4,
+        Text = 5,
+        Message = 6,
+        /// 4294967295 on the wire.
+        Other = -1,
+    }
+}
+
+enumerated! {
+    /// Values of the Required-Access-Info AVP (536).
+    pub enum RequiredAccessInfo ("Required-Access-Info") {
+        UserLocation = 0,
+        MsTimeZone = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Reservation-Priority AVP (458, ETSI).
+    pub enum ReservationPriority ("Reservation-Priority") {
+        Default = 0,
+        PriorityOne = 1,
+        PriorityTwo = 2,
+        PriorityThree = 3,
+        PriorityFour = 4,
+        PriorityFive = 5,
+        PrioritySix = 6,
+        PrioritySeven = 7,
+        PriorityEight = 8,
+        PriorityNine = 9,
+        PriorityTen = 10,
+        PriorityEleven = 11,
+        PriorityTwelve = 12,
+        PriorityThirteen = 13,
+        PriorityFourteen = 14,
+        PriorityFifteen = 15,
+    }
+}
+
+enumerated! {
+    /// Values of the Rx-Request-Type AVP (533).
+    pub enum RxRequestType ("Rx-Request-Type") {
+        InitialRequest = 0,
+        UpdateRequest = 1,
+        PcscfRestoration = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Service-Info-Status AVP (527).
+    pub enum ServiceInfoStatus ("Service-Info-Status") {
+        FinalServiceInformation = 0,
+        PreliminaryServiceInformation = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the SIP-Forking-Indication AVP (523).
+    pub enum SipForkingIndication ("SIP-Forking-Indication") {
+        SingleDialogue = 0,
+        SeveralDialogues = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Specific-Action AVP (513), the bearer events an AF
+    /// subscribes to in an AAR and is notified of in an RAR.
+    pub enum SpecificAction ("Specific-Action") {
+        ChargingCorrelationExchange = 1,
+        IndicationOfLossOfBearer = 2,
+        IndicationOfRecoveryOfBearer = 3,
+        IndicationOfReleaseOfBearer = 4,
+        IpCanChange = 6,
+        IndicationOfOutOfCredit = 7,
+        IndicationOfSuccessfulResourcesAllocation = 8,
+        IndicationOfFailedResourcesAllocation = 9,
+        IndicationOfLimitedPccDeployment = 10,
+        UsageReport = 11,
+        AccessNetworkInfoReport = 12,
+        IndicationOfRecoveryFromLimitedPccDeployment = 13,
+        IndicationOfAccessNetworkInfoReportingFailure = 14,
+        IndicationOfTransferPolicyExpired = 15,
+        PlmnChange = 16,
+    }
+}
+
+/// The Media-Sub-Component AVP (519), one IP flow of a media component.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct MediaSubComponent {
+    #[avp(name = "Flow-Number")]
+    pub flow_number: u32,
+    #[avp(name = "Flow-Description")]
+    pub flow_description: Vec<String>,
+    #[avp(name = "Flow-Status")]
+    pub flow_status: Option<FlowStatus>,
+    #[avp(name = "Flow-Usage")]
+    pub flow_usage: Option<FlowUsage>,
+    #[avp(name = "Max-Requested-Bandwidth-UL")]
+    pub max_requested_bandwidth_ul: Option<u32>,
+    #[avp(name = "Max-Requested-Bandwidth-DL")]
+    pub max_requested_bandwidth_dl: Option<u32>,
+    #[avp(name = "AF-Signalling-Protocol")]
+    pub af_signalling_protocol: Option<AfSignallingProtocol>,
+    #[avp(name = "ToS-Traffic-Class")]
+    pub tos_traffic_class: Option<Vec<u8>>,
+}
+
+/// The Media-Component-Description AVP (517), the service information of
+/// one media component of an AF session.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct MediaComponentDescription {
+    #[avp(name = "Media-Component-Number")]
+    pub media_component_number: u32,
+    #[avp(name = "Media-Sub-Component", grouped)]
+    pub media_sub_component: Vec<MediaSubComponent>,
+    #[avp(name = "AF-Application-Identifier")]
+    pub af_application_identifier: Option<Vec<u8>>,
+    #[avp(name = "Media-Type")]
+    pub media_type: Option<MediaType>,
+    #[avp(name = "Max-Requested-Bandwidth-UL")]
+    pub max_requested_bandwidth_ul: Option<u32>,
+    #[avp(name = "Max-Requested-Bandwidth-DL")]
+    pub max_requested_bandwidth_dl: Option<u32>,
+    #[avp(name = "Max-Supported-Bandwidth-UL")]
+    pub max_supported_bandwidth_ul: Option<u32>,
+    #[avp(name = "Max-Supported-Bandwidth-DL")]
+    pub max_supported_bandwidth_dl: Option<u32>,
+    #[avp(name = "Min-Desired-Bandwidth-UL")]
+    pub min_desired_bandwidth_ul: Option<u32>,
+    #[avp(name = "Min-Desired-Bandwidth-DL")]
+    pub min_desired_bandwidth_dl: Option<u32>,
+    #[avp(name = "Flow-Status")]
+    pub flow_status: Option<FlowStatus>,
+    #[avp(name = "Reservation-Priority")]
+    pub reservation_priority: Option<ReservationPriority>,
+    #[avp(name = "RS-Bandwidth")]
+    pub rs_bandwidth: Option<u32>,
+    #[avp(name = "RR-Bandwidth")]
+    pub rr_bandwidth: Option<u32>,
+    #[avp(name = "Codec-Data")]
+    pub codec_data: Vec<Vec<u8>>,
+}
+
+/// The Acceptable-Service-Info AVP (526), the bandwidth the PCRF would
+/// accept when rejecting an AAR.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AcceptableServiceInfo {
+    #[avp(name = "Media-Component-Description", grouped)]
+    pub media_component_description: Vec<MediaComponentDescription>,
+    #[avp(name = "Max-Requested-Bandwidth-DL")]
+    pub max_requested_bandwidth_dl: Option<u32>,
+    #[avp(name = "Max-Requested-Bandwidth-UL")]
+    pub max_requested_bandwidth_ul: Option<u32>,
+}
+
+/// The Access-Network-Charging-Identifier AVP (502).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AccessNetworkChargingIdentifier {
+    #[avp(name = "Access-Network-Charging-Identifier-Value")]
+    pub access_network_charging_identifier_value: Vec<u8>,
+    #[avp(name = "Flows", grouped)]
+    pub flows: Vec<Flows>,
+}
+
+/// Rx AA-Request (AAR), 3GPP TS 29.214 section 5.6.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AA, application = Rx, request, proxyable)]
+pub struct AaRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "IP-Domain-Id")]
+    pub ip_domain_id: Option<Vec<u8>>,
+    #[avp(name = "AF-Application-Identifier")]
+    pub af_application_identifier: Option<Vec<u8>>,
+    #[avp(name = "Media-Component-Description", grouped)]
+    pub media_component_description: Vec<MediaComponentDescription>,
+    #[avp(name = "Service-Info-Status")]
+    pub service_info_status: Option<ServiceInfoStatus>,
+    #[avp(name = "AF-Charging-Identifier")]
+    pub af_charging_identifier: Option<Vec<u8>>,
+    #[avp(name = "SIP-Forking-Indication")]
+    pub sip_forking_indication: Option<SipForkingIndication>,
+    #[avp(name = "Specific-Action")]
+    pub specific_action: Vec<SpecificAction>,
+    #[avp(name = "Subscription-Id", grouped)]
+    pub subscription_id: Vec<SubscriptionId>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Reservation-Priority")]
+    pub reservation_priority: Option<ReservationPriority>,
+    #[avp(name = "Framed-IP-Address")]
+    pub framed_ip_address: Option<Vec<u8>>,
+    #[avp(name = "Framed-IPv6-Prefix")]
+    pub framed_ipv6_prefix: Option<Vec<u8>>,
+    #[avp(name = "Called-Station-Id")]
+    pub called_station_id: Option<String>,
+    #[avp(name = "Service-URN")]
+    pub service_urn: Option<Vec<u8>>,
+    #[avp(name = "MPS-Identifier")]
+    pub mps_identifier: Option<Vec<u8>>,
+    #[avp(name = "Rx-Request-Type")]
+    pub rx_request_type: Option<RxRequestType>,
+    #[avp(name = "Required-Access-Info")]
+    pub required_access_info: Vec<RequiredAccessInfo>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Rx AA-Answer (AAA), 3GPP TS 29.214 section 5.6.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AA, application = Rx, proxyable)]
+pub struct AaAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Access-Network-Charging-Identifier", grouped)]
+    pub access_network_charging_identifier: Vec<AccessNetworkChargingIdentifier>,
+    #[avp(name = "Access-Network-Charging-Address")]
+    pub access_network_charging_address: Option<IpAddr>,
+    #[avp(name = "Acceptable-Service-Info", grouped)]
+    pub acceptable_service_info: Option<AcceptableServiceInfo>,
+    #[avp(name = "AN-GW-Address")]
+    pub an_gw_address: Vec<IpAddr>,
+    #[avp(name = "IP-CAN-Type")]
+    pub ip_can_type: Option<IpCanType>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: Option<RatType>,
+    #[avp(name = "Flows", grouped)]
+    pub flows: Vec<Flows>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+/// Rx Re-Auth-Request (RAR), 3GPP TS 29.214 section 5.6.3, sent by the PCRF
+/// to notify the AF of the events it subscribed to.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ReAuth, application = Rx, request, proxyable)]
+pub struct ReAuthRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Specific-Action")]
+    pub specific_action: Vec<SpecificAction>,
+    #[avp(name = "Access-Network-Charging-Identifier", grouped)]
+    pub access_network_charging_identifier: Vec<AccessNetworkChargingIdentifier>,
+    #[avp(name = "Access-Network-Charging-Address")]
+    pub access_network_charging_address: Option<IpAddr>,
+    #[avp(name = "AN-GW-Address")]
+    pub an_gw_address: Vec<IpAddr>,
+    #[avp(name = "Flows", grouped)]
+    pub flows: Vec<Flows>,
+    #[avp(name = "Subscription-Id", grouped)]
+    pub subscription_id: Vec<SubscriptionId>,
+    #[avp(name = "Abort-Cause")]
+    pub abort_cause: Option<AbortCause>,
+    #[avp(name = "IP-CAN-Type")]
+    pub ip_can_type: Option<IpCanType>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: Option<RatType>,
+    #[avp(name = "TGPP-User-Location-Info")]
+    pub tgpp_user_location_info: Option<Vec<u8>>,
+    #[avp(name = "TGPP-MS-TimeZone")]
+    pub tgpp_ms_timezone: Option<Vec<u8>>,
+    #[avp(name = "TGPP-SGSN-MCC-MNC")]
+    pub tgpp_sgsn_mcc_mnc: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Rx Re-Auth-Answer (RAA), 3GPP TS 29.214 section 5.6.4.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ReAuth, application = Rx, proxyable)]
+pub struct ReAuthAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Media-Component-Description", grouped)]
+    pub media_component_description: Vec<MediaComponentDescription>,
+    #[avp(name = "Service-URN")]
+    pub service_urn: Option<Vec<u8>>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+/// Rx Session-Termination-Request (STR), 3GPP TS 29.214 section 5.6.5.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = SessionTerminate,
+    application = Rx,
+    request,
+    proxyable
+)]
+pub struct SessionTerminationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Termination-Cause")]
+    pub termination_cause: TerminationCause,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Required-Access-Info")]
+    pub required_access_info: Vec<RequiredAccessInfo>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+}
+
+/// Rx Session-Termination-Answer (STA), 3GPP TS 29.214 section 5.6.6.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = SessionTerminate, application = Rx, proxyable)]
+pub struct SessionTerminationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "TGPP-User-Location-Info")]
+    pub tgpp_user_location_info: Option<Vec<u8>>,
+    #[avp(name = "TGPP-MS-TimeZone")]
+    pub tgpp_ms_timezone: Option<Vec<u8>>,
+    #[avp(name = "TGPP-SGSN-MCC-MNC")]
+    pub tgpp_sgsn_mcc_mnc: Option<String>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+}
+
+/// Rx Abort-Session-Request (ASR), 3GPP TS 29.214 section 5.6.7, sent by the
+/// PCRF when the bearers of an AF session are lost.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = AbortSession,
+    application = Rx,
+    request,
+    proxyable
+)]
+pub struct AbortSessionRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Abort-Cause")]
+    pub abort_cause: AbortCause,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Rx Abort-Session-Answer (ASA), 3GPP TS 29.214 section 5.6.8.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AbortSession, application = Rx, proxyable)]
+pub struct AbortSessionAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl AaRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(session_id: &str, node: &NodeConfig, destination_realm: &str) -> AaRequest {
+        AaRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: None,
+            ip_domain_id: None,
+            af_application_identifier: None,
+            media_component_description: Vec::new(),
+            service_info_status: None,
+            af_charging_identifier: None,
+            sip_forking_indication: None,
+            specific_action: Vec::new(),
+            subscription_id: Vec::new(),
+            supported_features: Vec::new(),
+            reservation_priority: None,
+            framed_ip_address: None,
+            framed_ipv6_prefix: None,
+            called_station_id: None,
+            service_urn: None,
+            mps_identifier: None,
+            rx_request_type: None,
+            required_access_info: Vec::new(),
+            origin_state_id: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AaAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &AaRequest, result_code: u32, node: &NodeConfig) -> AaAnswer {
+        AaAnswer {
+            session_id: request.session_id.clone(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            access_network_charging_identifier: Vec::new(),
+            access_network_charging_address: None,
+            acceptable_service_info: None,
+            an_gw_address: Vec::new(),
+            ip_can_type: None,
+            rat_type: None,
+            flows: Vec::new(),
+            supported_features: Vec::new(),
+            class: Vec::new(),
+            error_message: None,
+            error_reporting_host: None,
+            origin_state_id: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+impl ReAuthRequest {
+    /// Creates a request notifying `specific_action`, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        specific_action: SpecificAction,
+    ) -> ReAuthRequest {
+        ReAuthRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: destination_host.into(),
+            auth_application_id: APPLICATION_ID,
+            specific_action: vec![specific_action],
+            access_network_charging_identifier: Vec::new(),
+            access_network_charging_address: None,
+            an_gw_address: Vec::new(),
+            flows: Vec::new(),
+            subscription_id: Vec::new(),
+            abort_cause: None,
+            ip_can_type: None,
+            rat_type: None,
+            tgpp_user_location_info: None,
+            tgpp_ms_timezone: None,
+            tgpp_sgsn_mcc_mnc: None,
+            origin_state_id: None,
+            class: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ReAuthAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &ReAuthRequest, result_code: u32, node: &NodeConfig) -> ReAuthAnswer {
+        ReAuthAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            media_component_description: Vec::new(),
+            service_urn: None,
+            origin_state_id: None,
+            class: Vec::new(),
+            error_message: None,
+            error_reporting_host: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+impl SessionTerminationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        termination_cause: TerminationCause,
+    ) -> SessionTerminationRequest {
+        SessionTerminationRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            auth_application_id: APPLICATION_ID,
+            termination_cause,
+            destination_host: None,
+            required_access_info: Vec::new(),
+            class: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+            supported_features: Vec::new(),
+        }
+    }
+}
+
+impl SessionTerminationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &SessionTerminationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> SessionTerminationAnswer {
+        SessionTerminationAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            error_message: None,
+            error_reporting_host: None,
+            origin_state_id: None,
+            tgpp_user_location_info: None,
+            tgpp_ms_timezone: None,
+            tgpp_sgsn_mcc_mnc: None,
+            class: Vec::new(),
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+            supported_features: Vec::new(),
+        }
+    }
+}
+
+impl AbortSessionRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        abort_cause: AbortCause,
+    ) -> AbortSessionRequest {
+        AbortSessionRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: destination_host.into(),
+            auth_application_id: APPLICATION_ID,
+            abort_cause,
+            origin_state_id: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AbortSessionAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &AbortSessionRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> AbortSessionAnswer {
+        AbortSessionAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            origin_state_id: None,
+            error_message: None,
+            error_reporting_host: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::common::TGPP_VENDOR_ID;
+    use crate::applications::credit_control::SubscriptionIdType;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use std::io::Cursor;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    #[test]
+    fn test_aar_aaa_round_trip() {
+        let dict = dict();
+        let pcscf = NodeConfig::new("pcscf.example.com", "example.com");
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+
+        let mut aar = AaRequest::new("pcscf.example.com;1;1", &pcscf, "example.com");
+        aar.af_application_identifier = Some(b"IMS Services".to_vec());
+        aar.media_component_description
+            .push(MediaComponentDescription {
+                media_component_number: 1,
+                media_sub_component: vec![
+                    MediaSubComponent {
+                        flow_number: 1,
+                        flow_description: vec![
+                            "permit out 17 from 10.0.0.2 49152 to 10.0.0.1 49152".into(),
+                            "permit in 17 from 10.0.0.1 49152 to 10.0.0.2 49152".into(),
+                        ],
+                        ..Default::default()
+                    },
+                    MediaSubComponent {
+                        flow_number: 2,
+                        flow_usage: Some(FlowUsage::Rtcp),
+                        ..Default::default()
+                    },
+                ],
+                media_type: Some(MediaType::Audio),
+                max_requested_bandwidth_ul: Some(64_000),
+                max_requested_bandwidth_dl: Some(64_000),
+                flow_status: Some(FlowStatus::Enabled),
+                reservation_priority: Some(ReservationPriority::PriorityTwo),
+                codec_data: vec![b"uplink\noffer\nm=audio 49152 RTP/AVP 96".to_vec()],
+                ..Default::default()
+            });
+        aar.media_component_description
+            .push(MediaComponentDescription {
+                media_component_number: 2,
+                media_type: Some(MediaType::Other),
+                ..Default::default()
+            });
+        aar.specific_action = vec![
+            SpecificAction::IndicationOfLossOfBearer,
+            SpecificAction::IndicationOfReleaseOfBearer,
+        ];
+        aar.subscription_id.push(SubscriptionId {
+            subscription_id_type: SubscriptionIdType::EndUserSipUri,
+            subscription_id_data: "sip:alice@example.com".into(),
+        });
+        aar.framed_ip_address = Some(vec![10, 0, 0, 2]);
+        aar.rx_request_type = Some(RxRequestType::InitialRequest);
+
+        let msg = aar.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::AA);
+        assert_eq!(msg.get_application_id(), ApplicationId::Rx);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Media-Component-Description/Media-Sub-Component/Flow-Number")
+                .unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(AaRequest::from_message(&msg).unwrap(), aar);
+
+        let mut aaa = AaAnswer::new(&aar, 2001, &pcrf);
+        aaa.access_network_charging_identifier
+            .push(AccessNetworkChargingIdentifier {
+                access_network_charging_identifier_value: vec![0, 0, 0, 1],
+                flows: vec![Flows {
+                    media_component_number: 1,
+                    flow_number: vec![1, 2],
+                    final_unit_action: None,
+                }],
+            });
+        aaa.an_gw_address
+            .push(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        aaa.ip_can_type = Some(IpCanType::TgppEps);
+        aaa.rat_type = Some(RatType::Eutran);
+        aaa.supported_features.push(SupportedFeatures {
+            vendor_id: TGPP_VENDOR_ID,
+            feature_list_id: 1,
+            feature_list: 0x1,
+        });
+
+        let msg = round_trip(aaa.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_flags(), flags::PROXYABLE);
+        assert_eq!(AaAnswer::from_message(&msg).unwrap(), aaa);
+    }
+
+    #[test]
+    fn test_aaa_rejection() {
+        let dict = dict();
+        let pcscf = NodeConfig::new("pcscf.example.com", "example.com");
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+        let aar = AaRequest::new("pcscf.example.com;1;1", &pcscf, "example.com");
+
+        let mut aaa = AaAnswer::new(&aar, 0, &pcrf);
+        aaa.result_code = None;
+        aaa.experimental_result = Some(ExperimentalResult {
+            vendor_id: TGPP_VENDOR_ID,
+            experimental_result_code: result_code::INVALID_SERVICE_INFORMATION,
+        });
+        aaa.acceptable_service_info = Some(AcceptableServiceInfo {
+            max_requested_bandwidth_dl: Some(32_000),
+            max_requested_bandwidth_ul: Some(32_000),
+            ..Default::default()
+        });
+
+        let msg = round_trip(aaa.to_message(1, 2, &dict).unwrap(), &dict);
+        assert!(msg.get::<u32, _>("Result-Code").is_err());
+        assert_eq!(AaAnswer::from_message(&msg).unwrap(), aaa);
+    }
+
+    #[test]
+    fn test_rar_str_asr_round_trip() {
+        let dict = dict();
+        let pcscf = NodeConfig::new("pcscf.example.com", "example.com");
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+        let session_id = "pcscf.example.com;1;1";
+
+        let mut rar = ReAuthRequest::new(
+            session_id,
+            &pcrf,
+            "example.com",
+            "pcscf.example.com",
+            SpecificAction::IndicationOfLossOfBearer,
+        );
+        rar.flows.push(Flows {
+            media_component_number: 1,
+            flow_number: vec![1],
+            final_unit_action: None,
+        });
+        rar.tgpp_ms_timezone = Some(vec![0x40, 0x00]);
+        let msg = round_trip(rar.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::ReAuth);
+        assert_eq!(ReAuthRequest::from_message(&msg).unwrap(), rar);
+        let raa = ReAuthAnswer::new(&rar, 2001, &pcscf);
+        let msg = round_trip(raa.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(ReAuthAnswer::from_message(&msg).unwrap(), raa);
+
+        let str_req = SessionTerminationRequest::new(
+            session_id,
+            &pcscf,
+            "example.com",
+            TerminationCause::DiameterLogout,
+        );
+        let msg = round_trip(str_req.to_message(5, 6, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::SessionTerminate);
+        assert_eq!(msg.get_application_id(), ApplicationId::Rx);
+        assert_eq!(
+            SessionTerminationRequest::from_message(&msg).unwrap(),
+            str_req
+        );
+        let sta = SessionTerminationAnswer::new(&str_req, 2001, &pcrf);
+        let msg = round_trip(sta.to_message(5, 6, &dict).unwrap(), &dict);
+        assert_eq!(SessionTerminationAnswer::from_message(&msg).unwrap(), sta);
+
+        let asr = AbortSessionRequest::new(
+            session_id,
+            &pcrf,
+            "example.com",
+            "pcscf.example.com",
+            AbortCause::BearerReleased,
+        );
+        let msg = round_trip(asr.to_message(7, 8, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::AbortSession);
+        assert_eq!(AbortSessionRequest::from_message(&msg).unwrap(), asr);
+        let asa = AbortSessionAnswer::new(&asr, 2001, &pcscf);
+        let msg = round_trip(asa.to_message(7, 8, &dict).unwrap(), &dict);
+        assert_eq!(AbortSessionAnswer::from_message(&msg).unwrap(), asa);
+    }
+
+    #[test]
+    fn test_dictionary_with_gx() {
+        let dict = Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            crate::applications::gx::DICT_XML,
+            DICT_XML,
+        ]));
+        let pcscf = NodeConfig::new("pcscf.example.com", "example.com");
+        let mut aar = AaRequest::new("pcscf.example.com;1;1", &pcscf, "example.com");
+        aar.media_component_description
+            .push(MediaComponentDescription {
+                media_component_number: 1,
+                flow_status: Some(FlowStatus::Disabled),
+                ..Default::default()
+            });
+
+        let msg = round_trip(aar.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(AaRequest::from_message(&msg).unwrap(), aar);
+    }
+}
@@ -0,0 +1,1727 @@
+//! S6a Application
+//!
+//! Typed messages of the S6a interface between the MME and the HSS, as
+//! defined in [3GPP TS 29.272](https://www.3gpp.org/DynaReport/29272.htm).
+//!
+//! The S6a AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary.
+//!
+//! ```
+//! use diameter::applications::s6a::{
+//!     self, AuthenticationInfo, AuthenticationInformationAnswer,
+//!     AuthenticationInformationRequest, EUtranVector, RequestedEutranAuthenticationInfo,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, s6a::DICT_XML]));
+//! let mme = NodeConfig::new("mme.example.com", "example.com");
+//! let hss = NodeConfig::new("hss.example.com", "example.com");
+//!
+//! let mut air = AuthenticationInformationRequest::new(
+//!     "mme.example.com;1;1",
+//!     &mme,
+//!     "example.com",
+//!     "001010123456789",
+//!     &[0x00, 0xf1, 0x10],
+//! );
+//! air.requested_eutran_authentication_info = Some(RequestedEutranAuthenticationInfo {
+//!     number_of_requested_vectors: Some(1),
+//!     immediate_response_preferred: Some(1),
+//!     re_synchronization_info: None,
+//! });
+//!
+//! let mut aia = AuthenticationInformationAnswer::new(&air, 2001, &hss);
+//! aia.authentication_info = Some(AuthenticationInfo {
+//!     e_utran_vector: vec![EUtranVector {
+//!         item_number: Some(1),
+//!         rand: vec![0x01; 16],
+//!         xres: vec![0x02; 8],
+//!         autn: vec![0x03; 16],
+//!         kasme: vec![0x04; 32],
+//!     }],
+//!     ..Default::default()
+//! });
+//!
+//! let msg = aia.to_message(1, 1, &dict).unwrap();
+//! assert_eq!(AuthenticationInformationAnswer::from_message(&msg).unwrap(), aia);
+//! ```
+
+use crate::applications::common::{
+    AuthSessionState, ExperimentalResult, ProxyInfo, SupportedFeatures,
+    VendorSpecificApplicationId, TGPP_VENDOR_ID,
+};
+use crate::applications::gx::{AllocationRetentionPriority, QosClassIdentifier};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::{DiameterMessage, GroupedAvp};
+use std::net::IpAddr;
+
+pub use crate::applications::gx::RatType;
+
+/// Dictionary of the S6a application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-s6a.xml");
+
+/// Auth-Application-Id of the S6a application.
+pub const APPLICATION_ID: u32 = 16777251;
+
+/// Result-Code and Experimental-Result-Code values defined in
+/// 3GPP TS 29.272 section 7.4.
+pub mod result_code {
+    // Transient Failures
+    pub const DIAMETER_AUTHENTICATION_DATA_UNAVAILABLE: u32 = 4181;
+    pub const DIAMETER_ERROR_CAMEL_SUBSCRIPTION_PRESENT: u32 = 4182;
+
+    // Permanent Failures
+    pub const DIAMETER_ERROR_USER_UNKNOWN: u32 = 5001;
+    pub const DIAMETER_ERROR_ROAMING_NOT_ALLOWED: u32 = 5004;
+    pub const DIAMETER_ERROR_UNKNOWN_EPS_SUBSCRIPTION: u32 = 5420;
+    pub const DIAMETER_ERROR_RAT_NOT_ALLOWED: u32 = 5421;
+    pub const DIAMETER_ERROR_EQUIPMENT_UNKNOWN: u32 = 5422;
+    pub const DIAMETER_ERROR_UNKOWN_SERVING_NODE: u32 = 5423;
+}
+
+/// Bits of the ULR-Flags AVP (1405).
+pub mod ulr_flags {
+    pub const SINGLE_REGISTRATION_INDICATION: u32 = 1 << 0;
+    pub const S6A_S6D_INDICATOR: u32 = 1 << 1;
+    pub const SKIP_SUBSCRIBER_DATA: u32 = 1 << 2;
+    pub const GPRS_SUBSCRIPTION_DATA_INDICATOR: u32 = 1 << 3;
+    pub const NODE_TYPE_INDICATOR: u32 = 1 << 4;
+    pub const INITIAL_ATTACH_INDICATOR: u32 = 1 << 5;
+    pub const PS_LCS_NOT_SUPPORTED_BY_UE: u32 = 1 << 6;
+    pub const SMS_ONLY_INDICATION: u32 = 1 << 7;
+}
+
+/// Bits of the ULA-Flags AVP (1406).
+pub mod ula_flags {
+    pub const SEPARATION_INDICATION: u32 = 1 << 0;
+    pub const MME_REGISTERED_FOR_SMS: u32 = 1 << 1;
+}
+
+enumerated! {
+    /// Values of the Alert-Reason AVP (1434).
+    pub enum AlertReason ("Alert-Reason") {
+        UePresent = 0,
+        UeMemoryAvailable = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the All-APN-Configurations-Included-Indicator AVP (1428).
+    pub enum AllApnConfigurationsIncludedIndicator ("All-APN-Configurations-Included-Indicator") {
+        AllApnConfigurationsIncluded = 0,
+        ModifiedAddedApnConfigurationsIncluded = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Cancellation-Type AVP (1420).
+    pub enum CancellationType ("Cancellation-Type") {
+        MmeUpdateProcedure = 0,
+        SgsnUpdateProcedure = 1,
+        SubscriptionWithdrawal = 2,
+        UpdateProcedureIwf = 3,
+        InitialAttachProcedure = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Error-Diagnostic AVP (1614).
+    pub enum ErrorDiagnostic ("Error-Diagnostic") {
+        GprsDataSubscribed = 0,
+        NoGprsDataSubscribed = 1,
+        OdbAllApn = 2,
+        OdbHplmnApn = 3,
+        OdbVplmnApn = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Homogeneous-Support-of-IMS-Voice-Over-PS-Sessions AVP (1493).
+    pub enum HomogeneousSupportOfImsVoiceOverPsSessions ("Homogeneous-Support-of-IMS-Voice-Over-PS-Sessions") {
+        NotSupported = 0,
+        Supported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the IMS-Voice-Over-PS-Sessions-Supported AVP (1492).
+    pub enum ImsVoiceOverPsSessionsSupported ("IMS-Voice-Over-PS-Sessions-Supported") {
+        NotSupported = 0,
+        Supported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the LIPA-Permission AVP (1618).
+    pub enum LipaPermission ("LIPA-Permission") {
+        LipaProhibited = 0,
+        LipaOnly = 1,
+        LipaConditional = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the Network-Access-Mode AVP (1417).
+    pub enum NetworkAccessMode ("Network-Access-Mode") {
+        PacketAndCircuit = 0,
+        Reserved = 1,
+        OnlyPacket = 2,
+    }
+}
+
+enumerated! {
+    /// Values of the PDN-GW-Allocation-Type AVP (1438).
+    pub enum PdnGwAllocationType ("PDN-GW-Allocation-Type") {
+        Static = 0,
+        Dynamic = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the PDN-Type AVP (1456).
+    pub enum PdnType ("PDN-Type") {
+        Ipv4 = 0,
+        Ipv6 = 1,
+        Ipv4v6 = 2,
+        Ipv4OrIpv6 = 3,
+        NonIp = 4,
+    }
+}
+
+enumerated! {
+    /// Values of the Roaming-Restricted-Due-To-Unsupported-Feature AVP (1457).
+    pub enum RoamingRestrictedDueToUnsupportedFeature ("Roaming-Restricted-Due-To-Unsupported-Feature") {
+        RoamingRestrictedDueToUnsupportedFeature = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the SIPTO-Permission AVP (1613).
+    pub enum SiptoPermission ("SIPTO-Permission") {
+        SiptoAboveRanAllowed = 0,
+        SiptoAboveRanNotallowed = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Subscriber-Status AVP (1424).
+    pub enum SubscriberStatus ("Subscriber-Status") {
+        ServiceGranted = 0,
+        OperatorDeterminedBarring = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the UE-SRVCC-Capability AVP (1615).
+    pub enum UeSrvccCapability ("UE-SRVCC-Capability") {
+        UeSrvccNotSupported = 0,
+        UeSrvccSupported = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the VPLMN-Dynamic-Address-Allowed AVP (1432).
+    pub enum VplmnDynamicAddressAllowed ("VPLMN-Dynamic-Address-Allowed") {
+        Notallowed = 0,
+        Allowed = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the VPLMN-LIPA-Allowed AVP (1617).
+    pub enum VplmnLipaAllowed ("VPLMN-LIPA-Allowed") {
+        LipaNotallowed = 0,
+        LipaAllowed = 1,
+    }
+}
+
+/// The Terminal-Information AVP (1401).
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct TerminalInformation {
+    #[avp(name = "IMEI")]
+    pub imei: Option<String>,
+    #[avp(name = "Software-Version")]
+    pub software_version: Option<String>,
+}
+
+/// The Requested-EUTRAN-Authentication-Info AVP (1408).
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct RequestedEutranAuthenticationInfo {
+    #[avp(name = "Number-Of-Requested-Vectors")]
+    pub number_of_requested_vectors: Option<u32>,
+    #[avp(name = "Immediate-Response-Preferred")]
+    pub immediate_response_preferred: Option<u32>,
+    #[avp(name = "Re-Synchronization-Info")]
+    pub re_synchronization_info: Option<Vec<u8>>,
+}
+
+/// The Requested-UTRAN-GERAN-Authentication-Info AVP (1409).
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct RequestedUtranGeranAuthenticationInfo {
+    #[avp(name = "Number-Of-Requested-Vectors")]
+    pub number_of_requested_vectors: Option<u32>,
+    #[avp(name = "Immediate-Response-Preferred")]
+    pub immediate_response_preferred: Option<u32>,
+    #[avp(name = "Re-Synchronization-Info")]
+    pub re_synchronization_info: Option<Vec<u8>>,
+}
+
+/// The E-UTRAN-Vector AVP (1414), an EPS authentication vector.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct EUtranVector {
+    #[avp(name = "Item-Number")]
+    pub item_number: Option<u32>,
+    #[avp(name = "RAND")]
+    pub rand: Vec<u8>,
+    #[avp(name = "XRES")]
+    pub xres: Vec<u8>,
+    #[avp(name = "AUTN")]
+    pub autn: Vec<u8>,
+    #[avp(name = "KASME")]
+    pub kasme: Vec<u8>,
+}
+
+/// The UTRAN-Vector AVP (1415), a UMTS authentication vector.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct UtranVector {
+    #[avp(name = "Item-Number")]
+    pub item_number: Option<u32>,
+    #[avp(name = "RAND")]
+    pub rand: Vec<u8>,
+    #[avp(name = "XRES")]
+    pub xres: Vec<u8>,
+    #[avp(name = "AUTN")]
+    pub autn: Vec<u8>,
+    #[avp(name = "Confidentiality-Key")]
+    pub confidentiality_key: Vec<u8>,
+    #[avp(name = "Integrity-Key")]
+    pub integrity_key: Vec<u8>,
+}
+
+/// The GERAN-Vector AVP (1416), a GSM authentication triplet.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct GeranVector {
+    #[avp(name = "Item-Number")]
+    pub item_number: Option<u32>,
+    #[avp(name = "RAND")]
+    pub rand: Vec<u8>,
+    #[avp(name = "SRES")]
+    pub sres: Vec<u8>,
+    #[avp(name = "Kc")]
+    pub kc: Vec<u8>,
+}
+
+/// The Authentication-Info AVP (1413).
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct AuthenticationInfo {
+    #[avp(name = "E-UTRAN-Vector", grouped)]
+    pub e_utran_vector: Vec<EUtranVector>,
+    #[avp(name = "UTRAN-Vector", grouped)]
+    pub utran_vector: Vec<UtranVector>,
+    #[avp(name = "GERAN-Vector", grouped)]
+    pub geran_vector: Vec<GeranVector>,
+}
+
+/// The AMBR AVP (1435), the aggregate maximum bit rates.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct Ambr {
+    #[avp(name = "Max-Requested-Bandwidth-UL")]
+    pub max_requested_bandwidth_ul: u32,
+    #[avp(name = "Max-Requested-Bandwidth-DL")]
+    pub max_requested_bandwidth_dl: u32,
+}
+
+/// The EPS-Subscribed-QoS-Profile AVP (1431).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct EpsSubscribedQosProfile {
+    #[avp(name = "QoS-Class-Identifier")]
+    pub qos_class_identifier: QosClassIdentifier,
+    #[avp(name = "Allocation-Retention-Priority", grouped)]
+    pub allocation_retention_priority: AllocationRetentionPriority,
+}
+
+/// The APN-Configuration AVP (1430), the subscription of one APN.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ApnConfiguration {
+    #[avp(name = "Context-Identifier")]
+    pub context_identifier: u32,
+    #[avp(name = "Served-Party-IP-Address")]
+    pub served_party_ip_address: Vec<IpAddr>,
+    #[avp(name = "PDN-Type")]
+    pub pdn_type: PdnType,
+    #[avp(name = "Service-Selection")]
+    pub service_selection: String,
+    #[avp(name = "EPS-Subscribed-QoS-Profile", grouped)]
+    pub eps_subscribed_qos_profile: Option<EpsSubscribedQosProfile>,
+    #[avp(name = "VPLMN-Dynamic-Address-Allowed")]
+    pub vplmn_dynamic_address_allowed: Option<VplmnDynamicAddressAllowed>,
+    #[avp(name = "PDN-GW-Allocation-Type")]
+    pub pdn_gw_allocation_type: Option<PdnGwAllocationType>,
+    #[avp(name = "TGPP-Charging-Characteristics")]
+    pub tgpp_charging_characteristics: Option<String>,
+    #[avp(name = "AMBR", grouped)]
+    pub ambr: Option<Ambr>,
+    #[avp(name = "APN-OI-Replacement")]
+    pub apn_oi_replacement: Option<String>,
+    #[avp(name = "SIPTO-Permission")]
+    pub sipto_permission: Option<SiptoPermission>,
+    #[avp(name = "LIPA-Permission")]
+    pub lipa_permission: Option<LipaPermission>,
+}
+
+/// The APN-Configuration-Profile AVP (1429).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct ApnConfigurationProfile {
+    #[avp(name = "Context-Identifier")]
+    pub context_identifier: u32,
+    #[avp(name = "All-APN-Configurations-Included-Indicator")]
+    pub all_apn_configurations_included_indicator: AllApnConfigurationsIncludedIndicator,
+    #[avp(name = "APN-Configuration", grouped)]
+    pub apn_configuration: Vec<ApnConfiguration>,
+}
+
+/// The Subscription-Data AVP (1400), the EPS subscription of a user.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct SubscriptionData {
+    #[avp(name = "Subscriber-Status")]
+    pub subscriber_status: Option<SubscriberStatus>,
+    #[avp(name = "MSISDN")]
+    pub msisdn: Option<Vec<u8>>,
+    #[avp(name = "A-MSISDN")]
+    pub a_msisdn: Option<Vec<u8>>,
+    #[avp(name = "STN-SR")]
+    pub stn_sr: Option<Vec<u8>>,
+    #[avp(name = "Network-Access-Mode")]
+    pub network_access_mode: Option<NetworkAccessMode>,
+    #[avp(name = "Operator-Determined-Barring")]
+    pub operator_determined_barring: Option<u32>,
+    #[avp(name = "HPLMN-ODB")]
+    pub hplmn_odb: Option<u32>,
+    #[avp(name = "Regional-Subscription-Zone-Code")]
+    pub regional_subscription_zone_code: Vec<Vec<u8>>,
+    #[avp(name = "Access-Restriction-Data")]
+    pub access_restriction_data: Option<u32>,
+    #[avp(name = "APN-OI-Replacement")]
+    pub apn_oi_replacement: Option<String>,
+    #[avp(name = "TGPP-Charging-Characteristics")]
+    pub tgpp_charging_characteristics: Option<String>,
+    #[avp(name = "AMBR", grouped)]
+    pub ambr: Option<Ambr>,
+    #[avp(name = "APN-Configuration-Profile", grouped)]
+    pub apn_configuration_profile: Option<ApnConfigurationProfile>,
+    #[avp(name = "RAT-Frequency-Selection-Priority-ID")]
+    pub rat_frequency_selection_priority_id: Option<u32>,
+    #[avp(name = "Roaming-Restricted-Due-To-Unsupported-Feature")]
+    pub roaming_restricted_due_to_unsupported_feature:
+        Option<RoamingRestrictedDueToUnsupportedFeature>,
+    #[avp(name = "Subscribed-Periodic-RAU-TAU-Timer")]
+    pub subscribed_periodic_rau_tau_timer: Option<u32>,
+    #[avp(name = "MPS-Priority")]
+    pub mps_priority: Option<u32>,
+    #[avp(name = "VPLMN-LIPA-Allowed")]
+    pub vplmn_lipa_allowed: Option<VplmnLipaAllowed>,
+    #[avp(name = "Subscription-Data-Flags")]
+    pub subscription_data_flags: Option<u32>,
+}
+
+/// S6a Update-Location-Request (ULR), 3GPP TS 29.272 section 7.2.3, sent by the
+/// MME to register the UE.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UpdateLocation, application = S6a, request, proxyable)]
+pub struct UpdateLocationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Terminal-Information", grouped)]
+    pub terminal_information: Option<TerminalInformation>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: RatType,
+    #[avp(name = "ULR-Flags")]
+    pub ulr_flags: u32,
+    #[avp(name = "UE-SRVCC-Capability")]
+    pub ue_srvcc_capability: Option<UeSrvccCapability>,
+    #[avp(name = "Visited-PLMN-Id")]
+    pub visited_plmn_id: Vec<u8>,
+    #[avp(name = "SGSN-Number")]
+    pub sgsn_number: Option<Vec<u8>>,
+    #[avp(name = "Homogeneous-Support-of-IMS-Voice-Over-PS-Sessions")]
+    pub homogeneous_support_of_ims_voice_over_ps_sessions:
+        Option<HomogeneousSupportOfImsVoiceOverPsSessions>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Update-Location-Answer (ULA), 3GPP TS 29.272 section 7.2.4.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UpdateLocation, application = S6a, proxyable)]
+pub struct UpdateLocationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Error-Diagnostic")]
+    pub error_diagnostic: Option<ErrorDiagnostic>,
+    #[avp(name = "ULA-Flags")]
+    pub ula_flags: Option<u32>,
+    #[avp(name = "Subscription-Data", grouped)]
+    pub subscription_data: Option<SubscriptionData>,
+    #[avp(name = "Reset-ID")]
+    pub reset_id: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Authentication-Information-Request (AIR), 3GPP TS 29.272 section 7.2.5,
+/// sent by the MME to fetch authentication vectors.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = AuthenticationInformation,
+    application = S6a,
+    request,
+    proxyable,
+)]
+pub struct AuthenticationInformationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Requested-EUTRAN-Authentication-Info", grouped)]
+    pub requested_eutran_authentication_info: Option<RequestedEutranAuthenticationInfo>,
+    #[avp(name = "Requested-UTRAN-GERAN-Authentication-Info", grouped)]
+    pub requested_utran_geran_authentication_info: Option<RequestedUtranGeranAuthenticationInfo>,
+    #[avp(name = "Visited-PLMN-Id")]
+    pub visited_plmn_id: Vec<u8>,
+    #[avp(name = "AIR-Flags")]
+    pub air_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Authentication-Information-Answer (AIA), 3GPP TS 29.272 section 7.2.6.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AuthenticationInformation, application = S6a, proxyable)]
+pub struct AuthenticationInformationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Error-Diagnostic")]
+    pub error_diagnostic: Option<ErrorDiagnostic>,
+    #[avp(name = "Authentication-Info", grouped)]
+    pub authentication_info: Option<AuthenticationInfo>,
+    #[avp(name = "UE-Usage-Type")]
+    pub ue_usage_type: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Cancel-Location-Request (CLR), 3GPP TS 29.272 section 7.2.7, sent by the
+/// HSS to remove a subscriber from the MME.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = CancelLocation, application = S6a, request, proxyable)]
+pub struct CancelLocationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Cancellation-Type")]
+    pub cancellation_type: CancellationType,
+    #[avp(name = "CLR-Flags")]
+    pub clr_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Cancel-Location-Answer (CLA), 3GPP TS 29.272 section 7.2.8.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = CancelLocation, application = S6a, proxyable)]
+pub struct CancelLocationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Insert-Subscriber-Data-Request (IDR), 3GPP TS 29.272 section 7.2.9, sent
+/// by the HSS to update the subscription data in the MME.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = InsertSubscriberData, application = S6a, request, proxyable)]
+pub struct InsertSubscriberDataRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Subscription-Data", grouped)]
+    pub subscription_data: SubscriptionData,
+    #[avp(name = "IDR-Flags")]
+    pub idr_flags: Option<u32>,
+    #[avp(name = "Reset-ID")]
+    pub reset_id: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Insert-Subscriber-Data-Answer (IDA), 3GPP TS 29.272 section 7.2.10.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = InsertSubscriberData, application = S6a, proxyable)]
+pub struct InsertSubscriberDataAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "IMS-Voice-Over-PS-Sessions-Supported")]
+    pub ims_voice_over_ps_sessions_supported: Option<ImsVoiceOverPsSessionsSupported>,
+    #[avp(name = "Last-UE-Activity-Time")]
+    pub last_ue_activity_time: Option<DateTime<Utc>>,
+    #[avp(name = "RAT-Type")]
+    pub rat_type: Option<RatType>,
+    #[avp(name = "IDA-Flags")]
+    pub ida_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Delete-Subscriber-Data-Request (DSR), 3GPP TS 29.272 section 7.2.11,
+/// sent by the HSS to remove subscription data from the MME.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = DeleteSubscriberData, application = S6a, request, proxyable)]
+pub struct DeleteSubscriberDataRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "DSR-Flags")]
+    pub dsr_flags: u32,
+    #[avp(name = "Context-Identifier")]
+    pub context_identifier: Vec<u32>,
+    #[avp(name = "Trace-Reference")]
+    pub trace_reference: Option<Vec<u8>>,
+    #[avp(name = "TS-Code")]
+    pub ts_code: Vec<Vec<u8>>,
+    #[avp(name = "SS-Code")]
+    pub ss_code: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Delete-Subscriber-Data-Answer (DSA), 3GPP TS 29.272 section 7.2.12.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = DeleteSubscriberData, application = S6a, proxyable)]
+pub struct DeleteSubscriberDataAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "DSA-Flags")]
+    pub dsa_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Purge-UE-Request (PUR), 3GPP TS 29.272 section 7.2.13, sent by the MME
+/// when it deletes the subscription data of a UE.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PurgeUe, application = S6a, request, proxyable)]
+pub struct PurgeUeRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "PUR-Flags")]
+    pub pur_flags: Option<u32>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Purge-UE-Answer (PUA), 3GPP TS 29.272 section 7.2.14.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PurgeUe, application = S6a, proxyable)]
+pub struct PurgeUeAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "PUA-Flags")]
+    pub pua_flags: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Reset-Request (RSR), 3GPP TS 29.272 section 7.2.15, sent by the HSS
+/// after a restart.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Reset, application = S6a, request, proxyable)]
+pub struct ResetRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Id")]
+    pub user_id: Vec<String>,
+    #[avp(name = "Reset-ID")]
+    pub reset_id: Vec<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Reset-Answer (RSA), 3GPP TS 29.272 section 7.2.16.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Reset, application = S6a, proxyable)]
+pub struct ResetAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Notify-Request (NOR), 3GPP TS 29.272 section 7.2.17, sent by the MME to
+/// notify the HSS of an event.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Notify, application = S6a, request, proxyable)]
+pub struct NotifyRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Terminal-Information", grouped)]
+    pub terminal_information: Option<TerminalInformation>,
+    #[avp(name = "Context-Identifier")]
+    pub context_identifier: Option<u32>,
+    #[avp(name = "Service-Selection")]
+    pub service_selection: Option<String>,
+    #[avp(name = "Alert-Reason")]
+    pub alert_reason: Option<AlertReason>,
+    #[avp(name = "UE-SRVCC-Capability")]
+    pub ue_srvcc_capability: Option<UeSrvccCapability>,
+    #[avp(name = "NOR-Flags")]
+    pub nor_flags: Option<u32>,
+    #[avp(name = "Homogeneous-Support-of-IMS-Voice-Over-PS-Sessions")]
+    pub homogeneous_support_of_ims_voice_over_ps_sessions:
+        Option<HomogeneousSupportOfImsVoiceOverPsSessions>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// S6a Notify-Answer (NOA), 3GPP TS 29.272 section 7.2.18.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = Notify, application = S6a, proxyable)]
+pub struct NotifyAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+impl UpdateLocationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+        rat_type: RatType,
+        ulr_flags: u32,
+        visited_plmn_id: &[u8],
+    ) -> UpdateLocationRequest {
+        UpdateLocationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            terminal_information: None,
+            rat_type,
+            ulr_flags,
+            ue_srvcc_capability: None,
+            visited_plmn_id: visited_plmn_id.to_vec(),
+            sgsn_number: None,
+            homogeneous_support_of_ims_voice_over_ps_sessions: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl UpdateLocationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &UpdateLocationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> UpdateLocationAnswer {
+        UpdateLocationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            error_diagnostic: None,
+            ula_flags: None,
+            subscription_data: None,
+            reset_id: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AuthenticationInformationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+        visited_plmn_id: &[u8],
+    ) -> AuthenticationInformationRequest {
+        AuthenticationInformationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            requested_eutran_authentication_info: None,
+            requested_utran_geran_authentication_info: None,
+            visited_plmn_id: visited_plmn_id.to_vec(),
+            air_flags: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AuthenticationInformationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &AuthenticationInformationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> AuthenticationInformationAnswer {
+        AuthenticationInformationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            error_diagnostic: None,
+            authentication_info: None,
+            ue_usage_type: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl CancelLocationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_name: &str,
+        cancellation_type: CancellationType,
+    ) -> CancelLocationRequest {
+        CancelLocationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            cancellation_type,
+            clr_flags: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl CancelLocationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &CancelLocationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> CancelLocationAnswer {
+        CancelLocationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl InsertSubscriberDataRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_name: &str,
+        subscription_data: SubscriptionData,
+    ) -> InsertSubscriberDataRequest {
+        InsertSubscriberDataRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            subscription_data,
+            idr_flags: None,
+            reset_id: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl InsertSubscriberDataAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &InsertSubscriberDataRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> InsertSubscriberDataAnswer {
+        InsertSubscriberDataAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            ims_voice_over_ps_sessions_supported: None,
+            last_ue_activity_time: None,
+            rat_type: None,
+            ida_flags: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl DeleteSubscriberDataRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_name: &str,
+        dsr_flags: u32,
+    ) -> DeleteSubscriberDataRequest {
+        DeleteSubscriberDataRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            dsr_flags,
+            context_identifier: Vec::new(),
+            trace_reference: None,
+            ts_code: Vec::new(),
+            ss_code: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl DeleteSubscriberDataAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &DeleteSubscriberDataRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> DeleteSubscriberDataAnswer {
+        DeleteSubscriberDataAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            dsa_flags: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PurgeUeRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+    ) -> PurgeUeRequest {
+        PurgeUeRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            pur_flags: None,
+            supported_features: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PurgeUeAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &PurgeUeRequest, result_code: u32, node: &NodeConfig) -> PurgeUeAnswer {
+        PurgeUeAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            pua_flags: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ResetRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+    ) -> ResetRequest {
+        ResetRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            supported_features: Vec::new(),
+            user_id: Vec::new(),
+            reset_id: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ResetAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &ResetRequest, result_code: u32, node: &NodeConfig) -> ResetAnswer {
+        ResetAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl NotifyRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_name: &str,
+    ) -> NotifyRequest {
+        NotifyRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            user_name: user_name.into(),
+            supported_features: Vec::new(),
+            terminal_information: None,
+            context_identifier: None,
+            service_selection: None,
+            alert_reason: None,
+            ue_srvcc_capability: None,
+            nor_flags: None,
+            homogeneous_support_of_ims_voice_over_ps_sessions: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl NotifyAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &NotifyRequest, result_code: u32, node: &NodeConfig) -> NotifyAnswer {
+        NotifyAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::gx::{PreemptionCapability, PreemptionVulnerability};
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use chrono::TimeZone;
+    use std::io::Cursor;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    fn subscription_data() -> SubscriptionData {
+        SubscriptionData {
+            subscriber_status: Some(SubscriberStatus::ServiceGranted),
+            msisdn: Some(vec![0x19, 0x10, 0x32, 0x54]),
+            network_access_mode: Some(NetworkAccessMode::OnlyPacket),
+            access_restriction_data: Some(0),
+            ambr: Some(Ambr {
+                max_requested_bandwidth_ul: 50_000_000,
+                max_requested_bandwidth_dl: 100_000_000,
+            }),
+            apn_configuration_profile: Some(ApnConfigurationProfile {
+                context_identifier: 1,
+                all_apn_configurations_included_indicator:
+                    AllApnConfigurationsIncludedIndicator::AllApnConfigurationsIncluded,
+                apn_configuration: vec![ApnConfiguration {
+                    context_identifier: 1,
+                    served_party_ip_address: vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))],
+                    pdn_type: PdnType::Ipv4,
+                    service_selection: "internet".into(),
+                    eps_subscribed_qos_profile: Some(EpsSubscribedQosProfile {
+                        qos_class_identifier: QosClassIdentifier::Qci9,
+                        allocation_retention_priority: AllocationRetentionPriority {
+                            priority_level: 8,
+                            pre_emption_capability: Some(PreemptionCapability::Disabled),
+                            pre_emption_vulnerability: Some(PreemptionVulnerability::Enabled),
+                        },
+                    }),
+                    vplmn_dynamic_address_allowed: None,
+                    pdn_gw_allocation_type: Some(PdnGwAllocationType::Dynamic),
+                    tgpp_charging_characteristics: None,
+                    ambr: None,
+                    apn_oi_replacement: None,
+                    sipto_permission: None,
+                    lipa_permission: None,
+                }],
+            }),
+            subscribed_periodic_rau_tau_timer: Some(3240),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ulr_ula_round_trip() {
+        let dict = dict();
+        let mme = NodeConfig::new("mme.example.com", "example.com");
+        let hss = NodeConfig::new("hss.example.com", "example.com");
+
+        let mut ulr = UpdateLocationRequest::new(
+            "mme.example.com;1;1",
+            &mme,
+            "example.com",
+            "001010123456789",
+            RatType::Eutran,
+            ulr_flags::S6A_S6D_INDICATOR | ulr_flags::INITIAL_ATTACH_INDICATOR,
+            &[0x00, 0xf1, 0x10],
+        );
+        ulr.terminal_information = Some(TerminalInformation {
+            imei: Some("35254108".into()),
+            software_version: Some("01".into()),
+        });
+        ulr.ue_srvcc_capability = Some(UeSrvccCapability::UeSrvccSupported);
+
+        let msg = ulr.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::UpdateLocation);
+        assert_eq!(msg.get_application_id(), ApplicationId::S6a);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Vendor-Specific-Application-Id/Auth-Application-Id")
+                .unwrap(),
+            vec![APPLICATION_ID]
+        );
+        assert_eq!(msg.get::<u32, _>("ULR-Flags").unwrap(), 0x22);
+        assert_eq!(UpdateLocationRequest::from_message(&msg).unwrap(), ulr);
+
+        let mut ula = UpdateLocationAnswer::new(&ulr, 2001, &hss);
+        ula.ula_flags = Some(ula_flags::SEPARATION_INDICATION);
+        ula.subscription_data = Some(subscription_data());
+
+        let msg = round_trip(ula.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_flags(), flags::PROXYABLE);
+        assert_eq!(
+            msg.query_values::<String>(
+                "Subscription-Data/APN-Configuration-Profile/APN-Configuration[*]/Service-Selection"
+            )
+            .unwrap(),
+            vec!["internet".to_string()]
+        );
+        assert_eq!(UpdateLocationAnswer::from_message(&msg).unwrap(), ula);
+    }
+
+    #[test]
+    fn test_air_aia_round_trip() {
+        let dict = dict();
+        let mme = NodeConfig::new("mme.example.com", "example.com");
+        let hss = NodeConfig::new("hss.example.com", "example.com");
+
+        let mut air = AuthenticationInformationRequest::new(
+            "mme.example.com;1;2",
+            &mme,
+            "example.com",
+            "001010123456789",
+            &[0x00, 0xf1, 0x10],
+        );
+        air.requested_eutran_authentication_info = Some(RequestedEutranAuthenticationInfo {
+            number_of_requested_vectors: Some(2),
+            immediate_response_preferred: Some(1),
+            re_synchronization_info: None,
+        });
+
+        let msg = round_trip(air.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.get_command_code(),
+            CommandCode::AuthenticationInformation
+        );
+        assert_eq!(
+            AuthenticationInformationRequest::from_message(&msg).unwrap(),
+            air
+        );
+
+        let mut aia = AuthenticationInformationAnswer::new(&air, 2001, &hss);
+        aia.authentication_info = Some(AuthenticationInfo {
+            e_utran_vector: (1..=2)
+                .map(|i| EUtranVector {
+                    item_number: Some(i),
+                    rand: vec![i as u8; 16],
+                    xres: vec![0x02; 8],
+                    autn: vec![0x03; 16],
+                    kasme: vec![0x04; 32],
+                })
+                .collect(),
+            ..Default::default()
+        });
+
+        let msg = round_trip(aia.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Authentication-Info/E-UTRAN-Vector[*]/Item-Number")
+                .unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            AuthenticationInformationAnswer::from_message(&msg).unwrap(),
+            aia
+        );
+
+        // Unknown user, reported in an Experimental-Result
+        let mut aia = AuthenticationInformationAnswer::new(&air, 2001, &hss);
+        aia.result_code = None;
+        aia.experimental_result = Some(ExperimentalResult {
+            vendor_id: TGPP_VENDOR_ID,
+            experimental_result_code: result_code::DIAMETER_ERROR_USER_UNKNOWN,
+        });
+        let msg = round_trip(aia.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<u32>("Experimental-Result/Experimental-Result-Code")
+                .unwrap(),
+            vec![5001]
+        );
+        assert_eq!(
+            AuthenticationInformationAnswer::from_message(&msg).unwrap(),
+            aia
+        );
+    }
+
+    #[test]
+    fn test_hss_initiated_round_trip() {
+        let dict = dict();
+        let mme = NodeConfig::new("mme.example.com", "example.com");
+        let hss = NodeConfig::new("hss.example.com", "example.com");
+
+        let clr = CancelLocationRequest::new(
+            "hss.example.com;1;1",
+            &hss,
+            "example.com",
+            "mme.example.com",
+            "001010123456789",
+            CancellationType::SubscriptionWithdrawal,
+        );
+        let msg = round_trip(clr.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(CancelLocationRequest::from_message(&msg).unwrap(), clr);
+        let cla = CancelLocationAnswer::new(&clr, 2001, &mme);
+        let msg = round_trip(cla.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(CancelLocationAnswer::from_message(&msg).unwrap(), cla);
+
+        let idr = InsertSubscriberDataRequest::new(
+            "hss.example.com;1;2",
+            &hss,
+            "example.com",
+            "mme.example.com",
+            "001010123456789",
+            subscription_data(),
+        );
+        let msg = round_trip(idr.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(
+            InsertSubscriberDataRequest::from_message(&msg).unwrap(),
+            idr
+        );
+        let mut ida = InsertSubscriberDataAnswer::new(&idr, 2001, &mme);
+        ida.ims_voice_over_ps_sessions_supported = Some(ImsVoiceOverPsSessionsSupported::Supported);
+        ida.last_ue_activity_time = Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap());
+        ida.rat_type = Some(RatType::Eutran);
+        let msg = round_trip(ida.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(InsertSubscriberDataAnswer::from_message(&msg).unwrap(), ida);
+
+        let mut dsr = DeleteSubscriberDataRequest::new(
+            "hss.example.com;1;3",
+            &hss,
+            "example.com",
+            "mme.example.com",
+            "001010123456789",
+            1 << 1,
+        );
+        dsr.context_identifier = vec![1, 2];
+        let msg = round_trip(dsr.to_message(3, 3, &dict).unwrap(), &dict);
+        assert_eq!(
+            DeleteSubscriberDataRequest::from_message(&msg).unwrap(),
+            dsr
+        );
+
+        let mut rsr = ResetRequest::new(
+            "hss.example.com;1;4",
+            &hss,
+            "example.com",
+            "mme.example.com",
+        );
+        rsr.user_id.push("00101".into());
+        let msg = round_trip(rsr.to_message(4, 4, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::Reset);
+        assert_eq!(ResetRequest::from_message(&msg).unwrap(), rsr);
+        let rsa = ResetAnswer::new(&rsr, 2001, &mme);
+        let msg = round_trip(rsa.to_message(4, 4, &dict).unwrap(), &dict);
+        assert_eq!(ResetAnswer::from_message(&msg).unwrap(), rsa);
+    }
+
+    #[test]
+    fn test_pur_nor_round_trip() {
+        let dict = dict();
+        let mme = NodeConfig::new("mme.example.com", "example.com");
+        let hss = NodeConfig::new("hss.example.com", "example.com");
+
+        let pur = PurgeUeRequest::new(
+            "mme.example.com;1;3",
+            &mme,
+            "example.com",
+            "001010123456789",
+        );
+        let msg = round_trip(pur.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(PurgeUeRequest::from_message(&msg).unwrap(), pur);
+        let mut pua = PurgeUeAnswer::new(&pur, 2001, &hss);
+        pua.pua_flags = Some(1);
+        let msg = round_trip(pua.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(PurgeUeAnswer::from_message(&msg).unwrap(), pua);
+
+        let mut nor = NotifyRequest::new(
+            "mme.example.com;1;4",
+            &mme,
+            "example.com",
+            "001010123456789",
+        );
+        nor.destination_host = Some("hss.example.com".into());
+        nor.alert_reason = Some(AlertReason::UePresent);
+        nor.context_identifier = Some(1);
+        nor.service_selection = Some("internet".into());
+        let msg = round_trip(nor.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::Notify);
+        assert_eq!(NotifyRequest::from_message(&msg).unwrap(), nor);
+        let noa = NotifyAnswer::new(&nor, 2001, &hss);
+        let msg = round_trip(noa.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(NotifyAnswer::from_message(&msg).unwrap(), noa);
+    }
+}
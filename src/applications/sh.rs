@@ -0,0 +1,916 @@
+//! Sh Application
+//!
+//! Typed messages of the Sh interface between application servers and the
+//! HSS, as defined in
+//! [3GPP TS 29.329](https://www.3gpp.org/DynaReport/29329.htm).
+//!
+//! The Sh AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary. The User-Data AVP (702) carries the Sh-Data XML
+//! document (3GPP TS 29.328 annex D) and is named `Sh-User-Data` in the
+//! dictionary, as the Cx User-Data AVP (606) already uses `User-Data`.
+//!
+//! ```
+//! use diameter::applications::sh::{
+//!     self, DataReference, UserDataAnswer, UserDataRequest, UserIdentity,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, sh::DICT_XML]));
+//! let as_node = NodeConfig::new("as.ims.example.com", "ims.example.com");
+//! let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+//!
+//! let udr = UserDataRequest::new(
+//!     "as.ims.example.com;1;1",
+//!     &as_node,
+//!     "ims.example.com",
+//!     UserIdentity {
+//!         public_identity: Some("sip:alice@ims.example.com".into()),
+//!         msisdn: None,
+//!     },
+//!     DataReference::RepositoryData,
+//! );
+//!
+//! let mut uda = UserDataAnswer::new(&udr, 2001, &hss);
+//! uda.user_data = Some(b"<Sh-Data>...</Sh-Data>".to_vec());
+//!
+//! let msg = uda.to_message(1, 1, &dict).unwrap();
+//! let uda = UserDataAnswer::from_message(&msg).unwrap();
+//! let xml = String::from_utf8(uda.user_data.unwrap()).unwrap();
+//! assert_eq!(xml, "<Sh-Data>...</Sh-Data>");
+//! ```
+
+use crate::applications::common::{
+    AuthSessionState, ExperimentalResult, ProxyInfo, SupportedFeatures,
+    VendorSpecificApplicationId, TGPP_VENDOR_ID,
+};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::{DiameterMessage, GroupedAvp};
+
+pub use crate::applications::cx::SessionPriority;
+
+/// Dictionary of the Sh application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-sh.xml");
+
+/// Auth-Application-Id of the Sh application.
+pub const APPLICATION_ID: u32 = 16777217;
+
+/// Experimental-Result-Code values defined in 3GPP TS 29.329 section 6.2.
+pub mod result_code {
+    // Transient Failures
+    pub const DIAMETER_USER_DATA_NOT_AVAILABLE: u32 = 4100;
+    pub const DIAMETER_PRIOR_UPDATE_IN_PROGRESS: u32 = 4101;
+
+    // Permanent Failures
+    pub const DIAMETER_ERROR_USER_UNKNOWN: u32 = 5001;
+    pub const DIAMETER_ERROR_IDENTITIES_DONT_MATCH: u32 = 5002;
+    pub const DIAMETER_ERROR_FEATURE_UNSUPPORTED: u32 = 5011;
+    pub const DIAMETER_ERROR_USER_DATA_NOT_RECOGNIZED: u32 = 5100;
+    pub const DIAMETER_ERROR_OPERATION_NOT_ALLOWED: u32 = 5101;
+    pub const DIAMETER_ERROR_USER_DATA_CANNOT_BE_READ: u32 = 5102;
+    pub const DIAMETER_ERROR_USER_DATA_CANNOT_BE_MODIFIED: u32 = 5103;
+    pub const DIAMETER_ERROR_USER_DATA_CANNOT_BE_NOTIFIED: u32 = 5104;
+    pub const DIAMETER_ERROR_TRANSPARENT_DATA_OUT_OF_SYNC: u32 = 5105;
+    pub const DIAMETER_ERROR_SUBS_DATA_ABSENT: u32 = 5106;
+    pub const DIAMETER_ERROR_NO_SUBSCRIPTION_TO_DATA: u32 = 5107;
+    pub const DIAMETER_ERROR_DSAI_NOT_AVAILABLE: u32 = 5108;
+}
+
+enumerated! {
+    /// Values of the Current-Location AVP (707).
+    pub enum CurrentLocation ("Current-Location") {
+        DoNotNeedInitiateActiveLocationRetrieval = 0,
+        InitiateActiveLocationRetrieval = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Data-Reference AVP (703), the kind of data requested.
+    pub enum DataReference ("Data-Reference") {
+        RepositoryData = 0,
+        ImsPublicIdentity = 10,
+        ImsUserState = 11,
+        ScscfName = 12,
+        InitialFilterCriteria = 13,
+        LocationInformation = 14,
+        UserState = 15,
+        ChargingInformation = 16,
+        Msisdn = 17,
+        PsiActivation = 18,
+        Dsai = 19,
+        ServiceLevelTraceInfo = 21,
+        IpAddressSecureBindingInformation = 22,
+        ServicePriorityLevel = 23,
+        SmsRegistrationInfo = 24,
+        UeReachabilityForIp = 25,
+        TadsInformation = 26,
+        StnSr = 27,
+        UeSrvccCapability = 28,
+        ExtendedPriority = 29,
+        Csrn = 30,
+        ReferenceLocationInformation = 31,
+        Imsi = 32,
+        ImsPrivateUserIdentity = 33,
+        Imeisv = 34,
+        Ue5gSrvccCapability = 35,
+    }
+}
+
+enumerated! {
+    /// Values of the Identity-Set AVP (708).
+    pub enum IdentitySet ("Identity-Set") {
+        AllIdentities = 0,
+        RegisteredIdentities = 1,
+        ImplicitIdentities = 2,
+        AliasIdentities = 3,
+    }
+}
+
+enumerated! {
+    /// Values of the One-Time-Notification AVP (712).
+    pub enum OneTimeNotification ("One-Time-Notification") {
+        OneTimeNotificationRequested = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the Requested-Domain AVP (706).
+    pub enum RequestedDomain ("Requested-Domain") {
+        CsDomain = 0,
+        PsDomain = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Send-Data-Indication AVP (710).
+    pub enum SendDataIndication ("Send-Data-Indication") {
+        UserDataNotRequested = 0,
+        UserDataRequested = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the Serving-Node-Indication AVP (714).
+    pub enum ServingNodeIndication ("Serving-Node-Indication") {
+        OnlyServingNodesRequired = 0,
+    }
+}
+
+enumerated! {
+    /// Values of the Subs-Req-Type AVP (705).
+    pub enum SubsReqType ("Subs-Req-Type") {
+        Subscribe = 0,
+        Unsubscribe = 1,
+    }
+}
+
+/// The User-Identity AVP (700), the public identity or MSISDN of a user.
+#[derive(Debug, Clone, Default, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct UserIdentity {
+    #[avp(name = "Public-Identity")]
+    pub public_identity: Option<String>,
+    #[avp(name = "MSISDN")]
+    pub msisdn: Option<Vec<u8>>,
+}
+
+/// The Repository-Data-ID AVP (715).
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct RepositoryDataId {
+    #[avp(name = "Service-Indication")]
+    pub service_indication: Vec<u8>,
+    #[avp(name = "Sequence-Number")]
+    pub sequence_number: u32,
+}
+
+/// Sh User-Data-Request (UDR), 3GPP TS 29.329 section 6.1.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UserData, application = Sh, request, proxyable)]
+pub struct UserDataRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Identity", grouped)]
+    pub user_identity: UserIdentity,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Option<String>,
+    #[avp(name = "Service-Indication")]
+    pub service_indication: Vec<Vec<u8>>,
+    #[avp(name = "Data-Reference")]
+    pub data_reference: Vec<DataReference>,
+    #[avp(name = "Identity-Set")]
+    pub identity_set: Vec<IdentitySet>,
+    #[avp(name = "Requested-Domain")]
+    pub requested_domain: Option<RequestedDomain>,
+    #[avp(name = "Current-Location")]
+    pub current_location: Option<CurrentLocation>,
+    #[avp(name = "DSAI-Tag")]
+    pub dsai_tag: Vec<Vec<u8>>,
+    #[avp(name = "Session-Priority")]
+    pub session_priority: Option<SessionPriority>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Requested-Nodes")]
+    pub requested_nodes: Option<u32>,
+    #[avp(name = "Serving-Node-Indication")]
+    pub serving_node_indication: Option<ServingNodeIndication>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh User-Data-Answer (UDA), 3GPP TS 29.329 section 6.1.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = UserData, application = Sh, proxyable)]
+pub struct UserDataAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "Sh-User-Data")]
+    pub user_data: Option<Vec<u8>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Profile-Update-Request (PUR), 3GPP TS 29.329 section 6.1.3.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ProfileUpdate, application = Sh, request, proxyable)]
+pub struct ProfileUpdateRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Identity", grouped)]
+    pub user_identity: UserIdentity,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Data-Reference")]
+    pub data_reference: DataReference,
+    #[avp(name = "Sh-User-Data")]
+    pub user_data: Vec<u8>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Profile-Update-Answer (PUA), 3GPP TS 29.329 section 6.1.4.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = ProfileUpdate, application = Sh, proxyable)]
+pub struct ProfileUpdateAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "Repository-Data-ID", grouped)]
+    pub repository_data_id: Option<RepositoryDataId>,
+    #[avp(name = "Data-Reference")]
+    pub data_reference: Option<DataReference>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Subscribe-Notifications-Request (SNR), 3GPP TS 29.329 section 6.1.5.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = SubscribeNotifications, application = Sh, request, proxyable)]
+pub struct SubscribeNotificationsRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Identity", grouped)]
+    pub user_identity: UserIdentity,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "Service-Indication")]
+    pub service_indication: Vec<Vec<u8>>,
+    #[avp(name = "Send-Data-Indication")]
+    pub send_data_indication: Option<SendDataIndication>,
+    #[avp(name = "Server-Name")]
+    pub server_name: Option<String>,
+    #[avp(name = "Subs-Req-Type")]
+    pub subs_req_type: SubsReqType,
+    #[avp(name = "Data-Reference")]
+    pub data_reference: Vec<DataReference>,
+    #[avp(name = "Identity-Set")]
+    pub identity_set: Vec<IdentitySet>,
+    #[avp(name = "Expiry-Time")]
+    pub expiry_time: Option<DateTime<Utc>>,
+    #[avp(name = "DSAI-Tag")]
+    pub dsai_tag: Vec<Vec<u8>>,
+    #[avp(name = "One-Time-Notification")]
+    pub one_time_notification: Option<OneTimeNotification>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Subscribe-Notifications-Answer (SNA), 3GPP TS 29.329 section 6.1.6.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = SubscribeNotifications, application = Sh, proxyable)]
+pub struct SubscribeNotificationsAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "Sh-User-Data")]
+    pub user_data: Option<Vec<u8>>,
+    #[avp(name = "Expiry-Time")]
+    pub expiry_time: Option<DateTime<Utc>>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Push-Notification-Request (PNR), 3GPP TS 29.329 section 6.1.7.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PushNotification, application = Sh, request, proxyable)]
+pub struct PushNotificationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "User-Identity", grouped)]
+    pub user_identity: UserIdentity,
+    #[avp(name = "Wildcarded-Public-Identity")]
+    pub wildcarded_public_identity: Option<String>,
+    #[avp(name = "Wildcarded-IMPU")]
+    pub wildcarded_impu: Option<String>,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Sh-User-Data")]
+    pub user_data: Vec<u8>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sh Push-Notification-Answer (PNA), 3GPP TS 29.329 section 6.1.8.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = PushNotification, application = Sh, proxyable)]
+pub struct PushNotificationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Vendor-Specific-Application-Id", grouped)]
+    pub vendor_specific_application_id: Option<VendorSpecificApplicationId>,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Auth-Session-State")]
+    pub auth_session_state: AuthSessionState,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+impl UserDataRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_identity: UserIdentity,
+        data_reference: DataReference,
+    ) -> UserDataRequest {
+        UserDataRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            supported_features: Vec::new(),
+            user_identity,
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            server_name: None,
+            service_indication: Vec::new(),
+            data_reference: vec![data_reference],
+            identity_set: Vec::new(),
+            requested_domain: None,
+            current_location: None,
+            dsai_tag: Vec::new(),
+            session_priority: None,
+            user_name: None,
+            requested_nodes: None,
+            serving_node_indication: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl UserDataAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(request: &UserDataRequest, result_code: u32, node: &NodeConfig) -> UserDataAnswer {
+        UserDataAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            user_data: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ProfileUpdateRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_identity: UserIdentity,
+        data_reference: DataReference,
+        user_data: &[u8],
+    ) -> ProfileUpdateRequest {
+        ProfileUpdateRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            supported_features: Vec::new(),
+            user_identity,
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            user_name: None,
+            data_reference,
+            user_data: user_data.to_vec(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl ProfileUpdateAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &ProfileUpdateRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> ProfileUpdateAnswer {
+        ProfileUpdateAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            repository_data_id: None,
+            data_reference: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SubscribeNotificationsRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        user_identity: UserIdentity,
+        subs_req_type: SubsReqType,
+        data_reference: DataReference,
+    ) -> SubscribeNotificationsRequest {
+        SubscribeNotificationsRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: None,
+            destination_realm: destination_realm.into(),
+            supported_features: Vec::new(),
+            user_identity,
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            service_indication: Vec::new(),
+            send_data_indication: None,
+            server_name: None,
+            subs_req_type,
+            data_reference: vec![data_reference],
+            identity_set: Vec::new(),
+            expiry_time: None,
+            dsai_tag: Vec::new(),
+            one_time_notification: None,
+            user_name: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SubscribeNotificationsAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &SubscribeNotificationsRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> SubscribeNotificationsAnswer {
+        SubscribeNotificationsAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            user_data: None,
+            expiry_time: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PushNotificationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        user_identity: UserIdentity,
+        user_data: &[u8],
+    ) -> PushNotificationRequest {
+        PushNotificationRequest {
+            session_id: session_id.into(),
+            vendor_specific_application_id: Some(VendorSpecificApplicationId::auth(
+                TGPP_VENDOR_ID,
+                APPLICATION_ID,
+            )),
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_host: destination_host.into(),
+            destination_realm: destination_realm.into(),
+            supported_features: Vec::new(),
+            user_identity,
+            wildcarded_public_identity: None,
+            wildcarded_impu: None,
+            user_name: None,
+            user_data: user_data.to_vec(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl PushNotificationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id,
+    /// Vendor-Specific-Application-Id, Auth-Session-State and Proxy-Info, and
+    /// taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &PushNotificationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> PushNotificationAnswer {
+        PushNotificationAnswer {
+            session_id: request.session_id.clone(),
+            vendor_specific_application_id: request.vendor_specific_application_id.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            auth_session_state: request.auth_session_state,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            supported_features: Vec::new(),
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use chrono::TimeZone;
+    use std::fs;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    fn alice() -> UserIdentity {
+        UserIdentity {
+            public_identity: Some("sip:alice@ims.example.com".into()),
+            msisdn: None,
+        }
+    }
+
+    #[test]
+    fn test_udr_uda_pur_pua_round_trip() {
+        let dict = dict();
+        let as_node = NodeConfig::new("as.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+
+        let mut udr = UserDataRequest::new(
+            "as.ims.example.com;1;1",
+            &as_node,
+            "ims.example.com",
+            alice(),
+            DataReference::RepositoryData,
+        );
+        udr.data_reference.push(DataReference::ImsUserState);
+        udr.service_indication.push(b"mmtel".to_vec());
+        udr.identity_set.push(IdentitySet::AllIdentities);
+
+        let msg = udr.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::UserData);
+        assert_eq!(msg.get_application_id(), ApplicationId::Sh);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(
+            msg.query_values::<i32>("Data-Reference[*]").unwrap(),
+            vec![0, 11]
+        );
+        assert_eq!(UserDataRequest::from_message(&msg).unwrap(), udr);
+
+        let xml = "<?xml version=\"1.0\"?><Sh-Data><RepositoryData><ServiceIndication>mmtel\
+                   </ServiceIndication><SequenceNumber>1</SequenceNumber></RepositoryData>\
+                   </Sh-Data>";
+        let mut uda = UserDataAnswer::new(&udr, 2001, &hss);
+        uda.user_data = Some(xml.as_bytes().to_vec());
+        let msg = round_trip(uda.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<Vec<u8>>("Sh-User-Data").unwrap(),
+            vec![xml.as_bytes().to_vec()]
+        );
+        assert_eq!(UserDataAnswer::from_message(&msg).unwrap(), uda);
+
+        let pur = ProfileUpdateRequest::new(
+            "as.ims.example.com;1;2",
+            &as_node,
+            "ims.example.com",
+            alice(),
+            DataReference::RepositoryData,
+            xml.as_bytes(),
+        );
+        let msg = round_trip(pur.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(ProfileUpdateRequest::from_message(&msg).unwrap(), pur);
+
+        let mut pua = ProfileUpdateAnswer::new(&pur, 2001, &hss);
+        pua.repository_data_id = Some(RepositoryDataId {
+            service_indication: b"mmtel".to_vec(),
+            sequence_number: 2,
+        });
+        pua.data_reference = Some(DataReference::RepositoryData);
+        let msg = round_trip(pua.to_message(2, 2, &dict).unwrap(), &dict);
+        assert_eq!(ProfileUpdateAnswer::from_message(&msg).unwrap(), pua);
+    }
+
+    #[test]
+    fn test_snr_sna_pnr_pna_round_trip() {
+        let dict = dict();
+        let as_node = NodeConfig::new("as.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+
+        let mut snr = SubscribeNotificationsRequest::new(
+            "as.ims.example.com;1;3",
+            &as_node,
+            "ims.example.com",
+            alice(),
+            SubsReqType::Subscribe,
+            DataReference::ImsUserState,
+        );
+        snr.send_data_indication = Some(SendDataIndication::UserDataRequested);
+        snr.expiry_time = Some(Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+        snr.one_time_notification = Some(OneTimeNotification::OneTimeNotificationRequested);
+        let msg = round_trip(snr.to_message(3, 3, &dict).unwrap(), &dict);
+        assert_eq!(
+            SubscribeNotificationsRequest::from_message(&msg).unwrap(),
+            snr
+        );
+
+        let mut sna = SubscribeNotificationsAnswer::new(&snr, 2001, &hss);
+        sna.user_data = Some(b"<Sh-Data/>".to_vec());
+        sna.expiry_time = snr.expiry_time;
+        let msg = round_trip(sna.to_message(3, 3, &dict).unwrap(), &dict);
+        assert_eq!(
+            SubscribeNotificationsAnswer::from_message(&msg).unwrap(),
+            sna
+        );
+
+        let pnr = PushNotificationRequest::new(
+            "hss.ims.example.com;1;1",
+            &hss,
+            "ims.example.com",
+            "as.ims.example.com",
+            alice(),
+            b"<Sh-Data/>",
+        );
+        let msg = round_trip(pnr.to_message(4, 4, &dict).unwrap(), &dict);
+        assert_eq!(msg.get_command_code(), CommandCode::PushNotification);
+        assert_eq!(PushNotificationRequest::from_message(&msg).unwrap(), pnr);
+        let pna = PushNotificationAnswer::new(&pnr, 2001, &as_node);
+        let msg = round_trip(pna.to_message(4, 4, &dict).unwrap(), &dict);
+        assert_eq!(PushNotificationAnswer::from_message(&msg).unwrap(), pna);
+    }
+
+    #[test]
+    fn test_dictionary_with_cx_and_ro_rf() {
+        let dict = Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            &fs::read_to_string("dict/3gpp-ro-rf.xml").unwrap(),
+            crate::applications::cx::DICT_XML,
+            DICT_XML,
+        ]));
+        let as_node = NodeConfig::new("as.ims.example.com", "ims.example.com");
+        let hss = NodeConfig::new("hss.ims.example.com", "ims.example.com");
+        let udr = UserDataRequest::new(
+            "as.ims.example.com;1;1",
+            &as_node,
+            "ims.example.com",
+            alice(),
+            DataReference::RepositoryData,
+        );
+        let mut uda = UserDataAnswer::new(&udr, 2001, &hss);
+        uda.user_data = Some(b"<Sh-Data/>".to_vec());
+
+        // User-Data (702) of Sh and User-Data (606) of Cx coexist
+        let msg = round_trip(uda.to_message(1, 1, &dict).unwrap(), &dict);
+        assert_eq!(UserDataAnswer::from_message(&msg).unwrap(), uda);
+        assert_eq!(dict.get_avp_by_name("Sh-User-Data").unwrap().code, 702);
+        assert_eq!(dict.get_avp_by_name("User-Data").unwrap().code, 606);
+    }
+}
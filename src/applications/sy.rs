@@ -0,0 +1,606 @@
+//! Sy Application
+//!
+//! Typed Spending-Limit and Spending-Status-Notification messages of the Sy
+//! reference point between the PCRF and the OCS, as defined in
+//! [3GPP TS 29.219](https://www.3gpp.org/DynaReport/29219.htm).
+//!
+//! The Sy AVPs are defined in [`DICT_XML`], which is loaded on top of the
+//! default dictionary.
+//!
+//! The PCRF subscribes to policy counters with an SLR, and the OCS later
+//! reports their changes with an SNR sent over the same connection. On the
+//! OCS the connection is found through
+//! [`DiameterServer::peers`](crate::transport::DiameterServer::peers), while
+//! the PCRF answers it from
+//! [`DiameterClient::handle_with_requests`](crate::transport::DiameterClient::handle_with_requests):
+//!
+//! ```no_run
+//! use diameter::applications::sy::{
+//!     self, PolicyCounterStatusReport, SpendingStatusNotificationAnswer,
+//!     SpendingStatusNotificationRequest,
+//! };
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::{DiameterClient, DiameterServer};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! # async fn example(server: DiameterServer, mut client: DiameterClient) {
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, sy::DICT_XML]));
+//!
+//! // PCRF: answer the SNRs of the OCS
+//! let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+//! let mut handler = client.connect().await.unwrap();
+//! let dict_ref = Arc::clone(&dict);
+//! tokio::spawn(async move {
+//!     DiameterClient::handle_with_requests(&mut handler, Arc::clone(&dict_ref), move |req| {
+//!         let pcrf = pcrf.clone();
+//!         let dict = Arc::clone(&dict_ref);
+//!         async move {
+//!             let snr = SpendingStatusNotificationRequest::from_message(&req)?;
+//!             let sna = SpendingStatusNotificationAnswer::new(&snr, 2001, &pcrf);
+//!             sna.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+//!         }
+//!     })
+//!     .await;
+//! });
+//!
+//! // OCS: report a policy counter change to the PCRF
+//! let ocs = NodeConfig::new("ocs.example.com", "example.com");
+//! let peer = server.peers().get_by_origin_host("pcrf.example.com").unwrap();
+//! let mut snr = SpendingStatusNotificationRequest::new(
+//!     "pcrf.example.com;1;1",
+//!     &ocs,
+//!     "example.com",
+//!     "pcrf.example.com",
+//! );
+//! snr.policy_counter_status_report.push(PolicyCounterStatusReport {
+//!     policy_counter_identifier: "monthly-volume".into(),
+//!     policy_counter_status: "exhausted".into(),
+//!     pending_policy_counter_information: Vec::new(),
+//! });
+//! let seq_num = peer.get_next_seq_num();
+//! let req = snr.to_message(seq_num, seq_num, &dict).unwrap();
+//! let sna = peer.send_message(req).await.unwrap().await.unwrap();
+//! # }
+//! ```
+
+use crate::applications::common::{ExperimentalResult, ProxyInfo, SupportedFeatures};
+use crate::applications::credit_control::{RedirectHostUsage, SubscriptionId};
+use crate::node::NodeConfig;
+use crate::typed::{DateTime, Utc};
+use crate::{DiameterMessage, GroupedAvp};
+
+/// Dictionary of the Sy application.
+pub const DICT_XML: &str = include_str!("../../dict/3gpp-sy.xml");
+
+/// Auth-Application-Id of the Sy application.
+pub const APPLICATION_ID: u32 = 16777302;
+
+/// Experimental-Result-Code values defined in 3GPP TS 29.219 section 5.5.
+pub mod result_code {
+    // Transient Failures
+    pub const DIAMETER_ERROR_NO_AVAILABLE_POLICY_COUNTERS: u32 = 4241;
+
+    // Permanent Failures
+    pub const DIAMETER_ERROR_UNKNOWN_POLICY_COUNTERS: u32 = 5570;
+}
+
+enumerated! {
+    /// Values of the SL-Request-Type AVP (2904).
+    pub enum SlRequestType ("SL-Request-Type") {
+        InitialRequest = 0,
+        IntermediateRequest = 1,
+    }
+}
+
+enumerated! {
+    /// Values of the SN-Request-Type AVP (2907).
+    pub enum SnRequestType ("SN-Request-Type") {
+        NormalRequest = 0,
+        AggregatedRequest = 1,
+    }
+}
+
+/// The Pending-Policy-Counter-Information AVP (2905), a status a policy
+/// counter will take at a given time.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct PendingPolicyCounterInformation {
+    #[avp(name = "Policy-Counter-Status")]
+    pub policy_counter_status: String,
+    #[avp(name = "Pending-Policy-Counter-Change-Time")]
+    pub pending_policy_counter_change_time: DateTime<Utc>,
+}
+
+/// The Policy-Counter-Status-Report AVP (2903), the status of a policy
+/// counter.
+#[derive(Debug, Clone, PartialEq, GroupedAvp)]
+#[diameter(crate = "crate")]
+pub struct PolicyCounterStatusReport {
+    #[avp(name = "Policy-Counter-Identifier")]
+    pub policy_counter_identifier: String,
+    #[avp(name = "Policy-Counter-Status")]
+    pub policy_counter_status: String,
+    #[avp(name = "Pending-Policy-Counter-Information", grouped)]
+    pub pending_policy_counter_information: Vec<PendingPolicyCounterInformation>,
+}
+
+/// Sy Spending-Limit-Request (SLR), 3GPP TS 29.219 section 5.6.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = SpendingLimit,
+    application = Sy,
+    request,
+    proxyable
+)]
+pub struct SpendingLimitRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "SL-Request-Type")]
+    pub sl_request_type: SlRequestType,
+    #[avp(name = "Subscription-Id", grouped)]
+    pub subscription_id: Vec<SubscriptionId>,
+    #[avp(name = "Policy-Counter-Identifier")]
+    pub policy_counter_identifier: Vec<String>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sy Spending-Limit-Answer (SLA), 3GPP TS 29.219 section 5.6.3.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = SpendingLimit, application = Sy, proxyable)]
+pub struct SpendingLimitAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Policy-Counter-Status-Report", grouped)]
+    pub policy_counter_status_report: Vec<PolicyCounterStatusReport>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Supported-Features", grouped)]
+    pub supported_features: Vec<SupportedFeatures>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sy Spending-Status-Notification-Request (SNR), 3GPP TS 29.219 section
+/// 5.6.4, sent by the OCS to the PCRF.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = SpendingStatusNotification,
+    application = Sy,
+    request,
+    proxyable
+)]
+pub struct SpendingStatusNotificationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Policy-Counter-Status-Report", grouped)]
+    pub policy_counter_status_report: Vec<PolicyCounterStatusReport>,
+    #[avp(name = "SN-Request-Type")]
+    pub sn_request_type: Option<SnRequestType>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Sy Spending-Status-Notification-Answer (SNA), 3GPP TS 29.219 section
+/// 5.6.5.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = SpendingStatusNotification,
+    application = Sy,
+    proxyable
+)]
+pub struct SpendingStatusNotificationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: Option<u32>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+impl SpendingLimitRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        sl_request_type: SlRequestType,
+    ) -> SpendingLimitRequest {
+        SpendingLimitRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: None,
+            origin_state_id: None,
+            sl_request_type,
+            subscription_id: Vec::new(),
+            policy_counter_identifier: Vec::new(),
+            supported_features: Vec::new(),
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SpendingLimitAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &SpendingLimitRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> SpendingLimitAnswer {
+        SpendingLimitAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            policy_counter_status_report: Vec::new(),
+            error_message: None,
+            error_reporting_host: None,
+            supported_features: Vec::new(),
+            origin_state_id: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SpendingStatusNotificationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    ///
+    /// `session_id` is the Session-Id of the Sy session opened by the PCRF
+    /// and `destination_host` the Origin-Host of its SLR.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+    ) -> SpendingStatusNotificationRequest {
+        SpendingStatusNotificationRequest {
+            session_id: session_id.into(),
+            auth_application_id: APPLICATION_ID,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: destination_host.into(),
+            origin_state_id: None,
+            policy_counter_status_report: Vec::new(),
+            sn_request_type: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SpendingStatusNotificationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying Session-Id
+    /// and Proxy-Info, and taking Origin-Host and Origin-Realm from `node`.
+    pub fn new(
+        request: &SpendingStatusNotificationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> SpendingStatusNotificationAnswer {
+        SpendingStatusNotificationAnswer {
+            session_id: request.session_id.clone(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            result_code: Some(result_code),
+            experimental_result: None,
+            error_message: None,
+            error_reporting_host: None,
+            origin_state_id: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::common::TGPP_VENDOR_ID;
+    use crate::applications::credit_control::SubscriptionIdType;
+    use crate::dictionary::{self, Dictionary};
+    use crate::transport::{
+        DiameterClient, DiameterClientConfig, DiameterServer, DiameterServerConfig,
+    };
+    use crate::typed::TypedMessage;
+    use crate::{flags, ApplicationId, CommandCode};
+    use chrono::TimeZone;
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, DICT_XML]))
+    }
+
+    fn round_trip(msg: DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        DiameterMessage::decode_from(&mut cursor, Arc::clone(dict)).unwrap()
+    }
+
+    fn report(status: &str) -> PolicyCounterStatusReport {
+        PolicyCounterStatusReport {
+            policy_counter_identifier: "monthly-volume".into(),
+            policy_counter_status: status.into(),
+            pending_policy_counter_information: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_slr_sla_snr_sna_round_trip() {
+        let dict = dict();
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+        let ocs = NodeConfig::new("ocs.example.com", "example.com");
+
+        let mut slr = SpendingLimitRequest::new(
+            "pcrf.example.com;1;1",
+            &pcrf,
+            "example.com",
+            SlRequestType::InitialRequest,
+        );
+        slr.subscription_id.push(SubscriptionId {
+            subscription_id_type: SubscriptionIdType::EndUserImsi,
+            subscription_id_data: "525051234567890".into(),
+        });
+        slr.policy_counter_identifier = vec!["monthly-volume".into(), "roaming".into()];
+        slr.supported_features.push(SupportedFeatures {
+            vendor_id: TGPP_VENDOR_ID,
+            feature_list_id: 1,
+            feature_list: 0x1,
+        });
+
+        let msg = slr.to_message(1, 2, &dict).unwrap();
+        assert_eq!(msg.get_command_code(), CommandCode::SpendingLimit);
+        assert_eq!(msg.get_application_id(), ApplicationId::Sy);
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        let msg = round_trip(msg, &dict);
+        assert_eq!(SpendingLimitRequest::from_message(&msg).unwrap(), slr);
+
+        let mut sla = SpendingLimitAnswer::new(&slr, 2001, &ocs);
+        let mut pending = report("valid");
+        pending
+            .pending_policy_counter_information
+            .push(PendingPolicyCounterInformation {
+                policy_counter_status: "exhausted".into(),
+                pending_policy_counter_change_time: Utc
+                    .with_ymd_and_hms(2024, 2, 1, 0, 0, 0)
+                    .unwrap(),
+            });
+        sla.policy_counter_status_report.push(pending);
+        let msg = round_trip(sla.to_message(1, 2, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.query_values::<String>("Policy-Counter-Status-Report/Policy-Counter-Identifier")
+                .unwrap(),
+            vec!["monthly-volume"]
+        );
+        assert_eq!(SpendingLimitAnswer::from_message(&msg).unwrap(), sla);
+
+        let mut snr = SpendingStatusNotificationRequest::new(
+            &slr.session_id,
+            &ocs,
+            "example.com",
+            "pcrf.example.com",
+        );
+        snr.policy_counter_status_report.push(report("exhausted"));
+        snr.sn_request_type = Some(SnRequestType::NormalRequest);
+        let msg = round_trip(snr.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(
+            msg.get_command_code(),
+            CommandCode::SpendingStatusNotification
+        );
+        assert_eq!(
+            SpendingStatusNotificationRequest::from_message(&msg).unwrap(),
+            snr
+        );
+
+        let mut sna = SpendingStatusNotificationAnswer::new(&snr, 0, &pcrf);
+        sna.result_code = None;
+        sna.experimental_result = Some(ExperimentalResult {
+            vendor_id: TGPP_VENDOR_ID,
+            experimental_result_code: result_code::DIAMETER_ERROR_UNKNOWN_POLICY_COUNTERS,
+        });
+        let msg = round_trip(sna.to_message(3, 4, &dict).unwrap(), &dict);
+        assert_eq!(
+            SpendingStatusNotificationAnswer::from_message(&msg).unwrap(),
+            sna
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ocs_initiated_snr() {
+        let dict = dict();
+        let pcrf = NodeConfig::new("pcrf.example.com", "example.com");
+        let ocs = NodeConfig::new("ocs.example.com", "example.com");
+
+        // OCS answering SLRs
+        let mut server =
+            DiameterServer::new("0.0.0.0:3877", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let peers = server.peers();
+        let dict_ref = Arc::clone(&dict);
+        let ocs_ref = ocs.clone();
+        tokio::spawn(async move {
+            let dict = Arc::clone(&dict_ref);
+            server
+                .listen(
+                    move |req| {
+                        let dict = Arc::clone(&dict);
+                        let ocs = ocs_ref.clone();
+                        async move {
+                            let slr = SpendingLimitRequest::from_message(&req)?;
+                            let mut sla = SpendingLimitAnswer::new(&slr, 2001, &ocs);
+                            sla.policy_counter_status_report.push(report("valid"));
+                            sla.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        // PCRF answering SNRs
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3877", config);
+        let mut handler = client.connect().await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let dict_ref = Arc::clone(&dict);
+        let pcrf_ref = pcrf.clone();
+        tokio::spawn(async move {
+            let dict = Arc::clone(&dict_ref);
+            DiameterClient::handle_with_requests(&mut handler, dict_ref, move |req| {
+                let dict = Arc::clone(&dict);
+                let pcrf = pcrf_ref.clone();
+                let tx = tx.clone();
+                async move {
+                    let snr = SpendingStatusNotificationRequest::from_message(&req)?;
+                    let sna = SpendingStatusNotificationAnswer::new(&snr, 2001, &pcrf);
+                    let _ = tx.send(snr);
+                    sna.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &dict)
+                }
+            })
+            .await;
+        });
+
+        let mut slr = SpendingLimitRequest::new(
+            "pcrf.example.com;1;1",
+            &pcrf,
+            "example.com",
+            SlRequestType::InitialRequest,
+        );
+        slr.policy_counter_identifier.push("monthly-volume".into());
+        let seq_num = client.get_next_seq_num();
+        let req = slr.to_message(seq_num, seq_num, &dict).unwrap();
+        let res = client.send_message(req).await.unwrap().await.unwrap();
+        let sla = SpendingLimitAnswer::from_message(&res).unwrap();
+        assert_eq!(sla.result_code, Some(2001));
+        assert_eq!(sla.policy_counter_status_report, vec![report("valid")]);
+
+        // The OCS reports a change over the connection opened by the PCRF
+        let peer = peers.get_by_origin_host("pcrf.example.com").unwrap();
+        let mut snr = SpendingStatusNotificationRequest::new(
+            &slr.session_id,
+            &ocs,
+            "example.com",
+            "pcrf.example.com",
+        );
+        snr.policy_counter_status_report.push(report("exhausted"));
+        let seq_num = peer.get_next_seq_num();
+        let req = snr.to_message(seq_num, seq_num, &dict).unwrap();
+        let res = tokio::time::timeout(
+            Duration::from_secs(5),
+            peer.send_message(req).await.unwrap(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let sna = SpendingStatusNotificationAnswer::from_message(&res).unwrap();
+        assert_eq!(sna.result_code, Some(2001));
+        assert_eq!(sna.origin_host, "pcrf.example.com");
+        assert_eq!(rx.recv().await.unwrap(), snr);
+
+        // The PCRF can still send requests on the connection
+        let mut slr = slr.clone();
+        slr.sl_request_type = SlRequestType::IntermediateRequest;
+        let seq_num = client.get_next_seq_num();
+        let req = slr.to_message(seq_num, seq_num, &dict).unwrap();
+        let res = client.send_message(req).await.unwrap().await.unwrap();
+        assert!(SpendingLimitAnswer::from_message(&res).is_ok());
+    }
+}
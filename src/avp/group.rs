@@ -1,6 +1,8 @@
 use crate::avp::Avp;
-use crate::dictionary::Dictionary;
+use crate::avp::AvpPath;
+use crate::dictionary::{AvpKey, Dictionary};
 use crate::error::{Error, Result};
+use std::convert::TryFrom;
 use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
@@ -23,6 +25,52 @@ impl Grouped {
         &self.avps
     }
 
+    /// Returns the first AVP identified by `key`.
+    pub fn find_avp<K: Into<AvpKey>>(&self, key: K) -> Option<&Avp> {
+        let key = key.into();
+        self.avps.iter().find(|avp| avp.matches(&key))
+    }
+
+    /// Returns the value of the first AVP identified by `key`, converted to `T`.
+    pub fn get<T, K>(&self, key: K) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Avp, Error = Error>,
+        K: Into<AvpKey>,
+    {
+        let key = key.into();
+        let avp = self
+            .avps
+            .iter()
+            .find(|avp| avp.matches(&key))
+            .ok_or_else(|| self.dict.avp_not_found(&key))?;
+        T::try_from(avp)
+    }
+
+    /// Returns an iterator over all AVPs identified by `key`.
+    pub fn find_avps<K: Into<AvpKey>>(&self, key: K) -> impl Iterator<Item = &Avp> {
+        let key = key.into();
+        self.avps.iter().filter(move |avp| avp.matches(&key))
+    }
+
+    /// Returns the values of all AVPs identified by `key`, converted to `T`.
+    pub fn get_all<T, K>(&self, key: K) -> Result<Vec<T>>
+    where
+        T: for<'a> TryFrom<&'a Avp, Error = Error>,
+        K: Into<AvpKey>,
+    {
+        self.find_avps(key).map(T::try_from).collect()
+    }
+
+    /// Returns all AVPs of the group matching the path expression, walking
+    /// nested `Grouped` AVPs.
+    ///
+    /// See [`AvpPath`] for the syntax.
+    pub fn query(&self, path: &str) -> Result<Vec<&Avp>> {
+        let path: AvpPath = path.parse()?;
+        path.validate(&self.dict)?;
+        Ok(path.select(&self.avps))
+    }
+
     pub fn add(&mut self, avp: Avp) {
         self.avps.push(avp);
     }
@@ -32,6 +80,81 @@ impl Grouped {
         self.add(avp);
     }
 
+    /// Inserts an AVP at position `index`, shifting all AVPs after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of AVPs.
+    pub fn insert(&mut self, index: usize, avp: Avp) {
+        self.avps.insert(index, avp);
+    }
+
+    /// Removes and returns the first AVP identified by `key`.
+    pub fn remove_avp<K: Into<AvpKey>>(&mut self, key: K) -> Option<Avp> {
+        let key = key.into();
+        let index = self.avps.iter().position(|avp| avp.matches(&key))?;
+        Some(self.avps.remove(index))
+    }
+
+    /// Removes and returns all AVPs identified by `key`.
+    pub fn remove_avps<K: Into<AvpKey>>(&mut self, key: K) -> Vec<Avp> {
+        let key = key.into();
+        let (removed, kept) = self.avps.drain(..).partition(|avp| avp.matches(&key));
+        self.avps = kept;
+        removed
+    }
+
+    /// Retains only the AVPs for which `f` returns true.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Avp) -> bool,
+    {
+        self.avps.retain(f);
+    }
+
+    /// Replaces the value of the first AVP identified by `key`, keeping its flags.
+    pub fn replace_avp<K: Into<AvpKey>>(&mut self, key: K, value: AvpValue) -> Result<()> {
+        self.update_avp(key, |avp| avp.set_value(value))
+    }
+
+    /// Modifies the first AVP identified by `key` in place.
+    ///
+    /// The length of the group is computed from its AVPs, so the closure may
+    /// change the AVP value, including the contents of a nested `Grouped` AVP
+    /// via [`Avp::update_grouped`].
+    pub fn update_avp<K, F>(&mut self, key: K, f: F) -> Result<()>
+    where
+        K: Into<AvpKey>,
+        F: FnOnce(&mut Avp),
+    {
+        let key = key.into();
+        let avp = self
+            .avps
+            .iter_mut()
+            .find(|avp| avp.matches(&key))
+            .ok_or_else(|| self.dict.avp_not_found(&key))?;
+        f(avp);
+        Ok(())
+    }
+
+    /// Moves the AVP at position `from` to position `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is out of bounds.
+    pub fn move_avp(&mut self, from: usize, to: usize) {
+        let avp = self.avps.remove(from);
+        self.avps.insert(to, avp);
+    }
+
+    /// Sorts the AVPs with a comparator function, preserving the order of equal AVPs.
+    pub fn sort_avps_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&Avp, &Avp) -> std::cmp::Ordering,
+    {
+        self.avps.sort_by(compare);
+    }
+
     pub fn decode_from<R: Read + Seek>(
         reader: &mut R,
         len: usize,
@@ -139,4 +262,40 @@ mod tests {
             _ => panic!("invalid value, expected Unsigned32"),
         }
     }
+
+    #[test]
+    fn test_edit_avps() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let mut mscc = Grouped::new(vec![], Arc::clone(&dict));
+        mscc.add_avp(432, None, 0, Unsigned32::new(2).into());
+        mscc.add_avp(432, None, 0, Unsigned32::new(1).into());
+        mscc.add_avp(439, None, 0, Unsigned32::new(7).into());
+        mscc.insert(0, avp!(416, None, 0, Enumerated::new(1), Arc::clone(&dict)));
+
+        mscc.move_avp(0, 3);
+        let codes: Vec<u32> = mscc.avps().iter().map(|avp| avp.get_code()).collect();
+        assert_eq!(codes, vec![432, 432, 439, 416]);
+
+        mscc.sort_avps_by(|a, b| a.get_code().cmp(&b.get_code()));
+        let codes: Vec<u32> = mscc.avps().iter().map(|avp| avp.get_code()).collect();
+        assert_eq!(codes, vec![416, 432, 432, 439]);
+        assert_eq!(mscc.get_all::<u32, _>(432).unwrap(), vec![2, 1]);
+
+        mscc.replace_avp(439, Unsigned32::new(8).into()).unwrap();
+        assert_eq!(mscc.get::<u32, _>(439).unwrap(), 8);
+        match mscc.get::<u32, _>("Not-An-Avp") {
+            Err(Error::UnknownAvpName(name)) => assert_eq!(name, "Not-An-Avp"),
+            _ => panic!("expected UnknownAvpName"),
+        }
+        assert!(mscc.replace_avp(415, Unsigned32::new(1).into()).is_err());
+        assert_eq!(mscc.remove_avps(432).len(), 2);
+        assert!(mscc.remove_avp(416).is_some());
+        assert_eq!(mscc.avps().len(), 1);
+
+        let mut encoded = Vec::new();
+        mscc.encode_to(&mut encoded).unwrap();
+        assert_eq!(encoded.len() as u32, mscc.length());
+    }
 }
@@ -38,19 +38,23 @@ pub mod integer64;
 pub mod ipv4;
 pub mod ipv6;
 pub mod octetstring;
+pub mod path;
 pub mod time;
 pub mod unsigned32;
 pub mod unsigned64;
 pub mod uri;
 pub mod utf8string;
 
-use crate::dictionary::Dictionary;
+use crate::dictionary::{AvpKey, Dictionary};
 use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
 use core::fmt;
+use std::convert::TryFrom;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 pub use crate::avp::address::Address;
 pub use crate::avp::enumerated::Enumerated;
@@ -63,6 +67,7 @@ pub use crate::avp::integer64::Integer64;
 pub use crate::avp::ipv4::IPv4;
 pub use crate::avp::ipv6::IPv6;
 pub use crate::avp::octetstring::OctetString;
+pub use crate::avp::path::AvpPath;
 pub use crate::avp::time::Time;
 pub use crate::avp::unsigned32::Unsigned32;
 pub use crate::avp::unsigned64::Unsigned64;
@@ -168,6 +173,27 @@ impl AvpValue {
         }
     }
 
+    pub fn get_type(&self) -> AvpType {
+        match self {
+            AvpValue::Address(_) => AvpType::Address,
+            AvpValue::AddressIPv4(_) => AvpType::AddressIPv4,
+            AvpValue::AddressIPv6(_) => AvpType::AddressIPv6,
+            AvpValue::Float32(_) => AvpType::Float32,
+            AvpValue::Float64(_) => AvpType::Float64,
+            AvpValue::Enumerated(_) => AvpType::Enumerated,
+            AvpValue::Integer32(_) => AvpType::Integer32,
+            AvpValue::Integer64(_) => AvpType::Integer64,
+            AvpValue::Unsigned32(_) => AvpType::Unsigned32,
+            AvpValue::Unsigned64(_) => AvpType::Unsigned64,
+            AvpValue::UTF8String(_) => AvpType::UTF8String,
+            AvpValue::OctetString(_) => AvpType::OctetString,
+            AvpValue::Identity(_) => AvpType::Identity,
+            AvpValue::DiameterURI(_) => AvpType::DiameterURI,
+            AvpValue::Time(_) => AvpType::Time,
+            AvpValue::Grouped(_) => AvpType::Grouped,
+        }
+    }
+
     pub fn get_type_name(&self) -> &'static str {
         match self {
             AvpValue::Address(_) => "Address",
@@ -307,6 +333,356 @@ impl From<Grouped> for AvpValue {
     }
 }
 
+impl TryFrom<&Avp> for u32 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Unsigned32(v) => Ok(v.value()),
+            _ => Err(avp.invalid_conversion("u32")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for u64 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Unsigned64(v) => Ok(v.value()),
+            AvpValue::Unsigned32(v) => Ok(v.value() as u64),
+            _ => Err(avp.invalid_conversion("u64")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for i32 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Integer32(v) => Ok(v.value()),
+            AvpValue::Enumerated(v) => Ok(v.value()),
+            _ => Err(avp.invalid_conversion("i32")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for i64 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Integer64(v) => Ok(v.value()),
+            AvpValue::Integer32(v) => Ok(v.value() as i64),
+            AvpValue::Enumerated(v) => Ok(v.value() as i64),
+            _ => Err(avp.invalid_conversion("i64")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for f32 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Float32(v) => Ok(v.value()),
+            _ => Err(avp.invalid_conversion("f32")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for f64 {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Float64(v) => Ok(v.value()),
+            AvpValue::Float32(v) => Ok(v.value() as f64),
+            _ => Err(avp.invalid_conversion("f64")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for String {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::UTF8String(v) => Ok(v.value().to_string()),
+            AvpValue::Identity(v) => Ok(v.value().to_string()),
+            AvpValue::DiameterURI(v) => String::from_utf8(v.value().to_vec())
+                .map_err(|e| avp.invalid_value(&format!("invalid DiameterURI: {}", e))),
+            _ => Err(avp.invalid_conversion("String")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for Vec<u8> {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::OctetString(v) => Ok(v.value().to_vec()),
+            _ => Err(avp.invalid_conversion("Vec<u8>")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for IpAddr {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::AddressIPv4(v) => Ok(IpAddr::V4(*v.value())),
+            AvpValue::AddressIPv6(v) => Ok(IpAddr::V6(*v.value())),
+            AvpValue::Address(v) => match v.value() {
+                address::Value::IPv4(ip) => Ok(IpAddr::V4(*ip)),
+                address::Value::IPv6(ip) => Ok(IpAddr::V6(*ip)),
+                address::Value::E164(_) => {
+                    Err(avp.invalid_value("E.164 address cannot be converted to IpAddr"))
+                }
+            },
+            _ => Err(avp.invalid_conversion("IpAddr")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for Ipv4Addr {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match IpAddr::try_from(avp) {
+            Ok(IpAddr::V4(ip)) => Ok(ip),
+            Ok(IpAddr::V6(_)) => Err(avp.invalid_value("expected an IPv4 address")),
+            Err(_) => Err(avp.invalid_conversion("Ipv4Addr")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for Ipv6Addr {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match IpAddr::try_from(avp) {
+            Ok(IpAddr::V6(ip)) => Ok(ip),
+            Ok(IpAddr::V4(_)) => Err(avp.invalid_value("expected an IPv6 address")),
+            Err(_) => Err(avp.invalid_conversion("Ipv6Addr")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for DateTime<Utc> {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Time(v) => Ok(*v.value()),
+            _ => Err(avp.invalid_conversion("DateTime<Utc>")),
+        }
+    }
+}
+
+impl TryFrom<&Avp> for Grouped {
+    type Error = Error;
+
+    fn try_from(avp: &Avp) -> Result<Self> {
+        match avp.get_value() {
+            AvpValue::Grouped(v) => Ok(v.clone()),
+            _ => Err(avp.invalid_conversion("Grouped")),
+        }
+    }
+}
+
+/// Conversion of a value into an `AvpValue` of a given `AvpType`.
+///
+/// This is used where the AVP type is known from the dictionary, so that
+/// native Rust values can be encoded without naming the AVP wrapper type.
+/// Integers are converted to any integer AVP type they fit into, strings to
+/// any string-like type and IP addresses to any address type.
+pub trait IntoAvpValue {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue>;
+}
+
+fn invalid_encoding(value: &str, avp_type: AvpType) -> Error {
+    Error::InvalidAvpValue(format!("cannot encode {} as {:?}", value, avp_type))
+}
+
+impl IntoAvpValue for AvpValue {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        if self.get_type() == avp_type {
+            Ok(self)
+        } else {
+            Err(invalid_encoding(self.get_type_name(), avp_type))
+        }
+    }
+}
+
+macro_rules! impl_into_avp_value_for_avp {
+    ($($t:ty),*) => {
+        $(
+            impl IntoAvpValue for $t {
+                fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+                    AvpValue::from(self).into_avp_value(avp_type)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_avp_value_for_avp!(
+    Address,
+    IPv4,
+    IPv6,
+    Identity,
+    DiameterURI,
+    Enumerated,
+    Float32,
+    Float64,
+    Grouped,
+    Integer32,
+    Integer64,
+    OctetString,
+    Time,
+    Unsigned32,
+    Unsigned64,
+    UTF8String
+);
+
+macro_rules! impl_into_avp_value_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl IntoAvpValue for $t {
+                fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+                    let out_of_range = || {
+                        Error::InvalidAvpValue(format!(
+                            "value {} is out of range for {:?}",
+                            self, avp_type
+                        ))
+                    };
+                    match avp_type {
+                        AvpType::Unsigned32 => Ok(Unsigned32::new(
+                            u32::try_from(self).map_err(|_| out_of_range())?,
+                        )
+                        .into()),
+                        AvpType::Unsigned64 => Ok(Unsigned64::new(
+                            u64::try_from(self).map_err(|_| out_of_range())?,
+                        )
+                        .into()),
+                        AvpType::Integer32 => Ok(Integer32::new(
+                            i32::try_from(self).map_err(|_| out_of_range())?,
+                        )
+                        .into()),
+                        AvpType::Integer64 => Ok(Integer64::new(
+                            i64::try_from(self).map_err(|_| out_of_range())?,
+                        )
+                        .into()),
+                        AvpType::Enumerated => Ok(Enumerated::new(
+                            i32::try_from(self).map_err(|_| out_of_range())?,
+                        )
+                        .into()),
+                        _ => Err(invalid_encoding(stringify!($t), avp_type)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_into_avp_value_for_int!(u8, u16, u32, u64, i8, i16, i32, i64, usize);
+
+impl IntoAvpValue for f32 {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match avp_type {
+            AvpType::Float32 => Ok(Float32::new(self).into()),
+            AvpType::Float64 => Ok(Float64::new(self as f64).into()),
+            _ => Err(invalid_encoding("f32", avp_type)),
+        }
+    }
+}
+
+impl IntoAvpValue for f64 {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match avp_type {
+            AvpType::Float64 => Ok(Float64::new(self).into()),
+            _ => Err(invalid_encoding("f64", avp_type)),
+        }
+    }
+}
+
+impl IntoAvpValue for &str {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match avp_type {
+            AvpType::UTF8String => Ok(UTF8String::new(self).into()),
+            AvpType::Identity => Ok(Identity::new(self).into()),
+            AvpType::DiameterURI => Ok(DiameterURI::new(self.as_bytes().to_vec()).into()),
+            AvpType::OctetString => Ok(OctetString::new(self.as_bytes().to_vec()).into()),
+            _ => Err(invalid_encoding("string", avp_type)),
+        }
+    }
+}
+
+impl IntoAvpValue for String {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        self.as_str().into_avp_value(avp_type)
+    }
+}
+
+impl IntoAvpValue for &String {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        self.as_str().into_avp_value(avp_type)
+    }
+}
+
+impl IntoAvpValue for Vec<u8> {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match avp_type {
+            AvpType::OctetString => Ok(OctetString::new(self).into()),
+            AvpType::DiameterURI => Ok(DiameterURI::new(self).into()),
+            _ => Err(invalid_encoding("bytes", avp_type)),
+        }
+    }
+}
+
+impl IntoAvpValue for &[u8] {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        self.to_vec().into_avp_value(avp_type)
+    }
+}
+
+impl IntoAvpValue for IpAddr {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match (avp_type, self) {
+            (AvpType::Address, IpAddr::V4(ip)) => Ok(Address::from_ipv4(ip).into()),
+            (AvpType::Address, IpAddr::V6(ip)) => Ok(Address::from_ipv6(ip).into()),
+            (AvpType::AddressIPv4, IpAddr::V4(ip)) => Ok(IPv4::new(ip).into()),
+            (AvpType::AddressIPv6, IpAddr::V6(ip)) => Ok(IPv6::new(ip).into()),
+            _ => Err(invalid_encoding(&self.to_string(), avp_type)),
+        }
+    }
+}
+
+impl IntoAvpValue for Ipv4Addr {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        IpAddr::V4(self).into_avp_value(avp_type)
+    }
+}
+
+impl IntoAvpValue for Ipv6Addr {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        IpAddr::V6(self).into_avp_value(avp_type)
+    }
+}
+
+impl IntoAvpValue for DateTime<Utc> {
+    fn into_avp_value(self, avp_type: AvpType) -> Result<AvpValue> {
+        match avp_type {
+            AvpType::Time => Ok(Time::new(self).into()),
+            _ => Err(invalid_encoding("DateTime<Utc>", avp_type)),
+        }
+    }
+}
+
 impl AvpHeader {
     pub fn decode_from<R: Read>(reader: &mut R) -> Result<AvpHeader> {
         let mut b = [0; 8];
@@ -435,6 +811,87 @@ impl Avp {
         &self.value
     }
 
+    /// Replaces the value of the AVP, updating its length and padding.
+    pub fn set_value(&mut self, value: AvpValue) {
+        self.value = value;
+        self.update_length();
+    }
+
+    /// Sets the M and P flags. The V flag follows the vendor ID and is left unchanged.
+    pub fn set_flags(&mut self, flags: u8) {
+        self.header.flags.mandatory = (flags & flags::M) != 0;
+        self.header.flags.private = (flags & flags::P) != 0;
+    }
+
+    /// Modifies the AVPs of a `Grouped` value in place, updating the length
+    /// and padding of this AVP afterwards.
+    pub fn update_grouped<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Grouped),
+    {
+        match &mut self.value {
+            AvpValue::Grouped(group) => f(group),
+            _ => return Err(self.invalid_conversion("Grouped")),
+        }
+        self.update_length();
+        Ok(())
+    }
+
+    fn update_length(&mut self) {
+        let header_length = if self.header.vendor_id.is_some() {
+            12
+        } else {
+            8
+        };
+        self.header.length = header_length + self.value.length();
+        self.padding = Avp::pad_to_32_bits(self.value.length());
+    }
+
+    /// Returns the dictionary name of the AVP, if known.
+    pub fn get_name(&self) -> Option<&str> {
+        self.dict
+            .get_avp_name(self.get_code(), self.get_vendor_id())
+    }
+
+    /// Returns true if this AVP is identified by `key`.
+    ///
+    /// A plain code only matches AVPs without a vendor ID, names are resolved
+    /// through the dictionary.
+    pub fn matches(&self, key: &AvpKey) -> bool {
+        match key {
+            AvpKey::Code(code) => self.get_code() == *code && self.get_vendor_id().is_none(),
+            AvpKey::CodeAndVendor(code, vendor_id) => {
+                self.get_code() == *code && self.get_vendor_id() == Some(*vendor_id)
+            }
+            AvpKey::Name(name) => self.get_name() == Some(name.as_str()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let name = self.get_name().unwrap_or("Unknown");
+        match self.get_vendor_id() {
+            Some(vendor_id) => format!("{}({}:{})", name, self.get_code(), vendor_id),
+            None => format!("{}({})", name, self.get_code()),
+        }
+    }
+
+    pub(crate) fn invalid_conversion(&self, target: &str) -> Error {
+        Error::InvalidAvpValue(format!(
+            "cannot convert AVP {} of type {} to {}",
+            self.describe(),
+            self.get_value().get_type_name(),
+            target
+        ))
+    }
+
+    fn invalid_value(&self, msg: &str) -> Error {
+        Error::InvalidAvpValue(format!(
+            "invalid value for AVP {}: {}",
+            self.describe(),
+            msg
+        ))
+    }
+
     pub fn decode_from<R: Read + Seek>(reader: &mut R, dict: Arc<Dictionary>) -> Result<Avp> {
         let header = AvpHeader::decode_from(reader)?;
 
@@ -779,4 +1236,55 @@ mod tests {
         assert_eq!(avp.get_vendor_id(), None);
         assert_eq!(avp.get_utf8string().unwrap().value(), "session-id");
     }
+
+    #[test]
+    fn test_try_from_avp() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let avp = avp!(1, None, M, UTF8String::new("user"), Arc::clone(&dict));
+        assert_eq!(String::try_from(&avp).unwrap(), "user");
+        assert!(u32::try_from(&avp).is_err());
+
+        let avp = avp!(
+            257,
+            None,
+            M,
+            Address::from_ipv4(Ipv4Addr::new(127, 0, 0, 1)),
+            Arc::clone(&dict)
+        );
+        assert_eq!(
+            IpAddr::try_from(&avp).unwrap(),
+            IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))
+        );
+        assert!(Ipv6Addr::try_from(&avp).is_err());
+
+        let avp = avp!(
+            257,
+            None,
+            M,
+            Address::from_e164("359898000135".into()),
+            Arc::clone(&dict)
+        );
+        match IpAddr::try_from(&avp) {
+            Err(Error::InvalidAvpValue(msg)) => assert_eq!(
+                msg,
+                "invalid value for AVP Host-IP-Address(257): E.164 address cannot be converted to IpAddr"
+            ),
+            _ => panic!("expected InvalidAvpValue"),
+        }
+
+        let avp = avp!(
+            44,
+            None,
+            0,
+            OctetString::new(vec![1, 2, 3]),
+            Arc::clone(&dict)
+        );
+        assert_eq!(Vec::<u8>::try_from(&avp).unwrap(), vec![1, 2, 3]);
+
+        let now = Utc::now();
+        let avp = avp!(55, None, M, Time::new(now), Arc::clone(&dict));
+        assert_eq!(DateTime::<Utc>::try_from(&avp).unwrap(), now);
+    }
 }
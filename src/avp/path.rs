@@ -0,0 +1,248 @@
+//! Path-based AVP queries.
+//!
+//! A path walks through `Grouped` AVPs, one segment per nesting level,
+//! separated by `/`. Each segment identifies an AVP by name, by code or by
+//! `code:vendor_id`, optionally followed by an instance selector:
+//!
+//! * `Name` or `Name[*]` - all instances
+//! * `Name[n]` - the n-th instance (zero based)
+//!
+//! ```text
+//! Multiple-Services-Credit-Control[*]/Used-Service-Unit/CC-Total-Octets
+//! Subscription-Id[0]/Subscription-Id-Data
+//! 873:10415/874:10415/30
+//! ```
+
+use crate::avp::{Avp, AvpValue};
+use crate::dictionary::{AvpKey, Dictionary};
+use crate::error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvpPath {
+    segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSegment {
+    pub key: AvpKey,
+    pub index: PathIndex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathIndex {
+    All,
+    Nth(usize),
+}
+
+impl AvpPath {
+    pub fn new(segments: Vec<PathSegment>) -> AvpPath {
+        AvpPath { segments }
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Checks that every AVP name in the path is known to the dictionary.
+    pub fn validate(&self, dict: &Dictionary) -> Result<()> {
+        for segment in &self.segments {
+            if let AvpKey::Name(name) = &segment.key {
+                if dict.get_avp_by_name(name).is_none() {
+                    return Err(Error::UnknownAvpName(name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns all AVPs reached by walking the path from `avps`.
+    pub fn select<'a>(&self, avps: &'a [Avp]) -> Vec<&'a Avp> {
+        let mut current: Vec<&'a Avp> = Vec::new();
+        for (depth, segment) in self.segments.iter().enumerate() {
+            let candidates: Vec<&'a [Avp]> = if depth == 0 {
+                vec![avps]
+            } else {
+                current
+                    .iter()
+                    .filter_map(|avp| match avp.get_value() {
+                        AvpValue::Grouped(group) => Some(group.avps()),
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            current = candidates
+                .into_iter()
+                .flat_map(|avps| segment.select(avps))
+                .collect();
+        }
+        current
+    }
+}
+
+impl PathSegment {
+    fn select<'a>(&self, avps: &'a [Avp]) -> Vec<&'a Avp> {
+        let mut matches = avps.iter().filter(|avp| avp.matches(&self.key));
+        match self.index {
+            PathIndex::All => matches.collect(),
+            PathIndex::Nth(n) => matches.nth(n).into_iter().collect(),
+        }
+    }
+}
+
+impl FromStr for AvpPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<AvpPath> {
+        let segments = path
+            .split('/')
+            .map(|segment| segment.parse())
+            .collect::<Result<Vec<PathSegment>>>()?;
+        Ok(AvpPath { segments })
+    }
+}
+
+impl FromStr for PathSegment {
+    type Err = Error;
+
+    fn from_str(segment: &str) -> Result<PathSegment> {
+        let invalid = || Error::InvalidAvpPath(format!("invalid path segment: '{}'", segment));
+
+        let segment = segment.trim();
+        let (key, index) = match segment.find('[') {
+            Some(pos) => {
+                let selector = segment[pos + 1..].strip_suffix(']').ok_or_else(invalid)?;
+                let index = match selector {
+                    "*" => PathIndex::All,
+                    n => PathIndex::Nth(n.parse().map_err(|_| invalid())?),
+                };
+                (&segment[..pos], index)
+            }
+            None => (segment, PathIndex::All),
+        };
+
+        if key.is_empty() {
+            return Err(invalid());
+        }
+
+        let key = if key.starts_with(|c: char| c.is_ascii_digit()) {
+            match key.split_once(':') {
+                Some((code, vendor_id)) => AvpKey::CodeAndVendor(
+                    code.parse().map_err(|_| invalid())?,
+                    vendor_id.parse().map_err(|_| invalid())?,
+                ),
+                None => AvpKey::Code(key.parse().map_err(|_| invalid())?),
+            }
+        } else {
+            AvpKey::Name(key.to_string())
+        };
+
+        Ok(PathSegment { key, index })
+    }
+}
+
+impl fmt::Display for AvpPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", segment.key)?;
+            if let PathIndex::Nth(n) = segment.index {
+                write!(f, "[{}]", n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::flags::M;
+    use crate::avp::{Grouped, UTF8String, Unsigned32, Unsigned64};
+    use crate::dictionary;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_path() {
+        let path: AvpPath = "Multiple-Services-Credit-Control[*]/Used-Service-Unit[1]/421"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            path.segments(),
+            &[
+                PathSegment {
+                    key: AvpKey::Name("Multiple-Services-Credit-Control".into()),
+                    index: PathIndex::All,
+                },
+                PathSegment {
+                    key: AvpKey::Name("Used-Service-Unit".into()),
+                    index: PathIndex::Nth(1),
+                },
+                PathSegment {
+                    key: AvpKey::Code(421),
+                    index: PathIndex::All,
+                },
+            ]
+        );
+
+        let path: AvpPath = "873:10415/874:10415".parse().unwrap();
+        assert_eq!(path.segments()[0].key, AvpKey::CodeAndVendor(873, 10415));
+        assert_eq!(path.to_string(), "873:10415/874:10415");
+
+        assert!("Session-Id[".parse::<AvpPath>().is_err());
+        assert!("Session-Id[x]".parse::<AvpPath>().is_err());
+        assert!("Session-Id//Origin-Host".parse::<AvpPath>().is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+
+        let mut avps = vec![];
+        for octets in [100, 200] {
+            let mut usu = Grouped::new(vec![], Arc::clone(&dict));
+            usu.add_avp(421, None, M, Unsigned64::new(octets).into());
+            let mut mscc = Grouped::new(vec![], Arc::clone(&dict));
+            mscc.add_avp(432, None, M, Unsigned32::new(1).into());
+            mscc.add_avp(446, None, M, usu.into());
+            avps.push(Avp::new(456, None, M, mscc.into(), Arc::clone(&dict)));
+        }
+        avps.push(Avp::new(
+            263,
+            None,
+            M,
+            UTF8String::new("ses;1").into(),
+            Arc::clone(&dict),
+        ));
+
+        let path: AvpPath = "Multiple-Services-Credit-Control[*]/Used-Service-Unit/CC-Total-Octets"
+            .parse()
+            .unwrap();
+        let values: Vec<u64> = path
+            .select(&avps)
+            .iter()
+            .map(|avp| u64::try_from(*avp).unwrap())
+            .collect();
+        assert_eq!(values, vec![100, 200]);
+
+        let path: AvpPath = "Multiple-Services-Credit-Control[1]/446/421"
+            .parse()
+            .unwrap();
+        let found = path.select(&avps);
+        assert_eq!(found.len(), 1);
+        assert_eq!(u64::try_from(found[0]).unwrap(), 200);
+
+        // non-grouped AVPs have no children
+        let path: AvpPath = "Session-Id/Origin-Host".parse().unwrap();
+        assert!(path.select(&avps).is_empty());
+
+        let path: AvpPath = "Multiple-Services-Credit-Control/Does-Not-Exist"
+            .parse()
+            .unwrap();
+        assert!(path.validate(&dict).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+//! Fluent Diameter Message Builder
+//!
+//! Builds messages and grouped AVPs by dictionary names. The command code,
+//! application ID, AVP codes, vendor IDs and M flags are all taken from the
+//! `Dictionary`, and values are checked against the AVP type defined there.
+//!
+//! ```
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::DiameterMessage;
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!
+//! let ccr = DiameterMessage::request("Credit-Control", dict)
+//!     .hop_by_hop_id(1)
+//!     .end_to_end_id(1)
+//!     .avp("Session-Id", "ses;12345888")
+//!     .avp("Origin-Host", "host.example.com")
+//!     .avp("Origin-Realm", "realm.example.com")
+//!     .avp("CC-Request-Type", 1)
+//!     .avp("CC-Request-Number", 0)
+//!     .group("Subscription-Id", |g| {
+//!         g.avp("Subscription-Id-Type", 0)
+//!             .avp("Subscription-Id-Data", "6591234567")
+//!     })
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Errors such as unknown AVP names or mismatched value types are collected
+//! while building and the first one is returned by `build`.
+
+use crate::avp::flags::M;
+use crate::avp::{Avp, Grouped, IntoAvpValue};
+use crate::diameter::{flags, ApplicationId, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use std::sync::Arc;
+
+/// Builder for a `DiameterMessage`, created by [`DiameterMessage::request`]
+/// or [`DiameterMessage::answer`].
+pub struct MessageBuilder {
+    dict: Arc<Dictionary>,
+    command: String,
+    application_id: Option<ApplicationId>,
+    flags: u8,
+    hop_by_hop_id: u32,
+    end_to_end_id: u32,
+    avps: Vec<Avp>,
+    error: Option<Error>,
+}
+
+/// Builder for the AVPs of a `Grouped` AVP.
+pub struct GroupBuilder {
+    dict: Arc<Dictionary>,
+    avps: Vec<Avp>,
+    error: Option<Error>,
+}
+
+impl MessageBuilder {
+    /// Creates a builder for the command with the given dictionary name.
+    pub fn new(command: &str, flags: u8, dict: Arc<Dictionary>) -> MessageBuilder {
+        MessageBuilder {
+            dict,
+            command: command.into(),
+            application_id: None,
+            flags,
+            hop_by_hop_id: 0,
+            end_to_end_id: 0,
+            avps: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Sets the application ID.
+    ///
+    /// Defaults to the application that defines the command in the dictionary.
+    pub fn application(mut self, application_id: ApplicationId) -> Self {
+        self.application_id = Some(application_id);
+        self
+    }
+
+    /// Sets the command flags, replacing the ones set so far.
+    pub fn flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the P bit.
+    pub fn proxyable(mut self) -> Self {
+        self.flags |= flags::PROXYABLE;
+        self
+    }
+
+    /// Sets the Hop-by-Hop Identifier.
+    pub fn hop_by_hop_id(mut self, hop_by_hop_id: u32) -> Self {
+        self.hop_by_hop_id = hop_by_hop_id;
+        self
+    }
+
+    /// Sets the End-to-End Identifier.
+    pub fn end_to_end_id(mut self, end_to_end_id: u32) -> Self {
+        self.end_to_end_id = end_to_end_id;
+        self
+    }
+
+    /// Adds an AVP by dictionary name.
+    pub fn avp<V: IntoAvpValue>(mut self, name: &str, value: V) -> Self {
+        match new_avp(name, value, &self.dict) {
+            Ok(avp) => self.avps.push(avp),
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Adds a `Grouped` AVP by dictionary name, whose content is built by `f`.
+    pub fn group<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: FnOnce(GroupBuilder) -> GroupBuilder,
+    {
+        let group = f(GroupBuilder::new(Arc::clone(&self.dict))).build();
+        match group.and_then(|group| new_avp(name, group, &self.dict)) {
+            Ok(avp) => self.avps.push(avp),
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Adds an already constructed AVP.
+    pub fn push(mut self, avp: Avp) -> Self {
+        self.avps.push(avp);
+        self
+    }
+
+    /// Builds the message, returning the first error encountered.
+    pub fn build(self) -> Result<DiameterMessage> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        let code = self
+            .dict
+            .get_command_code_by_name(&self.command)
+            .ok_or_else(|| Error::UnknownCommand(self.command.clone()))?;
+        let application_id = match self.application_id {
+            Some(application_id) => application_id,
+            None => self
+                .dict
+                .get_command_application(code)
+                .unwrap_or(ApplicationId::Common),
+        };
+
+        let mut message = DiameterMessage::new(
+            code,
+            application_id,
+            self.flags,
+            self.hop_by_hop_id,
+            self.end_to_end_id,
+            self.dict,
+        );
+        for avp in self.avps {
+            message.add(avp);
+        }
+        Ok(message)
+    }
+
+    fn set_error(&mut self, e: Error) {
+        if self.error.is_none() {
+            self.error = Some(e);
+        }
+    }
+}
+
+impl GroupBuilder {
+    pub fn new(dict: Arc<Dictionary>) -> GroupBuilder {
+        GroupBuilder {
+            dict,
+            avps: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Adds an AVP by dictionary name.
+    pub fn avp<V: IntoAvpValue>(mut self, name: &str, value: V) -> Self {
+        match new_avp(name, value, &self.dict) {
+            Ok(avp) => self.avps.push(avp),
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Adds a nested `Grouped` AVP by dictionary name.
+    pub fn group<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: FnOnce(GroupBuilder) -> GroupBuilder,
+    {
+        let group = f(GroupBuilder::new(Arc::clone(&self.dict))).build();
+        match group.and_then(|group| new_avp(name, group, &self.dict)) {
+            Ok(avp) => self.avps.push(avp),
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Adds an already constructed AVP.
+    pub fn push(mut self, avp: Avp) -> Self {
+        self.avps.push(avp);
+        self
+    }
+
+    /// Builds the `Grouped` value, returning the first error encountered.
+    pub fn build(self) -> Result<Grouped> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(Grouped::new(self.avps, self.dict)),
+        }
+    }
+
+    fn set_error(&mut self, e: Error) {
+        if self.error.is_none() {
+            self.error = Some(e);
+        }
+    }
+}
+
+fn new_avp<V: IntoAvpValue>(name: &str, value: V, dict: &Arc<Dictionary>) -> Result<Avp> {
+    let avp_def = dict
+        .get_avp_by_name(name)
+        .ok_or_else(|| Error::UnknownAvpName(name.to_string()))?;
+
+    let value = value
+        .into_avp_value(avp_def.avp_type)
+        .map_err(|e| Error::InvalidAvpValue(format!("invalid value for AVP {}: {}", name, e)))?;
+
+    let flags = if avp_def.m_flag { M } else { 0 };
+    Ok(Avp::new(
+        avp_def.code,
+        avp_def.vendor_id,
+        flags,
+        value,
+        Arc::clone(dict),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::{Enumerated, UTF8String};
+    use crate::diameter::CommandCode;
+    use crate::dictionary;
+    use std::io::Cursor;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_build_request() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let ccr = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .proxyable()
+            .hop_by_hop_id(1234)
+            .end_to_end_id(5678)
+            .avp("Session-Id", "ses;12345888")
+            .avp("Origin-Host", "host.example.com")
+            .avp("Origin-Realm", "realm.example.com")
+            .avp("CC-Request-Type", Enumerated::new(1))
+            .avp("CC-Request-Number", 0)
+            .avp("Timezone-Offset", -60)
+            .group("Subscription-Id", |g| {
+                g.avp("Subscription-Id-Type", 0)
+                    .avp("Subscription-Id-Data", "6591234567")
+            })
+            .group("Multiple-Services-Credit-Control", |g| {
+                g.avp("Rating-Group", 100)
+                    .group("Used-Service-Unit", |g| g.avp("CC-Total-Octets", 1024))
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(ccr.get_command_code(), CommandCode::CreditControl);
+        assert_eq!(ccr.get_application_id(), ApplicationId::CreditControl);
+        assert_eq!(ccr.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        assert_eq!(ccr.get_hop_by_hop_id(), 1234);
+
+        let origin_host = ccr.find_avp("Origin-Host").unwrap();
+        assert!(origin_host.get_identity().is_some());
+        assert!(origin_host.get_flags().mandatory);
+
+        let timezone_offset = ccr.find_avp((571, 10415)).unwrap();
+        assert_eq!(timezone_offset.get_vendor_id(), Some(10415));
+        assert!(!timezone_offset.get_flags().mandatory);
+        assert!(ccr
+            .find_avp("CC-Request-Number")
+            .unwrap()
+            .get_unsigned32()
+            .is_some());
+
+        let mut encoded = Vec::new();
+        ccr.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        let ccr = DiameterMessage::decode_from(&mut cursor, dict).unwrap();
+        assert_eq!(
+            ccr.query_values::<u64>(
+                "Multiple-Services-Credit-Control/Used-Service-Unit/CC-Total-Octets"
+            )
+            .unwrap(),
+            vec![1024]
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let result = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .avp("Does-Not-Exist", 1)
+            .avp("Session-Id", "ses;1")
+            .build();
+        match result {
+            Err(Error::UnknownAvpName(name)) => assert_eq!(name, "Does-Not-Exist"),
+            _ => panic!("expected UnknownAvpName"),
+        }
+
+        let result = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .avp("CC-Request-Number", "zero")
+            .build();
+        match result {
+            Err(Error::InvalidAvpValue(msg)) => assert_eq!(
+                msg,
+                "invalid value for AVP CC-Request-Number: cannot encode string as Unsigned32"
+            ),
+            _ => panic!("expected InvalidAvpValue"),
+        }
+
+        let result = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .avp("CC-Request-Number", -1)
+            .build();
+        assert!(result.is_err());
+
+        let result = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .avp("Session-Id", UTF8String::new("ses;1"))
+            .avp("Origin-Host", UTF8String::new("host.example.com"))
+            .build();
+        assert!(result.is_err());
+
+        let result = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .group("Subscription-Id", |g| g.avp("Subscription-Id-Type", 0.5))
+            .build();
+        assert!(result.is_err());
+
+        let result = DiameterMessage::request("Not-A-Command", Arc::clone(&dict))
+            .avp("Session-Id", "ses;1")
+            .build();
+        match result {
+            Err(Error::UnknownCommand(name)) => assert_eq!(name, "Not-A-Command"),
+            _ => panic!("expected UnknownCommand"),
+        }
+
+        let result = DiameterMessage::request("No-Such-Command", Arc::clone(&dict)).build();
+        assert!(result.is_err());
+
+        let result = DiameterMessage::answer("Capabilities-Exchange", dict)
+            .avp("Host-IP-Address", Ipv4Addr::new(127, 0, 0, 1))
+            .build()
+            .unwrap();
+        assert_eq!(result.get_flags(), 0);
+        assert_eq!(result.get_application_id(), ApplicationId::Common);
+    }
+}
@@ -30,12 +30,20 @@
 //!   +-+-+-+-+-+-+-+-+
 //! ```
 
+use crate::avp::flags::M;
 use crate::avp::Avp;
+use crate::avp::AvpPath;
 use crate::avp::AvpValue;
-use crate::dictionary::Dictionary;
+use crate::avp::Identity;
+use crate::avp::UTF8String;
+use crate::avp::Unsigned32;
+use crate::builder::MessageBuilder;
+use crate::dictionary::{AvpKey, Dictionary};
 use crate::error::{Error, Result};
+use crate::node::NodeConfig;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use std::convert::TryFrom;
 use std::fmt;
 use std::io::Read;
 use std::io::Seek;
@@ -51,6 +59,58 @@ pub mod flags {
     pub const RETRANSMIT: u8 = 0x10;
 }
 
+/// Result-Code AVP values defined in RFC 6733 section 7.1.
+pub mod result_code {
+    // Informational
+    pub const DIAMETER_MULTI_ROUND_AUTH: u32 = 1001;
+
+    // Success
+    pub const DIAMETER_SUCCESS: u32 = 2001;
+    pub const DIAMETER_LIMITED_SUCCESS: u32 = 2002;
+
+    // Protocol Errors
+    pub const DIAMETER_COMMAND_UNSUPPORTED: u32 = 3001;
+    pub const DIAMETER_UNABLE_TO_DELIVER: u32 = 3002;
+    pub const DIAMETER_REALM_NOT_SERVED: u32 = 3003;
+    pub const DIAMETER_TOO_BUSY: u32 = 3004;
+    pub const DIAMETER_LOOP_DETECTED: u32 = 3005;
+    pub const DIAMETER_REDIRECT_INDICATION: u32 = 3006;
+    pub const DIAMETER_APPLICATION_UNSUPPORTED: u32 = 3007;
+    pub const DIAMETER_INVALID_HDR_BITS: u32 = 3008;
+    pub const DIAMETER_INVALID_AVP_BITS: u32 = 3009;
+    pub const DIAMETER_UNKNOWN_PEER: u32 = 3010;
+
+    // Transient Failures
+    pub const DIAMETER_AUTHENTICATION_REJECTED: u32 = 4001;
+    pub const DIAMETER_OUT_OF_SPACE: u32 = 4002;
+    pub const ELECTION_LOST: u32 = 4003;
+
+    // Permanent Failures
+    pub const DIAMETER_AVP_UNSUPPORTED: u32 = 5001;
+    pub const DIAMETER_UNKNOWN_SESSION_ID: u32 = 5002;
+    pub const DIAMETER_AUTHORIZATION_REJECTED: u32 = 5003;
+    pub const DIAMETER_INVALID_AVP_VALUE: u32 = 5004;
+    pub const DIAMETER_MISSING_AVP: u32 = 5005;
+    pub const DIAMETER_RESOURCES_EXCEEDED: u32 = 5006;
+    pub const DIAMETER_CONTRADICTING_AVPS: u32 = 5007;
+    pub const DIAMETER_AVP_NOT_ALLOWED: u32 = 5008;
+    pub const DIAMETER_AVP_OCCURS_TOO_MANY_TIMES: u32 = 5009;
+    pub const DIAMETER_NO_COMMON_APPLICATION: u32 = 5010;
+    pub const DIAMETER_UNSUPPORTED_VERSION: u32 = 5011;
+    pub const DIAMETER_UNABLE_TO_COMPLY: u32 = 5012;
+    pub const DIAMETER_INVALID_BIT_IN_HEADER: u32 = 5013;
+    pub const DIAMETER_INVALID_AVP_LENGTH: u32 = 5014;
+    pub const DIAMETER_INVALID_MESSAGE_LENGTH: u32 = 5015;
+    pub const DIAMETER_INVALID_AVP_BIT_COMBO: u32 = 5016;
+    pub const DIAMETER_NO_COMMON_SECURITY: u32 = 5017;
+
+    /// Returns true if the Result-Code is in the 3xxx protocol error range,
+    /// which requires the E bit to be set on the answer.
+    pub fn is_protocol_error(code: u32) -> bool {
+        (3000..4000).contains(&code)
+    }
+}
+
 /// Represents a Diameter message as defined in RFC 6733.
 ///
 /// It consists of a standard header, a list of Attribute-Value Pairs (AVPs)
@@ -78,7 +138,7 @@ pub struct DiameterHeader {
 }
 
 /// Enumerates various command codes used in Diameter messages.
-#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive)]
 pub enum CommandCode {
     Error = 0,
     CapabilitiesExchange = 257,
@@ -92,18 +152,42 @@ pub enum CommandCode {
     SpendingStatusNotification = 8388636,
     Accounting = 271,
     AA = 265,
+    UpdateLocation = 316,
+    CancelLocation = 317,
+    AuthenticationInformation = 318,
+    InsertSubscriberData = 319,
+    DeleteSubscriberData = 320,
+    PurgeUe = 321,
+    Reset = 322,
+    Notify = 323,
+    UserAuthorization = 300,
+    ServerAssignment = 301,
+    LocationInfo = 302,
+    MultimediaAuth = 303,
+    RegistrationTermination = 304,
+    PushProfile = 305,
+    UserData = 306,
+    ProfileUpdate = 307,
+    SubscribeNotifications = 308,
+    PushNotification = 309,
+    DiameterEap = 268,
 }
 
 /// Enumerates the different application IDs that can be used in Diameter messages
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive)]
 pub enum ApplicationId {
     Common = 0,
+    Nasreq = 1,
     Accounting = 3,
     CreditControl = 4,
     Gx = 16777238,
     Rx = 16777236,
     Sy = 16777302,
+    S6a = 16777251,
+    Cx = 16777216,
+    Sh = 16777217,
+    Eap = 5,
 }
 
 impl DiameterMessage {
@@ -130,12 +214,207 @@ impl DiameterMessage {
         DiameterMessage { header, avps, dict }
     }
 
+    /// Returns a [`MessageBuilder`] for a request of the command with the
+    /// given dictionary name, e.g. `"Credit-Control"`.
+    pub fn request(command: &str, dict: Arc<Dictionary>) -> MessageBuilder {
+        MessageBuilder::new(command, flags::REQUEST, dict)
+    }
+
+    /// Returns a [`MessageBuilder`] for an answer of the command with the
+    /// given dictionary name.
+    pub fn answer(command: &str, dict: Arc<Dictionary>) -> MessageBuilder {
+        MessageBuilder::new(command, 0, dict)
+    }
+
+    /// Creates an answer to `req` as described in RFC 6733 section 6.2.
+    ///
+    /// The answer has the same command code, application ID, Hop-by-Hop and
+    /// End-to-End identifiers as the request, with the R bit cleared and the
+    /// P bit copied. The Session-Id and Proxy-Info AVPs are copied from the
+    /// request, Origin-Host and Origin-Realm are taken from `node`.
+    pub fn create_answer(
+        req: &DiameterMessage,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> DiameterMessage {
+        let mut res = DiameterMessage::new(
+            req.get_command_code(),
+            req.get_application_id(),
+            req.get_flags() & flags::PROXYABLE,
+            req.get_hop_by_hop_id(),
+            req.get_end_to_end_id(),
+            Arc::clone(&req.dict),
+        );
+        if let Some(session_id) = req.get_avp(263) {
+            res.add(session_id.clone());
+        }
+        res.add_avp(268, None, M, Unsigned32::new(result_code).into());
+        res.add_avp(264, None, M, Identity::new(&node.origin_host).into());
+        res.add_avp(296, None, M, Identity::new(&node.origin_realm).into());
+        for proxy_info in req.find_avps(284) {
+            res.add(proxy_info.clone());
+        }
+        res
+    }
+
+    /// Creates an error answer to `req` with the E bit set.
+    ///
+    /// The answer follows the `answer-message` format of RFC 6733 section 7.2,
+    /// optionally carrying an Error-Message AVP.
+    pub fn create_error_answer(
+        req: &DiameterMessage,
+        result_code: u32,
+        error_message: Option<&str>,
+        node: &NodeConfig,
+    ) -> DiameterMessage {
+        let mut res = DiameterMessage::create_answer(req, result_code, node);
+        res.set_flags(res.get_flags() | flags::ERROR);
+        if let Some(error_message) = error_message {
+            let index = res.avps.iter().position(|avp| avp.get_code() == 284);
+            let avp = Avp::new(
+                281,
+                None,
+                0,
+                UTF8String::new(error_message).into(),
+                Arc::clone(&res.dict),
+            );
+            match index {
+                Some(index) => res.insert(index, avp),
+                None => res.add(avp),
+            }
+        }
+        res
+    }
+
+    /// Returns true if the R bit is set in the message header.
+    pub fn is_request(&self) -> bool {
+        self.header.flags & flags::REQUEST != 0
+    }
+
+    /// Returns true if the E bit is set in the message header.
+    pub fn is_error(&self) -> bool {
+        self.header.flags & flags::ERROR != 0
+    }
+
+    /// Returns true if a Route-Record AVP holds `identity`, i.e. the request
+    /// was already forwarded by the node with that identity.
+    pub fn has_route_record(&self, identity: &str) -> bool {
+        self.find_avps(282).any(|avp| {
+            String::try_from(avp).is_ok_and(|record| record.eq_ignore_ascii_case(identity))
+        })
+    }
+
+    /// Appends a Route-Record AVP with `identity`, the identity of the peer
+    /// a forwarded request was received from, as described in RFC 6733
+    /// section 6.1.9.
+    pub fn add_route_record(&mut self, identity: &str) {
+        self.add_avp(282, None, M, Identity::new(identity).into());
+    }
+
+    /// Checks a request about to be forwarded by `node` for a routing loop,
+    /// as described in RFC 6733 section 6.1.3.
+    ///
+    /// Returns the DIAMETER_LOOP_DETECTED answer to send back if a
+    /// Route-Record holds the Origin-Host of `node`.
+    pub fn check_loop(&self, node: &NodeConfig) -> Option<DiameterMessage> {
+        if !self.has_route_record(&node.origin_host) {
+            return None;
+        }
+        Some(DiameterMessage::create_error_answer(
+            self,
+            result_code::DIAMETER_LOOP_DETECTED,
+            None,
+            node,
+        ))
+    }
+
+    /// Returns the Proxy-Info AVPs of the message, in order.
+    pub fn proxy_info(&self) -> Vec<Avp> {
+        self.find_avps(284).cloned().collect()
+    }
+
+    /// Replaces the Proxy-Info AVPs of the message with `proxy_info`.
+    ///
+    /// A node forwarding a request sets the Proxy-Info of the request on its
+    /// answer, so that it is returned unchanged to the nodes that added it.
+    pub fn set_proxy_info(&mut self, proxy_info: Vec<Avp>) {
+        self.avps.retain(|avp| avp.get_code() != 284);
+        self.avps.extend(proxy_info);
+        self.update_length();
+    }
+
     /// Returns a reference to the AVP with the specified code,
     /// if it exists within the message.
     pub fn get_avp(&self, code: u32) -> Option<&Avp> {
         self.avps.iter().find(|avp| avp.get_code() == code)
     }
 
+    /// Returns a reference to the first AVP identified by `key`.
+    ///
+    /// The key can be an AVP code, a `(code, vendor_id)` pair or an AVP name,
+    /// e.g. `msg.find_avp("Session-Id")` or `msg.find_avp((1228, 10415))`.
+    pub fn find_avp<K: Into<AvpKey>>(&self, key: K) -> Option<&Avp> {
+        let key = key.into();
+        self.avps.iter().find(|avp| avp.matches(&key))
+    }
+
+    /// Returns the value of the first AVP identified by `key`, converted to `T`.
+    ///
+    /// ```no_run
+    /// # use diameter::DiameterMessage;
+    /// # fn f(msg: &DiameterMessage) -> diameter::Result<()> {
+    /// let session_id: String = msg.get("Session-Id")?;
+    /// let result_code = msg.get::<u32, _>(268)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get<T, K>(&self, key: K) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Avp, Error = Error>,
+        K: Into<AvpKey>,
+    {
+        let key = key.into();
+        let avp = self
+            .avps
+            .iter()
+            .find(|avp| avp.matches(&key))
+            .ok_or_else(|| self.dict.avp_not_found(&key))?;
+        T::try_from(avp)
+    }
+
+    /// Returns an iterator over all AVPs identified by `key`.
+    pub fn find_avps<K: Into<AvpKey>>(&self, key: K) -> impl Iterator<Item = &Avp> {
+        let key = key.into();
+        self.avps.iter().filter(move |avp| avp.matches(&key))
+    }
+
+    /// Returns the values of all AVPs identified by `key`, converted to `T`.
+    pub fn get_all<T, K>(&self, key: K) -> Result<Vec<T>>
+    where
+        T: for<'a> TryFrom<&'a Avp, Error = Error>,
+        K: Into<AvpKey>,
+    {
+        self.find_avps(key).map(T::try_from).collect()
+    }
+
+    /// Returns all AVPs matching the path expression, walking `Grouped` AVPs.
+    ///
+    /// See [`AvpPath`] for the syntax, e.g.
+    /// `Multiple-Services-Credit-Control[*]/Used-Service-Unit/CC-Total-Octets`.
+    pub fn query(&self, path: &str) -> Result<Vec<&Avp>> {
+        let path: AvpPath = path.parse()?;
+        path.validate(&self.dict)?;
+        Ok(path.select(&self.avps))
+    }
+
+    /// Returns the values of all AVPs matching the path expression, converted to `T`.
+    pub fn query_values<T>(&self, path: &str) -> Result<Vec<T>>
+    where
+        T: for<'a> TryFrom<&'a Avp, Error = Error>,
+    {
+        self.query(path)?.into_iter().map(T::try_from).collect()
+    }
+
     /// Provides a reference to the vector containing all AVPs in the message.
     pub fn get_avps(&self) -> &Vec<Avp> {
         &self.avps
@@ -160,6 +439,96 @@ impl DiameterMessage {
         Ok(())
     }
 
+    /// Inserts an AVP at position `index`, shifting all AVPs after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of AVPs.
+    pub fn insert(&mut self, index: usize, avp: Avp) {
+        self.avps.insert(index, avp);
+        self.update_length();
+    }
+
+    /// Removes and returns the first AVP identified by `key`.
+    pub fn remove_avp<K: Into<AvpKey>>(&mut self, key: K) -> Option<Avp> {
+        let key = key.into();
+        let index = self.avps.iter().position(|avp| avp.matches(&key))?;
+        let avp = self.avps.remove(index);
+        self.update_length();
+        Some(avp)
+    }
+
+    /// Removes and returns all AVPs identified by `key`.
+    pub fn remove_avps<K: Into<AvpKey>>(&mut self, key: K) -> Vec<Avp> {
+        let key = key.into();
+        let (removed, kept) = self.avps.drain(..).partition(|avp| avp.matches(&key));
+        self.avps = kept;
+        self.update_length();
+        removed
+    }
+
+    /// Retains only the AVPs for which `f` returns true.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Avp) -> bool,
+    {
+        self.avps.retain(f);
+        self.update_length();
+    }
+
+    /// Replaces the value of the first AVP identified by `key`, keeping its flags.
+    pub fn replace_avp<K: Into<AvpKey>>(&mut self, key: K, value: AvpValue) -> Result<()> {
+        self.update_avp(key, |avp| avp.set_value(value))
+    }
+
+    /// Modifies the first AVP identified by `key` in place.
+    ///
+    /// The message length is recalculated afterwards, so the closure may change
+    /// the AVP value, including the contents of a `Grouped` AVP via
+    /// [`Avp::update_grouped`].
+    pub fn update_avp<K, F>(&mut self, key: K, f: F) -> Result<()>
+    where
+        K: Into<AvpKey>,
+        F: FnOnce(&mut Avp),
+    {
+        let key = key.into();
+        let avp = self
+            .avps
+            .iter_mut()
+            .find(|avp| avp.matches(&key))
+            .ok_or_else(|| self.dict.avp_not_found(&key))?;
+        f(avp);
+        self.update_length();
+        Ok(())
+    }
+
+    /// Moves the AVP at position `from` to position `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is out of bounds.
+    pub fn move_avp(&mut self, from: usize, to: usize) {
+        let avp = self.avps.remove(from);
+        self.avps.insert(to, avp);
+    }
+
+    /// Sorts the AVPs with a comparator function, preserving the order of equal AVPs.
+    pub fn sort_avps_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&Avp, &Avp) -> std::cmp::Ordering,
+    {
+        self.avps.sort_by(compare);
+    }
+
+    fn update_length(&mut self) {
+        self.header.length = HEADER_LENGTH
+            + self
+                .avps
+                .iter()
+                .map(|avp| avp.get_length() + avp.get_padding() as u32)
+                .sum::<u32>();
+    }
+
     /// Returns the total length of the Diameter message, including the header and AVPs.
     pub fn get_length(&self) -> u32 {
         self.header.length
@@ -190,6 +559,31 @@ impl DiameterMessage {
         self.header.end_to_end_id
     }
 
+    /// Sets the command code in the message header.
+    pub fn set_command_code(&mut self, code: CommandCode) {
+        self.header.code = code;
+    }
+
+    /// Sets the application ID in the message header.
+    pub fn set_application_id(&mut self, application_id: ApplicationId) {
+        self.header.application_id = application_id;
+    }
+
+    /// Sets the flags in the message header.
+    pub fn set_flags(&mut self, flags: u8) {
+        self.header.flags = flags;
+    }
+
+    /// Sets the Hop-by-Hop Identifier in the message header.
+    pub fn set_hop_by_hop_id(&mut self, hop_by_hop_id: u32) {
+        self.header.hop_by_hop_id = hop_by_hop_id;
+    }
+
+    /// Sets the End-to-End Identifier in the message header.
+    pub fn set_end_to_end_id(&mut self, end_to_end_id: u32) {
+        self.header.end_to_end_id = end_to_end_id;
+    }
+
     /// Decodes a Diameter message from the given byte slice.
     pub fn decode_from<R: Read + Seek>(
         reader: &mut R,
@@ -404,6 +798,7 @@ mod tests {
     use crate::avp::utf8string::UTF8String;
     use crate::avp::AvpValue;
     use crate::avp::Integer32;
+    use crate::avp::OctetString;
     use crate::dictionary;
 
     use super::*;
@@ -603,4 +998,280 @@ mod tests {
         assert_eq!(message.get_avp(263).is_some(), true);
         assert_eq!(message.get_avp(415).is_none(), true);
     }
+
+    #[test]
+    fn test_get_typed_values() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let mut message = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            flags::REQUEST,
+            1234,
+            5678,
+            dict,
+        );
+        message.add_avp(263, None, M, UTF8String::new("ses;12345888").into());
+        message.add_avp(264, None, M, Identity::new("host.example.com").into());
+        message.add_avp(416, None, M, Enumerated::new(1).into());
+        message.add_avp(415, None, M, Unsigned32::new(1000).into());
+        message.add_avp(571, Some(10415), 0, Integer32::new(-60).into());
+
+        let session_id: String = message.get("Session-Id").unwrap();
+        assert_eq!(session_id, "ses;12345888");
+        let origin_host: String = message.get(264).unwrap();
+        assert_eq!(origin_host, "host.example.com");
+        assert_eq!(message.get::<i32, _>("CC-Request-Type").unwrap(), 1);
+        assert_eq!(message.get::<u32, _>(415).unwrap(), 1000);
+        assert_eq!(message.get::<u64, _>(415).unwrap(), 1000);
+        assert_eq!(message.get::<i32, _>((571, 10415)).unwrap(), -60);
+        assert_eq!(message.get::<i32, _>("Timezone-Offset").unwrap(), -60);
+
+        // vendor-specific AVPs are not matched by code alone
+        assert!(message.find_avp(571).is_none());
+        match message.get::<i32, _>(571) {
+            Err(Error::AvpNotFound(key)) => assert_eq!(key, "571"),
+            _ => panic!("expected AvpNotFound"),
+        }
+        match message.get::<u32, _>("Not-An-Avp") {
+            Err(Error::UnknownAvpName(name)) => assert_eq!(name, "Not-An-Avp"),
+            _ => panic!("expected UnknownAvpName"),
+        }
+        match message.get::<u32, _>("Destination-Host") {
+            Err(Error::AvpNotFound(key)) => assert_eq!(key, "Destination-Host"),
+            _ => panic!("expected AvpNotFound"),
+        }
+
+        match message.get::<u32, _>("Session-Id") {
+            Err(Error::InvalidAvpValue(msg)) => assert_eq!(
+                msg,
+                "cannot convert AVP Session-Id(263) of type UTF8String to u32"
+            ),
+            _ => panic!("expected InvalidAvpValue"),
+        }
+    }
+
+    #[test]
+    fn test_multi_instance_query() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let mut message = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            flags::REQUEST,
+            1234,
+            5678,
+            Arc::clone(&dict),
+        );
+        for (id_type, id_data) in [(0, "6591234567"), (1, "525011234567890")] {
+            let mut subscription_id = Grouped::new(vec![], Arc::clone(&dict));
+            subscription_id.add_avp(450, None, M, Enumerated::new(id_type).into());
+            subscription_id.add_avp(444, None, M, UTF8String::new(id_data).into());
+            message.add_avp(443, None, M, subscription_id.into());
+        }
+        for rating_group in [100, 200, 300] {
+            let mut mscc = Grouped::new(vec![], Arc::clone(&dict));
+            mscc.add_avp(432, None, M, Unsigned32::new(rating_group).into());
+            message.add_avp(456, None, M, mscc.into());
+        }
+
+        assert_eq!(message.find_avps("Subscription-Id").count(), 2);
+        assert_eq!(message.find_avps(456).count(), 3);
+
+        let ids: Vec<String> = message
+            .query_values("Subscription-Id[*]/Subscription-Id-Data")
+            .unwrap();
+        assert_eq!(ids, vec!["6591234567", "525011234567890"]);
+
+        let rating_groups: Vec<u32> = message
+            .query_values("Multiple-Services-Credit-Control/Rating-Group")
+            .unwrap();
+        assert_eq!(rating_groups, vec![100, 200, 300]);
+
+        let second: Vec<u32> = message
+            .query_values("Multiple-Services-Credit-Control[1]/Rating-Group")
+            .unwrap();
+        assert_eq!(second, vec![200]);
+
+        match message.query("Multiple-Services-Credit-Control/Not-An-Avp") {
+            Err(Error::UnknownAvpName(name)) => assert_eq!(name, "Not-An-Avp"),
+            _ => panic!("expected UnknownAvpName"),
+        }
+    }
+
+    #[test]
+    fn test_edit_message() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+
+        let mut message = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            flags::REQUEST,
+            1234,
+            5678,
+            Arc::clone(&dict),
+        );
+        message.add_avp(263, None, M, UTF8String::new("ses;12345888").into());
+        message.add_avp(293, None, M, Identity::new("ocs1.example.com").into());
+        message.add_avp(282, None, M, Identity::new("dra1.example.com").into());
+        message.add_avp(282, None, M, Identity::new("dra2.example.com").into());
+        let mut mscc = Grouped::new(vec![], Arc::clone(&dict));
+        mscc.add_avp(432, None, M, Unsigned32::new(100).into());
+        message.add_avp(456, None, M, mscc.into());
+
+        message
+            .replace_avp(
+                "Destination-Host",
+                Identity::new("ocs-backup.example.com").into(),
+            )
+            .unwrap();
+        assert_eq!(message.remove_avps("Route-Record").len(), 2);
+        message.set_flags(message.get_flags() | flags::PROXYABLE | flags::RETRANSMIT);
+        message.set_hop_by_hop_id(42);
+        message
+            .update_avp("Multiple-Services-Credit-Control", |avp| {
+                avp.update_grouped(|mscc| {
+                    mscc.add_avp(439, None, M, Unsigned32::new(7).into());
+                })
+                .unwrap();
+            })
+            .unwrap();
+        message.move_avp(2, 0);
+        assert!(message
+            .replace_avp("Route-Record", Identity::new("x").into())
+            .is_err());
+
+        let mut encoded = Vec::new();
+        message.encode_to(&mut encoded).unwrap();
+        assert_eq!(encoded.len() as u32, message.get_length());
+
+        let mut cursor = Cursor::new(&encoded);
+        let message = DiameterMessage::decode_from(&mut cursor, dict).unwrap();
+        assert_eq!(message.get_hop_by_hop_id(), 42);
+        assert_eq!(
+            message.get_flags(),
+            flags::REQUEST | flags::PROXYABLE | flags::RETRANSMIT
+        );
+        assert_eq!(message.get_avps()[0].get_code(), 456);
+        assert_eq!(
+            message.get::<String, _>("Destination-Host").unwrap(),
+            "ocs-backup.example.com"
+        );
+        assert!(message.find_avp("Route-Record").is_none());
+        assert_eq!(
+            message
+                .query_values::<u32>("Multiple-Services-Credit-Control/Service-Identifier")
+                .unwrap(),
+            vec![7]
+        );
+    }
+
+    #[test]
+    fn test_create_answer() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+        let node = NodeConfig::new("ocs.example.com", "example.com");
+
+        let mut req = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            flags::REQUEST | flags::PROXYABLE,
+            1234,
+            5678,
+            Arc::clone(&dict),
+        );
+        req.add_avp(263, None, M, UTF8String::new("ses;12345888").into());
+        req.add_avp(264, None, M, Identity::new("pcef.example.com").into());
+        let mut proxy_info = Grouped::new(vec![], Arc::clone(&dict));
+        proxy_info.add_avp(280, None, M, Identity::new("dra.example.com").into());
+        proxy_info.add_avp(33, None, M, OctetString::new(vec![1, 2, 3]).into());
+        req.add_avp(284, None, M, proxy_info.into());
+
+        let res = DiameterMessage::create_answer(&req, result_code::DIAMETER_SUCCESS, &node);
+        assert!(!res.is_request());
+        assert!(!res.is_error());
+        assert_eq!(res.get_flags(), flags::PROXYABLE);
+        assert_eq!(res.get_command_code(), CommandCode::CreditControl);
+        assert_eq!(res.get_application_id(), ApplicationId::CreditControl);
+        assert_eq!(res.get_hop_by_hop_id(), 1234);
+        assert_eq!(res.get_end_to_end_id(), 5678);
+        assert_eq!(res.get_avps()[0].get_code(), 263);
+        assert_eq!(res.get::<String, _>("Session-Id").unwrap(), "ses;12345888");
+        assert_eq!(res.get::<u32, _>("Result-Code").unwrap(), 2001);
+        assert_eq!(
+            res.get::<String, _>("Origin-Host").unwrap(),
+            "ocs.example.com"
+        );
+        assert_eq!(res.get::<String, _>("Origin-Realm").unwrap(), "example.com");
+        assert_eq!(
+            res.query_values::<String>("Proxy-Info/Proxy-Host").unwrap(),
+            vec!["dra.example.com"]
+        );
+
+        let res = DiameterMessage::create_error_answer(
+            &req,
+            result_code::DIAMETER_UNABLE_TO_DELIVER,
+            Some("no route"),
+            &node,
+        );
+        assert!(res.is_error());
+        assert_eq!(res.get_flags(), flags::PROXYABLE | flags::ERROR);
+        assert_eq!(res.get::<String, _>("Error-Message").unwrap(), "no route");
+        assert_eq!(res.get_avps().last().unwrap().get_code(), 284);
+
+        let mut encoded = Vec::new();
+        res.encode_to(&mut encoded).unwrap();
+        assert_eq!(encoded.len() as u32, res.get_length());
+    }
+
+    #[test]
+    fn test_route_record_and_proxy_info() {
+        let dict = Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]);
+        let dict = Arc::new(dict);
+        let node = NodeConfig::new("dra.example.com", "example.com");
+
+        let mut req = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            flags::REQUEST | flags::PROXYABLE,
+            1234,
+            5678,
+            Arc::clone(&dict),
+        );
+        req.add_avp(263, None, M, UTF8String::new("ses;12345888").into());
+        req.add_route_record("pcef.example.com");
+        assert!(req.has_route_record("PCEF.example.com"));
+        assert!(req.check_loop(&node).is_none());
+
+        req.add_route_record("dra.example.com");
+        let res = req.check_loop(&node).unwrap();
+        assert!(res.is_error());
+        assert_eq!(res.get_hop_by_hop_id(), 1234);
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_LOOP_DETECTED
+        );
+
+        let mut proxy_info = Grouped::new(vec![], Arc::clone(&dict));
+        proxy_info.add_avp(280, None, M, Identity::new("dra.example.net").into());
+        proxy_info.add_avp(33, None, M, OctetString::new(vec![1, 2, 3]).into());
+        req.add_avp(284, None, M, proxy_info.into());
+
+        // Proxy-Info lost or altered on the way back is restored
+        let mut res = DiameterMessage::create_answer(&req, result_code::DIAMETER_SUCCESS, &node);
+        res.remove_avps(284);
+        res.add_avp(284, None, M, Grouped::new(vec![], Arc::clone(&dict)).into());
+        res.set_proxy_info(req.proxy_info());
+        assert_eq!(res.proxy_info().len(), 1);
+        assert_eq!(
+            res.query_values::<String>("Proxy-Info/Proxy-Host").unwrap(),
+            vec!["dra.example.net"]
+        );
+        let mut encoded = Vec::new();
+        res.encode_to(&mut encoded).unwrap();
+        assert_eq!(encoded.len() as u32, res.get_length());
+    }
 }
@@ -5,21 +5,69 @@ use serde::Deserialize;
 use serde_xml_rs::from_str;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::RwLock;
 
 use crate::avp::AvpType;
+use crate::error::Error;
 
 #[derive(Debug, Clone)]
 pub struct Dictionary {
     avps: BTreeMap<AvpKey, AvpDefinition>,
     applications: HashMap<String, ApplicationId>,
     commands: HashMap<String, CommandCode>,
+    command_applications: HashMap<CommandCode, ApplicationId>,
 }
 
+/// Identifies an AVP either by code, by code and vendor, or by dictionary name.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AvpKey {
     Code(u32),
     CodeAndVendor(u32, u32),
+    Name(String),
+}
+
+impl From<u32> for AvpKey {
+    fn from(code: u32) -> Self {
+        AvpKey::Code(code)
+    }
+}
+
+impl From<(u32, u32)> for AvpKey {
+    fn from((code, vendor_id): (u32, u32)) -> Self {
+        AvpKey::CodeAndVendor(code, vendor_id)
+    }
+}
+
+impl From<(u32, Option<u32>)> for AvpKey {
+    fn from((code, vendor_id): (u32, Option<u32>)) -> Self {
+        match vendor_id {
+            Some(vendor_id) => AvpKey::CodeAndVendor(code, vendor_id),
+            None => AvpKey::Code(code),
+        }
+    }
+}
+
+impl From<&str> for AvpKey {
+    fn from(name: &str) -> Self {
+        AvpKey::Name(name.to_string())
+    }
+}
+
+impl From<String> for AvpKey {
+    fn from(name: String) -> Self {
+        AvpKey::Name(name)
+    }
+}
+
+impl fmt::Display for AvpKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AvpKey::Code(code) => write!(f, "{}", code),
+            AvpKey::CodeAndVendor(code, vendor_id) => write!(f, "{}:{}", code, vendor_id),
+            AvpKey::Name(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +85,7 @@ impl Dictionary {
             avps: BTreeMap::new(),
             applications: HashMap::new(),
             commands: HashMap::new(),
+            command_applications: HashMap::new(),
         };
 
         for xml in xmls {
@@ -76,6 +125,36 @@ impl Dictionary {
         self.avps.values().find(|avp| avp.name == name)
     }
 
+    /// Returns the definition identified by `key`, resolving names if needed.
+    pub fn get_avp_by_key(&self, key: &AvpKey) -> Option<&AvpDefinition> {
+        match key {
+            AvpKey::Name(name) => self.get_avp_by_name(name),
+            _ => self.avps.get(key),
+        }
+    }
+
+    /// Resolves `key` to an AVP code and optional vendor ID.
+    ///
+    /// Codes are returned as is, names are looked up in the dictionary.
+    pub fn resolve_avp_key(&self, key: &AvpKey) -> Option<(u32, Option<u32>)> {
+        match key {
+            AvpKey::Code(code) => Some((*code, None)),
+            AvpKey::CodeAndVendor(code, vendor_id) => Some((*code, Some(*vendor_id))),
+            AvpKey::Name(name) => self
+                .get_avp_by_name(name)
+                .map(|avp| (avp.code, avp.vendor_id)),
+        }
+    }
+
+    /// Returns the error for an AVP identified by `key` missing from a
+    /// message: an unknown name is reported as such, rather than as absent.
+    pub(crate) fn avp_not_found(&self, key: &AvpKey) -> Error {
+        match (key, self.resolve_avp_key(key)) {
+            (AvpKey::Name(name), None) => Error::UnknownAvpName(name.clone()),
+            _ => Error::AvpNotFound(key.to_string()),
+        }
+    }
+
     pub fn get_avp_type(&self, code: u32, vendor_id: Option<u32>) -> Option<&AvpType> {
         let key = match vendor_id {
             Some(vendor_id) => AvpKey::CodeAndVendor(code, vendor_id),
@@ -105,6 +184,14 @@ impl Dictionary {
     pub fn get_command_code_by_name(&self, name: &str) -> Option<CommandCode> {
         self.commands.get(name).map(|code| *code)
     }
+
+    /// Returns the application that defines the command in the dictionary.
+    ///
+    /// Commands shared between applications, such as Re-Auth, are reported
+    /// with the application that was loaded last.
+    pub fn get_command_application(&self, code: CommandCode) -> Option<ApplicationId> {
+        self.command_applications.get(&code).copied()
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -189,6 +276,7 @@ pub fn parse(xml: &str, dictionary: &mut Dictionary) {
             let cmd_code = cmd.code.parse::<u32>().unwrap();
             let cmd_code: CommandCode = CommandCode::from_u32(cmd_code).unwrap();
             dictionary.commands.insert(cmd.name.clone(), cmd_code);
+            dictionary.command_applications.insert(cmd_code, app_id);
         });
 
         app.avps.iter().for_each(|avp| {
@@ -203,6 +291,8 @@ pub fn parse(xml: &str, dictionary: &mut Dictionary) {
                 "Grouped" => AvpType::Grouped,
                 "DiameterIdentity" => AvpType::Identity,
                 "DiameterURI" => AvpType::DiameterURI,
+                // Filter rules are ASCII strings
+                "IPFilterRule" | "QoSFilterRule" => AvpType::UTF8String,
                 "Time" => AvpType::Time,
                 "Address" => AvpType::Address,
                 "IPv4" => AvpType::AddressIPv4,
@@ -641,6 +731,10 @@ lazy_static! {
 			<data type="Grouped"/>
 		</avp>
 
+		<avp name="Filter-Id" code="11" must="M" may="P" must-not="V" may-encrypt="Y">
+			<data type="UTF8String"/>
+		</avp>
+
 		<avp name="Firmware-Revision" code="267" must="-" may="-" must-not="P,V,M" may-encrypt="-">
 			<data type="Unsigned32"/>
 		</avp>
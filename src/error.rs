@@ -9,6 +9,10 @@ pub enum Error {
     EncodeError(String),
     UnknownAvpCode(u32),
     UnknownAvpName(String),
+    UnknownCommand(String),
+    AvpNotFound(String),
+    InvalidAvpValue(String),
+    InvalidAvpPath(String),
     ClientError(String),
     ServerError(String),
     IoError(std::io::Error),
@@ -27,6 +31,10 @@ impl fmt::Display for Error {
             Error::EncodeError(msg) => write!(f, "{}", msg),
             Error::UnknownAvpCode(code) => write!(f, "Unknown AVP code: {}", code),
             Error::UnknownAvpName(name) => write!(f, "Unknown AVP name: {}", name),
+            Error::UnknownCommand(name) => write!(f, "Unknown command: {}", name),
+            Error::AvpNotFound(key) => write!(f, "AVP not found: {}", key),
+            Error::InvalidAvpValue(msg) => write!(f, "{}", msg),
+            Error::InvalidAvpPath(msg) => write!(f, "{}", msg),
             Error::ClientError(msg) => write!(f, "{}", msg),
             Error::ServerError(msg) => write!(f, "{}", msg),
             Error::IoError(e) => write!(f, "{}", e),
@@ -13,12 +13,22 @@
 //! [`server`]: https://github.com/lwlee2608/diameter-rs/blob/v0.7.1/examples/server.rs
 //! [`client`]: https://github.com/lwlee2608/diameter-rs/blob/v0.7.1/examples/client.rs
 
+pub mod applications;
 pub mod avp;
+pub mod builder;
 pub mod diameter;
 pub mod dictionary;
 pub mod error;
+pub mod node;
+pub mod routing;
+pub mod session;
 pub mod transport;
+pub mod typed;
 
+pub use crate::builder::{GroupBuilder, MessageBuilder};
 pub use crate::diameter::flags;
+pub use crate::diameter::result_code;
 pub use crate::diameter::{ApplicationId, CommandCode, DiameterHeader, DiameterMessage};
 pub use crate::error::{Error, Result};
+pub use crate::node::NodeConfig;
+pub use diameter_derive::{DiameterMessage, GroupedAvp};
@@ -0,0 +1,20 @@
+//! Local Diameter Node Configuration
+
+/// Identity of the local Diameter node.
+///
+/// Used to fill in Origin-Host and Origin-Realm on messages originated by this node.
+#[derive(Debug, Clone)]
+pub struct NodeConfig {
+    pub origin_host: String,
+    pub origin_realm: String,
+}
+
+impl NodeConfig {
+    /// Creates a new `NodeConfig` with the given Origin-Host and Origin-Realm.
+    pub fn new(origin_host: &str, origin_realm: &str) -> NodeConfig {
+        NodeConfig {
+            origin_host: origin_host.into(),
+            origin_realm: origin_realm.into(),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Diameter Routing
+//!
+//! Request routing by Diameter agents, as described in
+//! [RFC 6733 section 6.1](https://tools.ietf.org/html/rfc6733#section-6.1).
+//!
+//! * [`table`] - The realm routing table, selecting the action and the
+//!   peers of a request.
+//! * [`relay`] - A relay agent forwarding requests between peer
+//!   connections.
+//! * [`redirect`] - A redirect agent, and a client following the
+//!   redirections of its requests.
+
+pub mod redirect;
+pub mod relay;
+pub mod table;
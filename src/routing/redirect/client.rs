@@ -0,0 +1,347 @@
+//! Redirect-Following Client
+//!
+//! A [`RedirectClient`] sends requests over a [`DiameterClient`] and, when
+//! they are answered with DIAMETER_REDIRECT_INDICATION, resends them to the
+//! nodes of the Redirect-Host AVPs, connecting to them as needed. The
+//! redirections are kept in a [`RedirectCache`] as allowed by their
+//! Redirect-Host-Usage and Redirect-Max-Cache-Time, and the later requests
+//! they apply to are sent to the redirected nodes directly.
+//!
+//! ```no_run
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::routing::redirect::client::{RedirectClient, RedirectClientConfig};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::DiameterMessage;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("dra.example.com:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle(&mut handler, dict_ref).await;
+//!     });
+//!     let config = RedirectClientConfig::default();
+//!     let client = RedirectClient::new(client, Arc::clone(&dict), config);
+//!
+//!     let ccr = DiameterMessage::request("Credit-Control", dict)
+//!         .proxyable()
+//!         .avp("Session-Id", "pcef.example.com;1;1")
+//!         .avp("Origin-Host", "pcef.example.com")
+//!         .avp("Origin-Realm", "example.com")
+//!         .avp("Destination-Realm", "ocs.example.com")
+//!         .build()
+//!         .unwrap();
+//!     let cca = client.send(ccr).await.unwrap();
+//!     println!("{}", cca);
+//! }
+//! ```
+
+use super::{Redirect, RedirectCache, RedirectHost};
+use crate::diameter::DiameterMessage;
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::transport::{DiameterClient, DiameterClientConfig};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Configuration of a [`RedirectClient`].
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectClientConfig {
+    /// Number of redirections followed for a request, after which the
+    /// redirect indication is returned.
+    pub max_redirects: usize,
+    /// Time to wait for an answer.
+    pub tx_timeout: Duration,
+    /// Whether to verify the certificate of the `aaas` nodes.
+    pub verify_cert: bool,
+}
+
+impl Default for RedirectClientConfig {
+    fn default() -> RedirectClientConfig {
+        RedirectClientConfig {
+            max_redirects: 3,
+            tx_timeout: Duration::from_secs(10),
+            verify_cert: true,
+        }
+    }
+}
+
+type Connection = Arc<tokio::sync::Mutex<DiameterClient>>;
+
+/// A client following the redirections of its requests.
+#[derive(Clone)]
+pub struct RedirectClient {
+    client: Connection,
+    connections: Arc<tokio::sync::Mutex<HashMap<String, Connection>>>,
+    cache: Arc<Mutex<RedirectCache>>,
+    dict: Arc<Dictionary>,
+    config: RedirectClientConfig,
+}
+
+impl RedirectClient {
+    /// Creates a client sending requests over `client`, unless they are
+    /// redirected.
+    ///
+    /// The client must be connected, with its answers handled by
+    /// [`DiameterClient::handle`] or [`DiameterClient::handle_with_requests`].
+    pub fn new(
+        client: DiameterClient,
+        dict: Arc<Dictionary>,
+        config: RedirectClientConfig,
+    ) -> RedirectClient {
+        RedirectClient {
+            client: Arc::new(tokio::sync::Mutex::new(client)),
+            connections: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            cache: Arc::new(Mutex::new(RedirectCache::new())),
+            dict,
+            config,
+        }
+    }
+
+    /// Returns the number of cached redirections.
+    pub fn cached(&self) -> Result<usize> {
+        let mut cache = self.cache.lock()?;
+        cache.purge();
+        Ok(cache.len())
+    }
+
+    /// Removes the cached redirections.
+    pub fn clear_cache(&self) -> Result<()> {
+        self.cache.lock()?.clear();
+        Ok(())
+    }
+
+    /// Sends a request and returns its answer, following redirections.
+    ///
+    /// The request is given a Hop-by-Hop Identifier of each connection it
+    /// is sent over, and keeps its End-to-End Identifier.
+    pub async fn send(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let mut encoded = Vec::new();
+        req.encode_to(&mut encoded)?;
+
+        let mut hosts = self.cache.lock()?.lookup(&req);
+        let mut redirects = 0;
+        loop {
+            let connection = match &hosts {
+                Some(hosts) => self.connect(hosts).await?,
+                None => None,
+            };
+            let connection = connection.unwrap_or_else(|| Arc::clone(&self.client));
+            let res = self.send_over(&connection, self.copy(&encoded)?).await?;
+
+            let Some(redirect) = Redirect::from_answer(&res) else {
+                return Ok(res);
+            };
+            if redirects == self.config.max_redirects {
+                log::warn!("Too many redirections, last to {:?}", redirect.hosts);
+                return Ok(res);
+            }
+            redirects += 1;
+            log::debug!("Redirected to {:?}", redirect.hosts);
+            self.cache.lock()?.insert(&req, &redirect);
+            hosts = Some(redirect.hosts);
+        }
+    }
+
+    fn copy(&self, encoded: &[u8]) -> Result<DiameterMessage> {
+        DiameterMessage::decode_from(&mut Cursor::new(encoded), Arc::clone(&self.dict))
+    }
+
+    async fn send_over(
+        &self,
+        connection: &Connection,
+        mut req: DiameterMessage,
+    ) -> Result<DiameterMessage> {
+        let answer = {
+            let mut client = connection.lock().await;
+            req.set_hop_by_hop_id(client.get_next_seq_num());
+            client.send_message(req).await?
+        };
+        tokio::time::timeout(self.config.tx_timeout, answer)
+            .await
+            .map_err(|_| Error::ClientError("Timed out waiting for answer".into()))?
+    }
+
+    /// Returns a connection to the first of `hosts` that can be reached, if
+    /// any.
+    async fn connect(&self, hosts: &[RedirectHost]) -> Result<Option<Connection>> {
+        let mut connections = self.connections.lock().await;
+        for host in hosts {
+            let address = host.address();
+            if let Some(connection) = connections.get(&address) {
+                return Ok(Some(Arc::clone(connection)));
+            }
+            let config = DiameterClientConfig {
+                use_tls: host.tls,
+                verify_cert: self.config.verify_cert,
+            };
+            let mut client = DiameterClient::new(&address, config);
+            let mut handler = match client.connect().await {
+                Ok(handler) => handler,
+                Err(e) => {
+                    log::warn!("Failed to connect to {}; error: {}", address, e);
+                    continue;
+                }
+            };
+            let dict = Arc::clone(&self.dict);
+            tokio::spawn(async move {
+                DiameterClient::handle(&mut handler, dict).await;
+            });
+            let connection = Arc::new(tokio::sync::Mutex::new(client));
+            connections.insert(address, Arc::clone(&connection));
+            return Ok(Some(connection));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::credit_control::RedirectHostUsage;
+    use crate::diameter::result_code;
+    use crate::dictionary;
+    use crate::node::NodeConfig;
+    use crate::routing::redirect::server::{RedirectAgent, RedirectConfig};
+    use crate::routing::table::{Route, RouteAction, RoutingTable};
+    use crate::transport::{DiameterServer, DiameterServerConfig};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn redirect_server(port: u16, dict: &Arc<Dictionary>) -> Arc<AtomicUsize> {
+        let mut table = RoutingTable::new();
+        table.add(Route::new(
+            "ocs.example.com",
+            None,
+            RouteAction::Redirect,
+            &["aaa://localhost:1", "aaa://localhost:3896"],
+        ));
+        table.add(Route::new(
+            "example.net",
+            None,
+            RouteAction::Redirect,
+            &["aaa://localhost:3895"],
+        ));
+        let node = NodeConfig::new("redirect.example.com", "example.com");
+        let config = RedirectConfig {
+            redirect_host_usage: RedirectHostUsage::AllRealm,
+            redirect_max_cache_time: Some(60),
+        };
+        let agent = RedirectAgent::new(table, Arc::clone(dict), node, config);
+        serve(port, dict, move |req| agent.handle(req)).await
+    }
+
+    async fn serve<F>(port: u16, dict: &Arc<Dictionary>, handler: F) -> Arc<AtomicUsize>
+    where
+        F: Fn(DiameterMessage) -> Result<DiameterMessage> + Clone + Send + Sync + 'static,
+    {
+        let addr = format!("0.0.0.0:{}", port);
+        let mut server = DiameterServer::new(&addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_ref = Arc::clone(&received);
+        let dict = Arc::clone(dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| {
+                        received_ref.fetch_add(1, Ordering::Relaxed);
+                        let res = handler(req);
+                        async move { res }
+                    },
+                    dict,
+                )
+                .await
+                .unwrap();
+        });
+        received
+    }
+
+    fn ccr(dict: &Arc<Dictionary>, session_id: &str, realm: &str) -> DiameterMessage {
+        DiameterMessage::request("Credit-Control", Arc::clone(dict))
+            .proxyable()
+            .end_to_end_id(42)
+            .avp("Session-Id", session_id)
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Destination-Realm", realm)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_follow_redirect() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let redirected = redirect_server(3895, &dict).await;
+        let node = NodeConfig::new("ocs1.example.com", "ocs.example.com");
+        let answered = serve(3896, &dict, move |req| {
+            Ok(DiameterMessage::create_answer(
+                &req,
+                result_code::DIAMETER_SUCCESS,
+                &node,
+            ))
+        })
+        .await;
+
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3895", config);
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+        let config = RedirectClientConfig {
+            max_redirects: 2,
+            ..Default::default()
+        };
+        let client = RedirectClient::new(client, Arc::clone(&dict), config);
+
+        // Redirected to the reachable host
+        let res = client
+            .send(ccr(&dict, "pcef.example.com;1;1", "ocs.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_SUCCESS
+        );
+        assert_eq!(res.get_end_to_end_id(), 42);
+        assert_eq!(redirected.load(Ordering::Relaxed), 1);
+        assert_eq!(client.cached().unwrap(), 1);
+
+        // The cached redirection applies to the realm
+        let res = client
+            .send(ccr(&dict, "pcef.example.com;1;2", "ocs.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<String, _>("Origin-Host").unwrap(),
+            "ocs1.example.com"
+        );
+        assert_eq!(redirected.load(Ordering::Relaxed), 1);
+        assert_eq!(answered.load(Ordering::Relaxed), 2);
+
+        // A redirection loop stops after the maximum of redirections
+        let res = client
+            .send(ccr(&dict, "pcef.example.com;1;3", "example.net"))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_REDIRECT_INDICATION
+        );
+        assert_eq!(redirected.load(Ordering::Relaxed), 4);
+
+        client.clear_cache().unwrap();
+        assert_eq!(client.cached().unwrap(), 0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Diameter Redirection
+//!
+//! Redirect agents answer requests with DIAMETER_REDIRECT_INDICATION and
+//! the Redirect-Host AVPs of the nodes to send them to, as described in
+//! [RFC 6733 section 6.1.7](https://tools.ietf.org/html/rfc6733#section-6.1.7).
+//! The Redirect-Host-Usage and Redirect-Max-Cache-Time AVPs tell the
+//! requester which of its later requests may be sent to the same nodes, and
+//! for how long.
+//!
+//! * [`server`] - A redirect agent answering requests by routing table.
+//! * [`client`] - A client resending redirected requests, and caching the
+//!   redirections.
+//!
+//! ```
+//! use diameter::routing::redirect::RedirectHost;
+//!
+//! let host = RedirectHost::parse("aaa://ocs1.example.com:3869;transport=tcp").unwrap();
+//! assert_eq!(host.host, "ocs1.example.com");
+//! assert_eq!(host.address(), "ocs1.example.com:3869");
+//! assert!(!host.tls);
+//! ```
+
+pub mod client;
+pub mod server;
+
+use crate::applications::credit_control::RedirectHostUsage;
+use crate::diameter::{result_code, ApplicationId, DiameterMessage};
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Default port of the `aaa` scheme.
+const AAA_PORT: u16 = 3868;
+/// Default port of the `aaas` scheme.
+const AAAS_PORT: u16 = 5658;
+
+/// A node given by a Redirect-Host AVP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectHost {
+    /// Identity of the node.
+    pub host: String,
+    /// Port of the node.
+    pub port: u16,
+    /// True for the `aaas` scheme, whose connections use TLS.
+    pub tls: bool,
+}
+
+impl RedirectHost {
+    /// Parses a DiameterURI, as described in
+    /// [RFC 6733 section 4.3.1](https://tools.ietf.org/html/rfc6733#section-4.3.1).
+    /// A bare identity is accepted too, as an `aaa` URI.
+    pub fn parse(uri: &str) -> Result<RedirectHost> {
+        let (tls, rest) = match uri.split_once("://") {
+            Some(("aaa", rest)) => (false, rest),
+            Some(("aaas", rest)) => (true, rest),
+            Some((scheme, _)) => {
+                return Err(Error::DecodeError(format!(
+                    "Unsupported DiameterURI scheme: {}",
+                    scheme
+                )))
+            }
+            None => (false, uri),
+        };
+        let authority = rest.split(';').next().unwrap_or_default();
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| Error::DecodeError(format!("Invalid DiameterURI: {}", uri)))?;
+                (host, port)
+            }
+            None if tls => (authority, AAAS_PORT),
+            None => (authority, AAA_PORT),
+        };
+        if host.is_empty() {
+            return Err(Error::DecodeError(format!("Invalid DiameterURI: {}", uri)));
+        }
+        Ok(RedirectHost {
+            host: host.into(),
+            port,
+            tls,
+        })
+    }
+
+    /// Returns the address to connect to the node.
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// The redirection carried by a DIAMETER_REDIRECT_INDICATION answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    /// The Redirect-Host AVPs, by order of preference.
+    pub hosts: Vec<RedirectHost>,
+    /// Requests the redirection applies to, DONT_CACHE if absent.
+    pub usage: RedirectHostUsage,
+    /// Redirect-Max-Cache-Time, how long the redirection may be cached.
+    pub max_cache_time: Option<Duration>,
+}
+
+impl Redirect {
+    /// Returns the redirection of an answer, or `None` if the answer is not
+    /// a redirect indication with at least one valid Redirect-Host.
+    pub fn from_answer(res: &DiameterMessage) -> Option<Redirect> {
+        if res.get::<u32, _>("Result-Code").ok()? != result_code::DIAMETER_REDIRECT_INDICATION {
+            return None;
+        }
+        let hosts: Vec<RedirectHost> = res
+            .get_all::<String, _>("Redirect-Host")
+            .ok()?
+            .iter()
+            .filter_map(|uri| match RedirectHost::parse(uri) {
+                Ok(host) => Some(host),
+                Err(e) => {
+                    log::warn!("Ignoring Redirect-Host {}; error: {}", uri, e);
+                    None
+                }
+            })
+            .collect();
+        if hosts.is_empty() {
+            return None;
+        }
+        Some(Redirect {
+            hosts,
+            usage: res
+                .get("Redirect-Host-Usage")
+                .unwrap_or(RedirectHostUsage::DontCache),
+            max_cache_time: res
+                .get::<u32, _>("Redirect-Max-Cache-Time")
+                .ok()
+                .map(|secs| Duration::from_secs(u64::from(secs))),
+        })
+    }
+}
+
+/// The requests a cached redirection applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Session(String),
+    User(String),
+    RealmAndApplication(String, ApplicationId),
+    Realm(String),
+    Application(ApplicationId),
+    Host(String),
+}
+
+impl CacheKey {
+    /// Returns the key of the requests a redirection with `usage` applies
+    /// to, for a request.
+    fn new(usage: RedirectHostUsage, req: &DiameterMessage) -> Option<CacheKey> {
+        let realm = || {
+            req.get::<String, _>("Destination-Realm")
+                .ok()
+                .map(|realm| realm.to_ascii_lowercase())
+        };
+        match usage {
+            RedirectHostUsage::DontCache | RedirectHostUsage::Unknown(_) => None,
+            RedirectHostUsage::AllSession => req.get("Session-Id").ok().map(CacheKey::Session),
+            RedirectHostUsage::AllUser => req.get("User-Name").ok().map(CacheKey::User),
+            RedirectHostUsage::RealmAndApplication => {
+                realm().map(|realm| CacheKey::RealmAndApplication(realm, req.get_application_id()))
+            }
+            RedirectHostUsage::AllRealm => realm().map(CacheKey::Realm),
+            RedirectHostUsage::AllApplication => {
+                Some(CacheKey::Application(req.get_application_id()))
+            }
+            RedirectHostUsage::AllHost => req
+                .get::<String, _>("Destination-Host")
+                .ok()
+                .map(|host| CacheKey::Host(host.to_ascii_lowercase())),
+        }
+    }
+}
+
+/// Cache of the redirections received by a client, as described in
+/// [RFC 6733 section 6.13](https://tools.ietf.org/html/rfc6733#section-6.13).
+///
+/// When several cached redirections apply to a request, the most specific
+/// one is used, from ALL_SESSION, ALL_USER, REALM_AND_APPLICATION,
+/// ALL_REALM, ALL_APPLICATION to ALL_HOST.
+#[derive(Debug, Default)]
+pub struct RedirectCache {
+    entries: HashMap<CacheKey, (Vec<RedirectHost>, Instant)>,
+}
+
+impl RedirectCache {
+    /// Creates an empty cache.
+    pub fn new() -> RedirectCache {
+        RedirectCache::default()
+    }
+
+    /// Caches the redirection of `req`, unless its usage is DONT_CACHE or
+    /// it has no Redirect-Max-Cache-Time.
+    pub fn insert(&mut self, req: &DiameterMessage, redirect: &Redirect) {
+        let Some(max_cache_time) = redirect.max_cache_time else {
+            return;
+        };
+        if let Some(key) = CacheKey::new(redirect.usage, req) {
+            let expiry = Instant::now() + max_cache_time;
+            self.entries.insert(key, (redirect.hosts.clone(), expiry));
+        }
+    }
+
+    /// Returns the nodes a request is redirected to, if a cached
+    /// redirection applies to it.
+    pub fn lookup(&mut self, req: &DiameterMessage) -> Option<Vec<RedirectHost>> {
+        self.purge();
+        [
+            RedirectHostUsage::AllSession,
+            RedirectHostUsage::AllUser,
+            RedirectHostUsage::RealmAndApplication,
+            RedirectHostUsage::AllRealm,
+            RedirectHostUsage::AllApplication,
+            RedirectHostUsage::AllHost,
+        ]
+        .into_iter()
+        .filter_map(|usage| CacheKey::new(usage, req))
+        .find_map(|key| self.entries.get(&key).map(|(hosts, _)| hosts.clone()))
+    }
+
+    /// Removes the expired redirections.
+    pub fn purge(&mut self) {
+        let now = Instant::now();
+        self.entries.retain(|_, (_, expiry)| *expiry > now);
+    }
+
+    /// Returns the number of cached redirections, including expired ones not
+    /// purged yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no redirection is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all the redirections.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Dictionary};
+    use std::sync::Arc;
+
+    fn request(dict: &Arc<Dictionary>, session_id: &str, realm: &str) -> DiameterMessage {
+        DiameterMessage::request("Credit-Control", Arc::clone(dict))
+            .avp("Session-Id", session_id)
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Destination-Realm", realm)
+            .build()
+            .unwrap()
+    }
+
+    fn redirect(host: &str, usage: RedirectHostUsage, max_cache_time: u64) -> Redirect {
+        Redirect {
+            hosts: vec![RedirectHost::parse(host).unwrap()],
+            usage,
+            max_cache_time: Some(Duration::from_secs(max_cache_time)),
+        }
+    }
+
+    #[test]
+    fn test_parse_redirect_host() {
+        let host = RedirectHost::parse("aaas://ocs1.example.com;transport=tcp").unwrap();
+        assert_eq!(host.address(), "ocs1.example.com:5658");
+        assert!(host.tls);
+        let host = RedirectHost::parse("ocs2.example.com").unwrap();
+        assert_eq!(host.address(), "ocs2.example.com:3868");
+        assert!(RedirectHost::parse("http://ocs1.example.com").is_err());
+        assert!(RedirectHost::parse("aaa://ocs1.example.com:port").is_err());
+        assert!(RedirectHost::parse("aaa://").is_err());
+    }
+
+    #[test]
+    fn test_from_answer() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let res = DiameterMessage::answer("Credit-Control", Arc::clone(&dict))
+            .avp("Result-Code", result_code::DIAMETER_REDIRECT_INDICATION)
+            .avp("Redirect-Host", "aaa://ocs1.example.com")
+            .avp("Redirect-Host", "mailto:ocs@example.com")
+            .avp("Redirect-Host", "aaa://ocs2.example.com:3869")
+            .avp("Redirect-Host-Usage", RedirectHostUsage::AllRealm)
+            .avp("Redirect-Max-Cache-Time", 60)
+            .build()
+            .unwrap();
+        let redirect = Redirect::from_answer(&res).unwrap();
+        assert_eq!(redirect.hosts.len(), 2);
+        assert_eq!(redirect.hosts[1].address(), "ocs2.example.com:3869");
+        assert_eq!(redirect.usage, RedirectHostUsage::AllRealm);
+        assert_eq!(redirect.max_cache_time, Some(Duration::from_secs(60)));
+
+        let res = DiameterMessage::answer("Credit-Control", dict)
+            .avp("Result-Code", result_code::DIAMETER_UNABLE_TO_DELIVER)
+            .avp("Redirect-Host", "aaa://ocs1.example.com")
+            .build()
+            .unwrap();
+        assert!(Redirect::from_answer(&res).is_none());
+    }
+
+    #[test]
+    fn test_cache() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let mut cache = RedirectCache::new();
+        let req = request(&dict, "ses;1", "ocs.example.com");
+
+        cache.insert(
+            &req,
+            &redirect("ocs1.example.com", RedirectHostUsage::DontCache, 60),
+        );
+        let mut uncached = redirect("ocs1.example.com", RedirectHostUsage::AllRealm, 60);
+        uncached.max_cache_time = None;
+        cache.insert(&req, &uncached);
+        assert!(cache.is_empty());
+
+        cache.insert(
+            &req,
+            &redirect("ocs1.example.com", RedirectHostUsage::AllRealm, 60),
+        );
+        cache.insert(
+            &req,
+            &redirect("ocs2.example.com", RedirectHostUsage::AllSession, 60),
+        );
+        assert_eq!(cache.len(), 2);
+
+        // The session redirection applies before the realm one
+        let hosts = cache.lookup(&req).unwrap();
+        assert_eq!(hosts[0].host, "ocs2.example.com");
+        let hosts = cache
+            .lookup(&request(&dict, "ses;2", "OCS.example.com"))
+            .unwrap();
+        assert_eq!(hosts[0].host, "ocs1.example.com");
+        assert!(cache
+            .lookup(&request(&dict, "ses;2", "example.net"))
+            .is_none());
+
+        // Expired redirections no longer apply
+        cache.insert(
+            &req,
+            &redirect("ocs3.example.com", RedirectHostUsage::AllSession, 0),
+        );
+        assert_eq!(cache.lookup(&req).unwrap()[0].host, "ocs1.example.com");
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.lookup(&req).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Redirect Agent
+//!
+//! A [`RedirectAgent`] answers each request with DIAMETER_REDIRECT_INDICATION,
+//! listing the peers of its route in a [`RoutingTable`] as Redirect-Host,
+//! whatever the action of the route. Requests without a route are answered
+//! with DIAMETER_REALM_NOT_SERVED.
+//!
+//! ```no_run
+//! use diameter::applications::credit_control::RedirectHostUsage;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::routing::redirect::server::{RedirectAgent, RedirectConfig};
+//! use diameter::routing::table::{Route, RouteAction, RoutingTable};
+//! use diameter::transport::{DiameterServer, DiameterServerConfig};
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let node = NodeConfig::new("redirect.example.com", "example.com");
+//!     let mut table = RoutingTable::new();
+//!     table.add(Route::new(
+//!         "ocs.example.com",
+//!         None,
+//!         RouteAction::Redirect,
+//!         &["aaa://ocs1.example.com:3868", "aaa://ocs2.example.com:3868"],
+//!     ));
+//!     let config = RedirectConfig {
+//!         redirect_host_usage: RedirectHostUsage::AllRealm,
+//!         redirect_max_cache_time: Some(600),
+//!     };
+//!     let agent = RedirectAgent::new(table, Arc::clone(&dict), node, config);
+//!
+//!     let config = DiameterServerConfig { native_tls: None };
+//!     let mut server = DiameterServer::new("0.0.0.0:3868", config).await.unwrap();
+//!     agent.listen(&mut server).await.unwrap();
+//! }
+//! ```
+
+use crate::applications::credit_control::RedirectHostUsage;
+use crate::avp::{DiameterURI, Enumerated, Unsigned32};
+use crate::diameter::{result_code, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::routing::table::RoutingTable;
+use crate::transport::DiameterServer;
+use std::sync::{Arc, Mutex};
+
+/// The Redirect-Host-Usage and Redirect-Max-Cache-Time of the redirect
+/// indications sent by a node.
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectConfig {
+    /// Redirect-Host-Usage, sent unless DONT_CACHE.
+    pub redirect_host_usage: RedirectHostUsage,
+    /// Redirect-Max-Cache-Time in seconds, sent along a Redirect-Host-Usage.
+    pub redirect_max_cache_time: Option<u32>,
+}
+
+impl Default for RedirectConfig {
+    fn default() -> RedirectConfig {
+        RedirectConfig {
+            redirect_host_usage: RedirectHostUsage::DontCache,
+            redirect_max_cache_time: None,
+        }
+    }
+}
+
+/// Creates the DIAMETER_REDIRECT_INDICATION answer to `req`, redirecting
+/// it to `hosts`.
+///
+/// The hosts are DiameterURIs, or identities sent as `aaa` URIs.
+pub fn redirect_answer(
+    req: &DiameterMessage,
+    hosts: &[String],
+    config: &RedirectConfig,
+    node: &NodeConfig,
+) -> Result<DiameterMessage> {
+    let mut res = DiameterMessage::create_error_answer(
+        req,
+        result_code::DIAMETER_REDIRECT_INDICATION,
+        None,
+        node,
+    );
+    for host in hosts {
+        let uri = if host.contains("://") {
+            host.clone()
+        } else {
+            format!("aaa://{}", host)
+        };
+        res.add_avp_by_name("Redirect-Host", DiameterURI::new(uri.into_bytes()).into())?;
+    }
+    if config.redirect_host_usage != RedirectHostUsage::DontCache {
+        let usage = Enumerated::new(config.redirect_host_usage.into());
+        res.add_avp_by_name("Redirect-Host-Usage", usage.into())?;
+        if let Some(max_cache_time) = config.redirect_max_cache_time {
+            let max_cache_time = Unsigned32::new(max_cache_time);
+            res.add_avp_by_name("Redirect-Max-Cache-Time", max_cache_time.into())?;
+        }
+    }
+    Ok(res)
+}
+
+/// A Diameter redirect agent.
+#[derive(Clone)]
+pub struct RedirectAgent {
+    table: Arc<Mutex<RoutingTable>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: RedirectConfig,
+}
+
+impl RedirectAgent {
+    /// Creates an agent redirecting requests with `table` as `node`.
+    pub fn new(
+        table: RoutingTable,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: RedirectConfig,
+    ) -> RedirectAgent {
+        RedirectAgent {
+            table: Arc::new(Mutex::new(table)),
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Replaces the routing table, for the requests received from now on.
+    pub fn set_table(&self, table: RoutingTable) -> Result<()> {
+        *self.table.lock()? = table;
+        Ok(())
+    }
+
+    /// Answers the requests received by `server`.
+    pub async fn listen(&self, server: &mut DiameterServer) -> Result<()> {
+        server.set_node(self.node.clone());
+        let this = self.clone();
+        server
+            .listen(
+                move |req| {
+                    let this = this.clone();
+                    async move { this.handle(req) }
+                },
+                Arc::clone(&self.dict),
+            )
+            .await
+    }
+
+    /// Returns the answer to a request.
+    pub fn handle(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        if !req.is_request() {
+            return Err(Error::ServerError("Not a request".into()));
+        }
+        let hosts = match req.get::<String, _>("Destination-Realm") {
+            Ok(realm) => {
+                let table = self.table.lock()?;
+                table
+                    .lookup(&realm, req.get_application_id())
+                    .map(|route| route.peers.clone())
+            }
+            Err(_) => None,
+        };
+        match hosts {
+            Some(hosts) => redirect_answer(&req, &hosts, &self.config, &self.node),
+            None => Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_REALM_NOT_SERVED,
+                None,
+                &self.node,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary;
+    use crate::routing::redirect::Redirect;
+    use crate::routing::table::{Route, RouteAction};
+    use std::time::Duration;
+
+    #[test]
+    fn test_redirect_agent() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let node = NodeConfig::new("redirect.example.com", "example.com");
+        let mut table = RoutingTable::new();
+        table.add(Route::new(
+            "ocs.example.com",
+            None,
+            RouteAction::Redirect,
+            &["ocs1.example.com", "aaas://ocs2.example.com:5659"],
+        ));
+        let config = RedirectConfig {
+            redirect_host_usage: RedirectHostUsage::RealmAndApplication,
+            redirect_max_cache_time: Some(300),
+        };
+        let agent = RedirectAgent::new(table, Arc::clone(&dict), node, config);
+
+        let request = |realm: &str| {
+            DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+                .proxyable()
+                .hop_by_hop_id(7)
+                .avp("Session-Id", "pcef.example.com;1;1")
+                .avp("Origin-Host", "pcef.example.com")
+                .avp("Origin-Realm", "example.com")
+                .avp("Destination-Realm", realm)
+                .build()
+                .unwrap()
+        };
+        let res = agent.handle(request("ocs.example.com")).unwrap();
+        assert!(res.is_error());
+        assert_eq!(res.get_hop_by_hop_id(), 7);
+        let redirect = Redirect::from_answer(&res).unwrap();
+        let hosts: Vec<_> = redirect.hosts.iter().map(|host| host.address()).collect();
+        assert_eq!(
+            hosts,
+            vec!["ocs1.example.com:3868", "ocs2.example.com:5659"]
+        );
+        assert_eq!(redirect.usage, RedirectHostUsage::RealmAndApplication);
+        assert_eq!(redirect.max_cache_time, Some(Duration::from_secs(300)));
+
+        let res = agent.handle(request("example.net")).unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_REALM_NOT_SERVED
+        );
+    }
+}
@@ -0,0 +1,769 @@
+//! Diameter Relay Agent
+//!
+//! A [`RelayAgent`] routes the requests it receives with a
+//! [`RoutingTable`], as described in
+//! [RFC 6733 section 6.1](https://tools.ietf.org/html/rfc6733#section-6.1).
+//! Requests for the node itself are passed to a [`LocalHandler`], and
+//! requests routed to other peers are forwarded over their connection:
+//!
+//! * the connections opened by the agent with a [`DiameterClient`], added
+//!   with [`add_peer`](RelayAgent::add_peer), and
+//! * the connections accepted by the servers the agent listens on.
+//!
+//! A forwarded request gets a Hop-by-Hop Identifier of the outgoing
+//! connection and a Route-Record with the identity of the peer it came
+//! from: the peer given to [`handle_from`](RelayAgent::handle_from), or the
+//! Origin-Host of the request. Requests already forwarded by the agent are
+//! answered with DIAMETER_LOOP_DETECTED. The answer is sent back on the
+//! connection the request came from, with the original Hop-by-Hop
+//! Identifier and the Proxy-Info of the request.
+//!
+//! Requests carrying a Destination-Host are sent to that peer when it is
+//! connected. Other requests are sent to the first connected peer of their
+//! route, and to the next one, with the T flag set, when sending fails or
+//! the connection closes before the answer. They are answered with
+//! DIAMETER_UNABLE_TO_DELIVER when no peer is left. Requests without the P
+//! flag are never forwarded, but handled locally.
+//!
+//! ```no_run
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::routing::relay::{RelayAgent, RelayConfig};
+//! use diameter::routing::table::{Route, RouteAction, RoutingTable};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::transport::{DiameterServer, DiameterServerConfig};
+//! use diameter::{ApplicationId, NodeConfig};
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let node = NodeConfig::new("dra.example.com", "example.com");
+//!     let mut table = RoutingTable::new();
+//!     table.add(Route::new(
+//!         "ocs.example.com",
+//!         Some(ApplicationId::CreditControl),
+//!         RouteAction::Relay,
+//!         &["ocs1.example.com"],
+//!     ));
+//!     let relay = RelayAgent::new((), table, Arc::clone(&dict), node, RelayConfig::default());
+//!
+//!     // Connection to an OCS, whose requests are routed too
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("ocs1.example.com:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!     let relay_ref = relay.clone();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle_with_requests(&mut handler, dict_ref, move |req| {
+//!             let relay = relay_ref.clone();
+//!             async move { relay.handle_from(req, "ocs1.example.com").await }
+//!         })
+//!         .await;
+//!     });
+//!     relay.add_peer("ocs1.example.com", client).unwrap();
+//!
+//!     let config = DiameterServerConfig { native_tls: None };
+//!     let mut server = DiameterServer::new("0.0.0.0:3868", config).await.unwrap();
+//!     relay.listen(&mut server).await.unwrap();
+//! }
+//! ```
+
+use super::redirect::server::{redirect_answer, RedirectConfig};
+use super::table::{RouteAction, RoutingTable};
+use crate::diameter::{flags, result_code, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::client::ResponseFuture;
+use crate::transport::{DiameterClient, DiameterServer, ServerPeer, ServerPeers};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Handler of the requests routed to the local node by a [`RelayAgent`].
+///
+/// The unit type handles no request, for agents that only relay.
+pub trait LocalHandler: Send + Sync + 'static {
+    /// Handles a request routed to the local node, returning its answer.
+    /// Requests without an answer are answered with
+    /// DIAMETER_APPLICATION_UNSUPPORTED, and requests for which an error is
+    /// returned with DIAMETER_UNABLE_TO_COMPLY.
+    fn handle(
+        &self,
+        _req: &DiameterMessage,
+    ) -> impl Future<Output = Result<Option<DiameterMessage>>> + Send {
+        async { Ok(None) }
+    }
+}
+
+impl LocalHandler for () {}
+
+/// Configuration of a [`RelayAgent`].
+#[derive(Debug, Clone, Copy)]
+pub struct RelayConfig {
+    /// Time to wait for the answer to a forwarded request, after which the
+    /// request is answered with DIAMETER_UNABLE_TO_DELIVER.
+    pub tx_timeout: Duration,
+    /// Redirect-Host-Usage and Redirect-Max-Cache-Time of the requests
+    /// answered for redirect routes.
+    pub redirect: RedirectConfig,
+}
+
+impl Default for RelayConfig {
+    fn default() -> RelayConfig {
+        RelayConfig {
+            tx_timeout: Duration::from_secs(10),
+            redirect: RedirectConfig::default(),
+        }
+    }
+}
+
+/// A connection a request can be forwarded over.
+enum Connection {
+    Client(Arc<tokio::sync::Mutex<DiameterClient>>),
+    Server(ServerPeer),
+}
+
+impl Connection {
+    /// Sends a request with a Hop-by-Hop Identifier of the connection.
+    async fn send(&self, mut req: DiameterMessage) -> Result<ResponseFuture> {
+        match self {
+            Connection::Client(client) => {
+                let mut client = client.lock().await;
+                req.set_hop_by_hop_id(client.get_next_seq_num());
+                client.send_message(req).await
+            }
+            Connection::Server(peer) => {
+                req.set_hop_by_hop_id(peer.get_next_seq_num());
+                peer.send_message(req).await
+            }
+        }
+    }
+}
+
+/// What to do with a request.
+enum Decision {
+    Local,
+    Forward(Vec<String>),
+    Redirect(Vec<String>),
+    Reject(u32),
+}
+
+/// A Diameter relay agent, forwarding requests between peer connections.
+pub struct RelayAgent<H> {
+    handler: Arc<H>,
+    table: Arc<Mutex<RoutingTable>>,
+    clients: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<DiameterClient>>>>>,
+    server_peers: Arc<Mutex<Vec<ServerPeers>>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: RelayConfig,
+}
+
+impl<H> Clone for RelayAgent<H> {
+    fn clone(&self) -> Self {
+        RelayAgent {
+            handler: Arc::clone(&self.handler),
+            table: Arc::clone(&self.table),
+            clients: Arc::clone(&self.clients),
+            server_peers: Arc::clone(&self.server_peers),
+            dict: Arc::clone(&self.dict),
+            node: self.node.clone(),
+            config: self.config,
+        }
+    }
+}
+
+impl<H: LocalHandler> RelayAgent<H> {
+    /// Creates an agent routing requests with `table` as `node`.
+    pub fn new(
+        handler: H,
+        table: RoutingTable,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: RelayConfig,
+    ) -> RelayAgent<H> {
+        RelayAgent {
+            handler: Arc::new(handler),
+            table: Arc::new(Mutex::new(table)),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            server_peers: Arc::new(Mutex::new(Vec::new())),
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Returns a copy of the routing table.
+    pub fn table(&self) -> Result<RoutingTable> {
+        Ok(self.table.lock()?.clone())
+    }
+
+    /// Replaces the routing table, for the requests received from now on.
+    pub fn set_table(&self, table: RoutingTable) -> Result<()> {
+        *self.table.lock()? = table;
+        Ok(())
+    }
+
+    /// Adds the connection of a client to the peer identified by
+    /// `origin_host`, replacing its previous connection.
+    ///
+    /// The client must be connected, with its answers handled by
+    /// [`DiameterClient::handle`] or [`DiameterClient::handle_with_requests`].
+    pub fn add_peer(&self, origin_host: &str, client: DiameterClient) -> Result<()> {
+        let client = Arc::new(tokio::sync::Mutex::new(client));
+        self.clients.lock()?.insert(origin_host.into(), client);
+        Ok(())
+    }
+
+    /// Removes the client connection of a peer, returning true if there was
+    /// one.
+    pub fn remove_peer(&self, origin_host: &str) -> Result<bool> {
+        Ok(self.clients.lock()?.remove(origin_host).is_some())
+    }
+
+    /// Routes the requests received by `server`, forwarding requests to the
+    /// peers connected to it too.
+    pub async fn listen(&self, server: &mut DiameterServer) -> Result<()> {
+        server.set_node(self.node.clone());
+        self.server_peers.lock()?.push(server.peers());
+        let this = self.clone();
+        server
+            .listen(
+                move |req| {
+                    let this = this.clone();
+                    async move { this.handle(req).await }
+                },
+                Arc::clone(&self.dict),
+            )
+            .await
+    }
+
+    /// Routes a request, returning the answer to send back.
+    ///
+    /// A request without Origin-Host is answered with DIAMETER_MISSING_AVP.
+    pub async fn handle(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let Ok(from) = req.get::<String, _>("Origin-Host") else {
+            return Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_MISSING_AVP,
+                None,
+                &self.node,
+            ));
+        };
+        self.handle_from(req, &from).await
+    }
+
+    /// Routes a request received from the peer identified by `from`, such
+    /// as a request of a peer added with [`add_peer`](RelayAgent::add_peer),
+    /// returning the answer to send back.
+    ///
+    /// A request which cannot be routed is answered with
+    /// DIAMETER_UNABLE_TO_COMPLY.
+    pub async fn handle_from(&self, req: DiameterMessage, from: &str) -> Result<DiameterMessage> {
+        if !req.is_request() {
+            return Err(Error::ServerError("Not a request".into()));
+        }
+        let unable_to_comply = DiameterMessage::create_error_answer(
+            &req,
+            result_code::DIAMETER_UNABLE_TO_COMPLY,
+            None,
+            &self.node,
+        );
+        match self.route(req, from).await {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                log::error!("Failed to route request; error: {}", e);
+                Ok(unable_to_comply)
+            }
+        }
+    }
+
+    async fn route(&self, req: DiameterMessage, from: &str) -> Result<DiameterMessage> {
+        match self.decide(&req)? {
+            Decision::Local => self.local(req).await,
+            Decision::Forward(peers) => self.forward(req, from, &peers).await,
+            Decision::Redirect(peers) => {
+                redirect_answer(&req, &peers, &self.config.redirect, &self.node)
+            }
+            Decision::Reject(result_code) => {
+                log::warn!(
+                    "Rejected {:?} request, Result-Code: {}",
+                    req.get_command_code(),
+                    result_code
+                );
+                Ok(DiameterMessage::create_error_answer(
+                    &req,
+                    result_code,
+                    None,
+                    &self.node,
+                ))
+            }
+        }
+    }
+
+    fn decide(&self, req: &DiameterMessage) -> Result<Decision> {
+        let destination_host = req.get::<String, _>("Destination-Host").ok();
+        if let Some(host) = &destination_host {
+            if host.eq_ignore_ascii_case(&self.node.origin_host) {
+                return Ok(Decision::Local);
+            }
+        }
+        // Requests without Destination-Realm are for the peer they are sent to
+        let Ok(realm) = req.get::<String, _>("Destination-Realm") else {
+            return Ok(Decision::Local);
+        };
+
+        let table = self.table.lock()?;
+        let Some(route) = table.lookup(&realm, req.get_application_id()) else {
+            if realm.eq_ignore_ascii_case(&self.node.origin_realm) {
+                return Ok(Decision::Local);
+            }
+            return Ok(Decision::Reject(result_code::DIAMETER_REALM_NOT_SERVED));
+        };
+        Ok(match route.action {
+            RouteAction::Local => Decision::Local,
+            RouteAction::Redirect => Decision::Redirect(route.peers.clone()),
+            RouteAction::Relay | RouteAction::Proxy => {
+                let peers = destination_host
+                    .into_iter()
+                    .chain(route.peers.iter().cloned())
+                    .collect();
+                Decision::Forward(peers)
+            }
+        })
+    }
+
+    async fn local(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let result_code = match self.handler.handle(&req).await {
+            Ok(Some(res)) => return Ok(res),
+            Ok(None) => result_code::DIAMETER_APPLICATION_UNSUPPORTED,
+            Err(e) => {
+                log::error!(
+                    "Failed to handle {:?}; error: {}",
+                    req.get_command_code(),
+                    e
+                );
+                result_code::DIAMETER_UNABLE_TO_COMPLY
+            }
+        };
+        Ok(DiameterMessage::create_error_answer(
+            &req,
+            result_code,
+            None,
+            &self.node,
+        ))
+    }
+
+    /// Returns the connection of a peer.
+    fn connection(&self, origin_host: &str) -> Result<Option<Connection>> {
+        if let Some(client) = self.clients.lock()?.get(origin_host) {
+            return Ok(Some(Connection::Client(Arc::clone(client))));
+        }
+        let server_peers = self.server_peers.lock()?;
+        Ok(server_peers
+            .iter()
+            .find_map(|peers| peers.get_by_origin_host(origin_host))
+            .map(Connection::Server))
+    }
+
+    /// Forwards a request to the first of `peers` it can be sent to.
+    ///
+    /// Requests without the P flag are handled locally, as described in
+    /// [RFC 6733 section 6.1.8](https://tools.ietf.org/html/rfc6733#section-6.1.8).
+    async fn forward(
+        &self,
+        mut req: DiameterMessage,
+        from: &str,
+        peers: &[String],
+    ) -> Result<DiameterMessage> {
+        if req.get_flags() & flags::PROXYABLE == 0 {
+            log::debug!(
+                "Handling non-proxyable {:?} request locally",
+                req.get_command_code()
+            );
+            return self.local(req).await;
+        }
+        if let Some(res) = req.check_loop(&self.node) {
+            log::warn!(
+                "Loop detected; Route-Record: {:?}",
+                req.get_all::<String, _>("Route-Record")
+            );
+            return Ok(res);
+        }
+        let undeliverable = DiameterMessage::create_error_answer(
+            &req,
+            result_code::DIAMETER_UNABLE_TO_DELIVER,
+            None,
+            &self.node,
+        );
+        let hop_by_hop_id = req.get_hop_by_hop_id();
+        let proxy_info = req.proxy_info();
+        req.add_route_record(from);
+        let mut encoded = Vec::new();
+        req.encode_to(&mut encoded)?;
+
+        // Tries the next connected peer when the request cannot be sent, or
+        // the connection fails before the answer
+        let mut retransmit = false;
+        for peer in peers {
+            let Some(connection) = self.connection(peer)? else {
+                continue;
+            };
+            let mut req =
+                DiameterMessage::decode_from(&mut Cursor::new(&encoded), Arc::clone(&self.dict))?;
+            if retransmit {
+                req.set_flags(req.get_flags() | flags::RETRANSMIT);
+            }
+            log::debug!(
+                "Forwarding {:?} request to {}",
+                req.get_command_code(),
+                peer
+            );
+            let answer = match connection.send(req).await {
+                Ok(answer) => tokio::time::timeout(self.config.tx_timeout, answer).await,
+                Err(e) => {
+                    log::error!("Failed to forward request to {}; error: {}", peer, e);
+                    retransmit = true;
+                    continue;
+                }
+            };
+            match answer {
+                Ok(Ok(mut res)) => {
+                    res.set_hop_by_hop_id(hop_by_hop_id);
+                    res.set_proxy_info(proxy_info);
+                    return Ok(res);
+                }
+                Ok(Err(e)) => {
+                    log::error!("No answer from {}; error: {}", peer, e);
+                    retransmit = true;
+                }
+                Err(_) => {
+                    log::warn!("Timed out waiting for the answer of {}", peer);
+                    return Ok(undeliverable);
+                }
+            }
+        }
+        log::warn!("No connected peer among {:?}", peers);
+        Ok(undeliverable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::credit_control::CcRequestType;
+    use crate::avp::Enumerated;
+    use crate::builder::GroupBuilder;
+    use crate::diameter::ApplicationId;
+    use crate::dictionary;
+    use crate::routing::table::Route;
+    use crate::transport::{DiameterClientConfig, DiameterServerConfig};
+
+    struct Echo {
+        node: NodeConfig,
+    }
+
+    impl LocalHandler for Echo {
+        async fn handle(&self, req: &DiameterMessage) -> Result<Option<DiameterMessage>> {
+            if req.get_application_id() == ApplicationId::Gx {
+                return Ok(None);
+            }
+            let res =
+                DiameterMessage::create_answer(req, result_code::DIAMETER_SUCCESS, &self.node);
+            Ok(Some(res))
+        }
+    }
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    fn table() -> RoutingTable {
+        let mut table = RoutingTable::new();
+        table.add(Route::new(
+            "ocs.example.com",
+            Some(ApplicationId::CreditControl),
+            RouteAction::Relay,
+            &["ocs1.example.com", "ocs2.example.com"],
+        ));
+        table.add(Route::new(
+            "example.com",
+            None,
+            RouteAction::Relay,
+            &["pcef.example.com"],
+        ));
+        table.add(Route::new(
+            "example.org",
+            None,
+            RouteAction::Redirect,
+            &["dra.example.org"],
+        ));
+        table
+    }
+
+    fn ccr(dict: &Arc<Dictionary>, hop_by_hop_id: u32, realm: &str) -> DiameterMessage {
+        DiameterMessage::request("Credit-Control", Arc::clone(dict))
+            .proxyable()
+            .hop_by_hop_id(hop_by_hop_id)
+            .end_to_end_id(hop_by_hop_id)
+            .avp("Session-Id", "pcef.example.com;1;1")
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Destination-Realm", realm)
+            .avp("Auth-Application-Id", 4)
+            .avp(
+                "CC-Request-Type",
+                Enumerated::new(CcRequestType::InitialRequest.into()),
+            )
+            .avp("CC-Request-Number", 0)
+            .build()
+            .unwrap()
+    }
+
+    fn client(port: u16) -> DiameterClient {
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        DiameterClient::new(&format!("localhost:{}", port), config)
+    }
+
+    #[tokio::test]
+    async fn test_routing_decisions() {
+        let dict = dict();
+        let node = NodeConfig::new("dra.example.com", "dra.example.com");
+        let echo = Echo { node: node.clone() };
+        let relay = RelayAgent::new(
+            echo,
+            table(),
+            Arc::clone(&dict),
+            node,
+            RelayConfig::default(),
+        );
+
+        // Unknown realm
+        let res = relay.handle(ccr(&dict, 1, "example.net")).await.unwrap();
+        assert!(res.is_error());
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_REALM_NOT_SERVED
+        );
+
+        // Own realm, handled locally
+        let res = relay
+            .handle(ccr(&dict, 2, "dra.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_SUCCESS
+        );
+        assert_eq!(res.get_hop_by_hop_id(), 2);
+
+        // No local application
+        let mut req = ccr(&dict, 3, "dra.example.com");
+        req.set_application_id(ApplicationId::Gx);
+        let res = relay.handle(req).await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_APPLICATION_UNSUPPORTED
+        );
+
+        // No connected peer
+        let res = relay
+            .handle(ccr(&dict, 4, "ocs.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_UNABLE_TO_DELIVER
+        );
+        assert_eq!(res.get_hop_by_hop_id(), 4);
+
+        // A non-proxyable request is not forwarded
+        let mut req = ccr(&dict, 7, "ocs.example.com");
+        req.set_flags(req.get_flags() & !flags::PROXYABLE);
+        let res = relay.handle(req).await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_SUCCESS
+        );
+
+        // Malformed request
+        let mut req = ccr(&dict, 8, "ocs.example.com");
+        req.remove_avps("Origin-Host");
+        let res = relay.handle(req).await.unwrap();
+        assert!(res.is_error());
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_MISSING_AVP
+        );
+
+        // Request already forwarded by the agent
+        let mut req = ccr(&dict, 6, "ocs.example.com");
+        req.add_route_record("dra.example.com");
+        req.add_route_record("dra.example.net");
+        let res = relay.handle_from(req, "dra.example.net").await.unwrap();
+        assert!(res.is_error());
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_LOOP_DETECTED
+        );
+
+        let res = relay.handle(ccr(&dict, 5, "example.org")).await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_REDIRECT_INDICATION
+        );
+        assert_eq!(
+            res.get::<String, _>("Redirect-Host").unwrap(),
+            "aaa://dra.example.org"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_relay() {
+        let dict = dict();
+        type Received = Arc<Mutex<Vec<(u32, Vec<String>)>>>;
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+
+        // OCS recording the requests it receives
+        let mut ocs_server =
+            DiameterServer::new("0.0.0.0:3893", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let ocs_peers = ocs_server.peers();
+        let received_ref = Arc::clone(&received);
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            ocs_server
+                .listen(
+                    move |req| {
+                        let received = Arc::clone(&received_ref);
+                        async move {
+                            let route_records = req.get_all("Route-Record")?;
+                            let hop_by_hop_id = req.get_hop_by_hop_id();
+                            received.lock()?.push((hop_by_hop_id, route_records));
+                            let node = NodeConfig::new("ocs1.example.com", "ocs.example.com");
+                            let mut res = DiameterMessage::create_answer(
+                                &req,
+                                result_code::DIAMETER_SUCCESS,
+                                &node,
+                            );
+                            // Proxy-Info dropped by the server
+                            res.remove_avps("Proxy-Info");
+                            Ok(res)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        // Relay connected to the OCS
+        let node = NodeConfig::new("dra.example.com", "dra.example.com");
+        let relay = RelayAgent::new((), table(), Arc::clone(&dict), node, RelayConfig::default());
+        let mut ocs_client = client(3893);
+        let mut handler = ocs_client.connect().await.unwrap();
+        let relay_ref = relay.clone();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle_with_requests(&mut handler, dict_ref, move |req| {
+                let relay = relay_ref.clone();
+                async move { relay.handle_from(req, "ocs2.example.com").await }
+            })
+            .await;
+        });
+        relay.add_peer("ocs2.example.com", ocs_client).unwrap();
+        // A peer whose connection fails, tried first
+        relay.add_peer("ocs1.example.com", client(1)).unwrap();
+        let mut server =
+            DiameterServer::new("0.0.0.0:3894", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let relay_ref = relay.clone();
+        tokio::spawn(async move {
+            relay_ref.listen(&mut server).await.unwrap();
+        });
+
+        // PCEF connected to the relay, answering RARs
+        let mut pcef = client(3894);
+        let mut handler = pcef.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle_with_requests(&mut handler, dict_ref, |req| async move {
+                let node = NodeConfig::new("pcef.example.com", "example.com");
+                Ok(DiameterMessage::create_answer(
+                    &req,
+                    result_code::DIAMETER_SUCCESS,
+                    &node,
+                ))
+            })
+            .await;
+        });
+
+        let mut req = ccr(&dict, 1000, "ocs.example.com");
+        let proxy_info = GroupBuilder::new(Arc::clone(&dict))
+            .avp("Proxy-Host", "pcef.example.com")
+            .avp("Proxy-State", b"state".to_vec())
+            .build()
+            .unwrap();
+        req.add_avp_by_name("Proxy-Info", proxy_info.into())
+            .unwrap();
+        let res = pcef.send_message(req).await.unwrap().await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_SUCCESS
+        );
+        assert_eq!(
+            res.get::<String, _>("Origin-Host").unwrap(),
+            "ocs1.example.com"
+        );
+        assert_eq!(res.get_hop_by_hop_id(), 1000);
+        assert_eq!(res.get_end_to_end_id(), 1000);
+        assert_eq!(
+            res.query_values::<Vec<u8>>("Proxy-Info/Proxy-State")
+                .unwrap(),
+            vec![b"state".to_vec()]
+        );
+        {
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 1);
+            assert_ne!(received[0].0, 1000);
+            assert_eq!(received[0].1, vec!["pcef.example.com"]);
+        }
+
+        // A request of the OCS is routed back to the PCEF
+        let ocs_peer = ocs_peers.get_by_origin_host("pcef.example.com").unwrap();
+        let rar = DiameterMessage::request("Re-Auth", Arc::clone(&dict))
+            .application(ApplicationId::CreditControl)
+            .proxyable()
+            .hop_by_hop_id(ocs_peer.get_next_seq_num())
+            .end_to_end_id(77)
+            .avp("Session-Id", "pcef.example.com;1;1")
+            .avp("Origin-Host", "ocs1.example.com")
+            .avp("Origin-Realm", "ocs.example.com")
+            .avp("Destination-Realm", "example.com")
+            .avp("Destination-Host", "pcef.example.com")
+            .avp("Auth-Application-Id", 4)
+            .avp("Re-Auth-Request-Type", Enumerated::new(0))
+            .build()
+            .unwrap();
+        let hop_by_hop_id = rar.get_hop_by_hop_id();
+        let res = ocs_peer.send_message(rar).await.unwrap().await.unwrap();
+        assert_eq!(
+            res.get::<u32, _>("Result-Code").unwrap(),
+            result_code::DIAMETER_SUCCESS
+        );
+        assert_eq!(
+            res.get::<String, _>("Origin-Host").unwrap(),
+            "pcef.example.com"
+        );
+        assert_eq!(res.get_hop_by_hop_id(), hop_by_hop_id);
+        assert_eq!(res.get_end_to_end_id(), 77);
+    }
+}
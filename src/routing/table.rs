@@ -0,0 +1,191 @@
+//! Realm Routing Table
+//!
+//! The realm-based routing table of
+//! [RFC 6733 section 2.7](https://tools.ietf.org/html/rfc6733#section-2.7),
+//! mapping the Destination-Realm and the application of a request to the
+//! action taken by the node and the peers the request is sent to.
+//!
+//! A route applies to a realm and either one application or all of them.
+//! The realm `*` matches any realm, and serves as the default route. The
+//! most specific route is selected: a route for the realm and application,
+//! then a route for the realm, then the default routes.
+//!
+//! ```
+//! use diameter::routing::table::{Route, RouteAction, RoutingTable};
+//! use diameter::ApplicationId;
+//!
+//! let mut table = RoutingTable::new();
+//! table.add(Route::new(
+//!     "ocs.example.com",
+//!     Some(ApplicationId::CreditControl),
+//!     RouteAction::Relay,
+//!     &["ocs1.example.com", "ocs2.example.com"],
+//! ));
+//! table.add(Route::new("example.com", None, RouteAction::Local, &[]));
+//!
+//! let route = table.lookup("OCS.example.com", ApplicationId::CreditControl).unwrap();
+//! assert_eq!(route.action, RouteAction::Relay);
+//! assert!(table.lookup("ocs.example.com", ApplicationId::Gx).is_none());
+//! ```
+
+use crate::diameter::ApplicationId;
+
+/// Realm matching any Destination-Realm.
+pub const ANY_REALM: &str = "*";
+
+/// Action taken for the requests matching a route, as described in
+/// [RFC 6733 section 2.7](https://tools.ietf.org/html/rfc6733#section-2.7).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteAction {
+    /// The request is processed by the node.
+    Local,
+    /// The request is forwarded to one of the peers of the route.
+    Relay,
+    /// The request is forwarded to one of the peers of the route, by a node
+    /// that may apply policies to it.
+    Proxy,
+    /// The request is answered with DIAMETER_REDIRECT_INDICATION, listing
+    /// the peers of the route as Redirect-Host.
+    Redirect,
+}
+
+/// An entry of the routing table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// Destination-Realm of the route, or `*` for any realm.
+    pub realm: String,
+    /// Application of the route, or `None` for all applications.
+    pub application_id: Option<ApplicationId>,
+    /// Action taken for the matching requests.
+    pub action: RouteAction,
+    /// Identities of the peers of the route, by order of preference.
+    pub peers: Vec<String>,
+}
+
+impl Route {
+    /// Creates a route.
+    pub fn new(
+        realm: &str,
+        application_id: Option<ApplicationId>,
+        action: RouteAction,
+        peers: &[&str],
+    ) -> Route {
+        Route {
+            realm: realm.into(),
+            application_id,
+            action,
+            peers: peers.iter().map(|peer| peer.to_string()).collect(),
+        }
+    }
+
+    fn matches(&self, realm: &str, application_id: Option<ApplicationId>) -> bool {
+        self.realm.eq_ignore_ascii_case(realm) && self.application_id == application_id
+    }
+}
+
+/// A realm-based routing table.
+#[derive(Debug, Clone, Default)]
+pub struct RoutingTable {
+    routes: Vec<Route>,
+}
+
+impl RoutingTable {
+    /// Creates an empty table.
+    pub fn new() -> RoutingTable {
+        RoutingTable::default()
+    }
+
+    /// Adds a route, replacing the route for the same realm and application.
+    pub fn add(&mut self, route: Route) {
+        self.remove(&route.realm, route.application_id);
+        self.routes.push(route);
+    }
+
+    /// Removes the route for a realm and application.
+    pub fn remove(&mut self, realm: &str, application_id: Option<ApplicationId>) -> Option<Route> {
+        let index = self
+            .routes
+            .iter()
+            .position(|route| route.matches(realm, application_id))?;
+        Some(self.routes.remove(index))
+    }
+
+    /// Returns the route of the requests of an application to a realm.
+    pub fn lookup(&self, realm: &str, application_id: ApplicationId) -> Option<&Route> {
+        [
+            (realm, Some(application_id)),
+            (realm, None),
+            (ANY_REALM, Some(application_id)),
+            (ANY_REALM, None),
+        ]
+        .into_iter()
+        .find_map(|(realm, application_id)| {
+            self.routes
+                .iter()
+                .find(|route| route.matches(realm, application_id))
+        })
+    }
+
+    /// Returns the routes of the table.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut table = RoutingTable::new();
+        table.add(Route::new(
+            ANY_REALM,
+            None,
+            RouteAction::Relay,
+            &["dra.example.net"],
+        ));
+        table.add(Route::new(
+            ANY_REALM,
+            Some(ApplicationId::Accounting),
+            RouteAction::Redirect,
+            &["acct.example.net"],
+        ));
+        table.add(Route::new("example.com", None, RouteAction::Local, &[]));
+        table.add(Route::new(
+            "example.com",
+            Some(ApplicationId::CreditControl),
+            RouteAction::Proxy,
+            &["ocs1.example.com"],
+        ));
+
+        let lookup = |realm, application_id| table.lookup(realm, application_id).unwrap().action;
+        assert_eq!(
+            lookup("example.com", ApplicationId::CreditControl),
+            RouteAction::Proxy
+        );
+        assert_eq!(lookup("Example.COM", ApplicationId::Gx), RouteAction::Local);
+        assert_eq!(
+            lookup("example.org", ApplicationId::Accounting),
+            RouteAction::Redirect
+        );
+        assert_eq!(lookup("example.org", ApplicationId::Gx), RouteAction::Relay);
+
+        // A route replaces the route for the same realm and application
+        table.add(Route::new(
+            "EXAMPLE.com",
+            Some(ApplicationId::CreditControl),
+            RouteAction::Relay,
+            &["ocs2.example.com"],
+        ));
+        assert_eq!(table.routes().len(), 4);
+        let route = table
+            .lookup("example.com", ApplicationId::CreditControl)
+            .unwrap();
+        assert_eq!(route.peers, vec!["ocs2.example.com"]);
+
+        assert!(table.remove(ANY_REALM, None).is_some());
+        assert!(table.remove(ANY_REALM, None).is_none());
+        assert!(table.lookup("example.org", ApplicationId::Gx).is_none());
+    }
+}
@@ -0,0 +1,710 @@
+//! Authorization Client Sessions
+//!
+//! The client side of the authorization session state machines of
+//! [RFC 6733 section 8.1](https://tools.ietf.org/html/rfc6733#section-8.1),
+//! running on top of a [`DiameterClient`] connection.
+//!
+//! An [`AuthClient`] sends the service-specific authorization requests of
+//! an application and follows the answers of the server: a successful
+//! answer opens the session, stateful unless it carries an
+//! Auth-Session-State of NO_STATE_MAINTAINED, and starts its
+//! Session-Timeout and Authorization-Lifetime timers. When the
+//! Authorization-Lifetime elapses the application is asked to re-authorize
+//! the session, which is terminated if the Auth-Grace-Period ends first.
+//! Stateful sessions are terminated with an STR, and the ASRs received from
+//! the server are answered after asking the [`AuthClientHandler`].
+//!
+//! ```no_run
+//! use diameter::applications::common::{AuthRequestType, TerminationCause};
+//! use diameter::applications::nasreq::{self, AaRequest};
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::session::auth::client::{AuthClient, AuthClientConfig, AuthClientHandler};
+//! use diameter::session::auth::AuthTimer;
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::typed::TypedMessage;
+//! use diameter::NodeConfig;
+//! use std::sync::Arc;
+//!
+//! struct Nas;
+//!
+//! impl AuthClientHandler for Nas {
+//!     async fn session_expired(&self, session_id: &str, timer: AuthTimer) {
+//!         println!("{} expired by {:?}, disconnecting the user", session_id, timer);
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, nasreq::DICT_XML]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("localhost:3868", config);
+//!     let mut handler = client.connect().await.unwrap();
+//!
+//!     let node = NodeConfig::new("nas.example.com", "example.com");
+//!     let config = AuthClientConfig::default();
+//!     let auth_client = AuthClient::new(Nas, client, Arc::clone(&dict), node.clone(), config);
+//!
+//!     // Answer the ASRs of the server
+//!     let auth_client_ref = auth_client.clone();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle_with_requests(&mut handler, dict_ref, move |req| {
+//!             let auth_client = auth_client_ref.clone();
+//!             async move { auth_client.handle_request(req).await }
+//!         })
+//!         .await;
+//!     });
+//!
+//!     let session_id = "nas.example.com;1;1";
+//!     let mut aar = AaRequest::new(
+//!         session_id,
+//!         &node,
+//!         "example.com",
+//!         AuthRequestType::AuthorizeOnly,
+//!     );
+//!     aar.user_name = Some("alice".into());
+//!     let answer = auth_client.send(aar.to_message(0, 0, &dict).unwrap()).await.unwrap();
+//!     println!("{:?} -> {:?}", answer, auth_client.state(session_id));
+//!
+//!     auth_client
+//!         .terminate(session_id, TerminationCause::DiameterLogout)
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+
+use super::{
+    answer_result_code, AbortSessionAnswer, AbortSessionRequest, AuthGrant, AuthState, AuthTimer,
+    SessionTerminationRequest,
+};
+use crate::applications::common::{AuthSessionState, TerminationCause};
+use crate::diameter::{result_code, ApplicationId, CommandCode, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::DiameterClient;
+use crate::typed::TypedMessage;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Application callbacks of an [`AuthClient`].
+pub trait AuthClientHandler: Send + Sync + 'static {
+    /// Called after a session changed state.
+    fn state_changed(&self, _session_id: &str, _old: AuthState, _new: AuthState) {}
+
+    /// Called when the Authorization-Lifetime of an open session elapsed.
+    /// The session is terminated unless the application re-authorizes it
+    /// within the Auth-Grace-Period.
+    fn reauth_due(&self, _session_id: &str) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Called when an open session expires, before it is terminated. The
+    /// service to the user must be stopped.
+    fn session_expired(
+        &self,
+        _session_id: &str,
+        _timer: AuthTimer,
+    ) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Decides whether to comply with an ASR, stopping the service to the
+    /// user when it does. The session is then terminated. Every ASR is
+    /// complied with unless overridden.
+    fn abort_requested(&self, _asr: &AbortSessionRequest) -> impl Future<Output = bool> + Send {
+        async { true }
+    }
+}
+
+/// Configuration of an [`AuthClient`].
+#[derive(Debug, Clone, Copy)]
+pub struct AuthClientConfig {
+    /// Time to wait for an answer.
+    pub tx_timeout: Duration,
+    /// Auth-Session-State of sessions whose request and answer carry none.
+    pub auth_session_state: AuthSessionState,
+}
+
+impl Default for AuthClientConfig {
+    fn default() -> AuthClientConfig {
+        AuthClientConfig {
+            tx_timeout: Duration::from_secs(10),
+            auth_session_state: AuthSessionState::StateMaintained,
+        }
+    }
+}
+
+/// A session known to the client.
+struct ClientSession {
+    state: AuthState,
+    application_id: ApplicationId,
+    stateful: bool,
+    outstanding: bool,
+    destination_realm: String,
+    destination_host: Option<String>,
+    user_name: Option<String>,
+    class: Vec<Vec<u8>>,
+    timer: Option<JoinHandle<()>>,
+}
+
+impl ClientSession {
+    fn set_state(&mut self, state: AuthState) -> Option<(AuthState, AuthState)> {
+        let old = std::mem::replace(&mut self.state, state);
+        (old != state).then_some((old, state))
+    }
+
+    fn stop_timer(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+    }
+}
+
+impl Drop for ClientSession {
+    fn drop(&mut self) {
+        self.stop_timer();
+    }
+}
+
+/// A client keeping the authorization sessions of an application.
+pub struct AuthClient<H> {
+    handler: Arc<H>,
+    client: Arc<tokio::sync::Mutex<DiameterClient>>,
+    sessions: Arc<std::sync::Mutex<HashMap<String, ClientSession>>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: AuthClientConfig,
+}
+
+impl<H> Clone for AuthClient<H> {
+    fn clone(&self) -> Self {
+        AuthClient {
+            handler: Arc::clone(&self.handler),
+            client: Arc::clone(&self.client),
+            sessions: Arc::clone(&self.sessions),
+            dict: Arc::clone(&self.dict),
+            node: self.node.clone(),
+            config: self.config,
+        }
+    }
+}
+
+impl<H: AuthClientHandler> AuthClient<H> {
+    /// Creates a client sending to `client`.
+    pub fn new(
+        handler: H,
+        client: DiameterClient,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: AuthClientConfig,
+    ) -> AuthClient<H> {
+        AuthClient {
+            handler: Arc::new(handler),
+            client: Arc::new(tokio::sync::Mutex::new(client)),
+            sessions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Returns the state of a session, Idle when it is unknown.
+    pub fn state(&self, session_id: &str) -> AuthState {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|sessions| sessions.get(session_id).map(|session| session.state))
+            .unwrap_or(AuthState::Idle)
+    }
+
+    /// Returns the Session-Ids of the sessions that are not Idle.
+    pub fn session_ids(&self) -> Result<Vec<String>> {
+        Ok(self.sessions.lock()?.keys().cloned().collect())
+    }
+
+    /// Sends a service-specific authorization request of the session named
+    /// by its Session-Id and returns the answer.
+    ///
+    /// The request opens an Idle session, or re-authorizes an open one. Its
+    /// hop-by-hop identifier is set by the client, as is its end-to-end
+    /// identifier when zero. A failure answer closes the session, with an
+    /// STR when it was open.
+    pub async fn send(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        if !req.is_request() {
+            return Err(Error::ClientError("Not a request".into()));
+        }
+        let session_id: String = req.get("Session-Id")?;
+        let requested = req.get::<AuthSessionState, _>("Auth-Session-State").ok();
+
+        let transition = {
+            let mut sessions = self.sessions.lock()?;
+            let session = sessions
+                .entry(session_id.clone())
+                .or_insert_with(|| ClientSession {
+                    state: AuthState::Idle,
+                    application_id: req.get_application_id(),
+                    stateful: true,
+                    outstanding: false,
+                    destination_realm: String::new(),
+                    destination_host: None,
+                    user_name: None,
+                    class: Vec::new(),
+                    timer: None,
+                });
+            if session.outstanding || session.state == AuthState::Discon {
+                return Err(Error::ClientError(format!(
+                    "Cannot send request in state {:?}",
+                    session.state
+                )));
+            }
+            session.outstanding = true;
+            if let Ok(realm) = req.get("Destination-Realm") {
+                session.destination_realm = realm;
+            }
+            if let Ok(user_name) = req.get("User-Name") {
+                session.user_name = Some(user_name);
+            }
+            match session.state {
+                AuthState::Idle => session.set_state(AuthState::Pending),
+                _ => None,
+            }
+        };
+        self.notify(&session_id, transition);
+
+        let answer = match self.transmit(req).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                let transition = {
+                    let mut sessions = self.sessions.lock()?;
+                    match sessions.get_mut(&session_id) {
+                        Some(session) if session.state == AuthState::Pending => {
+                            sessions.remove(&session_id);
+                            Some((AuthState::Pending, AuthState::Idle))
+                        }
+                        Some(session) => {
+                            session.outstanding = false;
+                            None
+                        }
+                        None => None,
+                    }
+                };
+                self.notify(&session_id, transition);
+                return Err(e);
+            }
+        };
+
+        let result_code = answer_result_code(&answer);
+        let stateful = answer
+            .get::<AuthSessionState, _>("Auth-Session-State")
+            .ok()
+            .or(requested)
+            .unwrap_or(self.config.auth_session_state)
+            == AuthSessionState::StateMaintained;
+        let mut reauth_failed = false;
+        let transition = {
+            let mut sessions = self.sessions.lock()?;
+            let Some(session) = sessions.get_mut(&session_id) else {
+                // Aborted while the request was outstanding
+                return Ok(answer);
+            };
+            session.outstanding = false;
+            if result_code == result_code::DIAMETER_MULTI_ROUND_AUTH {
+                None
+            } else if (2000..3000).contains(&result_code) {
+                session.stateful = stateful;
+                session.destination_host = answer.get("Origin-Host").ok();
+                session.class = answer.query_values("Class").unwrap_or_default();
+                session.stop_timer();
+                session.timer = self.start_timer(&session_id, AuthGrant::from_message(&answer));
+                session.set_state(AuthState::Open)
+            } else if session.state == AuthState::Open {
+                reauth_failed = true;
+                None
+            } else {
+                let old = session.state;
+                sessions.remove(&session_id);
+                Some((old, AuthState::Idle))
+            }
+        };
+        self.notify(&session_id, transition);
+
+        if reauth_failed {
+            log::warn!(
+                "Re-authorization failed; session: {}, Result-Code: {}",
+                session_id,
+                result_code
+            );
+            self.terminate(&session_id, TerminationCause::DiameterAdministrative)
+                .await?;
+        }
+        Ok(answer)
+    }
+
+    /// Terminates an open session, sending an STR when it is stateful.
+    ///
+    /// The session is Idle once the STA is received, or when the STR cannot
+    /// be delivered, in which case the error is returned.
+    pub async fn terminate(&self, session_id: &str, cause: TerminationCause) -> Result<()> {
+        let (str, application_id, transition) = {
+            let mut sessions = self.sessions.lock()?;
+            let session = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| Error::ClientError(format!("Unknown session {}", session_id)))?;
+            if session.state != AuthState::Open {
+                return Err(Error::ClientError(format!(
+                    "Cannot terminate session in state {:?}",
+                    session.state
+                )));
+            }
+            session.stop_timer();
+            if !session.stateful {
+                sessions.remove(session_id);
+                (
+                    None,
+                    ApplicationId::Common,
+                    Some((AuthState::Open, AuthState::Idle)),
+                )
+            } else {
+                let mut str = SessionTerminationRequest::new(
+                    session_id,
+                    &self.node,
+                    &session.destination_realm,
+                    session.application_id as u32,
+                    cause,
+                );
+                str.destination_host = session.destination_host.clone();
+                str.user_name = session.user_name.clone();
+                str.class = session.class.clone();
+                let transition = session.set_state(AuthState::Discon);
+                (Some(str), session.application_id, transition)
+            }
+        };
+        self.notify(session_id, transition);
+        let Some(str) = str else {
+            return Ok(());
+        };
+
+        let mut req = str.to_message(0, 0, &self.dict)?;
+        req.set_application_id(application_id);
+        let result = self.transmit(req).await;
+        let transition = self
+            .sessions
+            .lock()?
+            .remove(session_id)
+            .map(|session| (session.state, AuthState::Idle));
+        self.notify(session_id, transition);
+
+        let sta = result?;
+        let result_code = answer_result_code(&sta);
+        if !(2000..3000).contains(&result_code) {
+            log::warn!(
+                "Session termination rejected; session: {}, Result-Code: {}",
+                session_id,
+                result_code
+            );
+        }
+        Ok(())
+    }
+
+    /// Handles a request initiated by the server, returning the answer to
+    /// send, as the request handler of
+    /// [`DiameterClient::handle_with_requests`].
+    ///
+    /// ASRs of open sessions are answered with DIAMETER_SUCCESS when the
+    /// handler complies, and the session is then terminated. Other requests
+    /// are answered with DIAMETER_COMMAND_UNSUPPORTED.
+    pub async fn handle_request(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        if req.get_command_code() != CommandCode::AbortSession {
+            return Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_COMMAND_UNSUPPORTED,
+                None,
+                &self.node,
+            ));
+        }
+        let asr = match AbortSessionRequest::from_message(&req) {
+            Ok(asr) => asr,
+            Err(e) => {
+                log::warn!("Invalid Abort-Session-Request; error: {}", e);
+                let result_code = match e {
+                    Error::AvpNotFound(_) => result_code::DIAMETER_MISSING_AVP,
+                    Error::InvalidAvpValue(_) => result_code::DIAMETER_INVALID_AVP_VALUE,
+                    _ => result_code::DIAMETER_UNABLE_TO_COMPLY,
+                };
+                return Ok(DiameterMessage::create_answer(
+                    &req,
+                    result_code,
+                    &self.node,
+                ));
+            }
+        };
+
+        let result_code = match self.state(&asr.session_id) {
+            AuthState::Open if self.handler.abort_requested(&asr).await => {
+                let this = self.clone();
+                let session_id = asr.session_id.clone();
+                tokio::spawn(async move {
+                    let cause = TerminationCause::DiameterAdministrative;
+                    if let Err(e) = this.terminate(&session_id, cause).await {
+                        log::error!("Failed to terminate session; error: {}", e);
+                    }
+                });
+                result_code::DIAMETER_SUCCESS
+            }
+            AuthState::Open => result_code::DIAMETER_UNABLE_TO_COMPLY,
+            _ => result_code::DIAMETER_UNKNOWN_SESSION_ID,
+        };
+        let asa = AbortSessionAnswer::new(&asr, result_code, &self.node);
+        let mut answer =
+            asa.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &self.dict)?;
+        answer.set_application_id(req.get_application_id());
+        Ok(answer)
+    }
+
+    fn notify(&self, session_id: &str, transition: Option<(AuthState, AuthState)>) {
+        if let Some((old, new)) = transition {
+            log::debug!("Session {}: {:?} -> {:?}", session_id, old, new);
+            self.handler.state_changed(session_id, old, new);
+        }
+    }
+
+    fn start_timer(&self, session_id: &str, grant: AuthGrant) -> Option<JoinHandle<()>> {
+        let expiry = grant.expiry();
+        if expiry.is_none() && grant.authorization_lifetime.is_none() {
+            return None;
+        }
+        let this = self.clone();
+        let session_id = session_id.to_string();
+        let start = Instant::now();
+        Some(tokio::spawn(async move {
+            if let Some(lifetime) = grant.authorization_lifetime {
+                if !matches!(expiry, Some((after, _)) if after <= lifetime) {
+                    tokio::time::sleep_until(start + lifetime).await;
+                    // Run apart from the timer, which re-authorization restarts
+                    let handler = Arc::clone(&this.handler);
+                    let session_id = session_id.clone();
+                    tokio::spawn(async move { handler.reauth_due(&session_id).await });
+                }
+            }
+            if let Some((after, timer)) = expiry {
+                tokio::time::sleep_until(start + after).await;
+                this.expire(&session_id, timer).await;
+            }
+        }))
+    }
+
+    async fn expire(&self, session_id: &str, timer: AuthTimer) {
+        match self.sessions.lock() {
+            // Detach the running timer, so that terminating does not abort it
+            Ok(mut sessions) => match sessions.get_mut(session_id) {
+                Some(session) => session.timer = None,
+                None => return,
+            },
+            Err(_) => return,
+        }
+        log::info!("{:?} expired; session: {}", timer, session_id);
+        self.handler.session_expired(session_id, timer).await;
+        let cause = match timer {
+            AuthTimer::SessionTimeout => TerminationCause::DiameterSessionTimeout,
+            AuthTimer::AuthorizationLifetime => TerminationCause::DiameterAuthExpired,
+        };
+        if let Err(e) = self.terminate(session_id, cause).await {
+            log::error!("Failed to terminate expired session; error: {}", e);
+        }
+    }
+
+    async fn transmit(&self, mut req: DiameterMessage) -> Result<DiameterMessage> {
+        let response = {
+            let mut client = self.client.lock().await;
+            let seq_num = client.get_next_seq_num();
+            req.set_hop_by_hop_id(seq_num);
+            if req.get_end_to_end_id() == 0 {
+                req.set_end_to_end_id(seq_num);
+            }
+            client.send_message(req).await?
+        };
+        match tokio::time::timeout(self.config.tx_timeout, response).await {
+            Ok(answer) => answer,
+            Err(_) => Err(Error::ClientError("Timed out waiting for answer".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::common::AuthRequestType;
+    use crate::applications::nasreq::{self, AaAnswer, AaRequest};
+    use crate::avp::Unsigned32;
+    use crate::dictionary;
+    use crate::session::auth::SessionTerminationAnswer;
+    use crate::transport::{DiameterClientConfig, DiameterServer, DiameterServerConfig};
+    use tokio::sync::mpsc;
+
+    /// Reports the timer events of the sessions.
+    struct Nas {
+        events: mpsc::UnboundedSender<Option<AuthTimer>>,
+    }
+
+    impl AuthClientHandler for Nas {
+        async fn reauth_due(&self, _session_id: &str) {
+            let _ = self.events.send(None);
+        }
+
+        async fn session_expired(&self, _session_id: &str, timer: AuthTimer) {
+            let _ = self.events.send(Some(timer));
+        }
+    }
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            nasreq::DICT_XML,
+        ]))
+    }
+
+    /// Starts a server granting the `timers` to every session, and returns
+    /// a client connected to it and the causes of the STRs it receives.
+    async fn setup(
+        port: u16,
+        timers: &'static [(&'static str, u32)],
+    ) -> (
+        AuthClient<Nas>,
+        mpsc::UnboundedReceiver<Option<AuthTimer>>,
+        mpsc::UnboundedReceiver<TerminationCause>,
+    ) {
+        let dict = dict();
+        let addr = format!("0.0.0.0:{}", port);
+        let mut server = DiameterServer::new(&addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let (str_tx, str_rx) = mpsc::unbounded_channel();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            let dict = Arc::clone(&dict_ref);
+            let aaa = NodeConfig::new("aaa.example.com", "example.com");
+            server
+                .listen(
+                    move |req| {
+                        let dict = Arc::clone(&dict);
+                        let aaa = aaa.clone();
+                        let str_tx = str_tx.clone();
+                        async move {
+                            let (hop_by_hop, end_to_end) =
+                                (req.get_hop_by_hop_id(), req.get_end_to_end_id());
+                            if req.get_command_code() == CommandCode::SessionTerminate {
+                                let str = SessionTerminationRequest::from_message(&req)?;
+                                let _ = str_tx.send(str.termination_cause);
+                                let sta = SessionTerminationAnswer::new(&str, 2001, &aaa);
+                                return sta.to_message(hop_by_hop, end_to_end, &dict);
+                            }
+                            let aar = AaRequest::from_message(&req)?;
+                            let aaa = AaAnswer::new(&aar, 2001, &aaa);
+                            let mut answer = aaa.to_message(hop_by_hop, end_to_end, &dict)?;
+                            for (name, value) in timers {
+                                answer.add_avp_by_name(name, Unsigned32::new(*value).into())?;
+                            }
+                            Ok(answer)
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new(&format!("localhost:{}", port), config);
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let auth_client = AuthClient::new(
+            Nas { events: events_tx },
+            client,
+            dict,
+            nas,
+            AuthClientConfig::default(),
+        );
+        (auth_client, events_rx, str_rx)
+    }
+
+    fn aar(auth_client: &AuthClient<Nas>, session_id: &str) -> DiameterMessage {
+        let aar = AaRequest::new(
+            session_id,
+            &auth_client.node,
+            "example.com",
+            AuthRequestType::AuthorizeOnly,
+        );
+        aar.to_message(0, 0, &auth_client.dict).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session_timeout() {
+        let (auth_client, mut events, mut strs) = setup(3891, &[("Session-Timeout", 1)]).await;
+
+        let session_id = "nas.example.com;5;1";
+        auth_client
+            .send(aar(&auth_client, session_id))
+            .await
+            .unwrap();
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+
+        assert_eq!(
+            events.recv().await.unwrap(),
+            Some(AuthTimer::SessionTimeout)
+        );
+        assert_eq!(
+            strs.recv().await.unwrap(),
+            TerminationCause::DiameterSessionTimeout
+        );
+        while auth_client.state(session_id) != AuthState::Idle {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorization_lifetime() {
+        let (auth_client, mut events, mut strs) = setup(
+            3892,
+            &[("Authorization-Lifetime", 1), ("Auth-Grace-Period", 1)],
+        )
+        .await;
+
+        let session_id = "nas.example.com;6;1";
+        auth_client
+            .send(aar(&auth_client, session_id))
+            .await
+            .unwrap();
+
+        // Re-authorizing within the grace period restarts the timers
+        assert_eq!(events.recv().await.unwrap(), None);
+        auth_client
+            .send(aar(&auth_client, session_id))
+            .await
+            .unwrap();
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+
+        assert_eq!(events.recv().await.unwrap(), None);
+        assert_eq!(
+            events.recv().await.unwrap(),
+            Some(AuthTimer::AuthorizationLifetime)
+        );
+        assert_eq!(
+            strs.recv().await.unwrap(),
+            TerminationCause::DiameterAuthExpired
+        );
+        assert!(strs.try_recv().is_err());
+    }
+}
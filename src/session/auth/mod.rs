@@ -0,0 +1,463 @@
+//! Authorization Sessions
+//!
+//! The authorization session state machines of
+//! [RFC 6733 section 8.1](https://tools.ietf.org/html/rfc6733#section-8.1),
+//! for any auth application. The [`client`] and the [`server`] keep their
+//! sessions by Session-Id and take care of what the base protocol defines
+//! around the service-specific authorization messages of the application:
+//!
+//! * Auth-Session-State, selecting a stateful or a stateless session.
+//! * Session-Timeout, Authorization-Lifetime and Auth-Grace-Period, which
+//!   supervise open sessions.
+//! * Session-Termination-Request and Answer (STR/STA), sent by the client
+//!   when the service ends.
+//! * Abort-Session-Request and Answer (ASR/ASA), sent by the server to stop
+//!   the service.
+//!
+//! The base STR, STA, ASR and ASA below carry the Common application id in
+//! their header, which is replaced by the id of the application the session
+//! belongs to before they are sent:
+//!
+//! ```
+//! use diameter::applications::common::TerminationCause;
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::session::auth::SessionTerminationRequest;
+//! use diameter::typed::TypedMessage;
+//! use diameter::{ApplicationId, NodeConfig};
+//! use std::sync::Arc;
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//! let node = NodeConfig::new("nas.example.com", "example.com");
+//!
+//! let str = SessionTerminationRequest::new(
+//!     "nas.example.com;1;1",
+//!     &node,
+//!     "example.com",
+//!     ApplicationId::Nasreq as u32,
+//!     TerminationCause::DiameterLogout,
+//! );
+//! let mut msg = str.to_message(1, 1, &dict).unwrap();
+//! msg.set_application_id(ApplicationId::Nasreq);
+//! assert_eq!(SessionTerminationRequest::from_message(&msg).unwrap(), str);
+//! ```
+
+pub mod client;
+pub mod server;
+
+use crate::applications::common::{ExperimentalResult, ProxyInfo, TerminationCause};
+use crate::applications::credit_control::RedirectHostUsage;
+use crate::diameter::result_code;
+use crate::node::NodeConfig;
+use crate::DiameterMessage;
+use std::time::Duration;
+
+/// Value of Authorization-Lifetime meaning that no re-authorization is
+/// expected.
+const UNLIMITED_LIFETIME: u32 = u32::MAX;
+
+/// States of an authorization session, shared by the client and the server
+/// state machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthState {
+    /// No session.
+    Idle,
+    /// The client waits for the answer opening the session.
+    Pending,
+    /// The session is authorized.
+    Open,
+    /// The session is being terminated, waiting for an STA or an ASA.
+    Discon,
+}
+
+/// The timers expiring an open session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthTimer {
+    /// The Session-Timeout of the session elapsed.
+    SessionTimeout,
+    /// The session was not re-authorized within the Authorization-Lifetime
+    /// and the Auth-Grace-Period.
+    AuthorizationLifetime,
+}
+
+/// The authorization timers granted to a session by an answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuthGrant {
+    /// Session-Timeout, or None when the session is not limited.
+    pub session_timeout: Option<Duration>,
+    /// Authorization-Lifetime, or None when no re-authorization is expected.
+    pub authorization_lifetime: Option<Duration>,
+    /// Auth-Grace-Period.
+    pub auth_grace_period: Duration,
+}
+
+impl AuthGrant {
+    /// Reads the Session-Timeout, Authorization-Lifetime and
+    /// Auth-Grace-Period AVPs of `msg`.
+    ///
+    /// A Session-Timeout of zero means that the session is not limited, and
+    /// an Authorization-Lifetime of 0xFFFFFFFF that it needs no
+    /// re-authorization.
+    pub fn from_message(msg: &DiameterMessage) -> AuthGrant {
+        let seconds = |name: &str| msg.get::<u32, _>(name).ok();
+        AuthGrant {
+            session_timeout: seconds("Session-Timeout")
+                .filter(|&timeout| timeout != 0)
+                .map(|timeout| Duration::from_secs(timeout.into())),
+            authorization_lifetime: seconds("Authorization-Lifetime")
+                .filter(|&lifetime| lifetime != UNLIMITED_LIFETIME)
+                .map(|lifetime| Duration::from_secs(lifetime.into())),
+            auth_grace_period: Duration::from_secs(
+                seconds("Auth-Grace-Period").unwrap_or(0).into(),
+            ),
+        }
+    }
+
+    /// Returns when the session expires, counted from the answer, and the
+    /// timer expiring it.
+    pub fn expiry(&self) -> Option<(Duration, AuthTimer)> {
+        let lifetime = self.authorization_lifetime.map(|lifetime| {
+            (
+                lifetime + self.auth_grace_period,
+                AuthTimer::AuthorizationLifetime,
+            )
+        });
+        let timeout = self
+            .session_timeout
+            .map(|timeout| (timeout, AuthTimer::SessionTimeout));
+        match (timeout, lifetime) {
+            (Some(timeout), Some(lifetime)) => Some(if lifetime.0 < timeout.0 {
+                lifetime
+            } else {
+                timeout
+            }),
+            (timeout, lifetime) => timeout.or(lifetime),
+        }
+    }
+}
+
+/// Returns the Result-Code of an answer, or the Experimental-Result-Code
+/// when it has none.
+pub(crate) fn answer_result_code(msg: &DiameterMessage) -> u32 {
+    msg.get::<u32, _>("Result-Code")
+        .ok()
+        .or_else(|| {
+            msg.query_values::<u32>("Experimental-Result/Experimental-Result-Code")
+                .ok()
+                .and_then(|codes| codes.first().copied())
+        })
+        .unwrap_or(result_code::DIAMETER_UNABLE_TO_COMPLY)
+}
+
+/// Session-Termination-Request (STR), RFC 6733 section 8.4.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = SessionTerminate,
+    application = Common,
+    request,
+    proxyable
+)]
+pub struct SessionTerminationRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "Termination-Cause")]
+    pub termination_cause: TerminationCause,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: Option<String>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Session-Termination-Answer (STA), RFC 6733 section 8.4.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = SessionTerminate, application = Common, proxyable)]
+pub struct SessionTerminationAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Class")]
+    pub class: Vec<Vec<u8>>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+/// Abort-Session-Request (ASR), RFC 6733 section 8.5.1.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(
+    crate = "crate",
+    command = AbortSession,
+    application = Common,
+    request,
+    proxyable
+)]
+pub struct AbortSessionRequest {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "Destination-Realm")]
+    pub destination_realm: String,
+    #[avp(name = "Destination-Host")]
+    pub destination_host: String,
+    #[avp(name = "Auth-Application-Id")]
+    pub auth_application_id: u32,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+    #[avp(name = "Route-Record")]
+    pub route_record: Vec<String>,
+}
+
+/// Abort-Session-Answer (ASA), RFC 6733 section 8.5.2.
+#[derive(Debug, Clone, PartialEq, DiameterMessage)]
+#[diameter(crate = "crate", command = AbortSession, application = Common, proxyable)]
+pub struct AbortSessionAnswer {
+    #[avp(name = "Session-Id")]
+    pub session_id: String,
+    #[avp(name = "Result-Code")]
+    pub result_code: u32,
+    #[avp(name = "Origin-Host")]
+    pub origin_host: String,
+    #[avp(name = "Origin-Realm")]
+    pub origin_realm: String,
+    #[avp(name = "User-Name")]
+    pub user_name: Option<String>,
+    #[avp(name = "Origin-State-Id")]
+    pub origin_state_id: Option<u32>,
+    #[avp(name = "Error-Message")]
+    pub error_message: Option<String>,
+    #[avp(name = "Error-Reporting-Host")]
+    pub error_reporting_host: Option<String>,
+    #[avp(name = "Experimental-Result", grouped)]
+    pub experimental_result: Option<ExperimentalResult>,
+    #[avp(name = "Redirect-Host")]
+    pub redirect_host: Vec<String>,
+    #[avp(name = "Redirect-Host-Usage")]
+    pub redirect_host_usage: Option<RedirectHostUsage>,
+    #[avp(name = "Redirect-Max-Cache-Time")]
+    pub redirect_max_cache_time: Option<u32>,
+    #[avp(name = "Proxy-Info", grouped)]
+    pub proxy_info: Vec<ProxyInfo>,
+}
+
+impl SessionTerminationRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        auth_application_id: u32,
+        termination_cause: TerminationCause,
+    ) -> SessionTerminationRequest {
+        SessionTerminationRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            auth_application_id,
+            termination_cause,
+            user_name: None,
+            destination_host: None,
+            class: Vec::new(),
+            origin_state_id: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl SessionTerminationAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying the
+    /// Session-Id, User-Name, Class and Proxy-Info, and taking Origin-Host
+    /// and Origin-Realm from `node`.
+    pub fn new(
+        request: &SessionTerminationRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> SessionTerminationAnswer {
+        SessionTerminationAnswer {
+            session_id: request.session_id.clone(),
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            user_name: request.user_name.clone(),
+            class: request.class.clone(),
+            error_message: None,
+            error_reporting_host: None,
+            origin_state_id: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+impl AbortSessionRequest {
+    /// Creates a request with the mandatory AVPs, taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        session_id: &str,
+        node: &NodeConfig,
+        destination_realm: &str,
+        destination_host: &str,
+        auth_application_id: u32,
+    ) -> AbortSessionRequest {
+        AbortSessionRequest {
+            session_id: session_id.into(),
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            destination_realm: destination_realm.into(),
+            destination_host: destination_host.into(),
+            auth_application_id,
+            user_name: None,
+            origin_state_id: None,
+            proxy_info: Vec::new(),
+            route_record: Vec::new(),
+        }
+    }
+}
+
+impl AbortSessionAnswer {
+    /// Creates an answer to `request` with a Result-Code, copying the
+    /// Session-Id, User-Name and Proxy-Info, and taking Origin-Host and
+    /// Origin-Realm from `node`.
+    pub fn new(
+        request: &AbortSessionRequest,
+        result_code: u32,
+        node: &NodeConfig,
+    ) -> AbortSessionAnswer {
+        AbortSessionAnswer {
+            session_id: request.session_id.clone(),
+            result_code,
+            origin_host: node.origin_host.clone(),
+            origin_realm: node.origin_realm.clone(),
+            user_name: request.user_name.clone(),
+            origin_state_id: None,
+            error_message: None,
+            error_reporting_host: None,
+            experimental_result: None,
+            redirect_host: Vec::new(),
+            redirect_host_usage: None,
+            redirect_max_cache_time: None,
+            proxy_info: request.proxy_info.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::Unsigned32;
+    use crate::dictionary::{self, Dictionary};
+    use crate::typed::TypedMessage;
+    use crate::{ApplicationId, CommandCode};
+    use std::sync::Arc;
+
+    fn dict() -> Arc<Dictionary> {
+        Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]))
+    }
+
+    #[test]
+    fn test_grant_expiry() {
+        let dict = dict();
+        let node = NodeConfig::new("aaa.example.com", "example.com");
+        let str = SessionTerminationRequest::new(
+            "nas.example.com;1;1",
+            &node,
+            "example.com",
+            1,
+            TerminationCause::DiameterLogout,
+        );
+        let sta = SessionTerminationAnswer::new(&str, 2001, &node);
+        let mut msg = sta.to_message(1, 1, &dict).unwrap();
+        assert_eq!(AuthGrant::from_message(&msg), AuthGrant::default());
+        assert_eq!(AuthGrant::default().expiry(), None);
+        assert_eq!(answer_result_code(&msg), 2001);
+
+        msg.add_avp_by_name("Session-Timeout", Unsigned32::new(0).into())
+            .unwrap();
+        msg.add_avp_by_name("Authorization-Lifetime", Unsigned32::new(60).into())
+            .unwrap();
+        msg.add_avp_by_name("Auth-Grace-Period", Unsigned32::new(30).into())
+            .unwrap();
+        let grant = AuthGrant::from_message(&msg);
+        assert_eq!(grant.session_timeout, None);
+        assert_eq!(
+            grant.expiry(),
+            Some((Duration::from_secs(90), AuthTimer::AuthorizationLifetime))
+        );
+
+        let grant = AuthGrant {
+            session_timeout: Some(Duration::from_secs(60)),
+            ..grant
+        };
+        assert_eq!(
+            grant.expiry(),
+            Some((Duration::from_secs(60), AuthTimer::SessionTimeout))
+        );
+    }
+
+    #[test]
+    fn test_asr_application_id() {
+        let dict = dict();
+        let node = NodeConfig::new("aaa.example.com", "example.com");
+        let asr = AbortSessionRequest::new(
+            "nas.example.com;1;1",
+            &node,
+            "example.com",
+            "nas.example.com",
+            ApplicationId::Nasreq as u32,
+        );
+        let mut msg = asr.to_message(1, 1, &dict).unwrap();
+        msg.set_application_id(ApplicationId::Nasreq);
+        assert_eq!(msg.get_command_code(), CommandCode::AbortSession);
+        assert_eq!(msg.get_application_id(), ApplicationId::Nasreq);
+        assert_eq!(AbortSessionRequest::from_message(&msg).unwrap(), asr);
+
+        let asa = AbortSessionAnswer::new(&asr, 2001, &node);
+        let msg = asa.to_message(1, 1, &dict).unwrap();
+        assert_eq!(AbortSessionAnswer::from_message(&msg).unwrap(), asa);
+    }
+}
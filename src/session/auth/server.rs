@@ -0,0 +1,987 @@
+//! Authorization Server Sessions
+//!
+//! The server side of the authorization session state machines of
+//! [RFC 6733 section 8.1](https://tools.ietf.org/html/rfc6733#section-8.1).
+//!
+//! An [`AuthServer`] passes the service-specific authorization requests of
+//! an application to an [`AuthServerHandler`] and keeps the sessions its
+//! successful answers open, unless they are stateless. It completes the
+//! answers with the Auth-Session-State and the timers of its configuration,
+//! releases the sessions that are not re-authorized or terminated in time,
+//! answers STRs, and aborts sessions with an ASR sent over the connection
+//! of the client. Given a [`SessionStore`] with
+//! [`set_store`](AuthServer::set_store), it persists its sessions there.
+//!
+//! ```no_run
+//! use diameter::applications::nasreq::{self, AaAnswer, AaRequest};
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::session::auth::server::{AuthServer, AuthServerConfig, AuthServerHandler};
+//! use diameter::transport::{DiameterServer, DiameterServerConfig};
+//! use diameter::typed::TypedMessage;
+//! use diameter::{DiameterMessage, NodeConfig, Result};
+//! use std::sync::Arc;
+//!
+//! struct Aaa {
+//!     dict: Arc<Dictionary>,
+//!     node: NodeConfig,
+//! }
+//!
+//! impl AuthServerHandler for Aaa {
+//!     async fn authorize(&self, req: &DiameterMessage) -> Result<DiameterMessage> {
+//!         let aar = AaRequest::from_message(req)?;
+//!         let aaa = AaAnswer::new(&aar, 2001, &self.node);
+//!         aaa.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &self.dict)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML, nasreq::DICT_XML]));
+//!     let node = NodeConfig::new("aaa.example.com", "example.com");
+//!     let config = AuthServerConfig {
+//!         session_timeout: Some(3600),
+//!         ..Default::default()
+//!     };
+//!     let aaa = Aaa { dict: Arc::clone(&dict), node: node.clone() };
+//!     let auth_server = AuthServer::new(aaa, dict, node, config);
+//!
+//!     let config = DiameterServerConfig { native_tls: None };
+//!     let mut server = DiameterServer::new("0.0.0.0:3868", config).await.unwrap();
+//!     auth_server.listen(&mut server).await.unwrap();
+//! }
+//! ```
+
+use super::{
+    answer_result_code, AbortSessionRequest, AuthGrant, AuthState, AuthTimer,
+    SessionTerminationAnswer, SessionTerminationRequest,
+};
+use crate::applications::common::AuthSessionState;
+use crate::avp::{Enumerated, Unsigned32};
+use crate::builder::GroupBuilder;
+use crate::diameter::{result_code, ApplicationId, CommandCode, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::session::store::{self, RecordReader, RecordWriter, SessionRecord, SessionStore};
+use crate::transport::{DiameterServer, ServerPeers};
+use crate::typed::TypedMessage;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Application callbacks of an [`AuthServer`].
+pub trait AuthServerHandler: Send + Sync + 'static {
+    /// Handles a service-specific authorization request and returns the
+    /// answer. A successful answer opens or re-authorizes the session, and a
+    /// failure closes it. Returning an error answers with
+    /// DIAMETER_UNABLE_TO_COMPLY.
+    fn authorize(
+        &self,
+        req: &DiameterMessage,
+    ) -> impl Future<Output = Result<DiameterMessage>> + Send;
+
+    /// Called after a session changed state.
+    fn state_changed(&self, _session_id: &str, _old: AuthState, _new: AuthState) {}
+
+    /// Called when the client terminates an open session with an STR.
+    fn session_terminated(
+        &self,
+        _str: &SessionTerminationRequest,
+    ) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Called when an open session expires and is released without an STR.
+    fn session_expired(
+        &self,
+        _session_id: &str,
+        _timer: AuthTimer,
+    ) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// Configuration of an [`AuthServer`].
+#[derive(Debug, Clone, Copy)]
+pub struct AuthServerConfig {
+    /// Auth-Session-State of sessions whose request and answer carry none.
+    pub auth_session_state: AuthSessionState,
+    /// Session-Timeout added to successful answers that have none.
+    pub session_timeout: Option<u32>,
+    /// Authorization-Lifetime added to successful answers that have none.
+    pub authorization_lifetime: Option<u32>,
+    /// Auth-Grace-Period added to successful answers that have none.
+    pub auth_grace_period: Option<u32>,
+    /// Time to wait for an ASA.
+    pub tx_timeout: Duration,
+}
+
+impl Default for AuthServerConfig {
+    fn default() -> AuthServerConfig {
+        AuthServerConfig {
+            auth_session_state: AuthSessionState::StateMaintained,
+            session_timeout: None,
+            authorization_lifetime: None,
+            auth_grace_period: None,
+            tx_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A stateful session kept by the server.
+struct ServerSession {
+    state: AuthState,
+    application_id: ApplicationId,
+    origin_host: String,
+    origin_realm: String,
+    user_name: Option<String>,
+    deadline: Option<(Instant, AuthTimer)>,
+}
+
+impl SessionRecord for ServerSession {
+    fn encode_record(&self, writer: &mut RecordWriter) {
+        writer.put_u8(match self.state {
+            AuthState::Discon => 1,
+            _ => 0,
+        });
+        writer.put_u32(self.application_id as u32);
+        writer.put_str(&self.origin_host);
+        writer.put_str(&self.origin_realm);
+        writer.put_opt_str(self.user_name.as_deref());
+        writer.put_deadline(self.deadline.map(|(deadline, _)| deadline));
+        writer.put_u8(match self.deadline {
+            Some((_, AuthTimer::AuthorizationLifetime)) => 1,
+            _ => 0,
+        });
+    }
+
+    fn decode_record(reader: &mut RecordReader) -> Result<ServerSession> {
+        let state = match reader.get_u8()? {
+            1 => AuthState::Discon,
+            _ => AuthState::Open,
+        };
+        let application_id = reader.get_u32()?;
+        let application_id = ApplicationId::from_u32(application_id).ok_or_else(|| {
+            Error::DecodeError(format!("Unknown application id: {}", application_id))
+        })?;
+        let origin_host = reader.get_str()?;
+        let origin_realm = reader.get_str()?;
+        let user_name = reader.get_opt_str()?;
+        let deadline = reader.get_deadline()?;
+        let timer = match reader.get_u8()? {
+            1 => AuthTimer::AuthorizationLifetime,
+            _ => AuthTimer::SessionTimeout,
+        };
+        Ok(ServerSession {
+            state,
+            application_id,
+            origin_host,
+            origin_realm,
+            user_name,
+            deadline: deadline.map(|deadline| (deadline, timer)),
+        })
+    }
+}
+
+/// A server keeping the authorization sessions of an application.
+pub struct AuthServer<H> {
+    handler: Arc<H>,
+    sessions: Arc<Mutex<HashMap<String, ServerSession>>>,
+    store: Option<Arc<dyn SessionStore>>,
+    dict: Arc<Dictionary>,
+    node: NodeConfig,
+    config: AuthServerConfig,
+}
+
+impl<H> Clone for AuthServer<H> {
+    fn clone(&self) -> Self {
+        AuthServer {
+            handler: Arc::clone(&self.handler),
+            sessions: Arc::clone(&self.sessions),
+            store: self.store.clone(),
+            dict: Arc::clone(&self.dict),
+            node: self.node.clone(),
+            config: self.config,
+        }
+    }
+}
+
+impl<H: AuthServerHandler> AuthServer<H> {
+    /// Creates a server answering as `node`.
+    pub fn new(
+        handler: H,
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        config: AuthServerConfig,
+    ) -> AuthServer<H> {
+        AuthServer {
+            handler: Arc::new(handler),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            store: None,
+            dict,
+            node,
+            config,
+        }
+    }
+
+    /// Persists the sessions in `store`, taking over the sessions it holds,
+    /// such as the sessions left open by a previous run of the server.
+    pub fn set_store(&mut self, store: Arc<dyn SessionStore>) -> Result<()> {
+        let mut sessions = self.sessions.lock()?;
+        for (session_id, record) in store.records()? {
+            sessions.insert(session_id, store::decode(&record)?);
+        }
+        for (session_id, session) in sessions.iter() {
+            store.put(session_id, &store::encode(session))?;
+        }
+        drop(sessions);
+        self.store = Some(store);
+        Ok(())
+    }
+
+    /// Serves requests received by `server`, supervising sessions until
+    /// the server stops listening.
+    pub async fn listen(&self, server: &mut DiameterServer) -> Result<()> {
+        server.set_node(self.node.clone());
+        let this = self.clone();
+        let supervisor = tokio::spawn(async move {
+            loop {
+                if let Err(e) = this.expire_sessions().await {
+                    log::error!("Failed to expire sessions; error: {}", e);
+                }
+                let latest = Instant::now() + Duration::from_secs(1);
+                let next = this.next_deadline().unwrap_or(latest).min(latest);
+                tokio::time::sleep_until(next).await;
+            }
+        });
+
+        let this = self.clone();
+        let result = server
+            .listen(
+                move |req| {
+                    let this = this.clone();
+                    async move { this.handle(req).await }
+                },
+                Arc::clone(&self.dict),
+            )
+            .await;
+        supervisor.abort();
+        result
+    }
+
+    /// Handles a request, returning the answer to send.
+    ///
+    /// STRs terminate the session they name, or are answered with
+    /// DIAMETER_UNKNOWN_SESSION_ID. Any other request is passed to the
+    /// handler as a service-specific authorization request.
+    pub async fn handle(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        match req.get_command_code() {
+            CommandCode::SessionTerminate => self.terminate(req).await,
+            CommandCode::AbortSession => Ok(DiameterMessage::create_error_answer(
+                &req,
+                result_code::DIAMETER_COMMAND_UNSUPPORTED,
+                None,
+                &self.node,
+            )),
+            _ => self.authorize(req).await,
+        }
+    }
+
+    /// Returns the state of a session, Idle when it is unknown.
+    pub fn state(&self, session_id: &str) -> AuthState {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|sessions| sessions.get(session_id).map(|session| session.state))
+            .unwrap_or(AuthState::Idle)
+    }
+
+    /// Returns the Session-Ids of the sessions kept by the server.
+    pub fn session_ids(&self) -> Result<Vec<String>> {
+        Ok(self.sessions.lock()?.keys().cloned().collect())
+    }
+
+    /// Releases the open sessions whose Session-Timeout, or
+    /// Authorization-Lifetime and Auth-Grace-Period, have elapsed, notifying
+    /// the handler, and returns their Session-Ids.
+    pub async fn expire_sessions(&self) -> Result<Vec<String>> {
+        let expired = {
+            let mut sessions = self.sessions.lock()?;
+            let now = Instant::now();
+            let expired: Vec<(String, AuthState, AuthTimer)> = sessions
+                .iter()
+                .filter_map(|(session_id, session)| match session.deadline {
+                    Some((deadline, timer)) if deadline <= now => {
+                        Some((session_id.clone(), session.state, timer))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for (session_id, _, _) in &expired {
+                sessions.remove(session_id);
+                self.discard(session_id);
+            }
+            expired
+        };
+        for (session_id, state, timer) in &expired {
+            log::info!("{:?} expired; session: {}", timer, session_id);
+            self.notify(session_id, Some((*state, AuthState::Idle)));
+            self.handler.session_expired(session_id, *timer).await;
+        }
+        Ok(expired
+            .into_iter()
+            .map(|(session_id, _, _)| session_id)
+            .collect())
+    }
+
+    /// Aborts an open session, sending an ASR to the client over its
+    /// connection in `peers`.
+    ///
+    /// Returns true when the client complies, closing the session, and
+    /// false when it refuses, leaving the session open.
+    pub async fn abort(&self, peers: &ServerPeers, session_id: &str) -> Result<bool> {
+        let (asr, application_id, transition) = {
+            let mut sessions = self.sessions.lock()?;
+            let session = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| Error::ServerError(format!("Unknown session {}", session_id)))?;
+            if session.state != AuthState::Open {
+                return Err(Error::ServerError(format!(
+                    "Cannot abort session in state {:?}",
+                    session.state
+                )));
+            }
+            let mut asr = AbortSessionRequest::new(
+                session_id,
+                &self.node,
+                &session.origin_realm,
+                &session.origin_host,
+                session.application_id as u32,
+            );
+            asr.user_name = session.user_name.clone();
+            session.state = AuthState::Discon;
+            self.save(session_id, session);
+            (
+                asr,
+                session.application_id,
+                (AuthState::Open, AuthState::Discon),
+            )
+        };
+        self.notify(session_id, Some(transition));
+
+        let result = self.send_asr(peers, &asr, application_id).await;
+        let complied =
+            matches!(&result, Ok(asa) if (2000..3000).contains(&answer_result_code(asa)));
+        let transition = {
+            let mut sessions = self.sessions.lock()?;
+            match sessions.get_mut(session_id) {
+                Some(session) if session.state == AuthState::Discon => {
+                    if complied {
+                        sessions.remove(session_id);
+                        self.discard(session_id);
+                        Some((AuthState::Discon, AuthState::Idle))
+                    } else {
+                        session.state = AuthState::Open;
+                        self.save(session_id, session);
+                        Some((AuthState::Discon, AuthState::Open))
+                    }
+                }
+                _ => None,
+            }
+        };
+        self.notify(session_id, transition);
+        result.map(|_| complied)
+    }
+
+    async fn send_asr(
+        &self,
+        peers: &ServerPeers,
+        asr: &AbortSessionRequest,
+        application_id: ApplicationId,
+    ) -> Result<DiameterMessage> {
+        let peer = peers
+            .get_by_origin_host(&asr.destination_host)
+            .ok_or_else(|| {
+                Error::ServerError(format!("No connection to {}", asr.destination_host))
+            })?;
+        let seq_num = peer.get_next_seq_num();
+        let mut req = asr.to_message(seq_num, seq_num, &self.dict)?;
+        req.set_application_id(application_id);
+        let response = peer.send_message(req).await?;
+        match tokio::time::timeout(self.config.tx_timeout, response).await {
+            Ok(answer) => answer,
+            Err(_) => Err(Error::ServerError("Timed out waiting for answer".into())),
+        }
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let sessions = self.sessions.lock().ok()?;
+        sessions
+            .values()
+            .filter_map(|session| session.deadline.map(|(deadline, _)| deadline))
+            .min()
+    }
+
+    fn save(&self, session_id: &str, session: &ServerSession) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.put(session_id, &store::encode(session)) {
+                log::error!("Failed to store session {}; error: {}", session_id, e);
+            }
+        }
+    }
+
+    fn discard(&self, session_id: &str) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(session_id) {
+                log::error!(
+                    "Failed to remove stored session {}; error: {}",
+                    session_id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Creates the DIAMETER_MISSING_AVP answer to `req`, with a Failed-AVP
+    /// holding an empty `name` AVP when its type allows it.
+    fn missing_avp(&self, req: &DiameterMessage, name: &str) -> DiameterMessage {
+        let mut res = DiameterMessage::create_error_answer(
+            req,
+            result_code::DIAMETER_MISSING_AVP,
+            None,
+            &self.node,
+        );
+        let failed = GroupBuilder::new(Arc::clone(&self.dict))
+            .avp(name, "")
+            .build();
+        if let Ok(failed) = failed {
+            if let Err(e) = res.add_avp_by_name("Failed-AVP", failed.into()) {
+                log::warn!("Failed to add Failed-AVP; error: {}", e);
+            }
+        }
+        res
+    }
+
+    fn notify(&self, session_id: &str, transition: Option<(AuthState, AuthState)>) {
+        if let Some((old, new)) = transition {
+            log::debug!("Session {}: {:?} -> {:?}", session_id, old, new);
+            self.handler.state_changed(session_id, old, new);
+        }
+    }
+
+    async fn authorize(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let session_id: String = match req.get("Session-Id") {
+            Ok(session_id) => session_id,
+            Err(_) => return Ok(self.missing_avp(&req, "Session-Id")),
+        };
+        let (origin_host, origin_realm): (String, String) =
+            match (req.get("Origin-Host"), req.get("Origin-Realm")) {
+                (Ok(origin_host), Ok(origin_realm)) => (origin_host, origin_realm),
+                (Err(_), _) => return Ok(self.missing_avp(&req, "Origin-Host")),
+                (_, Err(_)) => return Ok(self.missing_avp(&req, "Origin-Realm")),
+            };
+        let requested = req.get::<AuthSessionState, _>("Auth-Session-State").ok();
+
+        let mut answer = match self.handler.authorize(&req).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                log::error!("Failed to authorize; session: {}, error: {}", session_id, e);
+                DiameterMessage::create_answer(
+                    &req,
+                    result_code::DIAMETER_UNABLE_TO_COMPLY,
+                    &self.node,
+                )
+            }
+        };
+        let result_code = answer_result_code(&answer);
+        if result_code == result_code::DIAMETER_MULTI_ROUND_AUTH {
+            return Ok(answer);
+        }
+
+        let mut transition = None;
+        if (2000..3000).contains(&result_code) {
+            let auth_session_state = match answer.get("Auth-Session-State") {
+                Ok(auth_session_state) => auth_session_state,
+                Err(_) => {
+                    let auth_session_state = requested.unwrap_or(self.config.auth_session_state);
+                    answer.add_avp_by_name(
+                        "Auth-Session-State",
+                        Enumerated::new(auth_session_state.into()).into(),
+                    )?;
+                    auth_session_state
+                }
+            };
+            let timers = [
+                ("Session-Timeout", self.config.session_timeout),
+                ("Authorization-Lifetime", self.config.authorization_lifetime),
+                ("Auth-Grace-Period", self.config.auth_grace_period),
+            ];
+            for (name, value) in timers {
+                if let (Some(value), Err(_)) = (value, answer.get::<u32, _>(name)) {
+                    answer.add_avp_by_name(name, Unsigned32::new(value).into())?;
+                }
+            }
+
+            let mut sessions = self.sessions.lock()?;
+            if auth_session_state == AuthSessionState::StateMaintained {
+                let grant = AuthGrant::from_message(&answer);
+                let deadline = grant
+                    .expiry()
+                    .map(|(after, timer)| (Instant::now() + after, timer));
+                let session = ServerSession {
+                    state: AuthState::Open,
+                    application_id: req.get_application_id(),
+                    origin_host,
+                    origin_realm,
+                    user_name: req.get("User-Name").ok(),
+                    deadline,
+                };
+                self.save(&session_id, &session);
+                let old = sessions.insert(session_id.clone(), session);
+                let old = old.map_or(AuthState::Idle, |session| session.state);
+                transition = (old != AuthState::Open).then_some((old, AuthState::Open));
+            } else if let Some(session) = sessions.remove(&session_id) {
+                self.discard(&session_id);
+                transition = Some((session.state, AuthState::Idle));
+            }
+        } else if let Some(session) = self.sessions.lock()?.remove(&session_id) {
+            self.discard(&session_id);
+            log::info!(
+                "Authorization failed; session: {}, Result-Code: {}",
+                session_id,
+                result_code
+            );
+            transition = Some((session.state, AuthState::Idle));
+        }
+        self.notify(&session_id, transition);
+        Ok(answer)
+    }
+
+    async fn terminate(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let str = match SessionTerminationRequest::from_message(&req) {
+            Ok(str) => str,
+            Err(e) => {
+                log::warn!("Invalid Session-Termination-Request; error: {}", e);
+                let result_code = match e {
+                    Error::AvpNotFound(name) => return Ok(self.missing_avp(&req, &name)),
+                    Error::InvalidAvpValue(_) => result_code::DIAMETER_INVALID_AVP_VALUE,
+                    _ => result_code::DIAMETER_UNABLE_TO_COMPLY,
+                };
+                return Ok(DiameterMessage::create_error_answer(
+                    &req,
+                    result_code,
+                    None,
+                    &self.node,
+                ));
+            }
+        };
+
+        let removed = self.sessions.lock()?.remove(&str.session_id);
+        let result_code = match removed {
+            Some(session) => {
+                self.discard(&str.session_id);
+                self.notify(&str.session_id, Some((session.state, AuthState::Idle)));
+                self.handler.session_terminated(&str).await;
+                result_code::DIAMETER_SUCCESS
+            }
+            None => result_code::DIAMETER_UNKNOWN_SESSION_ID,
+        };
+        let sta = SessionTerminationAnswer::new(&str, result_code, &self.node);
+        let mut answer =
+            sta.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &self.dict)?;
+        answer.set_application_id(req.get_application_id());
+        Ok(answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::common::{AuthRequestType, TerminationCause};
+    use crate::applications::nasreq::{self, AaAnswer, AaRequest};
+    use crate::dictionary;
+    use crate::session::auth::client::{AuthClient, AuthClientConfig, AuthClientHandler};
+    use crate::session::store::memory::MemoryStore;
+    use crate::transport::{DiameterClient, DiameterClientConfig, DiameterServerConfig};
+
+    #[derive(Default)]
+    struct Records {
+        terminated: Vec<TerminationCause>,
+        expired: Vec<AuthTimer>,
+    }
+
+    struct Aaa {
+        dict: Arc<Dictionary>,
+        node: NodeConfig,
+        records: Arc<Mutex<Records>>,
+    }
+
+    impl AuthServerHandler for Aaa {
+        async fn authorize(&self, req: &DiameterMessage) -> Result<DiameterMessage> {
+            let aar = AaRequest::from_message(req)?;
+            let result_code = match aar.user_name.as_deref() {
+                Some("mallory") => result_code::DIAMETER_AUTHENTICATION_REJECTED,
+                _ => result_code::DIAMETER_SUCCESS,
+            };
+            let aaa = AaAnswer::new(&aar, result_code, &self.node);
+            aaa.to_message(req.get_hop_by_hop_id(), req.get_end_to_end_id(), &self.dict)
+        }
+
+        async fn session_terminated(&self, str: &SessionTerminationRequest) {
+            let mut records = self.records.lock().unwrap();
+            records.terminated.push(str.termination_cause);
+        }
+
+        async fn session_expired(&self, _session_id: &str, timer: AuthTimer) {
+            self.records.lock().unwrap().expired.push(timer);
+        }
+    }
+
+    /// Complies with the ASRs of every user but bob.
+    struct Nas;
+
+    impl AuthClientHandler for Nas {
+        async fn abort_requested(&self, asr: &AbortSessionRequest) -> bool {
+            asr.user_name.as_deref() != Some("bob")
+        }
+    }
+
+    struct Setup {
+        auth_server: AuthServer<Aaa>,
+        peers: ServerPeers,
+        records: Arc<Mutex<Records>>,
+        dict: Arc<Dictionary>,
+    }
+
+    async fn setup(port: u16, config: AuthServerConfig) -> Setup {
+        let dict = Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            nasreq::DICT_XML,
+        ]));
+        let records = Arc::new(Mutex::new(Records::default()));
+        let node = NodeConfig::new("aaa.example.com", "example.com");
+        let aaa = Aaa {
+            dict: Arc::clone(&dict),
+            node: node.clone(),
+            records: Arc::clone(&records),
+        };
+        let auth_server = AuthServer::new(aaa, Arc::clone(&dict), node, config);
+        let addr = format!("0.0.0.0:{}", port);
+        let mut server = DiameterServer::new(&addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let peers = server.peers();
+        let auth_server_ref = auth_server.clone();
+        tokio::spawn(async move {
+            auth_server_ref.listen(&mut server).await.unwrap();
+        });
+        Setup {
+            auth_server,
+            peers,
+            records,
+            dict,
+        }
+    }
+
+    fn client(port: u16) -> DiameterClient {
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        DiameterClient::new(&format!("localhost:{}", port), config)
+    }
+
+    async fn auth_client(setup: &Setup, port: u16) -> AuthClient<Nas> {
+        let mut client = client(port);
+        let mut handler = client.connect().await.unwrap();
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let auth_client = AuthClient::new(
+            Nas,
+            client,
+            Arc::clone(&setup.dict),
+            nas,
+            AuthClientConfig::default(),
+        );
+        let auth_client_ref = auth_client.clone();
+        let dict = Arc::clone(&setup.dict);
+        tokio::spawn(async move {
+            DiameterClient::handle_with_requests(&mut handler, dict, move |req| {
+                let auth_client = auth_client_ref.clone();
+                async move { auth_client.handle_request(req).await }
+            })
+            .await;
+        });
+        auth_client
+    }
+
+    fn aar(dict: &Arc<Dictionary>, session_id: &str, user_name: &str) -> DiameterMessage {
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let mut aar = AaRequest::new(
+            session_id,
+            &nas,
+            "example.com",
+            AuthRequestType::AuthorizeOnly,
+        );
+        aar.user_name = Some(user_name.into());
+        aar.to_message(0, 0, dict).unwrap()
+    }
+
+    async fn wait_for<F: Fn() -> bool>(condition: F) {
+        for _ in 0..50 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("condition not met");
+    }
+
+    #[tokio::test]
+    async fn test_stateful_session() {
+        let config = AuthServerConfig {
+            session_timeout: Some(3600),
+            ..Default::default()
+        };
+        let setup = setup(3887, config).await;
+        let auth_client = auth_client(&setup, 3887).await;
+        let auth_server = &setup.auth_server;
+
+        let session_id = "nas.example.com;1;1";
+        let answer = auth_client
+            .send(aar(&setup.dict, session_id, "alice"))
+            .await
+            .unwrap();
+        assert_eq!(answer.get::<u32, _>("Result-Code").unwrap(), 2001);
+        assert_eq!(answer.get::<i32, _>("Auth-Session-State").unwrap(), 0);
+        assert_eq!(answer.get::<u32, _>("Session-Timeout").unwrap(), 3600);
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+        assert_eq!(auth_server.state(session_id), AuthState::Open);
+
+        // Re-authorization keeps the session open
+        auth_client
+            .send(aar(&setup.dict, session_id, "alice"))
+            .await
+            .unwrap();
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+
+        auth_client
+            .terminate(session_id, TerminationCause::DiameterLogout)
+            .await
+            .unwrap();
+        assert_eq!(auth_client.state(session_id), AuthState::Idle);
+        assert_eq!(auth_server.state(session_id), AuthState::Idle);
+        assert_eq!(
+            setup.records.lock().unwrap().terminated,
+            vec![TerminationCause::DiameterLogout]
+        );
+        assert!(auth_client
+            .terminate(session_id, TerminationCause::DiameterLogout)
+            .await
+            .is_err());
+
+        // A rejected request leaves no session
+        let session_id = "nas.example.com;1;2";
+        let answer = auth_client
+            .send(aar(&setup.dict, session_id, "mallory"))
+            .await
+            .unwrap();
+        assert_eq!(answer.get::<u32, _>("Result-Code").unwrap(), 4001);
+        assert_eq!(auth_client.state(session_id), AuthState::Idle);
+        assert!(auth_server.session_ids().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_avp() {
+        let setup = setup(3907, AuthServerConfig::default()).await;
+        let auth_server = &setup.auth_server;
+
+        for name in ["Session-Id", "Origin-Realm"] {
+            let mut req = aar(&setup.dict, "nas.example.com;7;1", "alice");
+            req.remove_avps(name);
+            let res = auth_server.handle(req).await.unwrap();
+            assert!(res.is_error());
+            assert_eq!(
+                res.get::<u32, _>("Result-Code").unwrap(),
+                result_code::DIAMETER_MISSING_AVP
+            );
+            let failed = res.query(&format!("Failed-AVP/{}", name)).unwrap();
+            assert_eq!(failed.len(), 1);
+        }
+        assert!(auth_server.session_ids().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stateless_session() {
+        let config = AuthServerConfig {
+            auth_session_state: AuthSessionState::NoStateMaintained,
+            ..Default::default()
+        };
+        let setup = setup(3888, config).await;
+        let auth_client = auth_client(&setup, 3888).await;
+
+        let session_id = "nas.example.com;2;1";
+        let answer = auth_client
+            .send(aar(&setup.dict, session_id, "alice"))
+            .await
+            .unwrap();
+        assert_eq!(answer.get::<i32, _>("Auth-Session-State").unwrap(), 1);
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+        assert!(setup.auth_server.session_ids().unwrap().is_empty());
+
+        // No STR is sent for a stateless session
+        auth_client
+            .terminate(session_id, TerminationCause::DiameterLogout)
+            .await
+            .unwrap();
+        assert_eq!(auth_client.state(session_id), AuthState::Idle);
+        assert!(setup.records.lock().unwrap().terminated.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abort_session() {
+        let setup = setup(3889, AuthServerConfig::default()).await;
+        let auth_client = auth_client(&setup, 3889).await;
+        let auth_server = &setup.auth_server;
+
+        let session_id = "nas.example.com;3;1";
+        auth_client
+            .send(aar(&setup.dict, session_id, "alice"))
+            .await
+            .unwrap();
+        assert!(auth_server.abort(&setup.peers, session_id).await.unwrap());
+        assert_eq!(auth_server.state(session_id), AuthState::Idle);
+        wait_for(|| auth_client.state(session_id) == AuthState::Idle).await;
+
+        // The client refuses to abort the session of bob
+        let session_id = "nas.example.com;3;2";
+        auth_client
+            .send(aar(&setup.dict, session_id, "bob"))
+            .await
+            .unwrap();
+        assert!(!auth_server.abort(&setup.peers, session_id).await.unwrap());
+        assert_eq!(auth_server.state(session_id), AuthState::Open);
+        assert_eq!(auth_client.state(session_id), AuthState::Open);
+
+        assert!(auth_server
+            .abort(&setup.peers, "nas.example.com;3;3")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_expiry() {
+        let config = AuthServerConfig {
+            authorization_lifetime: Some(0),
+            auth_grace_period: Some(1),
+            ..Default::default()
+        };
+        let setup = setup(3890, config).await;
+        let mut client = client(3890);
+        let mut handler = client.connect().await.unwrap();
+        let dict = Arc::clone(&setup.dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict).await;
+        });
+
+        let session_id = "nas.example.com;4;1";
+        let mut req = aar(&setup.dict, session_id, "alice");
+        let seq_num = client.get_next_seq_num();
+        req.set_hop_by_hop_id(seq_num);
+        req.set_end_to_end_id(seq_num);
+        let answer = client.send_message(req).await.unwrap().await.unwrap();
+        assert_eq!(answer.get::<u32, _>("Authorization-Lifetime").unwrap(), 0);
+        assert_eq!(setup.auth_server.state(session_id), AuthState::Open);
+
+        let records = Arc::clone(&setup.records);
+        wait_for(|| records.lock().unwrap().expired == vec![AuthTimer::AuthorizationLifetime])
+            .await;
+        assert_eq!(setup.auth_server.state(session_id), AuthState::Idle);
+
+        // The STR of an expired session is unknown
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let str = SessionTerminationRequest::new(
+            session_id,
+            &nas,
+            "example.com",
+            nasreq::APPLICATION_ID,
+            TerminationCause::DiameterLogout,
+        );
+        let seq_num = client.get_next_seq_num();
+        let mut req = str.to_message(seq_num, seq_num, &setup.dict).unwrap();
+        req.set_application_id(ApplicationId::Nasreq);
+        let answer = client.send_message(req).await.unwrap().await.unwrap();
+        assert_eq!(answer.get_application_id(), ApplicationId::Nasreq);
+        let sta = SessionTerminationAnswer::from_message(&answer).unwrap();
+        assert_eq!(sta.result_code, result_code::DIAMETER_UNKNOWN_SESSION_ID);
+    }
+
+    #[tokio::test]
+    async fn test_store() {
+        let dict = Arc::new(Dictionary::new(&[
+            &dictionary::DEFAULT_DICT_XML,
+            nasreq::DICT_XML,
+        ]));
+        let node = NodeConfig::new("aaa.example.com", "example.com");
+        let store: Arc<dyn SessionStore> = Arc::new(MemoryStore::new());
+        let new_server = || {
+            let aaa = Aaa {
+                dict: Arc::clone(&dict),
+                node: node.clone(),
+                records: Arc::new(Mutex::new(Records::default())),
+            };
+            let config = AuthServerConfig {
+                session_timeout: Some(3600),
+                ..Default::default()
+            };
+            AuthServer::new(aaa, Arc::clone(&dict), node.clone(), config)
+        };
+
+        let mut auth_server = new_server();
+        auth_server.set_store(Arc::clone(&store)).unwrap();
+        for (session_id, user_name) in [
+            ("nas.example.com;5;1", "alice"),
+            ("nas.example.com;5;2", "bob"),
+        ] {
+            let answer = auth_server
+                .handle(aar(&dict, session_id, user_name))
+                .await
+                .unwrap();
+            assert_eq!(
+                answer.get::<u32, _>("Result-Code").unwrap(),
+                result_code::DIAMETER_SUCCESS
+            );
+        }
+        let nas = NodeConfig::new("nas.example.com", "example.com");
+        let str = SessionTerminationRequest::new(
+            "nas.example.com;5;2",
+            &nas,
+            "example.com",
+            nasreq::APPLICATION_ID,
+            TerminationCause::DiameterLogout,
+        );
+        auth_server
+            .handle(str.to_message(0, 0, &dict).unwrap())
+            .await
+            .unwrap();
+        drop(auth_server);
+
+        // A new server takes over the sessions left open
+        let mut auth_server = new_server();
+        auth_server.set_store(store).unwrap();
+        assert_eq!(
+            auth_server.session_ids().unwrap(),
+            vec!["nas.example.com;5;1"]
+        );
+        assert_eq!(auth_server.state("nas.example.com;5;1"), AuthState::Open);
+        let sessions = auth_server.sessions.lock().unwrap();
+        let session = &sessions["nas.example.com;5;1"];
+        assert_eq!(session.application_id, ApplicationId::Nasreq);
+        assert_eq!(session.origin_host, "nas.example.com");
+        assert_eq!(session.user_name.as_deref(), Some("alice"));
+        let (deadline, timer) = session.deadline.unwrap();
+        assert_eq!(timer, AuthTimer::SessionTimeout);
+        assert!(deadline > Instant::now() + Duration::from_secs(3590));
+    }
+}
@@ -0,0 +1,122 @@
+//! Session-Id Generation
+//!
+//! Session-Ids in the format recommended by
+//! [RFC 6733 section 8.8](https://tools.ietf.org/html/rfc6733#section-8.8):
+//!
+//! ```text
+//! <DiameterIdentity>;<high 32 bits>;<low 32 bits>[;<optional value>]
+//! ```
+//!
+//! The high 32 bits are seeded with the time the generator is created, and
+//! the 64 bits they form with the low 32 bits are incremented for each
+//! Session-Id, so that Session-Ids stay unique across restarts of the node.
+//!
+//! ```
+//! use diameter::session::id::SessionIdGenerator;
+//!
+//! let generator = SessionIdGenerator::with_seed("nas.example.com", 1700000000);
+//! assert_eq!(generator.generate(), "nas.example.com;1700000000;0");
+//! assert_eq!(generator.generate_with("port-1"), "nas.example.com;1700000000;1;port-1");
+//! ```
+
+use crate::node::NodeConfig;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A generator of unique Session-Ids for a Diameter node.
+#[derive(Debug)]
+pub struct SessionIdGenerator {
+    identity: String,
+    next: AtomicU64,
+}
+
+impl SessionIdGenerator {
+    /// Creates a generator for the node identified by `identity`, seeding the
+    /// high 32 bits with the current time in seconds.
+    pub fn new(identity: &str) -> SessionIdGenerator {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        SessionIdGenerator::with_seed(identity, now as u32)
+    }
+
+    /// Creates a generator for the Origin-Host of `node`.
+    pub fn from_node(node: &NodeConfig) -> SessionIdGenerator {
+        SessionIdGenerator::new(&node.origin_host)
+    }
+
+    /// Creates a generator with the given high 32 bits, such as a boot
+    /// counter kept by the node.
+    pub fn with_seed(identity: &str, high: u32) -> SessionIdGenerator {
+        SessionIdGenerator {
+            identity: identity.into(),
+            next: AtomicU64::new(u64::from(high) << 32),
+        }
+    }
+
+    /// Returns the identity the Session-Ids start with.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Returns a new Session-Id.
+    pub fn generate(&self) -> String {
+        let (high, low) = self.next_value();
+        format!("{};{};{}", self.identity, high, low)
+    }
+
+    /// Returns a new Session-Id ending with an optional value, such as the
+    /// port or the user the session belongs to.
+    pub fn generate_with(&self, optional: &str) -> String {
+        let (high, low) = self.next_value();
+        format!("{};{};{};{}", self.identity, high, low, optional)
+    }
+
+    fn next_value(&self) -> (u32, u32) {
+        // The high bits are incremented when the low bits wrap around
+        let value = self.next.fetch_add(1, Ordering::Relaxed);
+        ((value >> 32) as u32, value as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_wrap_around() {
+        let generator = SessionIdGenerator::with_seed("nas.example.com", 7);
+        generator
+            .next
+            .store((7 << 32) | u64::from(u32::MAX), Ordering::Relaxed);
+        assert_eq!(generator.generate(), "nas.example.com;7;4294967295");
+        assert_eq!(generator.generate(), "nas.example.com;8;0");
+    }
+
+    #[test]
+    fn test_unique_across_threads() {
+        let node = NodeConfig::new("nas.example.com", "example.com");
+        let generator = Arc::new(SessionIdGenerator::from_node(&node));
+        assert_eq!(generator.identity(), "nas.example.com");
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let generator = Arc::clone(&generator);
+                std::thread::spawn(move || {
+                    (0..1000).map(|_| generator.generate()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut ids = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert_eq!(id.split(';').count(), 3);
+                assert!(ids.insert(id));
+            }
+        }
+        assert_eq!(ids.len(), 4000);
+    }
+}
@@ -0,0 +1,16 @@
+//! Diameter Sessions
+//!
+//! The session layer shared by the Diameter applications, as described in
+//! [RFC 6733 section 8](https://tools.ietf.org/html/rfc6733#section-8).
+//!
+//! * [`auth`] - Authorization session state machines, keeping the sessions
+//!   of any auth application by Session-Id.
+//! * [`id`] - Generation of unique Session-Ids.
+//! * [`registry`] - A concurrent registry of the sessions of an application
+//!   and their state.
+//! * [`store`] - Storage of session state, in memory or in a file.
+
+pub mod auth;
+pub mod id;
+pub mod registry;
+pub mod store;
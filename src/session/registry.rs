@@ -0,0 +1,384 @@
+//! Session Registry
+//!
+//! A [`SessionRegistry`] keeps the state an application associates with
+//! each of its sessions, by Session-Id. It is shared between tasks by
+//! cloning it, and releases the sessions whose expiry has passed when
+//! [`expire`](SessionRegistry::expire) is called.
+//!
+//! A registry created [`with_store`](SessionRegistry::with_store) writes
+//! each change to a [`SessionStore`], and starts with the sessions found in
+//! the store.
+//!
+//! ```
+//! use diameter::session::id::SessionIdGenerator;
+//! use diameter::session::registry::SessionRegistry;
+//! use std::time::Duration;
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Subscriber {
+//!     user_name: String,
+//!     used_octets: u64,
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let generator = SessionIdGenerator::new("pgw.example.com");
+//!     let registry = SessionRegistry::new();
+//!
+//!     let session_id = generator.generate();
+//!     let subscriber = Subscriber { user_name: "alice".into(), used_octets: 0 };
+//!     registry.insert(&session_id, subscriber).unwrap();
+//!     registry.set_timeout(&session_id, Some(Duration::from_secs(3600))).unwrap();
+//!
+//!     registry.update(&session_id, |subscriber| subscriber.used_octets += 1500).unwrap();
+//!     let subscriber = registry.get(&session_id).unwrap().unwrap();
+//!     assert_eq!(subscriber.used_octets, 1500);
+//!
+//!     assert!(registry.expire().unwrap().is_empty());
+//!     assert_eq!(registry.session_ids().unwrap(), vec![session_id]);
+//! }
+//! ```
+
+use super::store::{RecordReader, RecordWriter, SessionRecord, SessionStore};
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A registered session.
+struct Entry<S> {
+    state: S,
+    expiry: Option<Instant>,
+}
+
+impl<S: SessionRecord> SessionRecord for Entry<S> {
+    fn encode_record(&self, writer: &mut RecordWriter) {
+        writer.put_deadline(self.expiry);
+        self.state.encode_record(writer);
+    }
+
+    fn decode_record(reader: &mut RecordReader) -> Result<Entry<S>> {
+        let expiry = reader.get_deadline()?;
+        let state = S::decode_record(reader)?;
+        Ok(Entry { state, expiry })
+    }
+}
+
+/// The store a registry writes its sessions to.
+struct Persistence<S> {
+    store: Arc<dyn SessionStore>,
+    encode: fn(&Entry<S>) -> Vec<u8>,
+}
+
+impl<S> Clone for Persistence<S> {
+    fn clone(&self) -> Self {
+        Persistence {
+            store: Arc::clone(&self.store),
+            encode: self.encode,
+        }
+    }
+}
+
+/// A concurrent map of sessions by Session-Id, with an optional expiry per
+/// session.
+pub struct SessionRegistry<S> {
+    sessions: Arc<Mutex<HashMap<String, Entry<S>>>>,
+    persistence: Option<Persistence<S>>,
+}
+
+impl<S> Clone for SessionRegistry<S> {
+    fn clone(&self) -> Self {
+        SessionRegistry {
+            sessions: Arc::clone(&self.sessions),
+            persistence: self.persistence.clone(),
+        }
+    }
+}
+
+impl<S> Default for SessionRegistry<S> {
+    fn default() -> Self {
+        SessionRegistry {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            persistence: None,
+        }
+    }
+}
+
+impl<S: SessionRecord> SessionRegistry<S> {
+    /// Creates a registry persisted in `store`, loading the sessions it
+    /// holds.
+    pub fn with_store(store: Arc<dyn SessionStore>) -> Result<SessionRegistry<S>> {
+        let mut sessions = HashMap::new();
+        for (session_id, record) in store.records()? {
+            sessions.insert(session_id, super::store::decode(&record)?);
+        }
+        Ok(SessionRegistry {
+            sessions: Arc::new(Mutex::new(sessions)),
+            persistence: Some(Persistence {
+                store,
+                encode: super::store::encode::<Entry<S>>,
+            }),
+        })
+    }
+}
+
+impl<S> SessionRegistry<S> {
+    /// Creates an empty registry.
+    pub fn new() -> SessionRegistry<S> {
+        SessionRegistry::default()
+    }
+
+    /// Registers a session without expiry, returning the state it replaces.
+    pub fn insert(&self, session_id: &str, state: S) -> Result<Option<S>> {
+        let entry = Entry {
+            state,
+            expiry: None,
+        };
+        let mut sessions = self.sessions.lock()?;
+        self.persist(session_id, &entry)?;
+        let old = sessions.insert(session_id.into(), entry);
+        Ok(old.map(|entry| entry.state))
+    }
+
+    /// Sets the time after which a session expires, or removes its expiry.
+    /// Returns false when the session is not registered.
+    pub fn set_timeout(&self, session_id: &str, timeout: Option<Duration>) -> Result<bool> {
+        let mut sessions = self.sessions.lock()?;
+        match sessions.get_mut(session_id) {
+            Some(entry) => {
+                entry.expiry = timeout.map(|timeout| Instant::now() + timeout);
+                self.persist(session_id, entry)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Applies `f` to the state of a session, returning its result, or None
+    /// when the session is not registered.
+    pub fn update<R, F>(&self, session_id: &str, f: F) -> Result<Option<R>>
+    where
+        F: FnOnce(&mut S) -> R,
+    {
+        let mut sessions = self.sessions.lock()?;
+        let Some(entry) = sessions.get_mut(session_id) else {
+            return Ok(None);
+        };
+        let result = f(&mut entry.state);
+        self.persist(session_id, entry)?;
+        Ok(Some(result))
+    }
+
+    /// Unregisters a session, returning its state.
+    pub fn remove(&self, session_id: &str) -> Result<Option<S>> {
+        let mut sessions = self.sessions.lock()?;
+        let old = sessions.remove(session_id);
+        if old.is_some() {
+            self.unpersist(session_id)?;
+        }
+        Ok(old.map(|entry| entry.state))
+    }
+
+    /// Returns true if the session is registered.
+    pub fn contains(&self, session_id: &str) -> Result<bool> {
+        Ok(self.sessions.lock()?.contains_key(session_id))
+    }
+
+    /// Returns the number of registered sessions.
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.sessions.lock()?.len())
+    }
+
+    /// Returns true if no session is registered.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.sessions.lock()?.is_empty())
+    }
+
+    /// Returns the Session-Ids of the registered sessions.
+    pub fn session_ids(&self) -> Result<Vec<String>> {
+        Ok(self.sessions.lock()?.keys().cloned().collect())
+    }
+
+    /// Calls `f` with each registered session.
+    ///
+    /// The registry is locked meanwhile, so `f` must not use it.
+    pub fn for_each<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&str, &S),
+    {
+        let sessions = self.sessions.lock()?;
+        for (session_id, entry) in sessions.iter() {
+            f(session_id, &entry.state);
+        }
+        Ok(())
+    }
+
+    /// Unregisters the sessions whose expiry has passed, returning them.
+    pub fn expire(&self) -> Result<Vec<(String, S)>> {
+        let mut sessions = self.sessions.lock()?;
+        let now = Instant::now();
+        let expired: Vec<String> = sessions
+            .iter()
+            .filter(|(_, entry)| entry.expiry.is_some_and(|expiry| expiry <= now))
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        Ok(expired
+            .into_iter()
+            .filter_map(|session_id| {
+                let entry = sessions.remove(&session_id)?;
+                if let Err(e) = self.unpersist(&session_id) {
+                    log::error!("Failed to remove expired session; error: {}", e);
+                }
+                Some((session_id, entry.state))
+            })
+            .collect())
+    }
+
+    /// Returns the earliest expiry of the registered sessions.
+    pub fn next_expiry(&self) -> Result<Option<Instant>> {
+        Ok(self
+            .sessions
+            .lock()?
+            .values()
+            .filter_map(|entry| entry.expiry)
+            .min())
+    }
+
+    fn persist(&self, session_id: &str, entry: &Entry<S>) -> Result<()> {
+        match &self.persistence {
+            Some(persistence) => persistence
+                .store
+                .put(session_id, &(persistence.encode)(entry)),
+            None => Ok(()),
+        }
+    }
+
+    fn unpersist(&self, session_id: &str) -> Result<()> {
+        match &self.persistence {
+            Some(persistence) => persistence.store.remove(session_id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: Clone> SessionRegistry<S> {
+    /// Returns a copy of the state of a session.
+    pub fn get(&self, session_id: &str) -> Result<Option<S>> {
+        let sessions = self.sessions.lock()?;
+        Ok(sessions.get(session_id).map(|entry| entry.state.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::store::memory::MemoryStore;
+
+    #[test]
+    fn test_registry() {
+        let registry = SessionRegistry::new();
+        assert!(registry.is_empty().unwrap());
+        assert_eq!(registry.insert("nas.example.com;1;1", 1).unwrap(), None);
+        assert_eq!(registry.insert("nas.example.com;1;2", 2).unwrap(), None);
+        assert_eq!(registry.insert("nas.example.com;1;1", 3).unwrap(), Some(1));
+
+        let shared = registry.clone();
+        assert_eq!(
+            shared.update("nas.example.com;1;2", |n| *n *= 10).unwrap(),
+            Some(())
+        );
+        assert_eq!(
+            shared.update("nas.example.com;1;3", |n| *n *= 10).unwrap(),
+            None
+        );
+        assert_eq!(registry.get("nas.example.com;1;2").unwrap(), Some(20));
+        assert!(registry.contains("nas.example.com;1;1").unwrap());
+        assert_eq!(registry.len().unwrap(), 2);
+
+        let mut total = 0;
+        registry.for_each(|_, n| total += n).unwrap();
+        assert_eq!(total, 23);
+
+        let mut session_ids = registry.session_ids().unwrap();
+        session_ids.sort();
+        assert_eq!(
+            session_ids,
+            vec!["nas.example.com;1;1", "nas.example.com;1;2"]
+        );
+
+        assert_eq!(registry.remove("nas.example.com;1;1").unwrap(), Some(3));
+        assert_eq!(registry.remove("nas.example.com;1;1").unwrap(), None);
+        assert_eq!(registry.len().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_expire() {
+        let registry = SessionRegistry::new();
+        registry.insert("nas.example.com;1;1", "a").unwrap();
+        registry.insert("nas.example.com;1;2", "b").unwrap();
+        registry.insert("nas.example.com;1;3", "c").unwrap();
+        let hour = Some(Duration::from_secs(3600));
+        assert!(registry
+            .set_timeout("nas.example.com;1;1", Some(Duration::ZERO))
+            .unwrap());
+        assert!(registry.set_timeout("nas.example.com;1;2", hour).unwrap());
+        assert!(!registry.set_timeout("nas.example.com;1;4", hour).unwrap());
+        assert!(registry.next_expiry().unwrap().unwrap() <= Instant::now());
+
+        assert_eq!(
+            registry.expire().unwrap(),
+            vec![("nas.example.com;1;1".to_string(), "a")]
+        );
+        assert!(registry.next_expiry().unwrap().unwrap() > Instant::now());
+
+        // Removing the expiry keeps the session
+        registry.set_timeout("nas.example.com;1;2", None).unwrap();
+        assert!(registry.expire().unwrap().is_empty());
+        assert_eq!(registry.next_expiry().unwrap(), None);
+        assert_eq!(registry.len().unwrap(), 2);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter(u32);
+
+    impl SessionRecord for Counter {
+        fn encode_record(&self, writer: &mut RecordWriter) {
+            writer.put_u32(self.0);
+        }
+
+        fn decode_record(reader: &mut RecordReader) -> Result<Counter> {
+            Ok(Counter(reader.get_u32()?))
+        }
+    }
+
+    #[test]
+    fn test_with_store() {
+        let store: Arc<dyn SessionStore> = Arc::new(MemoryStore::new());
+        let registry = SessionRegistry::with_store(Arc::clone(&store)).unwrap();
+        registry.insert("nas.example.com;1;1", Counter(1)).unwrap();
+        registry.insert("nas.example.com;1;2", Counter(2)).unwrap();
+        registry.insert("nas.example.com;1;3", Counter(3)).unwrap();
+        registry
+            .update("nas.example.com;1;1", |counter| counter.0 += 10)
+            .unwrap();
+        let hour = Some(Duration::from_secs(3600));
+        registry.set_timeout("nas.example.com;1;1", hour).unwrap();
+        registry
+            .set_timeout("nas.example.com;1;2", Some(Duration::ZERO))
+            .unwrap();
+        registry.remove("nas.example.com;1;3").unwrap();
+        assert_eq!(registry.expire().unwrap().len(), 1);
+        assert_eq!(store.records().unwrap().len(), 1);
+
+        // A new registry starts with the stored sessions
+        let registry = SessionRegistry::<Counter>::with_store(store).unwrap();
+        assert_eq!(registry.session_ids().unwrap(), vec!["nas.example.com;1;1"]);
+        assert_eq!(
+            registry.get("nas.example.com;1;1").unwrap(),
+            Some(Counter(11))
+        );
+        let expiry = registry.next_expiry().unwrap().unwrap();
+        assert!(expiry > Instant::now() + Duration::from_secs(3590));
+    }
+}
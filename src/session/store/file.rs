@@ -0,0 +1,335 @@
+//! File-Backed Session Store
+//!
+//! A [`FileStore`] appends each change to a log file and syncs it to disk
+//! before returning, so that the records survive a crash of the process.
+//! The records are kept in memory too, and read back from the log when the
+//! file is opened again. A change left incomplete by a crash is discarded.
+//!
+//! Each entry of the log is preceded by its length, and records the new
+//! record of a session or its removal. Once the entries that no longer
+//! count outweigh the live records, the log is rewritten with the live
+//! records only.
+//!
+//! A change that fails to be written is cut off the log, so that the log
+//! never holds more than the records in memory. When the log can't be cut
+//! back either, the store refuses the changes that follow until it is
+//! compacted.
+
+use super::{RecordReader, RecordWriter, SessionStore};
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Entry storing the record of a session.
+const PUT: u8 = 1;
+/// Entry removing the record of a session.
+const REMOVE: u8 = 2;
+/// Size of the stale entries under which the log is never rewritten.
+const MIN_COMPACTION_SIZE: usize = 64 * 1024;
+
+struct Log {
+    file: File,
+    /// The live records, with the size of the entry that stored them.
+    records: HashMap<String, (Vec<u8>, usize)>,
+    live_size: usize,
+    stale_size: usize,
+    /// Whether an incomplete entry could not be cut off the log.
+    poisoned: bool,
+}
+
+/// A session store persisted in an append-only log file.
+pub struct FileStore {
+    path: PathBuf,
+    log: Mutex<Log>,
+}
+
+impl FileStore {
+    /// Opens the store kept in the file at `path`, creating the file if it
+    /// does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileStore> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+
+        let mut records = HashMap::new();
+        let mut live_size = 0;
+        let mut stale_size = 0;
+        let mut offset = 0;
+        while data.len() - offset >= 4 {
+            let length = u32::from_be_bytes(data[offset..offset + 4].try_into()?) as usize;
+            let Some(entry) = data.get(offset + 4..offset + 4 + length) else {
+                break;
+            };
+            let Ok((op, session_id, record)) = parse_entry(entry) else {
+                break;
+            };
+            let size = 4 + length;
+            if let Some((_, old_size)) = records.remove(&session_id) {
+                live_size -= old_size;
+                stale_size += old_size;
+            }
+            if op == PUT {
+                records.insert(session_id, (record, size));
+                live_size += size;
+            } else {
+                stale_size += size;
+            }
+            offset += size;
+        }
+        if offset < data.len() {
+            log::warn!(
+                "Discarding {} bytes of incomplete session record in {}",
+                data.len() - offset,
+                path.display()
+            );
+            file.set_len(offset as u64)?;
+            file.sync_data()?;
+        }
+
+        Ok(FileStore {
+            path,
+            log: Mutex::new(Log {
+                file,
+                records,
+                live_size,
+                stale_size,
+                poisoned: false,
+            }),
+        })
+    }
+
+    /// Rewrites the log with the live records only.
+    pub fn compact(&self) -> Result<()> {
+        let mut log = self.log.lock()?;
+        self.rewrite(&mut log)
+    }
+
+    fn rewrite(&self, log: &mut Log) -> Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let write = || -> Result<()> {
+            let mut file = File::create(&tmp_path)?;
+            for (session_id, (record, _)) in &log.records {
+                file.write_all(&entry(PUT, session_id, record))?;
+            }
+            file.sync_data()?;
+            fs::rename(&tmp_path, &self.path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            fs::remove_file(&tmp_path).ok();
+            return Err(e);
+        }
+        log.file = OpenOptions::new().append(true).open(&self.path)?;
+        log.stale_size = 0;
+        log.poisoned = false;
+        // The rename itself is durable once the directory is synced
+        sync_dir(&self.path)
+    }
+
+    fn append(&self, log: &mut Log, op: u8, session_id: &str, record: &[u8]) -> Result<()> {
+        if log.poisoned {
+            return Err(Error::IoError(std::io::Error::other(format!(
+                "Session log {} holds an incomplete entry",
+                self.path.display()
+            ))));
+        }
+        let entry = entry(op, session_id, record);
+        let len = log.file.metadata()?.len();
+        let written = log
+            .file
+            .write_all(&entry)
+            .and_then(|_| log.file.sync_data());
+        if let Err(e) = written {
+            // Cut the entry off, lest it be read back when the file is opened
+            let truncated = log.file.set_len(len).and_then(|_| log.file.sync_data());
+            if let Err(e) = truncated {
+                log::error!("Failed to truncate {}; error: {}", self.path.display(), e);
+                log.poisoned = true;
+            }
+            return Err(e.into());
+        }
+
+        if let Some((_, old_size)) = log.records.remove(session_id) {
+            log.live_size -= old_size;
+            log.stale_size += old_size;
+        }
+        if op == PUT {
+            log.records
+                .insert(session_id.into(), (record.to_vec(), entry.len()));
+            log.live_size += entry.len();
+        } else {
+            log.stale_size += entry.len();
+        }
+
+        if log.stale_size > MIN_COMPACTION_SIZE && log.stale_size > log.live_size {
+            // The change is stored already, the log is rewritten next time
+            if let Err(e) = self.rewrite(log) {
+                log::error!("Failed to compact {}; error: {}", self.path.display(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SessionStore for FileStore {
+    fn put(&self, session_id: &str, record: &[u8]) -> Result<()> {
+        let mut log = self.log.lock()?;
+        self.append(&mut log, PUT, session_id, record)
+    }
+
+    fn get(&self, session_id: &str) -> Result<Option<Vec<u8>>> {
+        let log = self.log.lock()?;
+        Ok(log
+            .records
+            .get(session_id)
+            .map(|(record, _)| record.clone()))
+    }
+
+    fn remove(&self, session_id: &str) -> Result<()> {
+        let mut log = self.log.lock()?;
+        if !log.records.contains_key(session_id) {
+            return Ok(());
+        }
+        self.append(&mut log, REMOVE, session_id, &[])
+    }
+
+    fn records(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        let log = self.log.lock()?;
+        Ok(log
+            .records
+            .iter()
+            .map(|(session_id, (record, _))| (session_id.clone(), record.clone()))
+            .collect())
+    }
+}
+
+/// Syncs the directory of `path`, so that a file renamed into it survives a
+/// crash.
+#[cfg(unix)]
+pub(crate) fn sync_dir(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories can't be synced on this platform, where the rename is
+/// durable on its own.
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Encodes a log entry, preceded by its length.
+fn entry(op: u8, session_id: &str, record: &[u8]) -> Vec<u8> {
+    let mut writer = RecordWriter::new();
+    writer.put_u8(op);
+    writer.put_str(session_id);
+    writer.put_bytes(record);
+    let body = writer.into_bytes();
+
+    let mut entry = Vec::with_capacity(4 + body.len());
+    entry.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    entry.extend_from_slice(&body);
+    entry
+}
+
+fn parse_entry(entry: &[u8]) -> Result<(u8, String, Vec<u8>)> {
+    let mut reader = RecordReader::new(entry);
+    let op = reader.get_u8()?;
+    let session_id = reader.get_str()?;
+    let record = reader.get_bytes()?;
+    if !reader.is_empty() || (op != PUT && op != REMOVE) {
+        return Err(Error::DecodeError("Invalid session log entry".into()));
+    }
+    Ok((op, session_id, record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "diameter-session-{}-{}.log",
+            name,
+            std::process::id()
+        ));
+        fs::remove_file(&path).ok();
+        path
+    }
+
+    #[test]
+    fn test_reopen() {
+        let path = path("reopen");
+        let store = FileStore::open(&path).unwrap();
+        store.put("nas.example.com;1;1", b"pending").unwrap();
+        store.put("nas.example.com;1;1", b"open").unwrap();
+        store.put("nas.example.com;1;2", b"open").unwrap();
+        store.remove("nas.example.com;1;2").unwrap();
+        drop(store);
+
+        // Records survive reopening, an incomplete entry is discarded
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&entry(PUT, "nas.example.com;1;3", b"open")[..10])
+            .unwrap();
+        drop(file);
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(
+            store.records().unwrap(),
+            vec![("nas.example.com;1;1".to_string(), b"open".to_vec())]
+        );
+
+        store.put("nas.example.com;1;4", b"open").unwrap();
+        drop(store);
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(
+            store.get("nas.example.com;1;4").unwrap(),
+            Some(b"open".to_vec())
+        );
+        assert_eq!(store.get("nas.example.com;1;2").unwrap(), None);
+        assert_eq!(store.records().unwrap().len(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compaction() {
+        let path = path("compaction");
+        let store = FileStore::open(&path).unwrap();
+        let record = vec![0; 1024];
+        for i in 0..200 {
+            store.put("nas.example.com;1;1", &record).unwrap();
+            store
+                .put(&format!("nas.example.com;2;{}", i), &record)
+                .unwrap();
+            store.remove(&format!("nas.example.com;2;{}", i)).unwrap();
+        }
+        // The log was rewritten as the updates and removals piled up
+        let size = fs::metadata(&path).unwrap().len() as usize;
+        assert!(size < 2 * MIN_COMPACTION_SIZE, "log size {}", size);
+
+        store.compact().unwrap();
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        assert!(!Path::new(&tmp_path).exists());
+        let size = fs::metadata(&path).unwrap().len() as usize;
+        assert_eq!(size, entry(PUT, "nas.example.com;1;1", &record).len());
+        drop(store);
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.records().unwrap().len(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+//! In-Memory Session Store
+//!
+//! A [`MemoryStore`] keeps session records for the lifetime of the process
+//! only, for nodes that do not need their sessions to survive a restart.
+
+use super::SessionStore;
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A session store kept in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn put(&self, session_id: &str, record: &[u8]) -> Result<()> {
+        self.records
+            .lock()?
+            .insert(session_id.into(), record.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, session_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.records.lock()?.get(session_id).cloned())
+    }
+
+    fn remove(&self, session_id: &str) -> Result<()> {
+        self.records.lock()?.remove(session_id);
+        Ok(())
+    }
+
+    fn records(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        let records = self.records.lock()?;
+        Ok(records
+            .iter()
+            .map(|(session_id, record)| (session_id.clone(), record.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryStore::new();
+        store.put("nas.example.com;1;1", b"open").unwrap();
+        store.put("nas.example.com;1;1", b"discon").unwrap();
+        store.put("nas.example.com;1;2", b"open").unwrap();
+        assert_eq!(
+            store.get("nas.example.com;1;1").unwrap(),
+            Some(b"discon".to_vec())
+        );
+
+        store.remove("nas.example.com;1;2").unwrap();
+        store.remove("nas.example.com;1;3").unwrap();
+        assert_eq!(store.get("nas.example.com;1;2").unwrap(), None);
+        assert_eq!(
+            store.records().unwrap(),
+            vec![("nas.example.com;1;1".to_string(), b"discon".to_vec())]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Session Storage
+//!
+//! The session layer keeps session state behind the [`SessionStore`] trait,
+//! so that it can be persisted and reloaded after a restart of the process.
+//! Two stores are provided:
+//!
+//! * [`MemoryStore`](memory::MemoryStore), keeping the records in memory
+//!   only.
+//! * [`FileStore`](file::FileStore), appending the records to a log file
+//!   synced on each change.
+//!
+//! A store maps Session-Ids to opaque records. The state kept in a
+//! [`SessionRegistry`](super::registry::SessionRegistry) is turned into
+//! records through the [`SessionRecord`] trait, and the
+//! [`AuthServer`](super::auth::server::AuthServer) and the
+//! [`CreditControlServer`](crate::applications::credit_control::server::CreditControlServer)
+//! persist their sessions once given a store with `set_store`.
+//!
+//! ```
+//! use diameter::session::store::memory::MemoryStore;
+//! use diameter::session::store::{RecordReader, RecordWriter, SessionRecord, SessionStore};
+//! use diameter::Result;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Subscriber {
+//!     user_name: String,
+//!     used_octets: u64,
+//! }
+//!
+//! impl SessionRecord for Subscriber {
+//!     fn encode_record(&self, writer: &mut RecordWriter) {
+//!         writer.put_str(&self.user_name);
+//!         writer.put_u64(self.used_octets);
+//!     }
+//!
+//!     fn decode_record(reader: &mut RecordReader) -> Result<Subscriber> {
+//!         Ok(Subscriber {
+//!             user_name: reader.get_str()?,
+//!             used_octets: reader.get_u64()?,
+//!         })
+//!     }
+//! }
+//!
+//! let store = MemoryStore::new();
+//! let subscriber = Subscriber { user_name: "alice".into(), used_octets: 1500 };
+//! let mut writer = RecordWriter::new();
+//! subscriber.encode_record(&mut writer);
+//! store.put("pgw.example.com;1;1", &writer.into_bytes()).unwrap();
+//!
+//! let record = store.get("pgw.example.com;1;1").unwrap().unwrap();
+//! let decoded = Subscriber::decode_record(&mut RecordReader::new(&record)).unwrap();
+//! assert_eq!(decoded, subscriber);
+//! ```
+
+pub mod file;
+pub mod memory;
+
+use crate::error::{Error, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// Storage of session records by Session-Id.
+pub trait SessionStore: Send + Sync {
+    /// Stores the record of a session, replacing the previous one.
+    fn put(&self, session_id: &str, record: &[u8]) -> Result<()>;
+
+    /// Returns the record of a session.
+    fn get(&self, session_id: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Removes the record of a session, if any.
+    fn remove(&self, session_id: &str) -> Result<()>;
+
+    /// Returns the records of all the stored sessions.
+    fn records(&self) -> Result<Vec<(String, Vec<u8>)>>;
+}
+
+/// Session state that can be written to a [`SessionStore`].
+pub trait SessionRecord: Sized {
+    /// Writes the state to `writer`.
+    fn encode_record(&self, writer: &mut RecordWriter);
+
+    /// Reads back the state written by `encode_record`.
+    fn decode_record(reader: &mut RecordReader) -> Result<Self>;
+}
+
+/// Writes the fields of a session record, in network byte order.
+#[derive(Debug, Default)]
+pub struct RecordWriter {
+    buf: Vec<u8>,
+}
+
+impl RecordWriter {
+    /// Creates an empty record.
+    pub fn new() -> RecordWriter {
+        RecordWriter::default()
+    }
+
+    /// Writes a byte.
+    pub fn put_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    /// Writes an unsigned 32-bit integer.
+    pub fn put_u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes an unsigned 64-bit integer.
+    pub fn put_u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes bytes, preceded by their length.
+    pub fn put_bytes(&mut self, value: &[u8]) {
+        self.put_u32(value.len() as u32);
+        self.buf.extend_from_slice(value);
+    }
+
+    /// Writes a string, preceded by its length.
+    pub fn put_str(&mut self, value: &str) {
+        self.put_bytes(value.as_bytes());
+    }
+
+    /// Writes an optional string.
+    pub fn put_opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.put_u8(1);
+                self.put_str(value);
+            }
+            None => self.put_u8(0),
+        }
+    }
+
+    /// Writes an optional deadline as wall-clock time, so that it can be
+    /// read back by another process.
+    pub fn put_deadline(&mut self, deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let at = SystemTime::now() + remaining;
+                let millis = at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_millis() as u64)
+                    .unwrap_or(0);
+                self.put_u8(1);
+                self.put_u64(millis);
+            }
+            None => self.put_u8(0),
+        }
+    }
+
+    /// Returns the record.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads the fields of a session record written by a [`RecordWriter`].
+#[derive(Debug)]
+pub struct RecordReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RecordReader<'a> {
+    /// Creates a reader of `data`.
+    pub fn new(data: &'a [u8]) -> RecordReader<'a> {
+        RecordReader { data }
+    }
+
+    /// Returns true if the whole record was read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(Error::DecodeError("Truncated session record".into()));
+        }
+        let (value, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(value)
+    }
+
+    /// Reads a byte.
+    pub fn get_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads an unsigned 32-bit integer.
+    pub fn get_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    /// Reads an unsigned 64-bit integer.
+    pub fn get_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    /// Reads bytes preceded by their length.
+    pub fn get_bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.get_u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    /// Reads a string preceded by its length.
+    pub fn get_str(&mut self) -> Result<String> {
+        String::from_utf8(self.get_bytes()?)
+            .map_err(|e| Error::DecodeError(format!("Invalid session record: {}", e)))
+    }
+
+    /// Reads an optional string.
+    pub fn get_opt_str(&mut self) -> Result<Option<String>> {
+        match self.get_u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.get_str()?)),
+        }
+    }
+
+    /// Reads an optional deadline. A deadline that passed while the record
+    /// was stored is returned as the current time.
+    pub fn get_deadline(&mut self) -> Result<Option<Instant>> {
+        if self.get_u8()? == 0 {
+            return Ok(None);
+        }
+        let at = UNIX_EPOCH + Duration::from_millis(self.get_u64()?);
+        let remaining = at.duration_since(SystemTime::now()).unwrap_or_default();
+        Ok(Some(Instant::now() + remaining))
+    }
+}
+
+/// Encodes `state` as a record.
+pub(crate) fn encode<S: SessionRecord>(state: &S) -> Vec<u8> {
+    let mut writer = RecordWriter::new();
+    state.encode_record(&mut writer);
+    writer.into_bytes()
+}
+
+/// Decodes a record written by [`encode`].
+pub(crate) fn decode<S: SessionRecord>(record: &[u8]) -> Result<S> {
+    S::decode_record(&mut RecordReader::new(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_fields() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let mut writer = RecordWriter::new();
+        writer.put_u8(7);
+        writer.put_u32(3868);
+        writer.put_u64(u64::MAX);
+        writer.put_bytes(&[1, 2, 3]);
+        writer.put_str("nas.example.com");
+        writer.put_opt_str(None);
+        writer.put_opt_str(Some("alice"));
+        writer.put_deadline(None);
+        writer.put_deadline(Some(deadline));
+        let record = writer.into_bytes();
+
+        let mut reader = RecordReader::new(&record);
+        assert_eq!(reader.get_u8().unwrap(), 7);
+        assert_eq!(reader.get_u32().unwrap(), 3868);
+        assert_eq!(reader.get_u64().unwrap(), u64::MAX);
+        assert_eq!(reader.get_bytes().unwrap(), vec![1, 2, 3]);
+        assert_eq!(reader.get_str().unwrap(), "nas.example.com");
+        assert_eq!(reader.get_opt_str().unwrap(), None);
+        assert_eq!(reader.get_opt_str().unwrap(), Some("alice".into()));
+        assert_eq!(reader.get_deadline().unwrap(), None);
+        let read = reader.get_deadline().unwrap().unwrap();
+        assert!(read.max(deadline) - read.min(deadline) < Duration::from_millis(10));
+        assert!(reader.is_empty());
+        assert!(reader.get_u8().is_err());
+
+        let mut reader = RecordReader::new(&record[..8]);
+        reader.get_u8().unwrap();
+        reader.get_u32().unwrap();
+        assert!(reader.get_u64().is_err());
+    }
+}
@@ -2,6 +2,7 @@
 use crate::diameter::DiameterMessage;
 use crate::dictionary::Dictionary;
 use crate::error::{Error, Result};
+use crate::transport::peer::{PeerEntry, PeerStats, PeerTable};
 use crate::transport::Codec;
 use std::collections::HashMap;
 use std::future::Future;
@@ -33,6 +34,8 @@ pub struct DiameterClientConfig {
 ///     writer: An optional thread-safe writer for sending messages to the server.
 ///     msg_caches: A shared, mutable hash map that maps message IDs to channels for sending responses back to the caller.
 ///     seq_num: The next sequence number to use for a message.
+///     peer_table: The table the connection is recorded in.
+///     entry: The connection in the peer table, once connected.
 
 pub struct DiameterClient {
     config: DiameterClientConfig,
@@ -40,6 +43,8 @@ pub struct DiameterClient {
     writer: Option<Arc<Mutex<dyn AsyncWrite + Send + Unpin>>>,
     msg_caches: Arc<Mutex<HashMap<u32, Sender<DiameterMessage>>>>,
     seq_num: u32,
+    peer_table: PeerTable,
+    entry: Option<PeerEntry>,
 }
 
 impl DiameterClient {
@@ -60,15 +65,30 @@ impl DiameterClient {
             writer: None,
             msg_caches: Arc::new(Mutex::new(HashMap::new())),
             seq_num: 0,
+            peer_table: PeerTable::default(),
+            entry: None,
         }
     }
 
+    /// Records the connections of the client in `table`, instead of a
+    /// table of its own.
+    ///
+    /// Takes effect on the next `connect`. See the
+    /// [`peer`](crate::transport::peer) module.
+    pub fn set_peer_table(&mut self, table: PeerTable) {
+        self.peer_table = table;
+    }
+
     /// Establishes a connection to the Diameter server.
     ///
     /// Returns:
     ///    A `Result` containing a `ClientHandler` or an error if the connection cannot be established.
     pub async fn connect(&mut self) -> Result<ClientHandler> {
         let stream = TcpStream::connect(self.address.clone()).await?;
+        let entry = self
+            .peer_table
+            .open(stream.local_addr()?, stream.peer_addr()?, false);
+        self.entry = Some(entry.clone());
 
         if self.config.use_tls {
             let tls_connector = tokio_native_tls::TlsConnector::from(
@@ -80,27 +100,33 @@ impl DiameterClient {
             let (reader, writer) = tokio::io::split(tls_stream);
 
             // writer
-            let writer = Arc::new(Mutex::new(writer));
-            self.writer = Some(writer);
+            let writer: Arc<Mutex<dyn AsyncWrite + Send + Unpin>> = Arc::new(Mutex::new(writer));
+            self.writer = Some(Arc::clone(&writer));
 
             // reader
             let msg_caches = Arc::clone(&self.msg_caches);
             Ok(ClientHandler {
                 reader: Box::new(reader),
+                writer,
                 msg_caches,
+                peer_table: self.peer_table.clone(),
+                entry,
             })
         } else {
             let (reader, writer) = tokio::io::split(stream);
 
             // writer
-            let writer = Arc::new(Mutex::new(writer));
-            self.writer = Some(writer);
+            let writer: Arc<Mutex<dyn AsyncWrite + Send + Unpin>> = Arc::new(Mutex::new(writer));
+            self.writer = Some(Arc::clone(&writer));
 
             // reader
             let msg_caches = Arc::clone(&self.msg_caches);
             Ok(ClientHandler {
                 reader: Box::new(reader),
+                writer,
                 msg_caches,
+                peer_table: self.peer_table.clone(),
+                entry,
             })
         }
     }
@@ -133,24 +159,88 @@ impl DiameterClient {
     ///    }
     ///    ```
     pub async fn handle(handler: &mut ClientHandler, dictionary: Arc<Dictionary>) {
+        Self::handle_with_requests(handler, dictionary, |req: DiameterMessage| async move {
+            Err(Error::ClientError(format!(
+                "No handler for request {:?} hop_by_hop_id {}",
+                req.get_command_code(),
+                req.get_hop_by_hop_id()
+            )))
+        })
+        .await
+    }
+
+    /// Handles incoming Diameter messages, including requests initiated by
+    /// the server over the same connection.
+    ///
+    /// Answers are matched to the pending requests as in `handle`, while
+    /// requests are passed to `request_handler` and the answer it returns is
+    /// sent back to the server. A request for which the handler fails is
+    /// logged and left unanswered.
+    ///
+    /// Args:
+    ///    * handler: The `ClientHandler` for reading messages from the server.
+    ///    * dictionary: The `Dictionary` for decoding messages.
+    ///    * request_handler: A function or closure that takes a request and
+    ///      returns the answer to send.
+    pub async fn handle_with_requests<F, Fut>(
+        handler: &mut ClientHandler,
+        dictionary: Arc<Dictionary>,
+        request_handler: F,
+    ) where
+        F: Fn(DiameterMessage) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<DiameterMessage>> + Send + 'static,
+    {
         loop {
             match Codec::decode(&mut handler.reader, Arc::clone(&dictionary)).await {
+                Ok(msg) if msg.is_request() => {
+                    PeerStats::increment(&handler.entry.stats.requests_received);
+                    if handler.entry.identify(&msg) {
+                        handler.peer_table.update(&handler.entry);
+                    }
+                    let request_handler = request_handler.clone();
+                    let writer = Arc::clone(&handler.writer);
+                    let stats = Arc::clone(&handler.entry.stats);
+                    tokio::spawn(async move {
+                        let res = match request_handler(msg).await {
+                            Ok(res) => res,
+                            Err(e) => {
+                                log::error!("Failed to handle request; error: {:?}", e);
+                                return;
+                            }
+                        };
+                        let mut writer = writer.lock().await;
+                        match Codec::encode(&mut writer.deref_mut(), &res).await {
+                            Ok(_) => PeerStats::increment(&stats.answers_sent),
+                            Err(e) => log::error!("Failed to send answer; error: {:?}", e),
+                        }
+                    });
+                }
                 Ok(res) => {
+                    PeerStats::increment(&handler.entry.stats.answers_received);
+                    let mut updated = handler.entry.identify(&res);
+                    // Applications negotiated by capabilities exchange
+                    updated |= handler.entry.negotiate_capabilities(&res);
+                    if updated {
+                        handler.peer_table.update(&handler.entry);
+                    }
                     if let Err(e) = Self::process_decoded_msg(handler.msg_caches.clone(), res).await
                     {
+                        // A late or unsolicited answer must not tear down the connection
                         log::error!("Failed to process response; error: {:?}", e);
-                        return;
                     }
                 }
                 Err(e) => {
                     log::error!("Failed to read message from socket; error: {:?}", e);
+                    // Fail the pending requests, their answers will not arrive
+                    handler.msg_caches.lock().await.clear();
+                    handler.peer_table.close(&handler.entry);
                     return;
                 }
             }
         }
     }
 
-    async fn process_decoded_msg(
+    pub(crate) async fn process_decoded_msg(
         msg_caches: Arc<Mutex<HashMap<u32, Sender<DiameterMessage>>>>,
         res: DiameterMessage,
     ) -> Result<()> {
@@ -193,14 +283,29 @@ impl DiameterClient {
                 let mut msg_caches = self.msg_caches.lock().await;
                 msg_caches.insert(hop_by_hop, tx);
             }
+            if let Some(entry) = &self.entry {
+                entry.request_capabilities(&req);
+            }
             let mut writer = writer.lock().await;
-            Codec::encode(&mut writer.deref_mut(), &req).await?;
+            if let Err(e) = Codec::encode(&mut writer.deref_mut(), &req).await {
+                self.msg_caches.lock().await.remove(&hop_by_hop);
+                return Err(e);
+            }
+            if let Some(entry) = &self.entry {
+                PeerStats::increment(&entry.stats.requests_sent);
+            }
             Ok(ResponseFuture { receiver: rx })
         } else {
             Err(Error::ClientError("Not connected".into()))
         }
     }
 
+    /// Forgets a pending request, such as one whose answer is no longer
+    /// awaited, so that a late answer to it is discarded.
+    pub async fn cancel(&self, hop_by_hop: u32) {
+        self.msg_caches.lock().await.remove(&hop_by_hop);
+    }
+
     // Returns the next sequence number.
     pub fn get_next_seq_num(&mut self) -> u32 {
         self.seq_num += 1;
@@ -213,7 +318,17 @@ impl DiameterClient {
 pub struct ClientHandler {
     // reader: ReadHalf<TcpStream>,
     reader: Box<dyn AsyncRead + Send + Unpin>,
+    writer: Arc<Mutex<dyn AsyncWrite + Send + Unpin>>,
     msg_caches: Arc<Mutex<HashMap<u32, Sender<DiameterMessage>>>>,
+    peer_table: PeerTable,
+    entry: PeerEntry,
+}
+
+impl Drop for ClientHandler {
+    fn drop(&mut self) {
+        // The connection is closed along with its reader
+        self.peer_table.close(&self.entry);
+    }
 }
 
 /// A future for receiving a Diameter message response.
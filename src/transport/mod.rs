@@ -1,13 +1,17 @@
 //! Diameter Protocol Transport
 
 pub mod client;
+pub mod peer;
+pub mod pool;
 pub mod server;
 
 use crate::dictionary::Dictionary;
 pub use crate::transport::client::DiameterClient;
 pub use crate::transport::client::DiameterClientConfig;
+pub use crate::transport::pool::PeerPool;
 pub use crate::transport::server::DiameterServer;
 pub use crate::transport::server::DiameterServerConfig;
+pub use crate::transport::server::{ServerPeer, ServerPeers};
 
 use crate::diameter::DiameterMessage;
 use crate::error::{Error, Result};
@@ -83,9 +87,12 @@ mod tests {
     use crate::avp::utf8string::UTF8String;
     use crate::avp::Unsigned64;
     use crate::diameter::flags;
+    use crate::diameter::result_code;
     use crate::diameter::{ApplicationId, CommandCode, DiameterMessage};
     use crate::dictionary;
     use crate::dictionary::Dictionary;
+    use crate::node::NodeConfig;
+    use crate::transport::Codec;
     use crate::transport::DiameterClient;
     use crate::transport::DiameterClientConfig;
     use crate::transport::DiameterServer;
@@ -105,30 +112,20 @@ mod tests {
 
         let dict_ref = Arc::new(dict.clone());
         tokio::spawn(async move {
-            let dict_ref2 = Arc::clone(&dict_ref);
             server
                 .listen(
-                    move |req| {
-                        let dict_ref2 = Arc::clone(&dict_ref2);
-                        async move {
-                            println!("Request : {}", req);
-
-                            let mut res = DiameterMessage::new(
-                                req.get_command_code(),
-                                req.get_application_id(),
-                                req.get_flags() ^ flags::REQUEST,
-                                req.get_hop_by_hop_id(),
-                                req.get_end_to_end_id(),
-                                dict_ref2,
-                            );
-                            res.add_avp(264, None, M, Identity::new("host.example.com").into());
-                            res.add_avp(296, None, M, Identity::new("realm.example.com").into());
-                            res.add_avp(263, None, M, UTF8String::new("ses;123458890").into());
-                            res.add_avp(416, None, M, Enumerated::new(1).into());
-                            res.add_avp(415, None, M, Unsigned32::new(1000).into());
-                            res.add_avp(268, None, M, Unsigned32::new(2001).into());
-                            Ok(res)
-                        }
+                    move |req| async move {
+                        println!("Request : {}", req);
+
+                        let node = NodeConfig::new("host.example.com", "realm.example.com");
+                        let mut res = DiameterMessage::create_answer(
+                            &req,
+                            result_code::DIAMETER_SUCCESS,
+                            &node,
+                        );
+                        res.add_avp(416, None, M, Enumerated::new(1).into());
+                        res.add_avp(415, None, M, Unsigned32::new(1000).into());
+                        Ok(res)
                     },
                     dict_ref,
                 )
@@ -209,4 +206,144 @@ mod tests {
             handle.await.unwrap();
         }
     }
+
+    #[tokio::test]
+    async fn test_handler_error() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let mut server =
+            DiameterServer::new("0.0.0.0:3905", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        server.set_node(NodeConfig::new("server.example.com", "example.com"));
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| async move {
+                        let node = NodeConfig::new("server.example.com", "example.com");
+                        match req.get::<String, _>("Session-Id")?.as_str() {
+                            "fail" => Err(crate::Error::ServerError("Failed".into())),
+                            _ => Ok(DiameterMessage::create_answer(
+                                &req,
+                                result_code::DIAMETER_SUCCESS,
+                                &node,
+                            )),
+                        }
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        let client_config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3905", client_config);
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+
+        // A failing handler is answered with an error, keeping the connection
+        for (session_id, code) in [
+            ("fail", result_code::DIAMETER_UNABLE_TO_COMPLY),
+            ("ok", result_code::DIAMETER_SUCCESS),
+        ] {
+            let seq_num = client.get_next_seq_num();
+            let ccr = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+                .hop_by_hop_id(seq_num)
+                .avp("Session-Id", session_id)
+                .avp("Origin-Host", "client.example.com")
+                .avp("Origin-Realm", "example.com")
+                .build()
+                .unwrap();
+            let cca = client.send_message(ccr).await.unwrap().await.unwrap();
+            assert_eq!(cca.get::<u32, _>("Result-Code").unwrap(), code);
+            assert_eq!(cca.is_error(), code != result_code::DIAMETER_SUCCESS);
+            assert_eq!(
+                cca.get::<String, _>("Origin-Host").unwrap(),
+                "server.example.com"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_error_without_node() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let mut server =
+            DiameterServer::new("0.0.0.0:3911", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    |_req| async move { Err(crate::Error::ServerError("Failed".into())) },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        let client_config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3911", client_config);
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+
+        // Without a node to answer as, a failing handler closes the connection
+        let ccr = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .hop_by_hop_id(client.get_next_seq_num())
+            .avp("Session-Id", "fail")
+            .avp("Origin-Host", "client.example.com")
+            .avp("Origin-Realm", "example.com")
+            .build()
+            .unwrap();
+        let response = client.send_message(ccr).await.unwrap();
+        assert!(response.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_server_peer_closed() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let mut server =
+            DiameterServer::new("0.0.0.0:3906", DiameterServerConfig { native_tls: None })
+                .await
+                .unwrap();
+        let peers = server.peers();
+        let mut events = peers.subscribe();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(|req| async move { Ok(req) }, dict_ref)
+                .await
+                .unwrap();
+        });
+
+        let mut stream = tokio::net::TcpStream::connect("localhost:3906")
+            .await
+            .unwrap();
+        events.recv().await.unwrap();
+        let peer = peers.all().pop().unwrap();
+
+        // A request left unanswered when the connection closes
+        let asr = DiameterMessage::request("Abort-Session", Arc::clone(&dict))
+            .hop_by_hop_id(peer.get_next_seq_num())
+            .avp("Session-Id", "client.example.com;1;1")
+            .build()
+            .unwrap();
+        let answer = peer.send_message(asr).await.unwrap();
+        Codec::decode(&mut stream, Arc::clone(&dict)).await.unwrap();
+        drop(stream);
+        let res = tokio::time::timeout(std::time::Duration::from_secs(1), answer).await;
+        assert!(res.unwrap().is_err());
+    }
 }
@@ -0,0 +1,650 @@
+//! Diameter Peer Table
+//!
+//! A [`PeerTable`] records the connections of a node, with the identity of
+//! the peer, the applications negotiated by capabilities exchange and the
+//! number of messages exchanged. [`PeerTable::peers`] returns the current
+//! state of the peers, and [`PeerTable::subscribe`] reports their changes as
+//! [`PeerEvent`]s.
+//!
+//! The connections accepted by a [`DiameterServer`] are recorded in the
+//! table of its [`ServerPeers`], and the connections opened by a
+//! [`DiameterClient`] or a [`PeerPool`] in their own table. The same table
+//! can be set on all of them, for a view of all the peers of the node.
+//!
+//! ```no_run
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::peer::{PeerEvent, PeerTable};
+//! use diameter::transport::{DiameterClient, DiameterClientConfig};
+//! use diameter::transport::{DiameterServer, DiameterServerConfig};
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let peer_table = PeerTable::new();
+//!     let mut events = peer_table.subscribe();
+//!     tokio::spawn(async move {
+//!         while let Some(event) = events.recv().await {
+//!             match event {
+//!                 PeerEvent::Connected(peer) => println!("{} connected", peer.remote_address),
+//!                 PeerEvent::Updated(peer) => println!("{:?} updated", peer.origin_host),
+//!                 PeerEvent::Disconnected(peer) => println!("{:?} gone", peer.origin_host),
+//!             }
+//!         }
+//!     });
+//!
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let config = DiameterClientConfig { use_tls: false, verify_cert: false };
+//!     let mut client = DiameterClient::new("ocs.example.com:3868", config);
+//!     client.set_peer_table(peer_table.clone());
+//!     let mut handler = client.connect().await.unwrap();
+//!     let dict_ref = Arc::clone(&dict);
+//!     tokio::spawn(async move {
+//!         DiameterClient::handle(&mut handler, dict_ref).await;
+//!     });
+//!
+//!     let config = DiameterServerConfig { native_tls: None };
+//!     let mut server = DiameterServer::new("0.0.0.0:3868", config).await.unwrap();
+//!     server.set_peer_table(peer_table.clone());
+//!     server
+//!         .listen(|req| async move { Ok(req) }, dict)
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! [`DiameterServer`]: crate::transport::DiameterServer
+//! [`DiameterClient`]: crate::transport::DiameterClient
+//! [`PeerPool`]: crate::transport::PeerPool
+//! [`ServerPeers`]: crate::transport::ServerPeers
+
+use crate::diameter::{result_code, CommandCode, DiameterMessage};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// The Application Id advertised by relay agents, standing for all the
+/// applications.
+pub const RELAY_APPLICATION_ID: u32 = 0xffffffff;
+
+/// State of a peer connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerState {
+    /// The connection was established, and the peer did not identify
+    /// itself yet.
+    Connected,
+    /// The peer identified itself, by a capabilities exchange or another
+    /// message.
+    Open,
+    /// The connection was closed.
+    Closed,
+}
+
+/// Number of messages exchanged with a peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerCounters {
+    pub requests_received: u64,
+    pub answers_sent: u64,
+    pub requests_sent: u64,
+    pub answers_received: u64,
+}
+
+/// State and statistics of a peer connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerInfo {
+    /// Origin-Host of the last message received from the peer.
+    pub origin_host: Option<String>,
+    /// Origin-Realm of the last message received from the peer.
+    pub origin_realm: Option<String>,
+    /// The Application Ids supported by both the peer and the node, as
+    /// negotiated by capabilities exchange.
+    pub applications: Vec<u32>,
+    pub state: PeerState,
+    /// Whether the connection was accepted by a server, rather than opened
+    /// by a client.
+    pub accepted: bool,
+    pub local_address: SocketAddr,
+    pub remote_address: SocketAddr,
+    /// Time since the connection was established.
+    pub uptime: Duration,
+    pub counters: PeerCounters,
+}
+
+/// Changes of the peer table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerEvent {
+    /// A connection was established.
+    Connected(PeerInfo),
+    /// The peer identified itself, changed its identity, or negotiated its
+    /// applications.
+    Updated(PeerInfo),
+    /// The connection was closed.
+    Disconnected(PeerInfo),
+}
+
+impl PeerEvent {
+    /// Returns the state of the peer when the event occurred.
+    pub fn info(&self) -> &PeerInfo {
+        match self {
+            PeerEvent::Connected(info) => info,
+            PeerEvent::Updated(info) => info,
+            PeerEvent::Disconnected(info) => info,
+        }
+    }
+}
+
+/// The peer connections of a node.
+///
+/// Clones of a table share its peers and subscribers.
+#[derive(Clone, Default)]
+pub struct PeerTable {
+    peers: Arc<Mutex<Vec<PeerEntry>>>,
+    subscribers: Arc<Mutex<Vec<mpsc::UnboundedSender<PeerEvent>>>>,
+}
+
+impl PeerTable {
+    /// Creates an empty table.
+    pub fn new() -> PeerTable {
+        PeerTable::default()
+    }
+
+    /// Returns the state and statistics of all the connections, ordered by
+    /// remote address.
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        let mut peers: Vec<PeerInfo> = match self.peers.lock() {
+            Ok(peers) => peers.iter().map(PeerEntry::info).collect(),
+            Err(_) => Vec::new(),
+        };
+        peers.sort_by_key(|info| info.remote_address);
+        peers
+    }
+
+    /// Returns a receiver of the changes of the connections, from now on.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<PeerEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    /// Records a new connection.
+    pub(crate) fn open(
+        &self,
+        local_address: SocketAddr,
+        remote_address: SocketAddr,
+        accepted: bool,
+    ) -> PeerEntry {
+        let entry = PeerEntry {
+            local_address,
+            remote_address,
+            accepted,
+            established: Instant::now(),
+            details: Arc::new(Mutex::new(PeerDetails::new())),
+            stats: Arc::new(PeerStats::default()),
+        };
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.push(entry.clone());
+        }
+        self.notify(PeerEvent::Connected(entry.info()));
+        entry
+    }
+
+    /// Reports a change of a connection.
+    pub(crate) fn update(&self, entry: &PeerEntry) {
+        self.notify(PeerEvent::Updated(entry.info()));
+    }
+
+    /// Removes a closed connection.
+    pub(crate) fn close(&self, entry: &PeerEntry) {
+        if let Ok(mut details) = entry.details.lock() {
+            details.state = PeerState::Closed;
+        }
+        let removed = match self.peers.lock() {
+            Ok(mut peers) => {
+                let len = peers.len();
+                peers.retain(|peer| !Arc::ptr_eq(&peer.details, &entry.details));
+                peers.len() != len
+            }
+            Err(_) => false,
+        };
+        if removed {
+            self.notify(PeerEvent::Disconnected(entry.info()));
+        }
+    }
+
+    fn notify(&self, event: PeerEvent) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+/// A connection recorded in a [`PeerTable`], updated as messages are
+/// exchanged.
+#[derive(Clone)]
+pub(crate) struct PeerEntry {
+    local_address: SocketAddr,
+    remote_address: SocketAddr,
+    accepted: bool,
+    established: Instant,
+    details: Arc<Mutex<PeerDetails>>,
+    pub stats: Arc<PeerStats>,
+}
+
+impl PeerEntry {
+    pub fn info(&self) -> PeerInfo {
+        let details = self.details.lock().ok();
+        PeerInfo {
+            origin_host: details.as_ref().and_then(|d| d.origin_host.clone()),
+            origin_realm: details.as_ref().and_then(|d| d.origin_realm.clone()),
+            applications: details
+                .as_ref()
+                .map(|d| d.applications.clone())
+                .unwrap_or_default(),
+            state: details.as_ref().map_or(PeerState::Closed, |d| d.state),
+            accepted: self.accepted,
+            local_address: self.local_address,
+            remote_address: self.remote_address,
+            uptime: self.established.elapsed(),
+            counters: self.stats.counters(),
+        }
+    }
+
+    pub fn local_address(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    pub fn origin_host(&self) -> Option<String> {
+        self.details
+            .lock()
+            .ok()
+            .and_then(|details| details.origin_host.clone())
+    }
+
+    /// Records the Origin-Host and Origin-Realm of a message received from
+    /// the peer, returning whether they changed.
+    pub fn identify(&self, msg: &DiameterMessage) -> bool {
+        match self.details.lock() {
+            Ok(mut details) => details.identify(msg),
+            Err(_) => false,
+        }
+    }
+
+    /// Records the applications of a CER, to negotiate with its CEA.
+    pub fn request_capabilities(&self, cer: &DiameterMessage) {
+        if cer.get_command_code() != CommandCode::CapabilitiesExchange || !cer.is_request() {
+            return;
+        }
+        if let Ok(mut details) = self.details.lock() {
+            details.requested = Some(applications(cer));
+        }
+    }
+
+    /// Negotiates the applications of a successful CEA, returning whether
+    /// they were.
+    pub fn negotiate_capabilities(&self, cea: &DiameterMessage) -> bool {
+        if cea.get_command_code() != CommandCode::CapabilitiesExchange || cea.is_request() {
+            return false;
+        }
+        let Ok(mut details) = self.details.lock() else {
+            return false;
+        };
+        let Some(requested) = details.requested.take() else {
+            return false;
+        };
+        if !matches!(
+            cea.get::<u32, _>("Result-Code"),
+            Ok(result_code::DIAMETER_SUCCESS)
+        ) {
+            return false;
+        }
+        details.applications = negotiate(&requested, &applications(cea));
+        true
+    }
+}
+
+/// The identity and state of a peer.
+struct PeerDetails {
+    origin_host: Option<String>,
+    origin_realm: Option<String>,
+    applications: Vec<u32>,
+    /// The applications of the CER waiting for its CEA.
+    requested: Option<Vec<u32>>,
+    state: PeerState,
+}
+
+impl PeerDetails {
+    fn new() -> PeerDetails {
+        PeerDetails {
+            origin_host: None,
+            origin_realm: None,
+            applications: Vec::new(),
+            requested: None,
+            state: PeerState::Connected,
+        }
+    }
+
+    fn identify(&mut self, msg: &DiameterMessage) -> bool {
+        let Ok(origin_host) = msg.get::<String, _>("Origin-Host") else {
+            return false;
+        };
+        let origin_realm = msg.get::<String, _>("Origin-Realm").ok();
+        if self.state == PeerState::Open
+            && self.origin_host.as_deref() == Some(origin_host.as_str())
+            && self.origin_realm == origin_realm
+        {
+            return false;
+        }
+        self.origin_host = Some(origin_host);
+        self.origin_realm = origin_realm;
+        self.state = PeerState::Open;
+        true
+    }
+}
+
+/// Message counters of a peer, shared with the tasks serving it.
+#[derive(Default)]
+pub(crate) struct PeerStats {
+    pub requests_received: AtomicU64,
+    pub answers_sent: AtomicU64,
+    pub requests_sent: AtomicU64,
+    pub answers_received: AtomicU64,
+}
+
+impl PeerStats {
+    pub fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self) -> PeerCounters {
+        PeerCounters {
+            requests_received: self.requests_received.load(Ordering::Relaxed),
+            answers_sent: self.answers_sent.load(Ordering::Relaxed),
+            requests_sent: self.requests_sent.load(Ordering::Relaxed),
+            answers_received: self.answers_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Returns the Application Ids advertised in a CER or CEA.
+fn applications(msg: &DiameterMessage) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for path in [
+        "Auth-Application-Id",
+        "Acct-Application-Id",
+        "Vendor-Specific-Application-Id/Auth-Application-Id",
+        "Vendor-Specific-Application-Id/Acct-Application-Id",
+    ] {
+        ids.extend(msg.query_values::<u32>(path).unwrap_or_default());
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Returns the applications common to the CER and the CEA, as described in
+/// [RFC 6733 section 5.3](https://tools.ietf.org/html/rfc6733#section-5.3).
+fn negotiate(requested: &[u32], supported: &[u32]) -> Vec<u32> {
+    if supported.contains(&RELAY_APPLICATION_ID) {
+        return requested.to_vec();
+    }
+    if requested.contains(&RELAY_APPLICATION_ID) {
+        return supported.to_vec();
+    }
+    requested
+        .iter()
+        .filter(|id| supported.contains(id))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::Unsigned32;
+    use crate::dictionary::{self, Dictionary};
+    use crate::node::NodeConfig;
+    use crate::transport::pool::{PeerConfig, PeerPool};
+    use crate::transport::{DiameterClient, DiameterClientConfig};
+    use crate::transport::{DiameterServer, DiameterServerConfig};
+    use crate::ApplicationId;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&[4, 16777238], &[4, 16777236]), vec![4]);
+        assert_eq!(negotiate(&[4], &[RELAY_APPLICATION_ID]), vec![4]);
+        assert_eq!(negotiate(&[RELAY_APPLICATION_ID], &[1, 4]), vec![1, 4]);
+        assert!(negotiate(&[3], &[4]).is_empty());
+    }
+
+    async fn next(events: &mut mpsc::UnboundedReceiver<PeerEvent>) -> PeerEvent {
+        tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_peer_table() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let config = DiameterServerConfig { native_tls: None };
+        let mut server = DiameterServer::new("0.0.0.0:3904", config).await.unwrap();
+        let peers = server.peers();
+        let mut events = peers.subscribe();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    |req| async move {
+                        let node = NodeConfig::new("ocs.example.com", "example.com");
+                        let mut res = DiameterMessage::create_answer(
+                            &req,
+                            result_code::DIAMETER_SUCCESS,
+                            &node,
+                        );
+                        if req.get_command_code() == CommandCode::CapabilitiesExchange {
+                            for id in [4, ApplicationId::Rx as u32] {
+                                let id = Unsigned32::new(id).into();
+                                res.add_avp_by_name("Auth-Application-Id", id)?;
+                            }
+                        }
+                        Ok(res)
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3904", config);
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        let handle = tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+
+        let PeerEvent::Connected(info) = next(&mut events).await else {
+            panic!("Expected a connection");
+        };
+        assert_eq!(info.state, PeerState::Connected);
+        assert_eq!(info.origin_host, None);
+        assert_eq!(info.local_address.port(), 3904);
+
+        // Capabilities exchange
+        let seq_num = client.get_next_seq_num();
+        let cer = DiameterMessage::request("Capabilities-Exchange", Arc::clone(&dict))
+            .hop_by_hop_id(seq_num)
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Auth-Application-Id", 4u32)
+            .avp("Auth-Application-Id", ApplicationId::Gx as u32)
+            .build()
+            .unwrap();
+        client.send_message(cer).await.unwrap().await.unwrap();
+
+        let PeerEvent::Updated(info) = next(&mut events).await else {
+            panic!("Expected the identity of the peer");
+        };
+        assert_eq!(info.state, PeerState::Open);
+        assert_eq!(info.origin_host.as_deref(), Some("pcef.example.com"));
+        assert_eq!(info.origin_realm.as_deref(), Some("example.com"));
+        let PeerEvent::Updated(info) = next(&mut events).await else {
+            panic!("Expected the applications of the peer");
+        };
+        assert_eq!(info.applications, vec![4]);
+
+        // Requests of the same peer do not change the table
+        let seq_num = client.get_next_seq_num();
+        let ccr = DiameterMessage::request("Credit-Control", Arc::clone(&dict))
+            .hop_by_hop_id(seq_num)
+            .avp("Session-Id", "pcef.example.com;1;1")
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .build()
+            .unwrap();
+        client.send_message(ccr).await.unwrap().await.unwrap();
+
+        let table = peers.table();
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[0].state, PeerState::Open);
+        assert_eq!(table[0].applications, vec![4]);
+        assert_eq!(
+            table[0].counters,
+            PeerCounters {
+                requests_received: 2,
+                answers_sent: 2,
+                requests_sent: 0,
+                answers_received: 0,
+            }
+        );
+
+        handle.abort();
+        drop(client);
+        let PeerEvent::Disconnected(info) = next(&mut events).await else {
+            panic!("Expected a disconnection");
+        };
+        assert_eq!(info.state, PeerState::Closed);
+        assert_eq!(info.counters.answers_sent, 2);
+        assert!(peers.table().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shared_peer_table() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        let peer_table = PeerTable::new();
+        let mut events = peer_table.subscribe();
+
+        let config = DiameterServerConfig { native_tls: None };
+        let mut server = DiameterServer::new("0.0.0.0:3910", config).await.unwrap();
+        server.set_peer_table(peer_table.clone());
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    |req| async move {
+                        let node = NodeConfig::new("ocs.example.com", "example.com");
+                        let mut res = DiameterMessage::create_answer(
+                            &req,
+                            result_code::DIAMETER_SUCCESS,
+                            &node,
+                        );
+                        if req.get_command_code() == CommandCode::CapabilitiesExchange {
+                            let id = Unsigned32::new(RELAY_APPLICATION_ID).into();
+                            res.add_avp_by_name("Auth-Application-Id", id)?;
+                        }
+                        Ok(res)
+                    },
+                    dict_ref,
+                )
+                .await
+                .unwrap();
+        });
+
+        // Connections opened by a client
+        let config = DiameterClientConfig {
+            use_tls: false,
+            verify_cert: false,
+        };
+        let mut client = DiameterClient::new("localhost:3910", config);
+        client.set_peer_table(peer_table.clone());
+        let mut handler = client.connect().await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        let handle = tokio::spawn(async move {
+            DiameterClient::handle(&mut handler, dict_ref).await;
+        });
+        let mut accepted = Vec::new();
+        for _ in 0..2 {
+            let PeerEvent::Connected(info) = next(&mut events).await else {
+                panic!("Expected a connection");
+            };
+            accepted.push(info.accepted);
+        }
+        accepted.sort();
+        assert_eq!(accepted, vec![false, true]);
+
+        let seq_num = client.get_next_seq_num();
+        let cer = DiameterMessage::request("Capabilities-Exchange", Arc::clone(&dict))
+            .hop_by_hop_id(seq_num)
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Auth-Application-Id", ApplicationId::Gx as u32)
+            .build()
+            .unwrap();
+        client.send_message(cer).await.unwrap().await.unwrap();
+
+        let table = peer_table.peers();
+        assert_eq!(table.len(), 2);
+        let opened = table.iter().find(|info| !info.accepted).unwrap();
+        assert_eq!(opened.remote_address.port(), 3910);
+        assert_eq!(opened.state, PeerState::Open);
+        assert_eq!(opened.origin_host.as_deref(), Some("ocs.example.com"));
+        assert_eq!(opened.applications, vec![ApplicationId::Gx as u32]);
+        assert_eq!(opened.counters.requests_sent, 1);
+        assert_eq!(opened.counters.answers_received, 1);
+        let accepted = table.iter().find(|info| info.accepted).unwrap();
+        assert_eq!(accepted.local_address.port(), 3910);
+        assert_eq!(accepted.origin_host.as_deref(), Some("pcef.example.com"));
+
+        handle.abort();
+        loop {
+            let event = next(&mut events).await;
+            if let PeerEvent::Disconnected(info) = event {
+                if !info.accepted {
+                    break;
+                }
+            }
+        }
+        assert!(peer_table.peers().iter().all(|info| info.accepted));
+
+        // Connections opened by a pool
+        let pool = PeerPool::new(
+            vec![PeerConfig::new(
+                "ocs.example.com",
+                "example.com",
+                "localhost:3910",
+            )],
+            Arc::clone(&dict),
+            Default::default(),
+        );
+        pool.set_peer_table(peer_table.clone()).unwrap();
+        assert_eq!(pool.connect().await.unwrap(), 1);
+        let info = loop {
+            if let PeerEvent::Connected(info) = next(&mut events).await {
+                if !info.accepted {
+                    break info;
+                }
+            }
+        };
+        assert_eq!(info.remote_address.port(), 3910);
+        let table = pool.peer_table().unwrap().peers();
+        assert!(table
+            .iter()
+            .any(|peer| !peer.accepted && peer.local_address == info.local_address));
+    }
+}
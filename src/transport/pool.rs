@@ -0,0 +1,728 @@
+//! Diameter Peer Pool
+//!
+//! A [`PeerPool`] keeps a connection to each of several peers and sends
+//! every request to one of them:
+//!
+//! * a request carrying a Destination-Host is sent to that peer, when it is
+//!   in the pool, and fails when the peer is not connected;
+//! * with session pinning, the requests of a session are sent to the peer
+//!   which answered the previous request of the session, while it is
+//!   connected;
+//! * other requests are sent to a connected peer of their Destination-Realm,
+//!   of the lowest priority value, balanced by weight with
+//!   [`LoadBalancing`].
+//!
+//! When the connection to a peer fails, or the answer to a request does not
+//! arrive before the Tx timeout, the request is sent again to another peer,
+//! with the T flag set, as described in
+//! [RFC 6733 section 5.5.4](https://tools.ietf.org/html/rfc6733#section-5.5.4).
+//! Either way, the connection to the peer is dropped. The peers which are
+//! not connected are reconnected on the next request, once the reconnect
+//! interval has elapsed.
+//!
+//! The connections of the pool are recorded in a
+//! [`PeerTable`](crate::transport::peer::PeerTable), see
+//! [`PeerPool::set_peer_table`].
+//!
+//! ```no_run
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::transport::pool::{LoadBalancing, PeerConfig, PeerPool, PeerPoolConfig};
+//! use diameter::DiameterMessage;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//!     let peers = vec![
+//!         PeerConfig::new("ocs1.example.com", "ocs.example.com", "ocs1.example.com:3868"),
+//!         PeerConfig::new("ocs2.example.com", "ocs.example.com", "ocs2.example.com:3868"),
+//!         PeerConfig {
+//!             priority: 1,
+//!             ..PeerConfig::new("ocs3.example.com", "ocs.example.com", "ocs3.example.com:3868")
+//!         },
+//!     ];
+//!     let config = PeerPoolConfig {
+//!         load_balancing: LoadBalancing::LeastOutstanding,
+//!         session_pinning: true,
+//!         ..Default::default()
+//!     };
+//!     let pool = PeerPool::new(peers, Arc::clone(&dict), config);
+//!     pool.connect().await.unwrap();
+//!
+//!     let ccr = DiameterMessage::request("Credit-Control", dict)
+//!         .proxyable()
+//!         .avp("Session-Id", "pcef.example.com;1;1")
+//!         .avp("Origin-Host", "pcef.example.com")
+//!         .avp("Origin-Realm", "example.com")
+//!         .avp("Destination-Realm", "ocs.example.com")
+//!         .build()
+//!         .unwrap();
+//!     let cca = pool.send(ccr).await.unwrap();
+//!     println!("{}", cca);
+//! }
+//! ```
+
+use crate::diameter::{flags, DiameterMessage};
+use crate::dictionary::Dictionary;
+use crate::error::{Error, Result};
+use crate::transport::peer::PeerTable;
+use crate::transport::{DiameterClient, DiameterClientConfig};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::error::Elapsed;
+
+/// How requests are balanced between the peers of the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBalancing {
+    /// Weighted round-robin.
+    RoundRobin,
+    /// The peer with the fewest pending requests for its weight.
+    LeastOutstanding,
+}
+
+/// A peer of a [`PeerPool`].
+#[derive(Debug, Clone)]
+pub struct PeerConfig {
+    /// The Origin-Host of the peer, matched with Destination-Host.
+    pub origin_host: String,
+    /// The realm of the peer, matched with Destination-Realm.
+    pub realm: String,
+    /// The address to connect to.
+    pub address: String,
+    /// Priority of the peer, lower values being preferred.
+    pub priority: u32,
+    /// Relative share of the requests of the peer among the peers of the
+    /// same priority, at least 1.
+    pub weight: u32,
+    pub use_tls: bool,
+    pub verify_cert: bool,
+}
+
+impl PeerConfig {
+    /// Creates the configuration of a peer of priority 0 and weight 1,
+    /// connected over TCP.
+    pub fn new(origin_host: &str, realm: &str, address: &str) -> PeerConfig {
+        PeerConfig {
+            origin_host: origin_host.into(),
+            realm: realm.into(),
+            address: address.into(),
+            priority: 0,
+            weight: 1,
+            use_tls: false,
+            verify_cert: true,
+        }
+    }
+}
+
+/// Configuration of a [`PeerPool`].
+#[derive(Debug, Clone, Copy)]
+pub struct PeerPoolConfig {
+    pub load_balancing: LoadBalancing,
+    /// Whether the requests of a session are sent to the same peer.
+    pub session_pinning: bool,
+    /// Time to wait for an answer.
+    pub tx_timeout: Duration,
+    /// Time between two connection attempts to a peer.
+    pub reconnect_interval: Duration,
+}
+
+impl Default for PeerPoolConfig {
+    fn default() -> PeerPoolConfig {
+        PeerPoolConfig {
+            load_balancing: LoadBalancing::RoundRobin,
+            session_pinning: false,
+            tx_timeout: Duration::from_secs(10),
+            reconnect_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+type Connection = Arc<tokio::sync::Mutex<DiameterClient>>;
+
+struct Peer {
+    config: PeerConfig,
+    connection: Option<Connection>,
+    reader: Option<JoinHandle<()>>,
+    connected: Arc<AtomicBool>,
+    outstanding: Arc<AtomicUsize>,
+    current_weight: i64,
+    last_attempt: Option<Instant>,
+}
+
+impl Peer {
+    fn is_connected(&self) -> bool {
+        self.connection.is_some() && self.connected.load(Ordering::Relaxed)
+    }
+
+    fn weight(&self) -> u32 {
+        self.config.weight.max(1)
+    }
+
+    /// Drops the connection, stopping its reader, which closes the
+    /// connection once the requests being sent over it are written.
+    fn disconnect(&mut self) {
+        self.connected.store(false, Ordering::Relaxed);
+        self.connection = None;
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+    }
+}
+
+struct State {
+    peers: Vec<Peer>,
+    sessions: HashMap<String, usize>,
+    peer_table: PeerTable,
+}
+
+/// A client sending requests over a pool of peer connections.
+#[derive(Clone)]
+pub struct PeerPool {
+    state: Arc<Mutex<State>>,
+    dict: Arc<Dictionary>,
+    config: PeerPoolConfig,
+}
+
+impl PeerPool {
+    /// Creates a pool of `peers`, connected by [`connect`](PeerPool::connect)
+    /// or by the first request.
+    pub fn new(peers: Vec<PeerConfig>, dict: Arc<Dictionary>, config: PeerPoolConfig) -> PeerPool {
+        let peers = peers
+            .into_iter()
+            .map(|config| Peer {
+                config,
+                connection: None,
+                reader: None,
+                connected: Arc::new(AtomicBool::new(false)),
+                outstanding: Arc::new(AtomicUsize::new(0)),
+                current_weight: 0,
+                last_attempt: None,
+            })
+            .collect();
+        PeerPool {
+            state: Arc::new(Mutex::new(State {
+                peers,
+                sessions: HashMap::new(),
+                peer_table: PeerTable::default(),
+            })),
+            dict,
+            config,
+        }
+    }
+
+    /// Connects the peers which are not connected, and returns the number
+    /// of connected peers.
+    pub async fn connect(&self) -> Result<usize> {
+        self.connect_peers(true).await
+    }
+
+    /// Returns the Origin-Host of the connected peers.
+    pub fn connected(&self) -> Result<Vec<String>> {
+        let state = self.state.lock()?;
+        Ok(state
+            .peers
+            .iter()
+            .filter(|peer| peer.is_connected())
+            .map(|peer| peer.config.origin_host.clone())
+            .collect())
+    }
+
+    /// Returns the peer table the connections of the pool are recorded in.
+    pub fn peer_table(&self) -> Result<PeerTable> {
+        Ok(self.state.lock()?.peer_table.clone())
+    }
+
+    /// Records the connections of the pool in `table`, instead of a table
+    /// of its own, from the next connection on.
+    pub fn set_peer_table(&self, table: PeerTable) -> Result<()> {
+        self.state.lock()?.peer_table = table;
+        Ok(())
+    }
+
+    /// Forgets the peer of a session, once the session has ended.
+    pub fn release_session(&self, session_id: &str) -> Result<()> {
+        self.state.lock()?.sessions.remove(session_id);
+        Ok(())
+    }
+
+    /// Sends a request to a peer and returns its answer.
+    ///
+    /// The request is given a Hop-by-Hop Identifier of the connection it is
+    /// sent over, and keeps its End-to-End Identifier.
+    pub async fn send(&self, req: DiameterMessage) -> Result<DiameterMessage> {
+        let mut encoded = Vec::new();
+        req.encode_to(&mut encoded)?;
+        let session_id = if self.config.session_pinning {
+            req.get::<String, _>("Session-Id").ok()
+        } else {
+            None
+        };
+
+        self.connect_peers(false).await?;
+        let mut tried = vec![];
+        let mut error = None;
+        while let Some((index, connection, outstanding)) =
+            self.select(&req, session_id.as_deref(), &tried)?
+        {
+            let mut req = self.copy(&encoded)?;
+            if !tried.is_empty() {
+                req.set_flags(req.get_flags() | flags::RETRANSMIT);
+            }
+            tried.push(index);
+
+            outstanding.fetch_add(1, Ordering::Relaxed);
+            let res = send_over(&connection, req, self.config.tx_timeout).await;
+            outstanding.fetch_sub(1, Ordering::Relaxed);
+
+            match res {
+                Ok(Ok(res)) => {
+                    if let Some(session_id) = session_id {
+                        self.state.lock()?.sessions.insert(session_id, index);
+                    }
+                    return Ok(res);
+                }
+                Ok(Err(e)) => {
+                    let mut state = self.state.lock()?;
+                    let peer = &mut state.peers[index];
+                    log::warn!(
+                        "Failed to send to peer {}, failing over; error: {}",
+                        peer.config.origin_host,
+                        e
+                    );
+                    peer.disconnect();
+                    error = Some(e);
+                }
+                Err(_) => {
+                    // The peer is suspect, it is reconnected like a failed one
+                    let mut state = self.state.lock()?;
+                    let peer = &mut state.peers[index];
+                    log::warn!(
+                        "Timed out waiting for the answer of peer {}, failing over",
+                        peer.config.origin_host
+                    );
+                    peer.disconnect();
+                    error = Some(Error::ClientError("Timed out waiting for answer".into()));
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| Error::ClientError("No peer available".into())))
+    }
+
+    fn copy(&self, encoded: &[u8]) -> Result<DiameterMessage> {
+        DiameterMessage::decode_from(&mut Cursor::new(encoded), Arc::clone(&self.dict))
+    }
+
+    /// Selects the peer of a request among the connected peers not tried
+    /// yet.
+    fn select(
+        &self,
+        req: &DiameterMessage,
+        session_id: Option<&str>,
+        tried: &[usize],
+    ) -> Result<Option<(usize, Connection, Arc<AtomicUsize>)>> {
+        let mut state = self.state.lock()?;
+        let available = |peer: &Peer, index: usize| peer.is_connected() && !tried.contains(&index);
+
+        let index = 'select: {
+            if let Ok(host) = req.get::<String, _>("Destination-Host") {
+                let peer = state
+                    .peers
+                    .iter()
+                    .enumerate()
+                    .find(|(_, peer)| peer.config.origin_host.eq_ignore_ascii_case(&host));
+                if let Some((index, peer)) = peer {
+                    break 'select available(peer, index).then_some(index);
+                }
+            }
+
+            if let Some(&index) = session_id.and_then(|id| state.sessions.get(id)) {
+                if available(&state.peers[index], index) {
+                    break 'select Some(index);
+                }
+            }
+
+            let realm = req.get::<String, _>("Destination-Realm").ok();
+            let mut candidates: Vec<usize> = state
+                .peers
+                .iter()
+                .enumerate()
+                .filter(|(index, peer)| available(peer, *index))
+                .filter(|(_, peer)| match &realm {
+                    Some(realm) => peer.config.realm.eq_ignore_ascii_case(realm),
+                    None => true,
+                })
+                .map(|(index, _)| index)
+                .collect();
+            let Some(priority) = candidates
+                .iter()
+                .map(|&index| state.peers[index].config.priority)
+                .min()
+            else {
+                break 'select None;
+            };
+            candidates.retain(|&index| state.peers[index].config.priority == priority);
+
+            match self.config.load_balancing {
+                LoadBalancing::RoundRobin => Some(round_robin(&mut state.peers, &candidates)),
+                LoadBalancing::LeastOutstanding => {
+                    let peers = &state.peers;
+                    candidates.into_iter().min_by(|&a, &b| {
+                        let load = |index: usize, other: usize| {
+                            peers[index].outstanding.load(Ordering::Relaxed) as u64
+                                * peers[other].weight() as u64
+                        };
+                        load(a, b).cmp(&load(b, a))
+                    })
+                }
+            }
+        };
+
+        Ok(index.and_then(|index| {
+            let peer = &state.peers[index];
+            let connection = peer.connection.as_ref()?;
+            Some((index, Arc::clone(connection), Arc::clone(&peer.outstanding)))
+        }))
+    }
+
+    /// Connects the peers which are not connected, all of them when `force`,
+    /// or else those last tried before the reconnect interval.
+    async fn connect_peers(&self, force: bool) -> Result<usize> {
+        let peers: Vec<(usize, PeerConfig)> = {
+            let mut state = self.state.lock()?;
+            let now = Instant::now();
+            let interval = self.config.reconnect_interval;
+            state
+                .peers
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, peer)| !peer.is_connected())
+                .filter(|(_, peer)| {
+                    force
+                        || !matches!(peer.last_attempt,
+                            Some(last_attempt) if now.duration_since(last_attempt) < interval)
+                })
+                .map(|(index, peer)| {
+                    peer.last_attempt = Some(now);
+                    (index, peer.config.clone())
+                })
+                .collect()
+        };
+
+        let peer_table = self.peer_table()?;
+        for (index, config) in peers {
+            let client_config = DiameterClientConfig {
+                use_tls: config.use_tls,
+                verify_cert: config.verify_cert,
+            };
+            let mut client = DiameterClient::new(&config.address, client_config);
+            client.set_peer_table(peer_table.clone());
+            let mut handler = match client.connect().await {
+                Ok(handler) => handler,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to connect to peer {}; error: {}",
+                        config.origin_host,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let connected = Arc::new(AtomicBool::new(true));
+            let connected_ref = Arc::clone(&connected);
+            let dict = Arc::clone(&self.dict);
+            let reader = tokio::spawn(async move {
+                DiameterClient::handle(&mut handler, dict).await;
+                log::warn!("Connection to peer {} lost", config.origin_host);
+                connected_ref.store(false, Ordering::Relaxed);
+            });
+
+            let mut state = self.state.lock()?;
+            let peer = &mut state.peers[index];
+            peer.connection = Some(Arc::new(tokio::sync::Mutex::new(client)));
+            peer.reader = Some(reader);
+            peer.connected = connected;
+        }
+
+        let state = self.state.lock()?;
+        Ok(state
+            .peers
+            .iter()
+            .filter(|peer| peer.is_connected())
+            .count())
+    }
+}
+
+/// Sends a request over a connection and waits for its answer until the Tx
+/// timeout, after which the request is cancelled.
+async fn send_over(
+    connection: &Connection,
+    mut req: DiameterMessage,
+    tx_timeout: Duration,
+) -> std::result::Result<Result<DiameterMessage>, Elapsed> {
+    let mut hop_by_hop = None;
+    let res = tokio::time::timeout(tx_timeout, async {
+        let answer = {
+            let mut client = connection.lock().await;
+            let id = client.get_next_seq_num();
+            hop_by_hop = Some(id);
+            req.set_hop_by_hop_id(id);
+            client.send_message(req).await?
+        };
+        answer.await
+    })
+    .await;
+    if let (Err(_), Some(hop_by_hop)) = (&res, hop_by_hop) {
+        connection.lock().await.cancel(hop_by_hop).await;
+    }
+    res
+}
+
+/// Selects one of `candidates` by smooth weighted round-robin.
+fn round_robin(peers: &mut [Peer], candidates: &[usize]) -> usize {
+    let mut total = 0;
+    let mut selected = candidates[0];
+    for &index in candidates {
+        let weight = peers[index].weight() as i64;
+        peers[index].current_weight += weight;
+        total += weight;
+        if peers[index].current_weight > peers[selected].current_weight {
+            selected = index;
+        }
+    }
+    peers[selected].current_weight -= total;
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diameter::result_code;
+    use crate::dictionary;
+    use crate::node::NodeConfig;
+    use crate::transport::{Codec, DiameterServer, DiameterServerConfig};
+    use tokio::net::TcpListener;
+
+    async fn serve(port: u16, host: &str, delay: Duration, dict: &Arc<Dictionary>) {
+        let addr = format!("0.0.0.0:{}", port);
+        let mut server = DiameterServer::new(&addr, DiameterServerConfig { native_tls: None })
+            .await
+            .unwrap();
+        let node = NodeConfig::new(host, "ocs.example.com");
+        let dict = Arc::clone(dict);
+        tokio::spawn(async move {
+            server
+                .listen(
+                    move |req| {
+                        let node = node.clone();
+                        async move {
+                            tokio::time::sleep(delay).await;
+                            Ok(DiameterMessage::create_answer(
+                                &req,
+                                result_code::DIAMETER_SUCCESS,
+                                &node,
+                            ))
+                        }
+                    },
+                    dict,
+                )
+                .await
+                .unwrap();
+        });
+    }
+
+    fn ccr(dict: &Arc<Dictionary>, session_id: &str, host: Option<&str>) -> DiameterMessage {
+        let mut ccr = DiameterMessage::request("Credit-Control", Arc::clone(dict))
+            .proxyable()
+            .end_to_end_id(42)
+            .avp("Session-Id", session_id)
+            .avp("Origin-Host", "pcef.example.com")
+            .avp("Origin-Realm", "example.com")
+            .avp("Destination-Realm", "ocs.example.com");
+        if let Some(host) = host {
+            ccr = ccr.avp("Destination-Host", host);
+        }
+        ccr.build().unwrap()
+    }
+
+    async fn answered_by(pool: &PeerPool, req: DiameterMessage) -> String {
+        let res = pool.send(req).await.unwrap();
+        assert_eq!(res.get_end_to_end_id(), 42);
+        res.get::<String, _>("Origin-Host").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_robin() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        serve(3897, "ocs1.example.com", Duration::ZERO, &dict).await;
+        serve(3898, "ocs2.example.com", Duration::ZERO, &dict).await;
+        serve(3899, "ocs3.example.com", Duration::ZERO, &dict).await;
+
+        let peers = vec![
+            PeerConfig::new("ocs1.example.com", "ocs.example.com", "localhost:3897"),
+            PeerConfig {
+                weight: 3,
+                ..PeerConfig::new("ocs2.example.com", "ocs.example.com", "localhost:3898")
+            },
+            PeerConfig {
+                priority: 1,
+                ..PeerConfig::new("ocs3.example.com", "ocs.example.com", "localhost:3899")
+            },
+            PeerConfig::new("ocs4.example.com", "ocs.example.com", "localhost:1"),
+            PeerConfig::new("aaa.example.net", "example.net", "localhost:3899"),
+        ];
+        let config = PeerPoolConfig {
+            session_pinning: true,
+            ..Default::default()
+        };
+        let pool = PeerPool::new(peers, Arc::clone(&dict), config);
+        assert_eq!(pool.connect().await.unwrap(), 4);
+
+        // Balanced by weight among the peers of the lowest priority
+        let mut hosts = vec![];
+        for i in 0..8 {
+            let session_id = format!("pcef.example.com;1;{}", i);
+            hosts.push(answered_by(&pool, ccr(&dict, &session_id, None)).await);
+        }
+        let count = |host: &str| hosts.iter().filter(|h| *h == host).count();
+        assert_eq!(count("ocs1.example.com"), 2);
+        assert_eq!(count("ocs2.example.com"), 6);
+
+        // Pinned to the peer of the session
+        for _ in 0..3 {
+            let host = answered_by(&pool, ccr(&dict, "pcef.example.com;1;0", None)).await;
+            assert_eq!(host, hosts[0]);
+        }
+        pool.release_session("pcef.example.com;1;0").unwrap();
+
+        // Sent to the Destination-Host
+        let req = ccr(&dict, "pcef.example.com;1;9", Some("ocs3.example.com"));
+        assert_eq!(answered_by(&pool, req).await, "ocs3.example.com");
+        let res = pool
+            .send(ccr(&dict, "pcef.example.com;1;9", Some("ocs4.example.com")))
+            .await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_least_outstanding() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+        serve(3900, "ocs1.example.com", Duration::from_millis(500), &dict).await;
+        serve(3901, "ocs2.example.com", Duration::ZERO, &dict).await;
+
+        let peers = vec![
+            PeerConfig::new("ocs1.example.com", "ocs.example.com", "localhost:3900"),
+            PeerConfig::new("ocs2.example.com", "ocs.example.com", "localhost:3901"),
+        ];
+        let config = PeerPoolConfig {
+            load_balancing: LoadBalancing::LeastOutstanding,
+            ..Default::default()
+        };
+        let pool = PeerPool::new(peers, Arc::clone(&dict), config);
+        assert_eq!(pool.connect().await.unwrap(), 2);
+
+        let pool_ref = pool.clone();
+        let dict_ref = Arc::clone(&dict);
+        let slow = tokio::spawn(async move {
+            answered_by(&pool_ref, ccr(&dict_ref, "pcef.example.com;1;1", None)).await
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        for i in 2..5 {
+            let session_id = format!("pcef.example.com;1;{}", i);
+            let host = answered_by(&pool, ccr(&dict, &session_id, None)).await;
+            assert_eq!(host, "ocs2.example.com");
+        }
+        assert_eq!(slow.await.unwrap(), "ocs1.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+
+        // A peer failing with the request pending
+        let listener = TcpListener::bind("0.0.0.0:3902").await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        let failed = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let req = Codec::decode(&mut stream, dict_ref).await.unwrap();
+            req.get_flags() & flags::RETRANSMIT
+        });
+        serve(3903, "ocs2.example.com", Duration::ZERO, &dict).await;
+
+        let peers = vec![
+            PeerConfig::new("ocs1.example.com", "ocs.example.com", "localhost:3902"),
+            PeerConfig {
+                priority: 1,
+                ..PeerConfig::new("ocs2.example.com", "ocs.example.com", "localhost:3903")
+            },
+        ];
+        let pool = PeerPool::new(peers, Arc::clone(&dict), PeerPoolConfig::default());
+        assert_eq!(pool.connect().await.unwrap(), 2);
+
+        let res = pool
+            .send(ccr(&dict, "pcef.example.com;1;1", None))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.get::<String, _>("Origin-Host").unwrap(),
+            "ocs2.example.com"
+        );
+        assert_eq!(failed.await.unwrap(), 0);
+        assert_eq!(pool.connected().unwrap(), vec!["ocs2.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_tx_failover() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+
+        // A peer leaving the request unanswered
+        let listener = TcpListener::bind("0.0.0.0:3908").await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            Codec::decode(&mut stream, dict_ref).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+
+        // A peer answering the retransmitted request
+        let listener = TcpListener::bind("0.0.0.0:3909").await.unwrap();
+        let dict_ref = Arc::clone(&dict);
+        let retransmitted = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let req = Codec::decode(&mut stream, dict_ref).await.unwrap();
+            let node = NodeConfig::new("ocs2.example.com", "ocs.example.com");
+            let res = DiameterMessage::create_answer(&req, result_code::DIAMETER_SUCCESS, &node);
+            Codec::encode(&mut stream, &res).await.unwrap();
+            req.get_flags() & flags::RETRANSMIT != 0
+        });
+
+        let peers = vec![
+            PeerConfig::new("ocs1.example.com", "ocs.example.com", "localhost:3908"),
+            PeerConfig {
+                priority: 1,
+                ..PeerConfig::new("ocs2.example.com", "ocs.example.com", "localhost:3909")
+            },
+        ];
+        let config = PeerPoolConfig {
+            tx_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let pool = PeerPool::new(peers, Arc::clone(&dict), config);
+        assert_eq!(pool.connect().await.unwrap(), 2);
+
+        let host = answered_by(&pool, ccr(&dict, "pcef.example.com;1;1", None)).await;
+        assert_eq!(host, "ocs2.example.com");
+        assert!(retransmitted.await.unwrap());
+
+        // The peer which timed out is disconnected
+        let connected = pool.connected().unwrap();
+        assert!(!connected.contains(&"ocs1.example.com".to_string()));
+        let res = pool
+            .send(ccr(&dict, "pcef.example.com;1;2", Some("ocs1.example.com")))
+            .await;
+        assert!(res.is_err());
+    }
+}
@@ -1,14 +1,25 @@
 //! Diameter Protocol Server
-use crate::diameter::DiameterMessage;
+use crate::diameter::{result_code, DiameterMessage};
 use crate::dictionary::Dictionary;
-use crate::error::Result;
-use crate::transport::Codec;
+use crate::error::{Error, Result};
+use crate::node::NodeConfig;
+use crate::transport::client::ResponseFuture;
+use crate::transport::peer::{PeerEntry, PeerEvent, PeerInfo, PeerStats, PeerTable};
+use crate::transport::{Codec, DiameterClient};
+use std::collections::HashMap;
 use std::future::Future;
 use std::net::SocketAddr;
+use std::ops::DerefMut;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::oneshot::Sender;
+use tokio::sync::Mutex;
 
 /// Configuration for the Diameter server.
 ///
@@ -22,6 +33,8 @@ pub struct DiameterServerConfig {
 pub struct DiameterServer {
     listener: TcpListener,
     config: DiameterServerConfig,
+    peers: ServerPeers,
+    node: Option<NodeConfig>,
 }
 
 impl DiameterServer {
@@ -36,7 +49,39 @@ impl DiameterServer {
     ///     A `Result` containing the new `DiameterServer` instance or an `Error` if the binding fails.
     pub async fn new(addr: &str, config: DiameterServerConfig) -> Result<DiameterServer> {
         let listener = TcpListener::bind(addr).await?;
-        Ok(DiameterServer { listener, config })
+        Ok(DiameterServer {
+            listener,
+            config,
+            peers: ServerPeers::default(),
+            node: None,
+        })
+    }
+
+    /// Sets the identity of the node, sent in the answers the server
+    /// creates itself.
+    ///
+    /// Once a node is set, a request whose handler fails is answered with
+    /// DIAMETER_UNABLE_TO_COMPLY. Without a node, the connection is closed.
+    pub fn set_node(&mut self, node: NodeConfig) {
+        self.node = Some(node);
+    }
+
+    /// Returns the connections accepted by the server, through which
+    /// requests can be sent to the connected peers.
+    ///
+    /// The returned `ServerPeers` is shared with the server, so it can be
+    /// taken before `listen` and used while the server runs.
+    pub fn peers(&self) -> ServerPeers {
+        self.peers.clone()
+    }
+
+    /// Records the connections accepted by the server in `table`, instead
+    /// of a table of its own.
+    ///
+    /// The same table can be set on clients and pools, see the
+    /// [`peer`](crate::transport::peer) module.
+    pub fn set_peer_table(&mut self, table: PeerTable) {
+        self.peers.set_table(table);
     }
 
     /// Listens for incoming connections and processes Diameter messages.
@@ -46,6 +91,9 @@ impl DiameterServer {
     ///
     /// The server will listen indefinitely, handling each incoming connection in a loop.
     /// Each connection is handled in its own asynchronous task.
+    /// A request for which the handler returns an `Error` closes the
+    /// connection, or is answered with DIAMETER_UNABLE_TO_COMPLY once a node
+    /// is set, see `set_node`.
     ///
     /// Args:
     ///    * handler: A function or closure that takes a `DiameterMessage` and returns a `Result`
@@ -68,13 +116,19 @@ impl DiameterServer {
                     let acceptor = native_tls::TlsAcceptor::new(identity.clone())?;
                     let acceptor = tokio_native_tls::TlsAcceptor::from(acceptor);
                     let (stream, peer_addr) = self.listener.accept().await?;
+                    let local_addr = stream
+                        .local_addr()
+                        .or_else(|_| self.listener.local_addr())?;
                     match acceptor.accept(stream).await {
                         Ok(stream) => {
                             Self::handle_peer(
+                                local_addr,
                                 peer_addr,
                                 stream,
                                 handler.clone(),
                                 Arc::clone(&dict),
+                                self.peers.clone(),
+                                self.node.clone(),
                             );
                         }
                         Err(e) => {
@@ -84,21 +138,51 @@ impl DiameterServer {
                 }
                 None => {
                     let (stream, peer_addr) = self.listener.accept().await?;
-                    Self::handle_peer(peer_addr, stream, handler.clone(), Arc::clone(&dict));
+                    let local_addr = stream
+                        .local_addr()
+                        .or_else(|_| self.listener.local_addr())?;
+                    Self::handle_peer(
+                        local_addr,
+                        peer_addr,
+                        stream,
+                        handler.clone(),
+                        Arc::clone(&dict),
+                        self.peers.clone(),
+                        self.node.clone(),
+                    );
                 }
             };
         }
     }
 
-    fn handle_peer<F, Fut, S>(peer_addr: SocketAddr, stream: S, handler: F, dict: Arc<Dictionary>)
-    where
+    fn handle_peer<F, Fut, S>(
+        local_addr: SocketAddr,
+        peer_addr: SocketAddr,
+        stream: S,
+        handler: F,
+        dict: Arc<Dictionary>,
+        peers: ServerPeers,
+        node: Option<NodeConfig>,
+    ) where
         F: Fn(DiameterMessage) -> Fut + Clone + Send + 'static,
         Fut: Future<Output = Result<DiameterMessage>> + Send + 'static,
         S: AsyncReadExt + AsyncWriteExt + Unpin + Send + 'static,
     {
         tokio::spawn(async move {
             log::info!("[{}] Connection established", peer_addr);
-            match Self::process_incoming_message(stream, handler, dict).await {
+            let (mut reader, writer) = tokio::io::split(stream);
+            let peer = ServerPeer {
+                address: peer_addr,
+                entry: peers.peer_table().open(local_addr, peer_addr, true),
+                writer: Arc::new(Mutex::new(writer)),
+                msg_caches: Arc::new(Mutex::new(HashMap::new())),
+                seq_num: Arc::new(AtomicU32::new(0)),
+            };
+            peers.insert(peer.clone());
+            let result =
+                Self::process_incoming_message(&mut reader, &peer, &peers, handler, dict, node)
+                    .await;
+            match result {
                 Ok(_) => {
                     log::info!("[{}] Connection closed", peer_addr);
                 }
@@ -106,22 +190,28 @@ impl DiameterServer {
                     log::error!("Fatal error occurred: {:?}", e);
                 }
             }
+            // Fail the requests sent to the peer, their answers will not arrive
+            peer.msg_caches.lock().await.clear();
+            peers.remove(&peer);
         });
     }
 
-    async fn process_incoming_message<F, Fut, S>(
-        mut stream: S,
+    async fn process_incoming_message<F, Fut, R>(
+        reader: &mut R,
+        peer: &ServerPeer,
+        peers: &ServerPeers,
         handler: F,
         dict: Arc<Dictionary>,
+        node: Option<NodeConfig>,
     ) -> Result<()>
     where
         F: Fn(DiameterMessage) -> Fut,
         Fut: Future<Output = Result<DiameterMessage>>,
-        S: AsyncReadExt + AsyncWriteExt + Unpin,
+        R: AsyncReadExt + Unpin,
     {
         loop {
             // Read and decode the request
-            let req = match Codec::decode(&mut stream, Arc::clone(&dict)).await {
+            let req = match Codec::decode(reader, Arc::clone(&dict)).await {
                 Ok(req) => req,
                 Err(e) => match e {
                     crate::error::Error::IoError(ref e)
@@ -135,11 +225,215 @@ impl DiameterServer {
                 },
             };
 
-            // Process the request using the handler
-            let res = handler(req).await?;
+            // Answers to requests sent through the peer
+            if !req.is_request() {
+                PeerStats::increment(&peer.entry.stats.answers_received);
+                if let Err(e) =
+                    DiameterClient::process_decoded_msg(Arc::clone(&peer.msg_caches), req).await
+                {
+                    log::error!("Failed to process response; error: {:?}", e);
+                }
+                continue;
+            }
+            PeerStats::increment(&peer.entry.stats.requests_received);
+            if peer.entry.identify(&req) {
+                peers.peer_table().update(&peer.entry);
+            }
+            peer.entry.request_capabilities(&req);
+
+            // Process the request using the handler, answering
+            // DIAMETER_UNABLE_TO_COMPLY if it fails and the node is known
+            let answer = node.as_ref().map(|node| (node, answer_header(&req, &dict)));
+            let res = match handler(req).await {
+                Ok(res) => res,
+                Err(e) => {
+                    let Some((node, header)) = answer else {
+                        return Err(e);
+                    };
+                    log::error!(
+                        "[{}] Failed to handle request; error: {:?}",
+                        peer.address,
+                        e
+                    );
+                    DiameterMessage::create_error_answer(
+                        &header,
+                        result_code::DIAMETER_UNABLE_TO_COMPLY,
+                        None,
+                        node,
+                    )
+                }
+            };
 
             // Encode and send the response
-            Codec::encode(&mut stream, &res).await?;
+            {
+                let mut writer = peer.writer.lock().await;
+                Codec::encode(&mut writer.deref_mut(), &res).await?;
+            }
+            PeerStats::increment(&peer.entry.stats.answers_sent);
+
+            // Applications negotiated by capabilities exchange
+            if peer.entry.negotiate_capabilities(&res) {
+                peers.peer_table().update(&peer.entry);
+            }
+        }
+    }
+}
+
+/// Returns a copy of the header, Session-Id and Proxy-Info of a request, the
+/// parts an answer is built from, for answering once the request is gone.
+fn answer_header(req: &DiameterMessage, dict: &Arc<Dictionary>) -> DiameterMessage {
+    let mut header = DiameterMessage::new(
+        req.get_command_code(),
+        req.get_application_id(),
+        req.get_flags(),
+        req.get_hop_by_hop_id(),
+        req.get_end_to_end_id(),
+        Arc::clone(dict),
+    );
+    if let Some(session_id) = req.get_avp(263) {
+        header.add(session_id.clone());
+    }
+    for proxy_info in req.find_avps(284) {
+        header.add(proxy_info.clone());
+    }
+    header
+}
+
+/// A connection accepted by a `DiameterServer`, used to send requests to
+/// the connected peer.
+///
+/// The answers are read by the server and resolve the `ResponseFuture`
+/// returned by `send_message`.
+#[derive(Clone)]
+pub struct ServerPeer {
+    address: SocketAddr,
+    entry: PeerEntry,
+    writer: Arc<Mutex<dyn AsyncWrite + Send + Unpin>>,
+    msg_caches: Arc<Mutex<HashMap<u32, Sender<DiameterMessage>>>>,
+    seq_num: Arc<AtomicU32>,
+}
+
+impl ServerPeer {
+    /// Returns the remote address of the connection.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the local address of the connection.
+    pub fn local_address(&self) -> SocketAddr {
+        self.entry.local_address()
+    }
+
+    /// Returns the Origin-Host of the last request received from the peer.
+    pub fn origin_host(&self) -> Option<String> {
+        self.entry.origin_host()
+    }
+
+    /// Returns the current state and statistics of the connection.
+    pub fn info(&self) -> PeerInfo {
+        self.entry.info()
+    }
+
+    /// Sends a request to the peer and returns a future for receiving the
+    /// answer.
+    pub async fn send_message(&self, req: DiameterMessage) -> Result<ResponseFuture> {
+        if !req.is_request() {
+            return Err(Error::ServerError("Not a request".into()));
+        }
+        let (tx, rx) = oneshot::channel();
+        let hop_by_hop = req.get_hop_by_hop_id();
+        {
+            let mut msg_caches = self.msg_caches.lock().await;
+            msg_caches.insert(hop_by_hop, tx);
+        }
+        let mut writer = self.writer.lock().await;
+        if let Err(e) = Codec::encode(&mut writer.deref_mut(), &req).await {
+            self.msg_caches.lock().await.remove(&hop_by_hop);
+            return Err(e);
+        }
+        PeerStats::increment(&self.entry.stats.requests_sent);
+        Ok(ResponseFuture { receiver: rx })
+    }
+
+    /// Returns the next sequence number.
+    pub fn get_next_seq_num(&self) -> u32 {
+        self.seq_num.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// The connections currently accepted by a `DiameterServer`.
+///
+/// The connections are also recorded in a [`PeerTable`], see the
+/// [`peer`](crate::transport::peer) module.
+#[derive(Clone, Default)]
+pub struct ServerPeers {
+    peers: Arc<std::sync::Mutex<HashMap<SocketAddr, ServerPeer>>>,
+    table: Arc<std::sync::Mutex<PeerTable>>,
+}
+
+impl ServerPeers {
+    /// Returns the connection from `address`.
+    pub fn get(&self, address: &SocketAddr) -> Option<ServerPeer> {
+        self.peers.lock().ok()?.get(address).cloned()
+    }
+
+    /// Returns a connection of the peer identified by `origin_host`.
+    pub fn get_by_origin_host(&self, origin_host: &str) -> Option<ServerPeer> {
+        self.peers
+            .lock()
+            .ok()?
+            .values()
+            .find(|peer| peer.origin_host().as_deref() == Some(origin_host))
+            .cloned()
+    }
+
+    /// Returns all the connections.
+    pub fn all(&self) -> Vec<ServerPeer> {
+        match self.peers.lock() {
+            Ok(peers) => peers.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Returns the peer table the connections are recorded in.
+    pub fn peer_table(&self) -> PeerTable {
+        match self.table.lock() {
+            Ok(table) => table.clone(),
+            Err(_) => PeerTable::default(),
+        }
+    }
+
+    /// Returns the state and statistics of all the connections of the
+    /// peer table, ordered by remote address.
+    ///
+    /// The table also holds the connections of the clients and pools it
+    /// was set on.
+    pub fn table(&self) -> Vec<PeerInfo> {
+        self.peer_table().peers()
+    }
+
+    /// Returns a receiver of the changes of the connections of the peer
+    /// table, from now on.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<PeerEvent> {
+        self.peer_table().subscribe()
+    }
+
+    fn set_table(&self, table: PeerTable) {
+        if let Ok(mut current) = self.table.lock() {
+            *current = table;
+        }
+    }
+
+    fn insert(&self, peer: ServerPeer) {
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.insert(peer.address, peer);
+        }
+    }
+
+    fn remove(&self, peer: &ServerPeer) {
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.remove(&peer.address);
         }
+        self.peer_table().close(&peer.entry);
     }
 }
@@ -0,0 +1,389 @@
+//! Strongly-Typed Messages and Grouped AVPs
+//!
+//! The [`TypedMessage`] and [`TypedGroup`] traits map Rust structs to
+//! `DiameterMessage` and `Grouped` values. They are usually implemented with
+//! the `DiameterMessage` and `GroupedAvp` derive macros:
+//!
+//! ```
+//! use diameter::dictionary::{self, Dictionary};
+//! use diameter::typed::{TypedGroup, TypedMessage};
+//! use diameter::{DiameterMessage, GroupedAvp};
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug, GroupedAvp)]
+//! struct SubscriptionId {
+//!     #[avp(name = "Subscription-Id-Type")]
+//!     id_type: i32,
+//!     #[avp(name = "Subscription-Id-Data")]
+//!     id_data: String,
+//! }
+//!
+//! #[derive(Debug, DiameterMessage)]
+//! #[diameter(command = CreditControl, application = CreditControl, request)]
+//! struct Ccr {
+//!     #[avp(name = "Session-Id")]
+//!     session_id: String,
+//!     #[avp(code = 415)]
+//!     request_number: u32,
+//!     #[avp(code = 1, mandatory)]
+//!     user_name: Option<String>,
+//!     #[avp(name = "Subscription-Id", grouped)]
+//!     subscription_ids: Vec<SubscriptionId>,
+//! }
+//!
+//! let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+//! let ccr = Ccr {
+//!     session_id: "ses;1".into(),
+//!     request_number: 0,
+//!     user_name: None,
+//!     subscription_ids: vec![SubscriptionId { id_type: 0, id_data: "6591234567".into() }],
+//! };
+//! let msg: DiameterMessage = ccr.to_message(1, 1, &dict).unwrap();
+//! let decoded = Ccr::from_message(&msg).unwrap();
+//! assert_eq!(decoded.subscription_ids[0].id_data, "6591234567");
+//! ```
+//!
+//! Field attributes:
+//! * `name = "..."` - AVP name, resolved through the dictionary.
+//! * `code = N`, `vendor = N` - AVP code and optional vendor ID.
+//! * `mandatory` - sets the M flag, otherwise taken from the dictionary.
+//! * `grouped` - the field type implements `TypedGroup`.
+//!
+//! `Option<T>` fields are optional, `Vec<T>` fields hold all instances of
+//! the AVP (except `Vec<u8>`, which is an OctetString value), any other field
+//! is required.
+//!
+//! Structs defined inside this crate use `#[diameter(crate = "crate")]`.
+
+use crate::avp::flags::M;
+use crate::avp::{Avp, Grouped, IntoAvpValue};
+use crate::diameter::{flags, ApplicationId, CommandCode, DiameterMessage};
+use crate::dictionary::{AvpKey, Dictionary};
+use crate::error::{Error, Result};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+/// `Time` AVPs map to `DateTime<Utc>` fields.
+pub use chrono::{DateTime, Utc};
+
+/// A struct that maps to the AVPs of a `Grouped` AVP.
+pub trait TypedGroup: Sized {
+    /// Encodes the fields into AVPs.
+    fn to_avps(&self, dict: &Arc<Dictionary>) -> Result<Vec<Avp>>;
+
+    /// Decodes the fields from AVPs.
+    fn from_avps(avps: &[Avp]) -> Result<Self>;
+
+    fn to_grouped(&self, dict: &Arc<Dictionary>) -> Result<Grouped> {
+        Ok(Grouped::new(self.to_avps(dict)?, Arc::clone(dict)))
+    }
+
+    fn from_grouped(group: &Grouped) -> Result<Self> {
+        Self::from_avps(group.avps())
+    }
+}
+
+impl<G: TypedGroup> TypedGroup for Box<G> {
+    fn to_avps(&self, dict: &Arc<Dictionary>) -> Result<Vec<Avp>> {
+        G::to_avps(self, dict)
+    }
+
+    fn from_avps(avps: &[Avp]) -> Result<Self> {
+        G::from_avps(avps).map(Box::new)
+    }
+}
+
+/// A struct that maps to a Diameter request or answer.
+pub trait TypedMessage: TypedGroup {
+    const COMMAND_CODE: CommandCode;
+    const APPLICATION_ID: ApplicationId;
+    const FLAGS: u8;
+
+    /// Encodes the struct into a `DiameterMessage`.
+    fn to_message(
+        &self,
+        hop_by_hop_id: u32,
+        end_to_end_id: u32,
+        dict: &Arc<Dictionary>,
+    ) -> Result<DiameterMessage> {
+        let mut message = DiameterMessage::new(
+            Self::COMMAND_CODE,
+            Self::APPLICATION_ID,
+            Self::FLAGS,
+            hop_by_hop_id,
+            end_to_end_id,
+            Arc::clone(dict),
+        );
+        for avp in self.to_avps(dict)? {
+            message.add(avp);
+        }
+        Ok(message)
+    }
+
+    /// Decodes the struct from a `DiameterMessage`, checking the command
+    /// code and the R bit.
+    fn from_message(message: &DiameterMessage) -> Result<Self> {
+        if message.get_command_code() != Self::COMMAND_CODE {
+            return Err(Error::DecodeError(format!(
+                "unexpected command code: {}, expected {}",
+                message.get_command_code(),
+                Self::COMMAND_CODE
+            )));
+        }
+        if message.is_request() != (Self::FLAGS & flags::REQUEST != 0) {
+            return Err(Error::DecodeError(format!(
+                "unexpected R bit for {}",
+                Self::COMMAND_CODE
+            )));
+        }
+        Self::from_avps(message.get_avps())
+    }
+}
+
+/// Resolves the code, vendor ID, M flag and type of an AVP field.
+fn resolve(
+    key: &AvpKey,
+    mandatory: Option<bool>,
+    dict: &Dictionary,
+) -> Result<(u32, Option<u32>, u8, crate::avp::AvpType)> {
+    let avp_def = dict.get_avp_by_key(key).ok_or_else(|| match key {
+        AvpKey::Name(name) => Error::UnknownAvpName(name.clone()),
+        AvpKey::Code(code) | AvpKey::CodeAndVendor(code, _) => Error::UnknownAvpCode(*code),
+    })?;
+    let m_flag = mandatory.unwrap_or(avp_def.m_flag);
+    let flags = if m_flag { M } else { 0 };
+    Ok((avp_def.code, avp_def.vendor_id, flags, avp_def.avp_type))
+}
+
+/// Encodes a single field value into an AVP.
+pub fn encode_value<V: IntoAvpValue>(
+    key: &AvpKey,
+    mandatory: Option<bool>,
+    value: V,
+    dict: &Arc<Dictionary>,
+) -> Result<Avp> {
+    let (code, vendor_id, flags, avp_type) = resolve(key, mandatory, dict)?;
+    let value = value
+        .into_avp_value(avp_type)
+        .map_err(|e| Error::InvalidAvpValue(format!("invalid value for AVP {}: {}", key, e)))?;
+    Ok(Avp::new(code, vendor_id, flags, value, Arc::clone(dict)))
+}
+
+/// Encodes a `TypedGroup` field into a `Grouped` AVP.
+pub fn encode_group<G: TypedGroup>(
+    key: &AvpKey,
+    mandatory: Option<bool>,
+    value: &G,
+    dict: &Arc<Dictionary>,
+) -> Result<Avp> {
+    encode_value(key, mandatory, value.to_grouped(dict)?, dict)
+}
+
+/// Decodes the first AVP identified by `key`, failing if it is missing.
+pub fn decode_value<T>(avps: &[Avp], key: &AvpKey) -> Result<T>
+where
+    T: for<'a> TryFrom<&'a Avp, Error = Error>,
+{
+    decode_optional(avps, key)?.ok_or_else(|| Error::AvpNotFound(key.to_string()))
+}
+
+/// Decodes the first AVP identified by `key`, if present.
+pub fn decode_optional<T>(avps: &[Avp], key: &AvpKey) -> Result<Option<T>>
+where
+    T: for<'a> TryFrom<&'a Avp, Error = Error>,
+{
+    avps.iter()
+        .find(|avp| avp.matches(key))
+        .map(T::try_from)
+        .transpose()
+}
+
+/// Decodes all AVPs identified by `key`.
+pub fn decode_all<T>(avps: &[Avp], key: &AvpKey) -> Result<Vec<T>>
+where
+    T: for<'a> TryFrom<&'a Avp, Error = Error>,
+{
+    avps.iter()
+        .filter(|avp| avp.matches(key))
+        .map(T::try_from)
+        .collect()
+}
+
+/// Decodes the first `Grouped` AVP identified by `key`, failing if it is missing.
+pub fn decode_group<G: TypedGroup>(avps: &[Avp], key: &AvpKey) -> Result<G> {
+    decode_optional_group(avps, key)?.ok_or_else(|| Error::AvpNotFound(key.to_string()))
+}
+
+/// Decodes the first `Grouped` AVP identified by `key`, if present.
+pub fn decode_optional_group<G: TypedGroup>(avps: &[Avp], key: &AvpKey) -> Result<Option<G>> {
+    avps.iter()
+        .find(|avp| avp.matches(key))
+        .map(decode_grouped_avp)
+        .transpose()
+}
+
+/// Decodes all `Grouped` AVPs identified by `key`.
+pub fn decode_all_groups<G: TypedGroup>(avps: &[Avp], key: &AvpKey) -> Result<Vec<G>> {
+    avps.iter()
+        .filter(|avp| avp.matches(key))
+        .map(decode_grouped_avp)
+        .collect()
+}
+
+fn decode_grouped_avp<G: TypedGroup>(avp: &Avp) -> Result<G> {
+    match avp.get_grouped() {
+        Some(group) => G::from_grouped(group),
+        None => Err(avp.invalid_conversion("Grouped")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avp::Unsigned32;
+    use crate::dictionary;
+    use crate::{DiameterMessage, GroupedAvp};
+    use std::io::Cursor;
+    use std::net::Ipv4Addr;
+
+    #[derive(Debug, Clone, PartialEq, GroupedAvp)]
+    #[diameter(crate = "crate")]
+    struct ServiceUnit {
+        #[avp(name = "CC-Total-Octets")]
+        total_octets: Option<u64>,
+        #[avp(name = "CC-Time")]
+        time: Option<u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, GroupedAvp)]
+    #[diameter(crate = "crate")]
+    struct Mscc {
+        #[avp(name = "Rating-Group")]
+        rating_group: u32,
+        #[avp(name = "Used-Service-Unit", grouped)]
+        used: Vec<ServiceUnit>,
+        #[avp(name = "Granted-Service-Unit", grouped)]
+        granted: Option<ServiceUnit>,
+    }
+
+    #[derive(Debug, PartialEq, DiameterMessage)]
+    #[diameter(
+        crate = "crate",
+        command = CreditControl,
+        application = CreditControl,
+        request,
+        proxyable
+    )]
+    struct Ccr {
+        #[avp(name = "Session-Id")]
+        session_id: String,
+        #[avp(name = "Origin-Host")]
+        origin_host: String,
+        #[avp(code = 416)]
+        request_type: i32,
+        #[avp(code = 415)]
+        request_number: u32,
+        #[avp(code = 571, vendor = 10415, mandatory)]
+        timezone_offset: Option<i32>,
+        #[avp(name = "Class")]
+        class: Option<Vec<u8>>,
+        #[avp(name = "Host-IP-Address")]
+        host_ip_addresses: Vec<Ipv4Addr>,
+        #[avp(name = "Multiple-Services-Credit-Control", grouped)]
+        mscc: Vec<Mscc>,
+    }
+
+    #[derive(Debug, PartialEq, DiameterMessage)]
+    #[diameter(crate = "crate", command = CreditControl, application = CreditControl)]
+    struct Cca {
+        #[avp(name = "Session-Id")]
+        session_id: String,
+        #[avp(name = "Result-Code")]
+        result_code: u32,
+    }
+
+    #[test]
+    fn test_typed_message_round_trip() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+
+        let ccr = Ccr {
+            session_id: "ses;1".into(),
+            origin_host: "host.example.com".into(),
+            request_type: 1,
+            request_number: 0,
+            timezone_offset: Some(-60),
+            class: Some(vec![10, 0, 0, 1]),
+            host_ip_addresses: vec![Ipv4Addr::new(127, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)],
+            mscc: vec![Mscc {
+                rating_group: 100,
+                used: vec![
+                    ServiceUnit {
+                        total_octets: Some(1024),
+                        time: None,
+                    },
+                    ServiceUnit {
+                        total_octets: None,
+                        time: Some(60),
+                    },
+                ],
+                granted: None,
+            }],
+        };
+
+        let msg = ccr.to_message(1234, 5678, &dict).unwrap();
+        assert_eq!(msg.get_flags(), flags::REQUEST | flags::PROXYABLE);
+        assert_eq!(msg.get_application_id(), ApplicationId::CreditControl);
+        assert_eq!(msg.get_hop_by_hop_id(), 1234);
+
+        let timezone_offset = msg.find_avp((571, 10415)).unwrap();
+        assert!(timezone_offset.get_flags().mandatory);
+        assert!(!msg.find_avp("Session-Id").unwrap().get_flags().vendor);
+
+        let mut encoded = Vec::new();
+        msg.encode_to(&mut encoded).unwrap();
+        let mut cursor = Cursor::new(&encoded);
+        let msg = DiameterMessage::decode_from(&mut cursor, Arc::clone(&dict)).unwrap();
+
+        assert_eq!(Ccr::from_message(&msg).unwrap(), ccr);
+        assert!(Cca::from_message(&msg).is_err());
+    }
+
+    #[test]
+    fn test_typed_message_errors() {
+        let dict = Arc::new(Dictionary::new(&[&dictionary::DEFAULT_DICT_XML]));
+
+        let mut msg = DiameterMessage::new(
+            CommandCode::CreditControl,
+            ApplicationId::CreditControl,
+            0,
+            1,
+            1,
+            Arc::clone(&dict),
+        );
+        msg.add_avp(268, None, M, Unsigned32::new(2001).into());
+        match Cca::from_message(&msg) {
+            Err(Error::AvpNotFound(key)) => assert_eq!(key, "Session-Id"),
+            _ => panic!("expected AvpNotFound"),
+        }
+
+        msg.add_avp(263, None, M, Unsigned32::new(1).into());
+        assert!(matches!(
+            Cca::from_message(&msg),
+            Err(Error::InvalidAvpValue(_))
+        ));
+
+        let mut msg = DiameterMessage::new(
+            CommandCode::CapabilitiesExchange,
+            ApplicationId::Common,
+            0,
+            1,
+            1,
+            dict,
+        );
+        msg.add_avp(268, None, M, Unsigned32::new(2001).into());
+        assert!(matches!(
+            Cca::from_message(&msg),
+            Err(Error::DecodeError(_))
+        ));
+    }
+}